@@ -36,6 +36,9 @@ interface TenantConfig {
   updated_at: string;
 }
 
+/** Whether each feature is enabled, keyed by name (e.g. `polls`, `direct_messages`). */
+type TenantFeatures = Record<string, boolean>;
+
 export const tenantsApi = {
   get(id: string): Promise<Tenant> {
     return api.get<Tenant>(`/api/v1/tenants/${id}`);
@@ -53,6 +56,10 @@ export const tenantsApi = {
     return api.put<TenantConfig>(`/api/v1/tenants/${tenantId}/config`, { key, value });
   },
 
+  getFeatures(tenantId: string): Promise<TenantFeatures> {
+    return api.get<TenantFeatures>(`/api/v1/tenants/${tenantId}/features`);
+  },
+
   getBrandingHistory(tenantId: string): Promise<unknown[]> {
     return api.get(`/api/v1/tenants/${tenantId}/branding-history`);
   },
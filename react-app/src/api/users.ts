@@ -16,9 +16,19 @@ interface User {
   updated_at: string;
 }
 
+/** A search hit; non-admins only get the public fields, admins get the full user. */
+type UserSearchResult = Pick<User, 'id' | 'display_name' | 'avatar_url'> &
+  Partial<User> & { online: boolean };
+
 export const usersApi = {
-  search(query: string): Promise<User[]> {
-    return api.get<User[]>(`/api/v1/users/search?q=${encodeURIComponent(query)}`);
+  search(
+    query: string,
+    page = 1,
+    perPage = 50
+  ): Promise<{ page: number; per_page: number; total: number; data: UserSearchResult[] }> {
+    return api.get(
+      `/api/v1/users/search?q=${encodeURIComponent(query)}&page=${page}&per_page=${perPage}`
+    );
   },
 
   get(id: string): Promise<User> {
@@ -121,7 +121,7 @@ export function subscribeToRoomPolls(roomId: string) {
     } else if (event === 'poll_deleted') {
       const row = data as { id: string };
       removePoll(row.id);
-    } else if (event === 'poll_closed' || event === 'poll_vote_cast') {
+    } else if (event === 'poll_closed' || event === 'poll_results_updated') {
       setPolls(
         currentPolls.map((p) =>
           p.id === data.poll_id || p.id === data.id ? ({ ...p, ...data } as Poll) : p
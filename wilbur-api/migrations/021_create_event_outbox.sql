@@ -0,0 +1,15 @@
+-- Migration 021: Create transactional outbox for WebSocket event delivery
+-- Rows are written in the same transaction as the mutation they describe and
+-- broadcast by a background dispatcher once committed.
+
+CREATE TABLE event_outbox (
+    id          UUID        PRIMARY KEY DEFAULT gen_random_uuid(),
+    channel     VARCHAR     NOT NULL,
+    event       VARCHAR     NOT NULL,
+    payload     JSONB       NOT NULL,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    sent_at     TIMESTAMPTZ
+);
+
+CREATE INDEX idx_event_outbox_unsent ON event_outbox (created_at) WHERE sent_at IS NULL;
+CREATE INDEX idx_event_outbox_sent_at ON event_outbox (sent_at) WHERE sent_at IS NOT NULL;
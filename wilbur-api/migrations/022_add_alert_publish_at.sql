@@ -0,0 +1,8 @@
+-- Migration 022: Scheduled alerts
+-- An alert with publish_at set and is_active = false is pending; a background
+-- task activates it once publish_at has passed.
+
+ALTER TABLE alerts ADD COLUMN publish_at TIMESTAMPTZ;
+
+CREATE INDEX idx_alerts_scheduled ON alerts (publish_at)
+    WHERE is_active = false AND publish_at IS NOT NULL;
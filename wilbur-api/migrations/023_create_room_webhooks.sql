@@ -0,0 +1,38 @@
+-- Migration 023: Create outgoing room webhooks and their delivery log
+
+CREATE TABLE room_webhooks (
+    id          UUID        PRIMARY KEY DEFAULT gen_random_uuid(),
+    room_id     UUID        NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+    created_by  UUID        NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    url         TEXT        NOT NULL,
+    secret      VARCHAR     NOT NULL,
+    -- Empty array subscribes to every event
+    event_types TEXT[]      NOT NULL DEFAULT '{}',
+    is_active   BOOLEAN     NOT NULL DEFAULT true,
+    created_at  TIMESTAMPTZ DEFAULT NOW(),
+    updated_at  TIMESTAMPTZ DEFAULT NOW()
+);
+
+CREATE INDEX idx_room_webhooks_room_active ON room_webhooks (room_id) WHERE is_active = true;
+
+CREATE TRIGGER trg_room_webhooks_updated_at
+    BEFORE UPDATE ON room_webhooks
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at();
+
+CREATE TABLE webhook_deliveries (
+    id                  UUID        PRIMARY KEY DEFAULT gen_random_uuid(),
+    webhook_id          UUID        NOT NULL REFERENCES room_webhooks(id) ON DELETE CASCADE,
+    event               VARCHAR     NOT NULL,
+    payload             JSONB       NOT NULL,
+    -- pending | delivered | dead
+    status              VARCHAR     NOT NULL DEFAULT 'pending',
+    attempts            INT         NOT NULL DEFAULT 0,
+    response_status     INT,
+    last_error          TEXT,
+    next_attempt_at     TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    delivered_at        TIMESTAMPTZ,
+    created_at          TIMESTAMPTZ DEFAULT NOW()
+);
+
+CREATE INDEX idx_webhook_deliveries_webhook_created ON webhook_deliveries (webhook_id, created_at DESC);
+CREATE INDEX idx_webhook_deliveries_due ON webhook_deliveries (next_attempt_at) WHERE status = 'pending';
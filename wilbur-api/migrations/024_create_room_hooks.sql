@@ -0,0 +1,21 @@
+-- Migration 024: Create incoming room hooks and bot users
+
+-- Bot users author content posted through incoming hooks and cannot log in
+ALTER TABLE users ADD COLUMN is_bot BOOLEAN NOT NULL DEFAULT false;
+
+CREATE TABLE room_hooks (
+    id           UUID        PRIMARY KEY DEFAULT gen_random_uuid(),
+    room_id      UUID        NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+    bot_user_id  UUID        NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_by   UUID        NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name         VARCHAR(100) NOT NULL,
+    -- SHA-256 of the token; the raw token is only shown once on creation
+    token_hash   VARCHAR     UNIQUE NOT NULL,
+    -- message | alert (alert-capable hooks may also post messages)
+    scope        VARCHAR     NOT NULL DEFAULT 'message' CHECK (scope IN ('message', 'alert')),
+    last_used_at TIMESTAMPTZ,
+    revoked_at   TIMESTAMPTZ,
+    created_at   TIMESTAMPTZ DEFAULT NOW()
+);
+
+CREATE INDEX idx_room_hooks_room ON room_hooks (room_id);
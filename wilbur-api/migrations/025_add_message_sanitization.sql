@@ -0,0 +1,8 @@
+-- Migration 025: Add per-room content sanitization and sanitized message rendering
+
+ALTER TABLE rooms
+    ADD COLUMN sanitize_content BOOLEAN NOT NULL DEFAULT true,
+    ADD COLUMN linkify_urls     BOOLEAN NOT NULL DEFAULT false;
+
+-- HTML-safe rendering of content, NULL when the room has sanitization disabled
+ALTER TABLE chatmessages ADD COLUMN rendered_safe TEXT;
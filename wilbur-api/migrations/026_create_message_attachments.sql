@@ -0,0 +1,11 @@
+-- Migration 026: Link uploaded room files to chat messages
+
+CREATE TABLE message_attachments (
+    message_id  UUID        NOT NULL REFERENCES chatmessages(id) ON DELETE CASCADE,
+    file_id     UUID        NOT NULL REFERENCES room_files(id) ON DELETE CASCADE,
+    position    INT         NOT NULL DEFAULT 0,
+    created_at  TIMESTAMPTZ DEFAULT NOW(),
+    PRIMARY KEY (message_id, file_id)
+);
+
+CREATE INDEX idx_message_attachments_file ON message_attachments (file_id);
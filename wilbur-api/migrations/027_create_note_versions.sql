@@ -0,0 +1,13 @@
+-- Migration 027: Keep prior versions of room notes
+
+CREATE TABLE note_versions (
+    id          UUID        PRIMARY KEY DEFAULT gen_random_uuid(),
+    note_id     UUID        NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+    version     INT         NOT NULL,
+    title       VARCHAR     NOT NULL,
+    content     TEXT        DEFAULT '',
+    -- User whose edit replaced this version
+    edited_by   UUID        REFERENCES users(id) ON DELETE SET NULL,
+    created_at  TIMESTAMPTZ DEFAULT NOW(),
+    UNIQUE (note_id, version)
+);
@@ -0,0 +1,5 @@
+-- Migration 028: Add soft editing locks to room notes
+
+ALTER TABLE notes
+    ADD COLUMN locked_by       UUID REFERENCES users(id) ON DELETE SET NULL,
+    ADD COLUMN lock_expires_at TIMESTAMPTZ;
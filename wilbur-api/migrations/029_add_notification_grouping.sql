@@ -0,0 +1,11 @@
+-- Migration 029: Group repeated notifications into a single counted row
+-- Repeats with the same type and group key fold into the newest unread row,
+-- bumping its count and moving created_at to the latest occurrence.
+
+ALTER TABLE notifications
+    ADD COLUMN group_key VARCHAR,
+    ADD COLUMN count     INT NOT NULL DEFAULT 1;
+
+CREATE INDEX idx_notifications_group_unread
+    ON notifications (user_id, notification_type, group_key)
+    WHERE is_read = false AND group_key IS NOT NULL;
@@ -0,0 +1,12 @@
+-- Migration 030: Constrain notifications.notification_type to a known set of kinds
+
+CREATE TYPE notification_type AS ENUM ('mention', 'dm', 'mod_action', 'poll', 'alert', 'system');
+
+-- Anything written before the enum existed falls back to a generic system notification
+UPDATE notifications
+SET notification_type = 'system'
+WHERE notification_type NOT IN ('mention', 'dm', 'mod_action', 'poll', 'alert', 'system');
+
+ALTER TABLE notifications
+    ALTER COLUMN notification_type TYPE notification_type
+    USING notification_type::notification_type;
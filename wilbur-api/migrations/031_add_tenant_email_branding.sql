@@ -0,0 +1,5 @@
+-- Migration 031: Add transactional email branding to tenants
+
+ALTER TABLE tenants
+    ADD COLUMN email_header_url  TEXT,
+    ADD COLUMN email_footer_text TEXT;
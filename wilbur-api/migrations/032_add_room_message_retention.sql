@@ -0,0 +1,5 @@
+-- Migration 032: Add per-room message retention
+
+ALTER TABLE rooms
+    ADD COLUMN message_retention_days  INT     CHECK (message_retention_days >= 0),
+    ADD COLUMN retention_exempt_pinned BOOLEAN NOT NULL DEFAULT true;
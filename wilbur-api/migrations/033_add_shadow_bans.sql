@@ -0,0 +1,8 @@
+-- Migration 033: Add shadow bans
+-- Messages are stamped at send time so lifting a shadow ban doesn't reveal earlier posts.
+
+ALTER TABLE room_memberships
+    ADD COLUMN shadow_banned BOOLEAN NOT NULL DEFAULT false;
+
+ALTER TABLE chatmessages
+    ADD COLUMN is_shadowed BOOLEAN NOT NULL DEFAULT false;
@@ -0,0 +1,44 @@
+-- Migration 034: Deduplicate content reports and auto-hide heavily reported messages
+
+ALTER TABLE reported_content
+    ADD COLUMN report_count INT NOT NULL DEFAULT 1;
+
+CREATE TABLE report_reporters (
+    report_id   UUID        NOT NULL REFERENCES reported_content(id) ON DELETE CASCADE,
+    reporter_id UUID        NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    reason      TEXT        NOT NULL,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (report_id, reporter_id)
+);
+
+-- Every report keeps its reporter; duplicate pending reports fold into the oldest one
+WITH ranked AS (
+    SELECT id, reporter_id, reason, created_at,
+           CASE WHEN status = 'pending' THEN
+               FIRST_VALUE(id) OVER (
+                   PARTITION BY room_id, content_type, content_id, status = 'pending'
+                   ORDER BY created_at, id
+               )
+           ELSE id END AS keep_id
+    FROM reported_content
+)
+INSERT INTO report_reporters (report_id, reporter_id, reason, created_at)
+SELECT keep_id, reporter_id, reason, COALESCE(created_at, NOW())
+FROM ranked
+ON CONFLICT DO NOTHING;
+
+DELETE FROM reported_content rc
+WHERE NOT EXISTS (SELECT 1 FROM report_reporters rr WHERE rr.report_id = rc.id);
+
+UPDATE reported_content rc
+SET report_count = (SELECT COUNT(*) FROM report_reporters rr WHERE rr.report_id = rc.id);
+
+CREATE UNIQUE INDEX idx_reported_content_open
+    ON reported_content (room_id, content_type, content_id)
+    WHERE status = 'pending';
+
+ALTER TABLE rooms
+    ADD COLUMN report_auto_hide_threshold INT CHECK (report_auto_hide_threshold > 0);
+
+ALTER TABLE chatmessages
+    ADD COLUMN is_hidden BOOLEAN NOT NULL DEFAULT false;
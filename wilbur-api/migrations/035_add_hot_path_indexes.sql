@@ -0,0 +1,35 @@
+-- Migration 035: Indexes for the hot list and lookup queries
+--
+-- Already covered by earlier migrations and left alone:
+--   private_messages (chat_id, created_at)         idx_private_messages_chat_created (016)
+--   notifications (user_id, is_read, created_at)   idx_notifications_user_read_created (015)
+--   refresh_tokens (token_hash)                    UNIQUE constraint (006)
+--
+-- To confirm on a populated database, EXPLAIN the message list
+-- (`WHERE is_deleted = false AND room_id = $1 ORDER BY created_at DESC LIMIT 50`): it should be an
+-- Index Scan using idx_chatmessages_room_live_created with no Sort node, and the active
+-- room list should use idx_rooms_active_created rather than a Seq Scan followed by a Sort.
+
+-- Chat messages: page through live messages in a room without visiting soft-deleted rows.
+-- Supersedes the full (room_id, created_at DESC) index, which duplicates idx_chatmessages_room_created.
+CREATE INDEX IF NOT EXISTS idx_chatmessages_room_live_created
+    ON chatmessages (room_id, created_at DESC) WHERE is_deleted = false;
+DROP INDEX IF EXISTS idx_chatmessages_room_created_desc;
+
+-- Room memberships: list a room's members, or its active moderators, by status.
+-- Supersedes idx_room_memberships_room_id, which is a prefix of it.
+CREATE INDEX IF NOT EXISTS idx_room_memberships_room_status_role
+    ON room_memberships (room_id, status, role);
+DROP INDEX IF EXISTS idx_room_memberships_room_id;
+
+-- Notifications: newest-first listing without an is_read filter
+CREATE INDEX IF NOT EXISTS idx_notifications_user_created
+    ON notifications (user_id, created_at DESC);
+
+-- Rooms: the public list only shows active rooms, newest first
+CREATE INDEX IF NOT EXISTS idx_rooms_active_created
+    ON rooms (created_at DESC) WHERE is_active = true;
+
+-- Refresh tokens: revoke a user's live tokens on logout-all / password change
+CREATE INDEX IF NOT EXISTS idx_refresh_tokens_user_live
+    ON refresh_tokens (user_id) WHERE revoked = false;
@@ -0,0 +1,10 @@
+-- Migration 036: Pending email address changes awaiting verification of the new address
+
+CREATE TABLE email_change_requests (
+    id          UUID        PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id     UUID        UNIQUE NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    new_email   VARCHAR     NOT NULL,
+    token_hash  VARCHAR     UNIQUE NOT NULL,
+    expires_at  TIMESTAMPTZ NOT NULL,
+    created_at  TIMESTAMPTZ DEFAULT NOW()
+);
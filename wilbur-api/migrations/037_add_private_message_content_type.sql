@@ -0,0 +1,4 @@
+-- Migration 037: Content type for direct messages, matching room chat messages
+
+ALTER TABLE private_messages
+    ADD COLUMN content_type content_type NOT NULL DEFAULT 'text';
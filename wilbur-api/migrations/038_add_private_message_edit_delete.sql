@@ -0,0 +1,8 @@
+-- Migration 038: Edit and soft-delete support for direct messages
+
+ALTER TABLE private_messages
+    ADD COLUMN is_deleted BOOLEAN     NOT NULL DEFAULT false,
+    ADD COLUMN deleted_at TIMESTAMPTZ,
+    ADD COLUMN updated_at TIMESTAMPTZ DEFAULT NOW();
+
+UPDATE private_messages SET updated_at = created_at;
@@ -0,0 +1,9 @@
+-- Migration 039: Per-participant conversation state (archiving) for direct messages
+
+CREATE TABLE private_chat_states (
+    chat_id     UUID        NOT NULL REFERENCES private_chats(id) ON DELETE CASCADE,
+    user_id     UUID        NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    archived    BOOLEAN     NOT NULL DEFAULT false,
+    updated_at  TIMESTAMPTZ DEFAULT NOW(),
+    PRIMARY KEY (chat_id, user_id)
+);
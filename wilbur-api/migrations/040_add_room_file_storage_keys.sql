@@ -0,0 +1,35 @@
+-- Migration 040: Store object keys for room files and queue object deletion when rows go away
+
+ALTER TABLE room_files ADD COLUMN storage_key TEXT;
+
+-- Existing rows were all written under the rooms/{room}/files/{id}/{name} layout
+UPDATE room_files
+SET storage_key = 'rooms/' || room_id || '/files/' || id || '/' || file_name;
+
+ALTER TABLE room_files ALTER COLUMN storage_key SET NOT NULL;
+
+-- Objects whose rows were deleted, removed from storage by a background worker
+CREATE TABLE storage_deletions (
+    id              UUID        PRIMARY KEY DEFAULT gen_random_uuid(),
+    storage_key     TEXT        NOT NULL,
+    attempts        INTEGER     NOT NULL DEFAULT 0,
+    last_error      TEXT,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_storage_deletions_next_attempt ON storage_deletions (next_attempt_at);
+
+-- Every way a room file row disappears (direct delete, retention purge, room or user
+-- cascade) queues its object, in the same transaction as the delete
+CREATE OR REPLACE FUNCTION queue_room_file_deletion()
+RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO storage_deletions (storage_key) VALUES (OLD.storage_key);
+    RETURN OLD;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trg_room_files_queue_deletion
+    AFTER DELETE ON room_files
+    FOR EACH ROW EXECUTE FUNCTION queue_room_file_deletion();
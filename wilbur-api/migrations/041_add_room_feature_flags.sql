@@ -0,0 +1,4 @@
+-- Migration 041: Per-room feature flag overrides
+
+-- Feature name -> enabled; keys absent here fall back to the tenant, then the server default
+ALTER TABLE rooms ADD COLUMN feature_flags JSONB NOT NULL DEFAULT '{}';
@@ -0,0 +1,26 @@
+-- Migration 042: Denormalized latest-message metadata on direct message conversations
+
+ALTER TABLE private_chats
+    ADD COLUMN last_message_id        UUID,
+    ADD COLUMN last_message_sender_id UUID,
+    ADD COLUMN last_message_preview   TEXT,
+    ADD COLUMN last_message_at        TIMESTAMPTZ;
+
+UPDATE private_chats c SET
+    last_message_id        = lm.id,
+    last_message_sender_id = lm.sender_id,
+    last_message_preview   = LEFT(lm.content, 140),
+    last_message_at        = lm.created_at
+FROM (
+    SELECT DISTINCT ON (chat_id) chat_id, id, sender_id, content, created_at
+    FROM private_messages
+    WHERE is_deleted = false
+    ORDER BY chat_id, created_at DESC
+) lm
+WHERE lm.chat_id = c.id;
+
+-- Conversation lists sort each participant's chats by latest activity
+CREATE INDEX idx_private_chats_one_activity
+    ON private_chats (participant_one, (COALESCE(last_message_at, created_at)) DESC);
+CREATE INDEX idx_private_chats_two_activity
+    ON private_chats (participant_two, (COALESCE(last_message_at, created_at)) DESC);
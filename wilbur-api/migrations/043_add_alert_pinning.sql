@@ -0,0 +1,9 @@
+-- Migration 043: Pinned (sticky) alerts
+-- Pinned alerts are listed ahead of the rest, most recently pinned first.
+
+ALTER TABLE alerts
+    ADD COLUMN is_pinned BOOLEAN     NOT NULL DEFAULT false,
+    ADD COLUMN pinned_at TIMESTAMPTZ;
+
+CREATE INDEX idx_alerts_room_pinned ON alerts (room_id, pinned_at DESC)
+    WHERE is_pinned = true;
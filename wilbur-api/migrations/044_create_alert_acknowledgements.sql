@@ -0,0 +1,8 @@
+-- Migration 044: Per-user acknowledgements of room alerts
+
+CREATE TABLE alert_acknowledgements (
+    alert_id    UUID        NOT NULL REFERENCES alerts(id) ON DELETE CASCADE,
+    user_id     UUID        NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (alert_id, user_id)
+);
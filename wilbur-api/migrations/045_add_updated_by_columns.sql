@@ -0,0 +1,10 @@
+-- Migration 045: Record who last changed rooms, tenants, and memberships
+
+ALTER TABLE rooms
+    ADD COLUMN updated_by UUID REFERENCES users(id) ON DELETE SET NULL;
+
+ALTER TABLE tenants
+    ADD COLUMN updated_by UUID REFERENCES users(id) ON DELETE SET NULL;
+
+ALTER TABLE room_memberships
+    ADD COLUMN updated_by UUID REFERENCES users(id) ON DELETE SET NULL;
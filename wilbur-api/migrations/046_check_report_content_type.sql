@@ -0,0 +1,4 @@
+-- Migration 046: Reports may only target messages or users
+
+ALTER TABLE reported_content
+    ADD CONSTRAINT chk_reported_content_type CHECK (content_type IN ('message', 'user'));
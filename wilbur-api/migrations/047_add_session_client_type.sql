@@ -0,0 +1,7 @@
+-- Migration 047: Record which client type (and so which token lifetimes) a session uses
+
+ALTER TABLE sessions
+    ADD COLUMN client_type VARCHAR(32) NOT NULL DEFAULT 'web';
+
+ALTER TABLE refresh_tokens
+    ADD COLUMN client_type VARCHAR(32) NOT NULL DEFAULT 'web';
@@ -0,0 +1,14 @@
+-- Migration 048: Keep track of untouched originals of re-encoded room file images
+
+ALTER TABLE room_files ADD COLUMN original_storage_key TEXT;
+
+CREATE OR REPLACE FUNCTION queue_room_file_deletion()
+RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO storage_deletions (storage_key) VALUES (OLD.storage_key);
+    IF OLD.original_storage_key IS NOT NULL THEN
+        INSERT INTO storage_deletions (storage_key) VALUES (OLD.original_storage_key);
+    END IF;
+    RETURN OLD;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,42 @@
+-- Migration 049: Change and deletion timestamps for incremental sync
+
+ALTER TABLE alerts
+    ADD COLUMN updated_at TIMESTAMPTZ,
+    ADD COLUMN deleted_at TIMESTAMPTZ;
+
+UPDATE alerts SET updated_at = COALESCE(created_at, NOW());
+
+-- Deleted and cancelled alerts are inactive without a pending publish time
+UPDATE alerts SET deleted_at = updated_at WHERE is_active = false AND publish_at IS NULL;
+
+ALTER TABLE alerts
+    ALTER COLUMN updated_at SET DEFAULT NOW(),
+    ALTER COLUMN updated_at SET NOT NULL;
+
+CREATE TRIGGER trg_alerts_updated_at
+    BEFORE UPDATE ON alerts
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at();
+
+ALTER TABLE rooms ADD COLUMN deleted_at TIMESTAMPTZ;
+
+UPDATE rooms SET deleted_at = COALESCE(updated_at, NOW()) WHERE is_active = false;
+
+-- Deleting a notification now leaves a tombstone instead of removing the row
+ALTER TABLE notifications
+    ADD COLUMN updated_at TIMESTAMPTZ,
+    ADD COLUMN deleted_at TIMESTAMPTZ;
+
+UPDATE notifications SET updated_at = COALESCE(created_at, NOW());
+
+ALTER TABLE notifications
+    ALTER COLUMN updated_at SET DEFAULT NOW(),
+    ALTER COLUMN updated_at SET NOT NULL;
+
+CREATE TRIGGER trg_notifications_updated_at
+    BEFORE UPDATE ON notifications
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at();
+
+CREATE INDEX idx_chatmessages_room_updated ON chatmessages (room_id, updated_at);
+CREATE INDEX idx_alerts_room_updated ON alerts (room_id, updated_at);
+CREATE INDEX idx_rooms_updated ON rooms (updated_at);
+CREATE INDEX idx_notifications_user_updated ON notifications (user_id, updated_at);
@@ -0,0 +1,4 @@
+-- Migration 050: Per-room minimum roles for posting capabilities
+
+-- Capability name -> minimum member role; keys absent here use the built-in default
+ALTER TABLE rooms ADD COLUMN permissions JSONB NOT NULL DEFAULT '{}';
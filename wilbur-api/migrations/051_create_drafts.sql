@@ -0,0 +1,14 @@
+-- Migration 051: Private per-room drafts of messages and alerts
+
+CREATE TYPE draft_kind AS ENUM ('message', 'alert');
+
+-- One draft per author, room, and kind; saving again replaces it
+CREATE TABLE drafts (
+    user_id     UUID        NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    room_id     UUID        NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+    kind        draft_kind  NOT NULL,
+    content     JSONB       NOT NULL,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, room_id, kind)
+);
@@ -0,0 +1,4 @@
+-- Migration 052: When each user was last active
+
+-- Updated at most once a minute per user from authenticated requests and WebSocket activity
+ALTER TABLE users ADD COLUMN last_seen_at TIMESTAMPTZ;
@@ -0,0 +1,18 @@
+-- Migration 053: Tenant each user belongs to, for tenant isolation
+
+ALTER TABLE users ADD COLUMN tenant_id UUID REFERENCES tenants(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_users_tenant_id ON users (tenant_id);
+
+-- Existing users whose rooms all belong to a single tenant join that tenant
+UPDATE users u
+SET tenant_id = t.tenant_id
+FROM (
+    SELECT m.user_id, MIN(r.tenant_id::text)::uuid AS tenant_id
+    FROM room_memberships m
+    JOIN rooms r ON r.id = m.room_id
+    WHERE r.tenant_id IS NOT NULL
+    GROUP BY m.user_id
+    HAVING COUNT(DISTINCT r.tenant_id) = 1
+) t
+WHERE u.id = t.user_id;
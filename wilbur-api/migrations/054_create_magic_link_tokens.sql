@@ -0,0 +1,9 @@
+-- Migration 054: Single-use passwordless login links
+
+CREATE TABLE magic_link_tokens (
+    id          UUID        PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id     UUID        UNIQUE NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token_hash  VARCHAR     UNIQUE NOT NULL,
+    expires_at  TIMESTAMPTZ NOT NULL,
+    created_at  TIMESTAMPTZ DEFAULT NOW()
+);
@@ -0,0 +1,13 @@
+-- Migration 055: Per-user room ordering, hiding, and muting
+
+CREATE TABLE user_room_preferences (
+    user_id     UUID        NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    room_id     UUID        NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+    -- NULL sorts after every explicitly ordered room
+    sort_order  INTEGER,
+    is_hidden   BOOLEAN     NOT NULL DEFAULT false,
+    is_muted    BOOLEAN     NOT NULL DEFAULT false,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, room_id)
+);
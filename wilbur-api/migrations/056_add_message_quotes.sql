@@ -0,0 +1,8 @@
+-- Migration 056: Let a message quote an earlier message in the same room
+-- Hard-deleting the original only drops the reference; the quoting message stays.
+
+ALTER TABLE chatmessages
+    ADD COLUMN quoted_message_id UUID REFERENCES chatmessages(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_chatmessages_quoted ON chatmessages (quoted_message_id)
+    WHERE quoted_message_id IS NOT NULL;
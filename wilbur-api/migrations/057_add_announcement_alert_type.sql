@@ -0,0 +1,4 @@
+-- Migration 057: General announcements for non-trading rooms
+-- Announcements carry a body and no ticker or price fields; the API enforces this.
+
+ALTER TYPE alert_type ADD VALUE IF NOT EXISTS 'announcement';
@@ -0,0 +1,5 @@
+-- Migration 058: Per-user control over who may open a DM with them
+
+CREATE TYPE dm_policy AS ENUM ('anyone', 'shared_rooms', 'nobody');
+
+ALTER TABLE users ADD COLUMN dm_policy dm_policy NOT NULL DEFAULT 'anyone';
@@ -0,0 +1,6 @@
+-- Migration 059: Trigram index so user search can match anywhere in a display name
+
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX IF NOT EXISTS idx_users_display_name_trgm
+    ON users USING GIN (display_name gin_trgm_ops);
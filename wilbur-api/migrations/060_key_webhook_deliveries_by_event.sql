@@ -0,0 +1,7 @@
+-- Migration 060: Key webhook deliveries by the outbox event they carry
+-- Deliveries are queued in the same transaction as the event, and the key keeps a
+-- webhook from getting the same event twice.
+
+ALTER TABLE webhook_deliveries ADD COLUMN event_id UUID;
+
+CREATE UNIQUE INDEX idx_webhook_deliveries_webhook_event ON webhook_deliveries (webhook_id, event_id);
@@ -5,7 +5,7 @@ use crate::models::alert::Alert;
 
 pub async fn list_by_room(pool: &PgPool, room_id: Uuid) -> Result<Vec<Alert>, sqlx::Error> {
     sqlx::query_as::<_, Alert>(
-        "SELECT * FROM alerts WHERE room_id = $1 AND is_active = true ORDER BY created_at DESC LIMIT 200",
+        "SELECT * FROM alerts WHERE room_id = $1 AND is_active = true ORDER BY is_pinned DESC, pinned_at DESC, created_at DESC LIMIT 200",
     )
     .bind(room_id)
     .fetch_all(pool)
@@ -47,7 +47,7 @@ pub async fn create(
 }
 
 pub async fn delete(pool: &PgPool, id: Uuid) -> Result<(), sqlx::Error> {
-    sqlx::query("UPDATE alerts SET is_active = false WHERE id = $1")
+    sqlx::query("UPDATE alerts SET is_active = false, deleted_at = NOW() WHERE id = $1")
         .bind(id)
         .execute(pool)
         .await?;
@@ -10,7 +10,7 @@ pub async fn list_by_user(
     offset: i64,
 ) -> Result<Vec<Notification>, sqlx::Error> {
     sqlx::query_as::<_, Notification>(
-        "SELECT * FROM notifications WHERE user_id = $1 ORDER BY created_at DESC LIMIT $2 OFFSET $3",
+        "SELECT * FROM notifications WHERE user_id = $1 AND deleted_at IS NULL ORDER BY created_at DESC LIMIT $2 OFFSET $3",
     )
     .bind(user_id)
     .bind(limit)
@@ -36,7 +36,7 @@ pub async fn mark_all_read(pool: &PgPool, user_id: Uuid) -> Result<(), sqlx::Err
 }
 
 pub async fn delete(pool: &PgPool, id: Uuid) -> Result<(), sqlx::Error> {
-    sqlx::query("DELETE FROM notifications WHERE id = $1")
+    sqlx::query("UPDATE notifications SET deleted_at = NOW() WHERE id = $1")
         .bind(id)
         .execute(pool)
         .await?;
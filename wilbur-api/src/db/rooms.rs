@@ -68,7 +68,7 @@ pub async fn update(
 }
 
 pub async fn delete(pool: &PgPool, id: Uuid) -> Result<(), sqlx::Error> {
-    sqlx::query("UPDATE rooms SET is_active = false WHERE id = $1")
+    sqlx::query("UPDATE rooms SET is_active = false, deleted_at = NOW() WHERE id = $1")
         .bind(id)
         .execute(pool)
         .await?;
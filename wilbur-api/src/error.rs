@@ -1,23 +1,27 @@
-use axum::http::StatusCode;
+use axum::http::{header, StatusCode};
 use axum::response::{IntoResponse, Response};
-use serde_json::json;
+use axum::Json;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::i18n::{Locale, Message};
 
 #[derive(Debug, thiserror::Error)]
 pub enum AppError {
     #[error("Not found: {0}")]
-    NotFound(String),
+    NotFound(Message),
 
     #[error("Unauthorized: {0}")]
-    Unauthorized(String),
+    Unauthorized(Message),
 
     #[error("Forbidden: {0}")]
-    Forbidden(String),
+    Forbidden(Message),
 
     #[error("Bad request: {0}")]
-    BadRequest(String),
+    BadRequest(Message),
 
     #[error("Conflict: {0}")]
-    Conflict(String),
+    Conflict(Message),
 
     #[error("Internal error: {0}")]
     Internal(String),
@@ -26,7 +30,29 @@ pub enum AppError {
     Database(#[from] sqlx::Error),
 
     #[error("Validation error: {0}")]
-    Validation(String),
+    Validation(Message),
+
+    #[error("Too many requests: {0}")]
+    TooManyRequests(Message),
+
+    /// A dependency (such as object storage) is down or timed out; the client should retry.
+    #[error("Service unavailable: {0}")]
+    ServiceUnavailable(String),
+
+    /// The request ran past its time limit and was abandoned.
+    #[error("Timed out: {0}")]
+    Timeout(String),
+}
+
+/// Seconds clients are told to wait before retrying a 503.
+const RETRY_AFTER_SECS: &str = "30";
+
+/// JSON body of every error response.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorBody {
+    /// Human-readable description in the request's `Accept-Language` where a translation
+    /// exists; server-side failures are reported generically.
+    pub error: String,
 }
 
 impl IntoResponse for AppError {
@@ -38,25 +64,112 @@ impl IntoResponse for AppError {
             AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg.clone()),
             AppError::Conflict(msg) => (StatusCode::CONFLICT, msg.clone()),
             AppError::Validation(msg) => (StatusCode::UNPROCESSABLE_ENTITY, msg.clone()),
-            AppError::Internal(msg) => {
-                tracing::error!("Internal error: {msg}");
+            AppError::TooManyRequests(msg) => (StatusCode::TOO_MANY_REQUESTS, msg.clone()),
+            AppError::ServiceUnavailable(msg) => {
+                tracing::warn!("Service unavailable: {msg}");
                 (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "Internal server error".to_string(),
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    Message::new("Service temporarily unavailable"),
                 )
             }
-            AppError::Database(err) => {
-                tracing::error!("Database error: {err}");
+            AppError::Timeout(msg) => {
+                tracing::warn!("Request timed out: {msg}");
+                (
+                    StatusCode::GATEWAY_TIMEOUT,
+                    Message::new("Request timed out"),
+                )
+            }
+            AppError::Internal(msg) => {
+                tracing::error!("Internal error: {msg}");
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    "Internal server error".to_string(),
+                    Message::new("Internal server error"),
                 )
             }
+            AppError::Database(err) => match classify_db_error(err) {
+                Some((status, message)) => {
+                    if status == StatusCode::SERVICE_UNAVAILABLE {
+                        tracing::warn!("Database unavailable: {err}");
+                    } else {
+                        tracing::debug!("Rejected by database constraint: {err}");
+                    }
+                    (status, Message::new(message))
+                }
+                None => {
+                    tracing::error!("Database error: {err}");
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Message::new("Internal server error"),
+                    )
+                }
+            },
         };
 
-        let body = json!({ "error": message });
-        (status, axum::Json(body)).into_response()
+        // Server errors are logged above with their cause. Client errors are logged too, so
+        // rejections are searchable under the request span; validation messages can echo
+        // submitted values, so theirs is left out.
+        if status.is_client_error() {
+            if matches!(self, AppError::Validation(_)) {
+                tracing::warn!(status = status.as_u16(), "Request rejected: invalid input");
+            } else {
+                tracing::warn!(status = status.as_u16(), "Request rejected: {message}");
+            }
+        }
+
+        let error = message.render(Locale::current());
+        let mut response = (status, Json(ErrorBody { error })).into_response();
+        if status == StatusCode::SERVICE_UNAVAILABLE {
+            response.headers_mut().insert(
+                header::RETRY_AFTER,
+                header::HeaderValue::from_static(RETRY_AFTER_SECS),
+            );
+        }
+        response
     }
 }
 
+/// Status and generic message for database errors that aren't server bugs: bad client
+/// input (an invalid enum or number, an oversized value, a duplicate, a failed CHECK, a
+/// reference to a missing row) and an unreachable database. These would otherwise
+/// surface as a 500; details stay in the logs.
+fn classify_db_error(err: &sqlx::Error) -> Option<(StatusCode, &'static str)> {
+    match err {
+        sqlx::Error::PoolTimedOut | sqlx::Error::PoolClosed | sqlx::Error::Io(_) => {
+            return Some((
+                StatusCode::SERVICE_UNAVAILABLE,
+                "Service temporarily unavailable",
+            ))
+        }
+        _ => {}
+    }
+
+    let code = err.as_database_error()?.code()?;
+    let classified = match code.as_ref() {
+        // invalid_text_representation (bad enum/uuid/number literal), numeric_value_out_of_range,
+        // invalid_datetime_format, datetime_field_overflow
+        "22P02" | "22003" | "22007" | "22008" => {
+            (StatusCode::BAD_REQUEST, "Invalid value in request")
+        }
+        // string_data_right_truncation
+        "22001" => (
+            StatusCode::BAD_REQUEST,
+            "A value in the request is too long",
+        ),
+        // check_violation
+        "23514" => (
+            StatusCode::BAD_REQUEST,
+            "A value in the request is out of range",
+        ),
+        // foreign_key_violation
+        "23503" => (
+            StatusCode::BAD_REQUEST,
+            "A referenced resource does not exist",
+        ),
+        // unique_violation
+        "23505" => (StatusCode::CONFLICT, "Resource already exists"),
+        _ => return None,
+    };
+    Some(classified)
+}
+
 pub type AppResult<T> = Result<T, AppError>;
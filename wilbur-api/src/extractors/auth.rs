@@ -7,6 +7,7 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::error::AppError;
+use crate::middleware::request_span;
 use crate::state::AppState;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -22,7 +23,6 @@ pub struct Claims {
 #[derive(Debug, Clone)]
 pub struct AuthUser {
     pub id: Uuid,
-    pub email: String,
     pub role: String,
 }
 
@@ -48,11 +48,13 @@ impl FromRequestParts<Arc<AppState>> for AuthUser {
             &DecodingKey::from_secret(state.config.jwt_secret.as_bytes()),
             &Validation::default(),
         )
-        .map_err(|e| AppError::Unauthorized(format!("Invalid token: {e}")))?;
+        .map_err(|e| AppError::Unauthorized(format!("Invalid token: {e}").into()))?;
+
+        request_span::record_user(token_data.claims.sub);
+        state.touch_last_seen(token_data.claims.sub);
 
         Ok(AuthUser {
             id: token_data.claims.sub,
-            email: token_data.claims.email,
             role: token_data.claims.role,
         })
     }
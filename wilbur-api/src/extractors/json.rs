@@ -0,0 +1,77 @@
+use axum::extract::{FromRequest, Request};
+use axum::http::{header, HeaderMap};
+use axum::response::{IntoResponse, Response};
+use bytes::Bytes;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::error::{AppError, ErrorBody};
+
+/// Drop-in replacement for `axum::Json`. Bodies that can't be read as `T` are rejected with
+/// a 400 in the usual error envelope, naming the offending field path, instead of axum's
+/// plain-text rejection. As a response it behaves exactly like `axum::Json`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Json<T>(pub T);
+
+impl<T: DeserializeOwned, S: Send + Sync> FromRequest<S> for Json<T> {
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        if !has_json_content_type(req.headers()) {
+            return Err(AppError::BadRequest(
+                "Expected a JSON body with Content-Type: application/json".into(),
+            )
+            .into_response());
+        }
+
+        // Body-read failures (such as exceeding the size limit) keep their own status
+        let bytes = Bytes::from_request(req, state).await.map_err(|rejection| {
+            let status = rejection.status();
+            let body = ErrorBody {
+                error: rejection.body_text(),
+            };
+            (status, axum::Json(body)).into_response()
+        })?;
+
+        parse(&bytes).map(Json).map_err(IntoResponse::into_response)
+    }
+}
+
+impl<T: Serialize> IntoResponse for Json<T> {
+    fn into_response(self) -> Response {
+        axum::Json(self.0).into_response()
+    }
+}
+
+/// Deserialize a body, reporting where in the document it went wrong.
+fn parse<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, AppError> {
+    let mut deserializer = serde_json::Deserializer::from_slice(bytes);
+    let value = serde_path_to_error::deserialize(&mut deserializer).map_err(|e| {
+        let path = e.path().to_string();
+        let inner = e.into_inner();
+        if inner.is_syntax() || inner.is_eof() {
+            AppError::BadRequest(format!("Malformed JSON body: {inner}").into())
+        } else if path == "." {
+            AppError::BadRequest(format!("Invalid JSON body: {inner}").into())
+        } else {
+            AppError::BadRequest(format!("Invalid JSON body at `{path}`: {inner}").into())
+        }
+    })?;
+    deserializer
+        .end()
+        .map_err(|e| AppError::BadRequest(format!("Malformed JSON body: {e}").into()))?;
+
+    Ok(value)
+}
+
+/// `application/json` or any `+json` media type, as `axum::Json` accepts.
+fn has_json_content_type(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<mime::Mime>().ok())
+        .is_some_and(|mime| {
+            mime.type_() == "application"
+                && (mime.subtype() == "json" || mime.suffix().is_some_and(|s| s == "json"))
+        })
+}
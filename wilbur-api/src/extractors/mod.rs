@@ -1,3 +1,6 @@
 pub mod auth;
+pub mod json;
 pub mod pagination;
 pub mod room_access;
+pub mod soft_delete;
+pub mod sync;
@@ -1,23 +1,170 @@
+use std::sync::Arc;
+
+use axum::extract::{FromRequestParts, Query};
+use axum::http::request::Parts;
 use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::error::AppError;
+use crate::state::AppState;
+
+/// Default page size when the client does not specify `per_page`.
+const DEFAULT_PER_PAGE: u32 = 50;
+
+/// Query parameters accepted by paginated endpoints, before validation.
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RawPaginationParams {
+    /// 1-based page number (default 1).
+    page: Option<u32>,
+    /// Items per page (default 50, clamped to the configured maximum).
+    per_page: Option<u32>,
+}
+
+/// Sort direction for list endpoints that allow choosing it.
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl SortOrder {
+    /// SQL keyword for this direction. Never derived from raw user input.
+    pub fn as_sql(self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
+}
 
-#[derive(Debug, Deserialize)]
+/// Validated pagination parameters.
+///
+/// `page` is 1-based. Zero values are rejected with 400; a `per_page` above the
+/// configured maximum is clamped to that maximum.
+#[derive(Debug, Clone, Copy)]
 pub struct PaginationParams {
-    pub page: Option<u32>,
-    pub per_page: Option<u32>,
+    pub page: u32,
+    per_page: u32,
 }
 
 impl PaginationParams {
+    /// Build pagination bounds from optional raw values, enforcing `max_per_page`.
+    pub fn new(
+        page: Option<u32>,
+        per_page: Option<u32>,
+        max_per_page: u32,
+    ) -> Result<Self, AppError> {
+        if page == Some(0) {
+            return Err(AppError::BadRequest("page must be at least 1".into()));
+        }
+        if per_page == Some(0) {
+            return Err(AppError::BadRequest("per_page must be at least 1".into()));
+        }
+
+        let max_per_page = max_per_page.max(1);
+        Ok(Self {
+            page: page.unwrap_or(1),
+            per_page: per_page.unwrap_or(DEFAULT_PER_PAGE).min(max_per_page),
+        })
+    }
+
     pub fn offset(&self) -> i64 {
-        let page = self.page.unwrap_or(1).max(1);
-        let per_page = self.per_page();
-        ((page - 1) * per_page) as i64
+        (self.page as i64 - 1) * self.per_page as i64
     }
 
     pub fn per_page(&self) -> u32 {
-        self.per_page.unwrap_or(50).min(100)
+        self.per_page
     }
 
     pub fn limit(&self) -> i64 {
         self.per_page() as i64
     }
 }
+
+impl FromRequestParts<Arc<AppState>> for PaginationParams {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let Query(raw) = Query::<RawPaginationParams>::from_request_parts(parts, state)
+            .await
+            .map_err(|e| {
+                AppError::BadRequest(
+                    format!("Invalid pagination parameters: {}", e.body_text()).into(),
+                )
+            })?;
+
+        Self::new(raw.page, raw.per_page, state.config.pagination_max_per_page)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use axum::response::IntoResponse;
+
+    use super::*;
+
+    fn status(result: Result<PaginationParams, AppError>) -> StatusCode {
+        result.unwrap_err().into_response().status()
+    }
+
+    #[test]
+    fn zero_page_is_rejected() {
+        assert_eq!(
+            status(PaginationParams::new(Some(0), None, 100)),
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[test]
+    fn zero_per_page_is_rejected() {
+        assert_eq!(
+            status(PaginationParams::new(None, Some(0), 100)),
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[test]
+    fn defaults_to_first_page() {
+        let params = PaginationParams::new(None, None, 100).unwrap();
+        assert_eq!(params.page, 1);
+        assert_eq!(params.per_page(), DEFAULT_PER_PAGE);
+        assert_eq!(params.offset(), 0);
+    }
+
+    #[test]
+    fn per_page_is_clamped_to_the_maximum() {
+        let at_max = PaginationParams::new(None, Some(100), 100).unwrap();
+        assert_eq!(at_max.per_page(), 100);
+
+        let above_max = PaginationParams::new(None, Some(101), 100).unwrap();
+        assert_eq!(above_max.per_page(), 100);
+
+        let huge = PaginationParams::new(None, Some(100_000), 100).unwrap();
+        assert_eq!(huge.limit(), 100);
+    }
+
+    #[test]
+    fn default_per_page_is_clamped_to_a_smaller_maximum() {
+        let params = PaginationParams::new(None, None, 20).unwrap();
+        assert_eq!(params.per_page(), 20);
+    }
+
+    #[test]
+    fn zero_maximum_still_allows_one_item() {
+        let params = PaginationParams::new(None, Some(10), 0).unwrap();
+        assert_eq!(params.per_page(), 1);
+    }
+
+    #[test]
+    fn offset_uses_the_clamped_page_size() {
+        let params = PaginationParams::new(Some(3), Some(500), 100).unwrap();
+        assert_eq!(params.offset(), 200);
+    }
+}
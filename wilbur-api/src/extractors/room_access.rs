@@ -1,24 +1,101 @@
-use sqlx::PgPool;
+use std::sync::Arc;
+
+use axum::extract::{FromRequestParts, RawPathParams};
+use axum::http::request::Parts;
 use uuid::Uuid;
 
 use crate::{
     error::{AppError, AppResult},
+    extractors::auth::AuthUser,
+    middleware::request_span,
     models::membership::{MemberRole, MemberStatus, RoomMembership},
+    services::tenant_scope::require_room_tenant,
+    state::AppState,
 };
 
-/// Verify the user has an active membership in the given room.
-/// Returns the `RoomMembership` on success or `AppError::Forbidden` if not a member.
+/// The caller's active membership in the room named by the route's `{room_id}` path
+/// parameter. Declaring it in a handler's signature performs the membership check.
+pub struct RoomMember(pub RoomMembership);
+
+/// Like [`RoomMember`], but the caller must be a host or moderator of the room.
+pub struct RoomModerator(pub RoomMembership);
+
+/// Like [`RoomMember`], but the caller must be the room's host.
+pub struct RoomHost(pub RoomMembership);
+
+impl FromRequestParts<Arc<AppState>> for RoomMember {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let (user_id, room_id) = caller_and_room(parts, state).await?;
+        require_room_member(state, user_id, room_id).await.map(Self)
+    }
+}
+
+impl FromRequestParts<Arc<AppState>> for RoomModerator {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let (user_id, room_id) = caller_and_room(parts, state).await?;
+        require_room_moderator(state, user_id, room_id)
+            .await
+            .map(Self)
+    }
+}
+
+impl FromRequestParts<Arc<AppState>> for RoomHost {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let (user_id, room_id) = caller_and_room(parts, state).await?;
+        require_room_host(state, user_id, room_id).await.map(Self)
+    }
+}
+
+/// The authenticated caller and the `{room_id}` path parameter. A route without that
+/// parameter is a wiring mistake, so it fails as a server error rather than a 4xx.
+async fn caller_and_room(parts: &mut Parts, state: &Arc<AppState>) -> AppResult<(Uuid, Uuid)> {
+    let auth_user = AuthUser::from_request_parts(parts, state).await?;
+
+    let params = RawPathParams::from_request_parts(parts, state)
+        .await
+        .map_err(|e| AppError::Internal(format!("Room access check without path params: {e}")))?;
+    let room_id = params
+        .iter()
+        .find(|(name, _)| *name == "room_id")
+        .map(|(_, value)| value)
+        .ok_or_else(|| AppError::Internal("Room access check on a route without {room_id}".into()))?
+        .parse()
+        .map_err(|_| AppError::BadRequest("Invalid room ID".into()))?;
+    request_span::record_room(room_id);
+
+    Ok((auth_user.id, room_id))
+}
+
+/// Verify the user has an active membership in the given room, and that the room is within
+/// their tenant scope. Returns the `RoomMembership` on success or `AppError::Forbidden`.
 pub async fn require_room_member(
-    pool: &PgPool,
+    state: &AppState,
     user_id: Uuid,
     room_id: Uuid,
 ) -> AppResult<RoomMembership> {
+    require_room_tenant(state, user_id, room_id).await?;
+
     let membership = sqlx::query_as::<_, RoomMembership>(
         "SELECT * FROM room_memberships WHERE user_id = $1 AND room_id = $2",
     )
     .bind(user_id)
     .bind(room_id)
-    .fetch_optional(pool)
+    .fetch_optional(&state.pool)
     .await?
     .ok_or_else(|| AppError::Forbidden("You are not a member of this room".into()))?;
 
@@ -34,11 +111,11 @@ pub async fn require_room_member(
 /// Verify the user is a host or moderator in the given room.
 /// Returns the `RoomMembership` on success or `AppError::Forbidden` if insufficient role.
 pub async fn require_room_moderator(
-    pool: &PgPool,
+    state: &AppState,
     user_id: Uuid,
     room_id: Uuid,
 ) -> AppResult<RoomMembership> {
-    let membership = require_room_member(pool, user_id, room_id).await?;
+    let membership = require_room_member(state, user_id, room_id).await?;
 
     match membership.role {
         MemberRole::Host | MemberRole::Moderator => Ok(membership),
@@ -51,11 +128,11 @@ pub async fn require_room_moderator(
 /// Verify the user is the host of the given room.
 /// Returns the `RoomMembership` on success or `AppError::Forbidden` if not host.
 pub async fn require_room_host(
-    pool: &PgPool,
+    state: &AppState,
     user_id: Uuid,
     room_id: Uuid,
 ) -> AppResult<RoomMembership> {
-    let membership = require_room_member(pool, user_id, room_id).await?;
+    let membership = require_room_member(state, user_id, room_id).await?;
 
     if membership.role != MemberRole::Host {
         return Err(AppError::Forbidden(
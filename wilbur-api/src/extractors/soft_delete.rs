@@ -0,0 +1,79 @@
+//! Soft-delete convention.
+//!
+//! Deleting a room or an alert clears its `is_active` flag; deleting a chat message sets
+//! `is_deleted`. Reads leave those rows out -- lists filter them and single-resource
+//! lookups answer 404 -- unless the caller passes `?include_deleted=true` and is a global
+//! admin or, for room-scoped resources, one of the room's hosts or moderators.
+//!
+//! Exceptions: deleted direct messages stay in the conversation as blanked placeholders,
+//! and inactive media tracks have ended rather than been deleted, so they are never listed.
+
+use serde::Deserialize;
+use sqlx::PgPool;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use crate::{
+    error::{AppError, AppResult},
+    extractors::auth::AuthUser,
+    models::membership::{MemberRole, MemberStatus},
+};
+
+/// Query flag asking for soft-deleted rows as well.
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DeletedFilter {
+    /// Also return deleted items (room hosts and moderators, or admins, only).
+    #[serde(default)]
+    pub include_deleted: bool,
+}
+
+impl DeletedFilter {
+    /// Whether to return deleted rows. Asking without being allowed to see them is
+    /// `AppError::Forbidden` rather than a silently filtered result.
+    pub async fn resolve(
+        &self,
+        pool: &PgPool,
+        auth_user: &AuthUser,
+        room_id: Option<Uuid>,
+    ) -> AppResult<bool> {
+        if !self.include_deleted {
+            return Ok(false);
+        }
+        if can_view_deleted(pool, auth_user, room_id).await? {
+            Ok(true)
+        } else {
+            Err(AppError::Forbidden(
+                "Only moderators can view deleted items".into(),
+            ))
+        }
+    }
+}
+
+/// Global admins may see deleted rows anywhere; active hosts and moderators within their room.
+pub async fn can_view_deleted(
+    pool: &PgPool,
+    auth_user: &AuthUser,
+    room_id: Option<Uuid>,
+) -> AppResult<bool> {
+    if auth_user.role == "admin" {
+        return Ok(true);
+    }
+    let Some(room_id) = room_id else {
+        return Ok(false);
+    };
+
+    let role = sqlx::query_scalar::<_, MemberRole>(
+        "SELECT role FROM room_memberships WHERE user_id = $1 AND room_id = $2 AND status = $3",
+    )
+    .bind(auth_user.id)
+    .bind(room_id)
+    .bind(MemberStatus::Active)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(matches!(
+        role,
+        Some(MemberRole::Host | MemberRole::Moderator)
+    ))
+}
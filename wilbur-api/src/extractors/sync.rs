@@ -0,0 +1,21 @@
+//! Incremental sync.
+//!
+//! Lists that support it take `?updated_after=<RFC3339>` and then return only rows
+//! changed after that instant, oldest change first, so an offline client can store the
+//! newest `updated_at` it has seen and ask again from there. Deleted rows come back as
+//! tombstones with `deleted_at` set so the client can drop them; callers who may not
+//! view deleted items (see [`super::soft_delete`]) get tombstones with their content
+//! blanked. Messages removed by retention purges are gone for good and produce no
+//! tombstone; clients apply the room's retention period themselves.
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+/// Query parameter asking for changes since a point in time.
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SyncFilter {
+    /// Only rows created, changed, or deleted after this instant (RFC 3339), oldest first.
+    pub updated_after: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,635 @@
+//! Localized user-facing text.
+//!
+//! Messages are keyed by their English text, gettext-style, so a message without a
+//! translation still reads correctly. `{name}` placeholders are filled from the message's
+//! params after the template for the locale is chosen. The locale of the current request
+//! comes from its `Accept-Language` header (see [`crate::middleware::locale`]); anything
+//! unsupported falls back to English.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::LazyLock;
+
+use axum::http::{header, HeaderMap};
+
+/// A language the catalog has translations for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Locale {
+    #[default]
+    En,
+    Es,
+    Fr,
+    Pt,
+}
+
+tokio::task_local! {
+    static CURRENT: Locale;
+}
+
+impl Locale {
+    /// BCP 47 primary language tag.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Es => "es",
+            Locale::Fr => "fr",
+            Locale::Pt => "pt",
+        }
+    }
+
+    /// Match a language tag such as `pt-BR` on its primary subtag.
+    fn from_tag(tag: &str) -> Option<Locale> {
+        let primary = tag.split(['-', '_']).next()?.trim();
+        [Locale::En, Locale::Es, Locale::Fr, Locale::Pt]
+            .into_iter()
+            .find(|l| primary.eq_ignore_ascii_case(l.as_str()))
+    }
+
+    /// The supported language the client prefers most, by `q` weight and then by order.
+    /// English when none of the listed languages are supported.
+    pub fn from_accept_language(value: &str) -> Locale {
+        let mut best: Option<(Locale, f32)> = None;
+        for item in value.split(',') {
+            let mut parts = item.split(';');
+            let Some(locale) = parts.next().and_then(Locale::from_tag) else {
+                continue;
+            };
+            let q = parts
+                .find_map(|p| p.trim().strip_prefix("q="))
+                .and_then(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            if q > 0.0 && best.is_none_or(|(_, best_q)| q > best_q) {
+                best = Some((locale, q));
+            }
+        }
+        best.map(|(locale, _)| locale).unwrap_or_default()
+    }
+
+    pub fn from_headers(headers: &HeaderMap) -> Locale {
+        headers
+            .get(header::ACCEPT_LANGUAGE)
+            .and_then(|v| v.to_str().ok())
+            .map(Locale::from_accept_language)
+            .unwrap_or_default()
+    }
+
+    /// Locale of the request being handled; English outside of one.
+    pub fn current() -> Locale {
+        CURRENT.try_with(|l| *l).unwrap_or_default()
+    }
+
+    /// Run `f` with this as the current locale.
+    pub async fn scope<F: Future>(self, f: F) -> F::Output {
+        CURRENT.scope(self, f).await
+    }
+}
+
+/// A user-facing message: a catalog key plus values for its `{placeholders}`, rendered
+/// in a locale only when it is shown. `Display` renders English, for logs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    key: Cow<'static, str>,
+    params: Vec<(&'static str, String)>,
+}
+
+impl Message {
+    pub fn new(key: &'static str) -> Self {
+        Self {
+            key: Cow::Borrowed(key),
+            params: Vec::new(),
+        }
+    }
+
+    /// Fill the `{name}` placeholder.
+    pub fn with(mut self, name: &'static str, value: impl fmt::Display) -> Self {
+        self.params.push((name, value.to_string()));
+        self
+    }
+
+    pub fn render(&self, locale: Locale) -> String {
+        let mut text = translate(&self.key, locale).to_string();
+        for (name, value) in &self.params {
+            text = text.replace(&format!("{{{name}}}"), value);
+        }
+        text
+    }
+
+    /// Rendered in the current request's locale.
+    pub fn localized(&self) -> String {
+        self.render(Locale::current())
+    }
+}
+
+impl From<&'static str> for Message {
+    fn from(key: &'static str) -> Self {
+        Self::new(key)
+    }
+}
+
+/// Text formatted ahead of time; shown as-is unless it happens to match a catalog key.
+impl From<String> for Message {
+    fn from(text: String) -> Self {
+        Self {
+            key: Cow::Owned(text),
+            params: Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render(Locale::En))
+    }
+}
+
+/// The template for `key` in `locale`, or the key itself when there is no translation.
+fn translate(key: &str, locale: Locale) -> &str {
+    let Some(entry) = INDEX.get(key) else {
+        return key;
+    };
+    match locale {
+        Locale::En => entry.key,
+        Locale::Es => entry.es,
+        Locale::Fr => entry.fr,
+        Locale::Pt => entry.pt,
+    }
+}
+
+struct Translation {
+    key: &'static str,
+    es: &'static str,
+    fr: &'static str,
+    pt: &'static str,
+}
+
+static INDEX: LazyLock<HashMap<&'static str, &'static Translation>> =
+    LazyLock::new(|| CATALOG.iter().map(|t| (t.key, t)).collect());
+
+macro_rules! catalog {
+    ($($key:literal => { es: $es:literal, fr: $fr:literal, pt: $pt:literal $(,)? }),* $(,)?) => {
+        &[$(Translation { key: $key, es: $es, fr: $fr, pt: $pt }),*]
+    };
+}
+
+static CATALOG: &[Translation] = catalog! {
+    // Generic responses
+    "Internal server error" => {
+        es: "Error interno del servidor",
+        fr: "Erreur interne du serveur",
+        pt: "Erro interno do servidor",
+    },
+    "Service temporarily unavailable" => {
+        es: "Servicio no disponible temporalmente",
+        fr: "Service temporairement indisponible",
+        pt: "Serviço temporariamente indisponível",
+    },
+    "Request timed out" => {
+        es: "La solicitud ha excedido el tiempo de espera",
+        fr: "La requête a expiré",
+        pt: "A solicitação excedeu o tempo limite",
+    },
+    "Too many requests. Please try again later." => {
+        es: "Demasiadas solicitudes. Inténtalo de nuevo más tarde.",
+        fr: "Trop de requêtes. Veuillez réessayer plus tard.",
+        pt: "Muitas solicitações. Tente novamente mais tarde.",
+    },
+    "Invalid value in request" => {
+        es: "Valor no válido en la solicitud",
+        fr: "Valeur invalide dans la requête",
+        pt: "Valor inválido na solicitação",
+    },
+    "A value in the request is too long" => {
+        es: "Un valor de la solicitud es demasiado largo",
+        fr: "Une valeur de la requête est trop longue",
+        pt: "Um valor da solicitação é longo demais",
+    },
+    "A value in the request is out of range" => {
+        es: "Un valor de la solicitud está fuera de rango",
+        fr: "Une valeur de la requête est hors limites",
+        pt: "Um valor da solicitação está fora do intervalo",
+    },
+    "A referenced resource does not exist" => {
+        es: "Un recurso referenciado no existe",
+        fr: "Une ressource référencée n'existe pas",
+        pt: "Um recurso referenciado não existe",
+    },
+    "Resource already exists" => {
+        es: "El recurso ya existe",
+        fr: "La ressource existe déjà",
+        pt: "O recurso já existe",
+    },
+    "Expected a JSON body with Content-Type: application/json" => {
+        es: "Se esperaba un cuerpo JSON con Content-Type: application/json",
+        fr: "Un corps JSON avec Content-Type: application/json est attendu",
+        pt: "Era esperado um corpo JSON com Content-Type: application/json",
+    },
+    "Action failed" => {
+        es: "La acción ha fallado",
+        fr: "L'action a échoué",
+        pt: "A ação falhou",
+    },
+
+    // Not found
+    "Room not found" => {
+        es: "Sala no encontrada",
+        fr: "Salon introuvable",
+        pt: "Sala não encontrada",
+    },
+    "User not found" => {
+        es: "Usuario no encontrado",
+        fr: "Utilisateur introuvable",
+        pt: "Usuário não encontrado",
+    },
+    "Message not found" => {
+        es: "Mensaje no encontrado",
+        fr: "Message introuvable",
+        pt: "Mensagem não encontrada",
+    },
+    "Message not found in this room" => {
+        es: "Mensaje no encontrado en esta sala",
+        fr: "Message introuvable dans ce salon",
+        pt: "Mensagem não encontrada nesta sala",
+    },
+    "Message not found or not owned by you" => {
+        es: "Mensaje no encontrado o no es tuyo",
+        fr: "Message introuvable ou qui ne vous appartient pas",
+        pt: "Mensagem não encontrada ou não pertence a você",
+    },
+    "Alert not found" => {
+        es: "Alerta no encontrada",
+        fr: "Alerte introuvable",
+        pt: "Alerta não encontrado",
+    },
+    "Poll not found" => {
+        es: "Encuesta no encontrada",
+        fr: "Sondage introuvable",
+        pt: "Enquete não encontrada",
+    },
+    "Notification not found" => {
+        es: "Notificación no encontrada",
+        fr: "Notification introuvable",
+        pt: "Notificação não encontrada",
+    },
+    "File not found" => {
+        es: "Archivo no encontrado",
+        fr: "Fichier introuvable",
+        pt: "Arquivo não encontrado",
+    },
+    "File not found or not owned by you" => {
+        es: "Archivo no encontrado o no es tuyo",
+        fr: "Fichier introuvable ou qui ne vous appartient pas",
+        pt: "Arquivo não encontrado ou não pertence a você",
+    },
+    "Tenant not found" => {
+        es: "Organización no encontrada",
+        fr: "Organisation introuvable",
+        pt: "Organização não encontrada",
+    },
+    "Membership not found" => {
+        es: "Membresía no encontrada",
+        fr: "Adhésion introuvable",
+        pt: "Associação não encontrada",
+    },
+    "Draft not found" => {
+        es: "Borrador no encontrado",
+        fr: "Brouillon introuvable",
+        pt: "Rascunho não encontrado",
+    },
+
+    // Authentication
+    "Invalid email or password" => {
+        es: "Correo electrónico o contraseña incorrectos",
+        fr: "Adresse e-mail ou mot de passe incorrect",
+        pt: "E-mail ou senha inválidos",
+    },
+    "Please verify your email address before logging in" => {
+        es: "Verifica tu dirección de correo electrónico antes de iniciar sesión",
+        fr: "Veuillez vérifier votre adresse e-mail avant de vous connecter",
+        pt: "Verifique seu endereço de e-mail antes de entrar",
+    },
+    "Email already registered" => {
+        es: "El correo electrónico ya está registrado",
+        fr: "Cette adresse e-mail est déjà enregistrée",
+        pt: "E-mail já cadastrado",
+    },
+    "Email is already in use" => {
+        es: "El correo electrónico ya está en uso",
+        fr: "Cette adresse e-mail est déjà utilisée",
+        pt: "O e-mail já está em uso",
+    },
+    "New email matches your current email" => {
+        es: "El nuevo correo electrónico coincide con el actual",
+        fr: "La nouvelle adresse e-mail est identique à l'actuelle",
+        pt: "O novo e-mail é igual ao atual",
+    },
+    "Current password is incorrect" => {
+        es: "La contraseña actual es incorrecta",
+        fr: "Le mot de passe actuel est incorrect",
+        pt: "A senha atual está incorreta",
+    },
+    "Invalid or expired sign-in link" => {
+        es: "Enlace de inicio de sesión no válido o caducado",
+        fr: "Lien de connexion invalide ou expiré",
+        pt: "Link de acesso inválido ou expirado",
+    },
+    "Invalid or expired verification token" => {
+        es: "Token de verificación no válido o caducado",
+        fr: "Jeton de vérification invalide ou expiré",
+        pt: "Token de verificação inválido ou expirado",
+    },
+    "Invalid or expired reset token" => {
+        es: "Token de restablecimiento no válido o caducado",
+        fr: "Jeton de réinitialisation invalide ou expiré",
+        pt: "Token de redefinição inválido ou expirado",
+    },
+    "Invalid or expired email change token" => {
+        es: "Token de cambio de correo no válido o caducado",
+        fr: "Jeton de changement d'adresse e-mail invalide ou expiré",
+        pt: "Token de alteração de e-mail inválido ou expirado",
+    },
+    "Session expired or invalid. Please log in again." => {
+        es: "La sesión ha caducado o no es válida. Vuelve a iniciar sesión.",
+        fr: "Session expirée ou invalide. Veuillez vous reconnecter.",
+        pt: "Sessão expirada ou inválida. Entre novamente.",
+    },
+    "Missing authorization header" => {
+        es: "Falta la cabecera de autorización",
+        fr: "En-tête d'autorisation manquant",
+        pt: "Cabeçalho de autorização ausente",
+    },
+    "Invalid authorization header format" => {
+        es: "Formato de cabecera de autorización no válido",
+        fr: "Format d'en-tête d'autorisation invalide",
+        pt: "Formato de cabeçalho de autorização inválido",
+    },
+    "User no longer exists" => {
+        es: "El usuario ya no existe",
+        fr: "L'utilisateur n'existe plus",
+        pt: "O usuário não existe mais",
+    },
+
+    // Access
+    "Admin access required" => {
+        es: "Se requiere acceso de administrador",
+        fr: "Accès administrateur requis",
+        pt: "Acesso de administrador necessário",
+    },
+    "You are not a member of this room" => {
+        es: "No eres miembro de esta sala",
+        fr: "Vous n'êtes pas membre de ce salon",
+        pt: "Você não é membro desta sala",
+    },
+    "Your membership in this room is not active" => {
+        es: "Tu membresía en esta sala no está activa",
+        fr: "Votre adhésion à ce salon n'est pas active",
+        pt: "Sua associação nesta sala não está ativa",
+    },
+    "This room belongs to another tenant" => {
+        es: "Esta sala pertenece a otra organización",
+        fr: "Ce salon appartient à une autre organisation",
+        pt: "Esta sala pertence a outra organização",
+    },
+    "Only the host can perform this action" => {
+        es: "Solo el anfitrión puede realizar esta acción",
+        fr: "Seul l'hôte peut effectuer cette action",
+        pt: "Somente o anfitrião pode realizar esta ação",
+    },
+    "Only hosts and moderators can perform this action" => {
+        es: "Solo los anfitriones y moderadores pueden realizar esta acción",
+        fr: "Seuls les hôtes et les modérateurs peuvent effectuer cette action",
+        pt: "Somente anfitriões e moderadores podem realizar esta ação",
+    },
+    "Only moderators can view deleted items" => {
+        es: "Solo los moderadores pueden ver elementos eliminados",
+        fr: "Seuls les modérateurs peuvent voir les éléments supprimés",
+        pt: "Somente moderadores podem ver itens excluídos",
+    },
+    "You can only update your own profile" => {
+        es: "Solo puedes actualizar tu propio perfil",
+        fr: "Vous ne pouvez modifier que votre propre profil",
+        pt: "Você só pode atualizar seu próprio perfil",
+    },
+    "You can only update your own avatar" => {
+        es: "Solo puedes actualizar tu propio avatar",
+        fr: "Vous ne pouvez modifier que votre propre avatar",
+        pt: "Você só pode atualizar seu próprio avatar",
+    },
+    "You are not a participant of this chat" => {
+        es: "No participas en este chat",
+        fr: "Vous ne participez pas à cette conversation",
+        pt: "Você não participa desta conversa",
+    },
+    "File storage is not configured on this server" => {
+        es: "El almacenamiento de archivos no está configurado en este servidor",
+        fr: "Le stockage de fichiers n'est pas configuré sur ce serveur",
+        pt: "O armazenamento de arquivos não está configurado neste servidor",
+    },
+
+    // Moderation
+    "You cannot moderate yourself" => {
+        es: "No puedes moderarte a ti mismo",
+        fr: "Vous ne pouvez pas vous modérer vous-même",
+        pt: "Você não pode moderar a si mesmo",
+    },
+    "The room host cannot be moderated" => {
+        es: "El anfitrión de la sala no puede ser moderado",
+        fr: "L'hôte du salon ne peut pas être modéré",
+        pt: "O anfitrião da sala não pode ser moderado",
+    },
+    "Only the host can moderate a moderator" => {
+        es: "Solo el anfitrión puede moderar a un moderador",
+        fr: "Seul l'hôte peut modérer un modérateur",
+        pt: "Somente o anfitrião pode moderar um moderador",
+    },
+    "You cannot report yourself" => {
+        es: "No puedes denunciarte a ti mismo",
+        fr: "Vous ne pouvez pas vous signaler vous-même",
+        pt: "Você não pode denunciar a si mesmo",
+    },
+    "You cannot remove yourself from the room" => {
+        es: "No puedes expulsarte de la sala",
+        fr: "Vous ne pouvez pas vous retirer du salon",
+        pt: "Você não pode remover a si mesmo da sala",
+    },
+    "User is not banned in this room" => {
+        es: "El usuario no está vetado en esta sala",
+        fr: "L'utilisateur n'est pas banni de ce salon",
+        pt: "O usuário não está banido desta sala",
+    },
+
+    // Content
+    "Cannot create a DM with yourself" => {
+        es: "No puedes crear un mensaje directo contigo mismo",
+        fr: "Impossible de créer une conversation privée avec vous-même",
+        pt: "Não é possível criar uma conversa privada com você mesmo",
+    },
+    "You have not voted on this poll" => {
+        es: "No has votado en esta encuesta",
+        fr: "Vous n'avez pas voté à ce sondage",
+        pt: "Você não votou nesta enquete",
+    },
+    "Votes cannot be retracted from a closed poll" => {
+        es: "No se pueden retirar votos de una encuesta cerrada",
+        fr: "Impossible de retirer un vote d'un sondage clos",
+        pt: "Não é possível retirar votos de uma enquete encerrada",
+    },
+    "publish_at must be in the future" => {
+        es: "publish_at debe estar en el futuro",
+        fr: "publish_at doit être dans le futur",
+        pt: "publish_at deve estar no futuro",
+    },
+    "Announcements require a body" => {
+        es: "Los anuncios requieren un cuerpo",
+        fr: "Les annonces doivent avoir un contenu",
+        pt: "Os anúncios precisam de um corpo",
+    },
+    "Buy and sell alerts require a legal_disclosure of at least {min} characters" => {
+        es: "Las alertas de compra y venta requieren un legal_disclosure de al menos {min} caracteres",
+        fr: "Les alertes d'achat et de vente exigent un legal_disclosure d'au moins {min} caractères",
+        pt: "Alertas de compra e venda exigem um legal_disclosure de pelo menos {min} caracteres",
+    },
+    "Quoted message must be a message in this room" => {
+        es: "El mensaje citado debe ser un mensaje de esta sala",
+        fr: "Le message cité doit être un message de ce salon",
+        pt: "A mensagem citada deve ser uma mensagem desta sala",
+    },
+    "Attachments must be files you uploaded to this room" => {
+        es: "Los adjuntos deben ser archivos que subiste a esta sala",
+        fr: "Les pièces jointes doivent être des fichiers que vous avez envoyés dans ce salon",
+        pt: "Os anexos devem ser arquivos que você enviou para esta sala",
+    },
+
+    // Transactional email
+    "Verify your {name} account" => {
+        es: "Verifica tu cuenta de {name}",
+        fr: "Vérifiez votre compte {name}",
+        pt: "Verifique sua conta {name}",
+    },
+    "Welcome to {name}!" => {
+        es: "¡Te damos la bienvenida a {name}!",
+        fr: "Bienvenue sur {name} !",
+        pt: "Boas-vindas ao {name}!",
+    },
+    "Please verify your email address by clicking the button below." => {
+        es: "Verifica tu dirección de correo electrónico haciendo clic en el botón de abajo.",
+        fr: "Veuillez vérifier votre adresse e-mail en cliquant sur le bouton ci-dessous.",
+        pt: "Verifique seu endereço de e-mail clicando no botão abaixo.",
+    },
+    "Verify email" => {
+        es: "Verificar correo",
+        fr: "Vérifier l'adresse e-mail",
+        pt: "Verificar e-mail",
+    },
+    "This link expires in 24 hours." => {
+        es: "Este enlace caduca en 24 horas.",
+        fr: "Ce lien expire dans 24 heures.",
+        pt: "Este link expira em 24 horas.",
+    },
+    "Reset your {name} password" => {
+        es: "Restablece tu contraseña de {name}",
+        fr: "Réinitialisez votre mot de passe {name}",
+        pt: "Redefina sua senha do {name}",
+    },
+    "Reset your password" => {
+        es: "Restablece tu contraseña",
+        fr: "Réinitialisez votre mot de passe",
+        pt: "Redefina sua senha",
+    },
+    "You requested a password reset for your {name} account." => {
+        es: "Has solicitado restablecer la contraseña de tu cuenta de {name}.",
+        fr: "Vous avez demandé la réinitialisation du mot de passe de votre compte {name}.",
+        pt: "Você solicitou a redefinição da senha da sua conta {name}.",
+    },
+    "Reset password" => {
+        es: "Restablecer contraseña",
+        fr: "Réinitialiser le mot de passe",
+        pt: "Redefinir senha",
+    },
+    "This link expires in 1 hour. If you didn't request this, ignore this email." => {
+        es: "Este enlace caduca en 1 hora. Si no lo solicitaste, ignora este correo.",
+        fr: "Ce lien expire dans 1 heure. Si vous n'êtes pas à l'origine de cette demande, ignorez cet e-mail.",
+        pt: "Este link expira em 1 hora. Se você não fez essa solicitação, ignore este e-mail.",
+    },
+    "Sign in to {name}" => {
+        es: "Inicia sesión en {name}",
+        fr: "Connectez-vous à {name}",
+        pt: "Entre no {name}",
+    },
+    "Use the button below to sign in without your password." => {
+        es: "Usa el botón de abajo para iniciar sesión sin tu contraseña.",
+        fr: "Utilisez le bouton ci-dessous pour vous connecter sans mot de passe.",
+        pt: "Use o botão abaixo para entrar sem sua senha.",
+    },
+    "Sign in" => {
+        es: "Iniciar sesión",
+        fr: "Se connecter",
+        pt: "Entrar",
+    },
+    "This link expires in 15 minutes and works once. If you didn't request it, ignore this email." => {
+        es: "Este enlace caduca en 15 minutos y solo funciona una vez. Si no lo solicitaste, ignora este correo.",
+        fr: "Ce lien expire dans 15 minutes et ne fonctionne qu'une fois. Si vous ne l'avez pas demandé, ignorez cet e-mail.",
+        pt: "Este link expira em 15 minutos e funciona uma única vez. Se você não o solicitou, ignore este e-mail.",
+    },
+    "Confirm your new {name} email address" => {
+        es: "Confirma tu nueva dirección de correo de {name}",
+        fr: "Confirmez votre nouvelle adresse e-mail {name}",
+        pt: "Confirme seu novo endereço de e-mail do {name}",
+    },
+    "Confirm your new email address" => {
+        es: "Confirma tu nueva dirección de correo",
+        fr: "Confirmez votre nouvelle adresse e-mail",
+        pt: "Confirme seu novo endereço de e-mail",
+    },
+    "Someone asked to use this address for a {name} account. Confirm to finish the change." => {
+        es: "Alguien ha pedido usar esta dirección para una cuenta de {name}. Confirma para completar el cambio.",
+        fr: "Quelqu'un a demandé à utiliser cette adresse pour un compte {name}. Confirmez pour finaliser le changement.",
+        pt: "Alguém pediu para usar este endereço em uma conta {name}. Confirme para concluir a alteração.",
+    },
+    "Confirm email change" => {
+        es: "Confirmar cambio de correo",
+        fr: "Confirmer le changement d'adresse",
+        pt: "Confirmar alteração de e-mail",
+    },
+    "This link expires in 24 hours. If you didn't request this, ignore this email." => {
+        es: "Este enlace caduca en 24 horas. Si no lo solicitaste, ignora este correo.",
+        fr: "Ce lien expire dans 24 heures. Si vous n'êtes pas à l'origine de cette demande, ignorez cet e-mail.",
+        pt: "Este link expira em 24 horas. Se você não fez essa solicitação, ignore este e-mail.",
+    },
+    "Your {name} email address is being changed" => {
+        es: "Se está cambiando tu dirección de correo de {name}",
+        fr: "Votre adresse e-mail {name} est en cours de modification",
+        pt: "Seu endereço de e-mail do {name} está sendo alterado",
+    },
+    "Email change requested" => {
+        es: "Cambio de correo solicitado",
+        fr: "Changement d'adresse e-mail demandé",
+        pt: "Alteração de e-mail solicitada",
+    },
+    "A request was made to change your {name} sign-in email to {new_email}. The change takes effect once the new address is confirmed." => {
+        es: "Se ha solicitado cambiar tu correo de inicio de sesión de {name} a {new_email}. El cambio se aplicará cuando se confirme la nueva dirección.",
+        fr: "Une demande a été faite pour remplacer votre adresse e-mail de connexion {name} par {new_email}. Le changement prendra effet une fois la nouvelle adresse confirmée.",
+        pt: "Foi feita uma solicitação para alterar seu e-mail de acesso do {name} para {new_email}. A alteração entra em vigor quando o novo endereço for confirmado.",
+    },
+    "If this wasn't you, reset your password right away to secure your account." => {
+        es: "Si no fuiste tú, restablece tu contraseña de inmediato para proteger tu cuenta.",
+        fr: "Si ce n'était pas vous, réinitialisez immédiatement votre mot de passe pour sécuriser votre compte.",
+        pt: "Se não foi você, redefina sua senha imediatamente para proteger sua conta.",
+    },
+    "If the button doesn't work, copy this link into your browser:" => {
+        es: "Si el botón no funciona, copia este enlace en tu navegador:",
+        fr: "Si le bouton ne fonctionne pas, copiez ce lien dans votre navigateur :",
+        pt: "Se o botão não funcionar, copie este link no seu navegador:",
+    },
+    "Sent by {name}" => {
+        es: "Enviado por {name}",
+        fr: "Envoyé par {name}",
+        pt: "Enviado por {name}",
+    },
+    "Questions? Contact {support}" => {
+        es: "¿Tienes preguntas? Escribe a {support}",
+        fr: "Des questions ? Contactez {support}",
+        pt: "Dúvidas? Fale com {support}",
+    },
+};
@@ -2,16 +2,18 @@ use std::net::SocketAddr;
 use std::sync::Arc;
 
 use axum::{middleware as axum_middleware, Router};
+use sqlx::migrate::Migrator;
 use sqlx::postgres::PgPoolOptions;
 use tokio::net::TcpListener;
 use tower_http::compression::CompressionLayer;
-use tower_http::cors::CorsLayer;
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
 use tower_http::trace::TraceLayer;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 mod config;
 mod error;
 mod extractors;
+mod i18n;
 mod middleware;
 mod models;
 mod routes;
@@ -20,8 +22,12 @@ mod state;
 mod ws;
 
 use config::AppConfig;
+use services::feature_flags::Feature;
 use state::AppState;
 
+/// Migrations embedded at build time; `/health` compares them against the applied ones.
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
 #[tokio::main]
 async fn main() {
     // Load .env in development
@@ -37,7 +43,13 @@ async fn main() {
         .init();
 
     // Load configuration
-    let config = AppConfig::from_env().expect("Failed to load configuration");
+    let config = match AppConfig::from_env() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    };
 
     // Connect to database
     let pool = PgPoolOptions::new()
@@ -47,7 +59,7 @@ async fn main() {
         .expect("Failed to connect to database");
 
     // Run migrations
-    sqlx::migrate!("./migrations")
+    MIGRATOR
         .run(&pool)
         .await
         .expect("Failed to run database migrations");
@@ -55,43 +67,54 @@ async fn main() {
     tracing::info!("Database migrations applied successfully");
 
     // Initialize S3 client
-    let s3_config = aws_config::defaults(aws_config::BehaviorVersion::latest())
-        .endpoint_url(&config.s3_endpoint)
-        .region(aws_config::Region::new(config.s3_region.clone()))
-        .load()
-        .await;
+    let mut s3_loader = aws_config::defaults(aws_config::BehaviorVersion::latest())
+        .region(aws_config::Region::new(config.s3_region.clone()));
+    if config.storage_enabled() {
+        s3_loader = s3_loader.endpoint_url(&config.s3_endpoint);
+    } else {
+        tracing::warn!("File storage disabled: S3_ENDPOINT not set");
+    }
+    let s3_config = s3_loader.load().await;
     let s3_client = aws_sdk_s3::Client::new(&s3_config);
 
+    // Start the outbound email queue
+    let email = match services::email_service::EmailService::start(&config) {
+        Ok(email) => Some(email),
+        Err(e) => {
+            tracing::warn!("Email disabled: {e}");
+            None
+        }
+    };
+
     // Build application state
-    let state = Arc::new(AppState::new(pool, config.clone(), s3_client));
+    let state = Arc::new(AppState::new(pool, config.clone(), s3_client, email));
+
+    // Deliver committed outbox events to WebSocket subscribers
+    ws::outbox::spawn_dispatcher(state.clone());
+
+    // Publish scheduled alerts when they come due
+    services::alert_scheduler::spawn(state.clone());
+
+    // Purge messages past their room's retention window
+    services::message_retention::spawn(state.clone());
+
+    // Deliver room events to registered webhooks
+    services::webhook_dispatcher::spawn(state.clone());
+
+    // Remove objects from storage once their file records are deleted
+    services::storage_cleanup::spawn(state.clone());
+
+    // Reconcile stored objects against the database (dry run unless enabled)
+    services::storage_reaper::spawn(state.clone());
+
+    // Forget idle rate-limit buckets
+    middleware::rate_limit::spawn_cleanup(state.clone());
 
     // Build CORS layer
-    let cors = CorsLayer::new()
-        .allow_origin(
-            config
-                .allowed_origins
-                .iter()
-                .filter_map(|o| o.parse().ok())
-                .collect::<Vec<_>>(),
-        )
-        .allow_methods([
-            http::Method::GET,
-            http::Method::POST,
-            http::Method::PUT,
-            http::Method::DELETE,
-            http::Method::PATCH,
-            http::Method::OPTIONS,
-        ])
-        .allow_headers([
-            http::header::AUTHORIZATION,
-            http::header::CONTENT_TYPE,
-            http::header::ACCEPT,
-        ])
-        .allow_credentials(true);
+    let cors = middleware::cors::cors_layer(&config);
 
     // Build rate limiters
     let auth_limiter = middleware::rate_limit::create_auth_rate_limiter();
-    let api_limiter = middleware::rate_limit::create_api_rate_limiter();
 
     // Auth routes with stricter rate limiting
     let auth_routes = Router::new()
@@ -101,9 +124,24 @@ async fn main() {
             middleware::rate_limit::auth_rate_limit,
         ));
 
-    // All other API routes with general rate limiting
+    // Reject requests for features switched off for the room (or its tenant), or server-wide
+    let room_feature = |feature| {
+        axum_middleware::from_fn_with_state(
+            (state.clone(), feature),
+            middleware::features::require_room_feature,
+        )
+    };
+    let server_feature = |feature| {
+        axum_middleware::from_fn_with_state(
+            (state.clone(), feature),
+            middleware::features::require_feature,
+        )
+    };
+
+    // All other API routes with per-user (or per-IP) rate limiting
     let api_routes = Router::new()
         .merge(routes::health::router())
+        .merge(routes::docs::router())
         .nest("/ws", routes::ws::router())
         .nest("/api/v1/users", routes::users::router())
         .nest("/api/v1/rooms", routes::rooms::router())
@@ -111,35 +149,74 @@ async fn main() {
             "/api/v1/rooms/{room_id}/messages",
             routes::messages::router(),
         )
-        .nest("/api/v1/rooms/{room_id}/alerts", routes::alerts::router())
-        .nest("/api/v1/rooms/{room_id}/polls", routes::polls::router())
+        .nest(
+            "/api/v1/rooms/{room_id}/alerts",
+            routes::alerts::router().route_layer(room_feature(Feature::Alerts)),
+        )
+        .nest(
+            "/api/v1/rooms/{room_id}/polls",
+            routes::polls::router().route_layer(room_feature(Feature::Polls)),
+        )
+        .nest(
+            "/api/v1/rooms/{room_id}/webhooks",
+            routes::webhooks::router(),
+        )
+        .nest(
+            "/api/v1/rooms/{room_id}/hooks",
+            routes::room_hooks::router(),
+        )
+        .nest("/api/v1/rooms/{room_id}/stream", routes::stream::router())
+        .nest("/api/v1/rooms/{room_id}/drafts", routes::drafts::router())
+        .nest(
+            "/api/v1/rooms/{room_id}/analytics",
+            routes::analytics::router().route_layer(room_feature(Feature::RoomAnalytics)),
+        )
         .nest("/api/v1/integrations", routes::integrations::router())
         .nest("/api/v1/storage", routes::storage::router())
         .nest("/api/v1/themes", routes::themes::router())
         .nest("/api/v1/tenants", routes::tenants::router())
         .nest("/api/v1/livekit", routes::livekit::router())
         .nest("/api/v1/moderation", routes::moderation::router())
-        .nest("/api/v1/dm", routes::private_chats::router())
+        .nest("/api/v1/admin", routes::admin::router())
+        .nest(
+            "/api/v1/dm",
+            routes::private_chats::router().route_layer(server_feature(Feature::DirectMessages)),
+        )
         .nest("/api/v1/notifications", routes::notifications::router())
         .nest(
             "/api/v1/rooms/{room_id}/tracks",
             routes::media_tracks::router(),
         )
         .route_layer(axum_middleware::from_fn_with_state(
-            api_limiter,
+            state.clone(),
             middleware::rate_limit::api_rate_limit,
         ));
 
-    // Build router with security headers, rate limiting, CORS, and compression
+    // Build router with request time limits, localized errors, version/deprecation and security
+    // headers, rate limiting, CORS, and compression, tracing each request under a span keyed
+    // by its X-Request-Id
     let app = Router::new()
         .merge(auth_routes)
         .merge(api_routes)
+        .route_layer(axum_middleware::from_fn(
+            middleware::versioning::deprecation_headers,
+        ))
+        .layer(axum_middleware::from_fn_with_state(
+            state.clone(),
+            middleware::timeout::request_timeout,
+        ))
+        .layer(axum_middleware::from_fn(middleware::locale::request_locale))
+        .layer(axum_middleware::from_fn(
+            middleware::versioning::api_version_header,
+        ))
         .layer(axum_middleware::from_fn(
             middleware::security::security_headers,
         ))
         .layer(cors)
         .layer(CompressionLayer::new())
-        .layer(TraceLayer::new_for_http())
+        .layer(TraceLayer::new_for_http().make_span_with(middleware::request_span::make_span))
+        .layer(PropagateRequestIdLayer::x_request_id())
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
         .with_state(state);
 
     // Start server
@@ -149,10 +226,15 @@ async fn main() {
         .expect("Failed to bind address");
     tracing::info!("Server listening on {}", addr);
 
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
-        .await
-        .expect("Server error");
+    // Peer addresses key the per-IP rate limit. Graceful shutdown waits for in-flight requests,
+    // which the request time limits bound to at most UPLOAD_TIMEOUT_SECS.
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_signal())
+    .await
+    .expect("Server error");
 }
 
 async fn shutdown_signal() {
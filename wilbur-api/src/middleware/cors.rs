@@ -0,0 +1,65 @@
+use http::{header, HeaderName, HeaderValue, Method};
+use tower_http::cors::{Any, CorsLayer};
+
+use crate::{
+    config::AppConfig,
+    middleware::{request_span, versioning},
+};
+
+/// Build the CORS layer from configuration.
+///
+/// Browsers reject credentialed responses that use wildcard origins, methods, or
+/// headers, so credentialed mode reflects only the configured origins and uses the
+/// explicit method/header lists. Wildcard mode allows any origin without credentials.
+/// Either way `Location`, `X-Request-Id`, and the API version/deprecation headers are
+/// readable by clients.
+pub fn cors_layer(config: &AppConfig) -> CorsLayer {
+    let exposed: Vec<HeaderName> = versioning::EXPOSED_HEADERS
+        .into_iter()
+        .map(HeaderName::from_static)
+        .chain([
+            header::LOCATION,
+            HeaderName::from_static(request_span::REQUEST_ID_HEADER),
+        ])
+        .collect();
+
+    if !config.cors_allow_credentials {
+        return CorsLayer::new()
+            .allow_origin(Any)
+            .allow_methods(Any)
+            .allow_headers(Any)
+            .expose_headers(exposed);
+    }
+
+    let origins: Vec<HeaderValue> = config
+        .allowed_origins
+        .iter()
+        .filter(|o| o.as_str() != "*")
+        .filter_map(|o| match o.parse() {
+            Ok(v) => Some(v),
+            Err(_) => {
+                tracing::warn!(origin = %o, "Ignoring invalid CORS origin");
+                None
+            }
+        })
+        .collect();
+
+    let methods: Vec<Method> = config
+        .cors_allowed_methods
+        .iter()
+        .filter_map(|m| m.parse().ok())
+        .collect();
+
+    let headers: Vec<HeaderName> = config
+        .cors_allowed_headers
+        .iter()
+        .filter_map(|h| h.parse().ok())
+        .collect();
+
+    CorsLayer::new()
+        .allow_origin(origins)
+        .allow_methods(methods)
+        .allow_headers(headers)
+        .expose_headers(exposed)
+        .allow_credentials(true)
+}
@@ -0,0 +1,51 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use uuid::Uuid;
+
+use crate::{
+    error::{AppError, AppResult},
+    services::feature_flags::Feature,
+    state::AppState,
+};
+
+/// Route middleware for routers nested under `/rooms/{room_id}`: rejects requests when
+/// the feature is disabled for the room, its tenant, or the server.
+pub async fn require_room_feature(
+    State((state, feature)): State<(Arc<AppState>, Feature)>,
+    Path(params): Path<HashMap<String, String>>,
+    request: Request,
+    next: Next,
+) -> AppResult<Response> {
+    let room_id = params
+        .get("room_id")
+        .and_then(|id| Uuid::parse_str(id).ok())
+        .ok_or_else(|| AppError::BadRequest("Invalid room ID".into()))?;
+
+    if !state.room_feature_enabled(room_id, feature).await? {
+        return Err(disabled(feature));
+    }
+    Ok(next.run(request).await)
+}
+
+/// Route middleware for features that aren't room- or tenant-scoped: rejects requests
+/// when the server has the feature disabled.
+pub async fn require_feature(
+    State((state, feature)): State<(Arc<AppState>, Feature)>,
+    request: Request,
+    next: Next,
+) -> AppResult<Response> {
+    if !state.feature_enabled(None, feature).await? {
+        return Err(disabled(feature));
+    }
+    Ok(next.run(request).await)
+}
+
+fn disabled(feature: Feature) -> AppError {
+    AppError::Forbidden(format!("The '{}' feature is disabled", feature.as_str()).into())
+}
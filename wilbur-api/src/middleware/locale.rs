@@ -0,0 +1,10 @@
+use axum::{extract::Request, middleware::Next, response::Response};
+
+use crate::i18n::Locale;
+
+/// Middleware that makes the locale from the request's `Accept-Language` header current
+/// while it is handled, so error messages and emails are rendered in it.
+pub async fn request_locale(request: Request, next: Next) -> Response {
+    let locale = Locale::from_headers(request.headers());
+    locale.scope(next.run(request)).await
+}
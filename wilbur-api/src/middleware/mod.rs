@@ -1,2 +1,8 @@
+pub mod cors;
+pub mod features;
+pub mod locale;
 pub mod rate_limit;
+pub mod request_span;
 pub mod security;
+pub mod timeout;
+pub mod versioning;
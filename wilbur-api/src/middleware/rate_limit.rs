@@ -1,17 +1,26 @@
+use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
+use std::time::Duration;
 
 use axum::{
-    extract::{Request, State},
-    http::StatusCode,
+    extract::{ConnectInfo, FromRequestParts, Request, State},
+    http::{HeaderName, HeaderValue, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
 };
 use governor::{
-    clock::DefaultClock,
-    state::{InMemoryState, NotKeyed},
-    Quota, RateLimiter,
+    clock::{Clock, DefaultClock},
+    middleware::StateInformationMiddleware,
+    state::{keyed::DefaultKeyedStateStore, InMemoryState, NotKeyed},
+    DefaultKeyedRateLimiter, Quota, RateLimiter,
 };
 use std::num::NonZeroU32;
+use uuid::Uuid;
+
+use crate::{config::AppConfig, extractors::auth::AuthUser, state::AppState};
+
+/// How often idle per-user, per-IP, and per-hook buckets are forgotten.
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(600);
 
 /// Shared rate limiter for auth endpoints (5 req/min per IP — global bucket).
 /// In production, use a keyed rate limiter per-IP. This provides a simple global
@@ -25,11 +34,106 @@ pub fn create_auth_rate_limiter() -> Arc<AuthRateLimiter> {
     Arc::new(RateLimiter::direct(quota))
 }
 
-/// Create an API rate limiter: 200 requests per 60 seconds.
-pub fn create_api_rate_limiter() -> Arc<AuthRateLimiter> {
+/// A keyed limiter that reports the caller's remaining capacity on every check.
+type KeyedLimiter<K> =
+    RateLimiter<K, DefaultKeyedStateStore<K>, DefaultClock, StateInformationMiddleware>;
+
+/// General API limits: one bucket per authenticated user, sized by their role, and one
+/// per client IP for requests without a valid access token.
+pub struct ApiRateLimiter {
+    members: KeyedLimiter<Uuid>,
+    staff: KeyedLimiter<Uuid>,
+    ips: KeyedLimiter<IpAddr>,
+    member_quota: u32,
+    staff_quota: u32,
+    ip_quota: u32,
+    warn_percent: u32,
+    client_ip_header: Option<HeaderName>,
+}
+
+impl ApiRateLimiter {
+    pub fn new(config: &AppConfig) -> Self {
+        Self {
+            members: keyed_per_minute(config.rate_limit_member_per_min),
+            staff: keyed_per_minute(config.rate_limit_staff_per_min),
+            ips: keyed_per_minute(config.rate_limit_ip_per_min),
+            member_quota: config.rate_limit_member_per_min,
+            staff_quota: config.rate_limit_staff_per_min,
+            ip_quota: config.rate_limit_ip_per_min,
+            warn_percent: config.rate_limit_warn_percent,
+            client_ip_header: config
+                .client_ip_header
+                .as_deref()
+                .and_then(|h| HeaderName::from_bytes(h.as_bytes()).ok()),
+        }
+    }
+
+    /// The client address: the trusted proxy header when configured, else the peer.
+    fn client_ip(&self, request: &Request) -> Option<IpAddr> {
+        if let Some(header) = &self.client_ip_header {
+            if let Some(ip) = request
+                .headers()
+                .get(header)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.split(',').next())
+                .and_then(|v| v.trim().parse().ok())
+            {
+                return Some(ip);
+            }
+        }
+        request
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip())
+    }
+
+    /// Whether a bucket has used enough of its quota that the client should back off.
+    fn should_warn(&self, limit: u32, remaining: u32) -> bool {
+        let used = u64::from(limit.saturating_sub(remaining));
+        used * 100 >= u64::from(limit) * u64::from(self.warn_percent)
+    }
+
+    /// Forget buckets that have refilled completely.
+    fn retain_recent(&self) {
+        self.members.retain_recent();
+        self.staff.retain_recent();
+        self.ips.retain_recent();
+    }
+}
+
+fn keyed_per_minute<K: Clone + std::hash::Hash + Eq>(per_minute: u32) -> KeyedLimiter<K> {
+    let quota = Quota::per_minute(NonZeroU32::new(per_minute).unwrap_or(NonZeroU32::MIN));
+    RateLimiter::keyed(quota).with_middleware::<StateInformationMiddleware>()
+}
+
+/// Hosts, moderators, and admins get the larger quota.
+fn is_staff(role: &str) -> bool {
+    matches!(role, "admin" | "host" | "moderator")
+}
+
+/// Periodically drop idle rate-limit buckets, last-seen throttle entries, and event
+/// replay buffers so memory stays bounded.
+pub fn spawn_cleanup(state: Arc<AppState>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(CLEANUP_INTERVAL);
+        loop {
+            interval.tick().await;
+            state.api_limiter.retain_recent();
+            state.hook_limiter.retain_recent();
+            state.prune_last_seen();
+            state.ws_replay.prune_idle();
+        }
+    });
+}
+
+/// Per-hook rate limiter for incoming room hooks, keyed by hook ID.
+pub type HookRateLimiter = DefaultKeyedRateLimiter<Uuid>;
+
+/// Create an incoming hook rate limiter: 30 requests per 60 seconds per hook.
+pub fn create_hook_rate_limiter() -> HookRateLimiter {
     let quota =
-        Quota::per_minute(NonZeroU32::new(200).unwrap()).allow_burst(NonZeroU32::new(50).unwrap());
-    Arc::new(RateLimiter::direct(quota))
+        Quota::per_minute(NonZeroU32::new(30).unwrap()).allow_burst(NonZeroU32::new(10).unwrap());
+    RateLimiter::keyed(quota)
 }
 
 /// Middleware that enforces rate limiting on auth endpoints.
@@ -52,22 +156,70 @@ pub async fn auth_rate_limit(
     }
 }
 
-/// Middleware that enforces rate limiting on general API endpoints.
+/// Middleware that enforces rate limiting on general API endpoints. Requests with a valid
+/// access token count against the user's bucket; the rest against their IP's. Responses
+/// carry `X-RateLimit-Limit` and `X-RateLimit-Remaining` for the bucket used, plus
+/// `X-RateLimit-Warning` once the bucket passes the configured warning threshold.
 pub async fn api_rate_limit(
-    State(limiter): State<Arc<AuthRateLimiter>>,
+    State(state): State<Arc<AppState>>,
     request: Request,
     next: Next,
 ) -> Response {
-    match limiter.check() {
-        Ok(_) => next.run(request).await,
-        Err(_) => {
-            tracing::warn!("API rate limit exceeded");
-            (
+    let limiter = &state.api_limiter;
+
+    // An invalid token is left for the handler to reject; it still counts against the IP
+    let (mut parts, body) = request.into_parts();
+    let user = AuthUser::from_request_parts(&mut parts, &state).await.ok();
+    let request = Request::from_parts(parts, body);
+
+    let (limit, outcome) = match &user {
+        Some(user) if is_staff(&user.role) => {
+            (limiter.staff_quota, limiter.staff.check_key(&user.id))
+        }
+        Some(user) => (limiter.member_quota, limiter.members.check_key(&user.id)),
+        None => match limiter.client_ip(&request) {
+            Some(ip) => (limiter.ip_quota, limiter.ips.check_key(&ip)),
+            None => return next.run(request).await,
+        },
+    };
+
+    match outcome {
+        Ok(snapshot) => {
+            let remaining = snapshot.remaining_burst_capacity();
+            let mut response = next.run(request).await;
+            set_limit_headers(&mut response, limit, remaining);
+            if limiter.should_warn(limit, remaining) {
+                let message = format!(
+                    "{} of {limit} requests used in the current window; slow down to avoid being blocked",
+                    limit.saturating_sub(remaining)
+                );
+                if let Ok(value) = HeaderValue::from_str(&message) {
+                    response.headers_mut().insert("x-ratelimit-warning", value);
+                }
+            }
+            response
+        }
+        Err(not_until) => {
+            let wait = not_until.wait_time_from(DefaultClock::default().now());
+            let retry_after = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+            match &user {
+                Some(user) => tracing::warn!(user_id = %user.id, "API rate limit exceeded"),
+                None => tracing::warn!("API rate limit exceeded"),
+            }
+            let mut response = (
                 StatusCode::TOO_MANY_REQUESTS,
-                [("Retry-After", "30")],
+                [("Retry-After", retry_after.max(1).to_string())],
                 "Too many requests. Please try again later.",
             )
-                .into_response()
+                .into_response();
+            set_limit_headers(&mut response, limit, 0);
+            response
         }
     }
 }
+
+fn set_limit_headers(response: &mut Response, limit: u32, remaining: u32) {
+    let headers = response.headers_mut();
+    headers.insert("x-ratelimit-limit", HeaderValue::from(limit));
+    headers.insert("x-ratelimit-remaining", HeaderValue::from(remaining));
+}
@@ -0,0 +1,36 @@
+use axum::{body::Body, http::Request};
+use tracing::{field, Span};
+use uuid::Uuid;
+
+/// Header carrying the request ID; generated by `SetRequestIdLayer` when the client sent none.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Span wrapping each request, so every log line from a handler can be searched by request,
+/// user, and room. `user_id` and `room_id` start empty and are recorded by the auth and room
+/// access extractors. Only the path is recorded because query strings can carry tokens.
+pub fn make_span(request: &Request<Body>) -> Span {
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+
+    tracing::info_span!(
+        "request",
+        method = %request.method(),
+        path = request.uri().path(),
+        request_id,
+        user_id = field::Empty,
+        room_id = field::Empty,
+    )
+}
+
+/// Attach the authenticated caller to the current request span.
+pub fn record_user(user_id: Uuid) {
+    Span::current().record("user_id", field::display(user_id));
+}
+
+/// Attach the room being accessed to the current request span.
+pub fn record_room(room_id: Uuid) {
+    Span::current().record("room_id", field::display(room_id));
+}
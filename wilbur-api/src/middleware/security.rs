@@ -27,13 +27,16 @@ pub async fn security_headers(request: Request, next: Next) -> Response {
     );
 
     // Content Security Policy — restrict resource loading
-    // API server primarily serves JSON, but CSP protects any HTML error pages
-    headers.insert(
-        "Content-Security-Policy",
-        "default-src 'none'; frame-ancestors 'none'"
-            .parse()
-            .unwrap(),
-    );
+    // API server primarily serves JSON, but CSP protects any HTML error pages.
+    // Routes serving HTML (the API docs) set their own policy, which is kept.
+    if !headers.contains_key("Content-Security-Policy") {
+        headers.insert(
+            "Content-Security-Policy",
+            "default-src 'none'; frame-ancestors 'none'"
+                .parse()
+                .unwrap(),
+        );
+    }
 
     // Disable unnecessary browser features
     headers.insert(
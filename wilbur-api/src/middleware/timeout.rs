@@ -0,0 +1,40 @@
+use std::{sync::Arc, time::Duration};
+
+use axum::{
+    extract::{Request, State},
+    http::header,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::{error::AppError, state::AppState};
+
+/// Middleware that fails requests taking longer than the configured limit with a 504, so a
+/// hung query or storage call can't hold a connection (or delay graceful shutdown)
+/// indefinitely. Multipart requests are uploads and get the longer upload limit. Only the
+/// time to produce response headers counts: WebSocket upgrades and streamed downloads
+/// aren't cut off once they've started.
+pub async fn request_timeout(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let secs = if is_multipart(&request) {
+        state.config.upload_timeout_secs
+    } else {
+        state.config.request_timeout_secs
+    };
+
+    match tokio::time::timeout(Duration::from_secs(secs), next.run(request)).await {
+        Ok(response) => response,
+        Err(_) => AppError::Timeout(format!("Request exceeded {secs}s")).into_response(),
+    }
+}
+
+fn is_multipart(request: &Request) -> bool {
+    request
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("multipart/form-data"))
+}
@@ -0,0 +1,81 @@
+use axum::{
+    extract::{MatchedPath, Request},
+    http::HeaderValue,
+    middleware::Next,
+    response::Response,
+};
+
+/// Version reported in `X-API-Version` on every response.
+pub const API_VERSION: &str = "1";
+
+/// Headers set by this module, exposed to browser clients through CORS.
+pub const EXPOSED_HEADERS: [&str; 4] = ["x-api-version", "deprecation", "sunset", "link"];
+
+/// Deprecation details for one route.
+pub struct Deprecation {
+    /// When the route was deprecated, as a Unix timestamp (`Deprecation: @<ts>`).
+    pub deprecated_at: i64,
+    /// HTTP-date after which the route may stop responding (`Sunset`, RFC 8594).
+    pub sunset: Option<&'static str>,
+    /// Migration notes, sent as `Link: <url>; rel="deprecation"`.
+    pub link: Option<&'static str>,
+}
+
+/// Deprecated routes, keyed by their full route pattern as registered in the router
+/// (e.g. `/api/v1/integrations/{provider}/exchange`). Applies to every method on the route.
+///
+/// ```text
+/// ("/api/v1/rooms/{room_id}/legacy", Deprecation {
+///     deprecated_at: 1767225600,
+///     sunset: Some("Wed, 01 Jul 2026 00:00:00 GMT"),
+///     link: Some("https://docs.example.com/migrations/legacy"),
+/// }),
+/// ```
+static DEPRECATED_ROUTES: &[(&str, Deprecation)] = &[];
+
+/// Middleware that adds `X-API-Version` to all responses.
+pub async fn api_version_header(request: Request, next: Next) -> Response {
+    let mut response = next.run(request).await;
+    response
+        .headers_mut()
+        .insert("X-API-Version", HeaderValue::from_static(API_VERSION));
+    response
+}
+
+/// Route middleware that adds `Deprecation`, `Sunset`, and `Link` headers to responses
+/// from routes listed in [`DEPRECATED_ROUTES`]. Must be installed with `route_layer` so
+/// the matched route is known.
+pub async fn deprecation_headers(request: Request, next: Next) -> Response {
+    let deprecation = request
+        .extensions()
+        .get::<MatchedPath>()
+        .and_then(|path| lookup(path.as_str()));
+
+    let mut response = next.run(request).await;
+
+    if let Some(deprecation) = deprecation {
+        let headers = response.headers_mut();
+        headers.insert(
+            "Deprecation",
+            format!("@{}", deprecation.deprecated_at).parse().unwrap(),
+        );
+        if let Some(sunset) = deprecation.sunset.and_then(|s| s.parse().ok()) {
+            headers.insert("Sunset", sunset);
+        }
+        if let Some(link) = deprecation
+            .link
+            .and_then(|url| format!("<{url}>; rel=\"deprecation\"").parse().ok())
+        {
+            headers.append("Link", link);
+        }
+    }
+
+    response
+}
+
+fn lookup(route: &str) -> Option<&'static Deprecation> {
+    DEPRECATED_ROUTES
+        .iter()
+        .find(|(path, _)| *path == route)
+        .map(|(_, deprecation)| deprecation)
+}
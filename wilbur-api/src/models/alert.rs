@@ -4,6 +4,12 @@ use sqlx::FromRow;
 use uuid::Uuid;
 use validator::Validate;
 
+/// Shortest `legal_disclosure`, trimmed, accepted on `buy`/`sell` alerts.
+pub const MIN_LEGAL_DISCLOSURE_CHARS: usize = 20;
+
+/// Longest `legal_disclosure` accepted on any alert.
+pub const MAX_LEGAL_DISCLOSURE_CHARS: u64 = 2000;
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq)]
 #[sqlx(type_name = "alert_type", rename_all = "lowercase")]
 #[serde(rename_all = "lowercase")]
@@ -12,6 +18,20 @@ pub enum AlertType {
     Sell,
     Info,
     Warning,
+    /// General room announcement rather than a trade signal; has no ticker or prices.
+    Announcement,
+}
+
+impl AlertType {
+    /// Trade calls, which must carry a legal disclosure.
+    pub fn requires_disclosure(&self) -> bool {
+        matches!(self, AlertType::Buy | AlertType::Sell)
+    }
+
+    /// Whether ticker and price fields may be set on this type.
+    pub fn allows_trade_fields(&self) -> bool {
+        !matches!(self, AlertType::Announcement)
+    }
 }
 
 #[derive(Debug, Clone, FromRow, Serialize)]
@@ -29,7 +49,36 @@ pub struct Alert {
     pub media_url: Option<String>,
     pub legal_disclosure: Option<String>,
     pub is_active: bool,
+    pub is_pinned: bool,
+    pub pinned_at: Option<DateTime<Utc>>,
+    pub publish_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// Members who have acknowledged the alert; only selected by queries that need it.
+    #[sqlx(default)]
+    pub ack_count: i64,
+    /// Whether the requesting user has acknowledged it; only selected per user.
+    #[sqlx(default)]
+    pub acked_by_me: bool,
+}
+
+/// Whether an alert listing includes deactivated alerts; `All` is for moderators only.
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertStatusFilter {
+    #[default]
+    Active,
+    All,
+}
+
+/// Query filters for listing room alerts. Defaults match the unfiltered listing.
+#[derive(Debug, Deserialize)]
+pub struct AlertListQuery {
+    pub alert_type: Option<AlertType>,
+    pub ticker_symbol: Option<String>,
+    #[serde(default)]
+    pub status: AlertStatusFilter,
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -45,8 +94,11 @@ pub struct CreateAlertRequest {
     pub stop_loss: Option<f64>,
     pub take_profit: Option<f64>,
     pub media_url: Option<String>,
-    #[validate(length(max = 2000))]
+    /// Required on `buy`/`sell` alerts unless the tenant sets a default.
+    #[validate(length(max = MAX_LEGAL_DISCLOSURE_CHARS))]
     pub legal_disclosure: Option<String>,
+    /// When set, the alert stays hidden until this time and is then published.
+    pub publish_at: Option<DateTime<Utc>>,
 }
 
 /// Alert response for API consumers.
@@ -65,7 +117,14 @@ pub struct AlertResponse {
     pub media_url: Option<String>,
     pub legal_disclosure: Option<String>,
     pub is_active: bool,
+    pub is_pinned: bool,
+    pub pinned_at: Option<DateTime<Utc>>,
+    pub publish_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub ack_count: i64,
+    pub acked_by_me: bool,
 }
 
 impl From<Alert> for AlertResponse {
@@ -84,7 +143,31 @@ impl From<Alert> for AlertResponse {
             media_url: a.media_url,
             legal_disclosure: a.legal_disclosure,
             is_active: a.is_active,
+            is_pinned: a.is_pinned,
+            pinned_at: a.pinned_at,
+            publish_at: a.publish_at,
             created_at: a.created_at,
+            updated_at: a.updated_at,
+            deleted_at: a.deleted_at,
+            ack_count: a.ack_count,
+            acked_by_me: a.acked_by_me,
+        }
+    }
+}
+
+impl AlertResponse {
+    /// Strip a deleted alert down to what a sync client needs to drop it.
+    pub fn into_tombstone(self) -> Self {
+        Self {
+            title: String::new(),
+            body: None,
+            ticker_symbol: None,
+            entry_price: None,
+            stop_loss: None,
+            take_profit: None,
+            media_url: None,
+            legal_disclosure: None,
+            ..self
         }
     }
 }
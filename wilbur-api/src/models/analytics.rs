@@ -0,0 +1,75 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::models::alert::AlertType;
+
+/// Query parameters for room analytics. Both dates are inclusive UTC days.
+#[derive(Debug, Deserialize)]
+pub struct AnalyticsQuery {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+/// Activity summary for a room over a date range, shaped for charting: `days` is a
+/// dense series with one entry per day, including days with no activity.
+#[derive(Debug, Clone, Serialize)]
+pub struct RoomAnalytics {
+    pub room_id: Uuid,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub totals: AnalyticsTotals,
+    pub days: Vec<DailyActivity>,
+    pub top_posters: Vec<TopPoster>,
+    pub alerts_by_type: Vec<AlertTypeCount>,
+    pub polls: Vec<PollParticipation>,
+    pub generated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct AnalyticsTotals {
+    pub messages: i64,
+    /// Distinct members who posted in the range.
+    pub active_members: i64,
+    /// Current active memberships.
+    pub members: i64,
+    pub new_members: i64,
+    pub alerts: i64,
+    pub polls: i64,
+    pub poll_votes: i64,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct DailyActivity {
+    pub date: NaiveDate,
+    pub messages: i64,
+    /// Distinct members who posted that day.
+    pub active_members: i64,
+    pub new_members: i64,
+    pub alerts: i64,
+    pub poll_votes: i64,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct TopPoster {
+    pub user_id: Uuid,
+    pub display_name: Option<String>,
+    pub messages: i64,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct AlertTypeCount {
+    pub alert_type: AlertType,
+    pub count: i64,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct PollParticipation {
+    pub poll_id: Uuid,
+    pub question: String,
+    pub created_at: DateTime<Utc>,
+    pub votes: i64,
+    /// Votes as a fraction of current active members (0.0-1.0).
+    pub participation_rate: f64,
+}
@@ -0,0 +1,34 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq)]
+#[sqlx(type_name = "draft_kind", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum DraftKind {
+    Message,
+    Alert,
+}
+
+/// An unsent message or alert, visible only to its author.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct Draft {
+    pub room_id: Uuid,
+    pub kind: DraftKind,
+    /// Whatever the client's composer needs to restore its state.
+    pub content: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SaveDraftRequest {
+    pub kind: DraftKind,
+    pub content: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DraftListQuery {
+    pub kind: Option<DraftKind>,
+}
@@ -1,10 +1,11 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use utoipa::ToSchema;
 use uuid::Uuid;
 use validator::Validate;
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq, ToSchema)]
 #[sqlx(type_name = "member_role", rename_all = "lowercase")]
 #[serde(rename_all = "lowercase")]
 pub enum MemberRole {
@@ -13,7 +14,22 @@ pub enum MemberRole {
     Member,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq)]
+impl MemberRole {
+    /// Whether this role carries at least the privileges of `min` (host > moderator > member).
+    pub fn at_least(&self, min: &MemberRole) -> bool {
+        self.rank() >= min.rank()
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            MemberRole::Host => 2,
+            MemberRole::Moderator => 1,
+            MemberRole::Member => 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq, ToSchema)]
 #[sqlx(type_name = "member_status", rename_all = "lowercase")]
 #[serde(rename_all = "lowercase")]
 pub enum MemberStatus {
@@ -32,17 +48,22 @@ pub struct RoomMembership {
     pub city: Option<String>,
     pub state_name: Option<String>,
     pub country: Option<String>,
+    /// Messages from this member are stored but only shown to them and to moderators.
+    pub shadow_banned: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// User who last changed the membership's role or status.
+    pub updated_by: Option<Uuid>,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct UpdateMemberRoleRequest {
     pub role: MemberRole,
 }
 
-/// Membership response with basic info.
-#[derive(Debug, Serialize)]
+/// Membership response with basic info. Who last changed it is only filled in for hosts
+/// and moderators (see [`MembershipResponse::with_audit`]).
+#[derive(Debug, Serialize, ToSchema)]
 pub struct MembershipResponse {
     pub id: Uuid,
     pub user_id: Uuid,
@@ -53,6 +74,45 @@ pub struct MembershipResponse {
     pub state_name: Option<String>,
     pub country: Option<String>,
     pub created_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_by: Option<Uuid>,
+    /// Member's last activity anywhere; only filled in for member listings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_seen_at: Option<DateTime<Utc>>,
+    /// Whether the member has a WebSocket open right now; only filled in for member listings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub online: Option<bool>,
+}
+
+/// Membership row together with the member's `users.last_seen_at`.
+#[derive(Debug, Clone, FromRow)]
+pub struct MembershipWithLastSeen {
+    #[sqlx(flatten)]
+    pub membership: RoomMembership,
+    pub last_seen_at: Option<DateTime<Utc>>,
+}
+
+impl MembershipResponse {
+    /// Response including when and by whom the membership was last changed.
+    pub fn with_audit(m: RoomMembership) -> Self {
+        let (updated_at, updated_by) = (m.updated_at, m.updated_by);
+        Self {
+            updated_at: Some(updated_at),
+            updated_by,
+            ..Self::from(m)
+        }
+    }
+
+    /// Add when the member was last seen and whether they are online now.
+    pub fn with_presence(self, last_seen_at: Option<DateTime<Utc>>, online: bool) -> Self {
+        Self {
+            last_seen_at,
+            online: Some(online),
+            ..self
+        }
+    }
 }
 
 impl From<RoomMembership> for MembershipResponse {
@@ -67,6 +127,10 @@ impl From<RoomMembership> for MembershipResponse {
             state_name: m.state_name,
             country: m.country,
             created_at: m.created_at,
+            updated_at: None,
+            updated_by: None,
+            last_seen_at: None,
+            online: None,
         }
     }
 }
@@ -1,10 +1,13 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 use validator::Validate;
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq)]
+use crate::extractors::pagination::SortOrder;
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq, ToSchema)]
 #[sqlx(type_name = "content_type", rename_all = "lowercase")]
 #[serde(rename_all = "lowercase")]
 pub enum ContentType {
@@ -21,44 +24,103 @@ pub struct ChatMessageWithUser {
     pub user_id: Uuid,
     pub content: String,
     pub content_type: ContentType,
+    pub rendered_safe: Option<String>,
     pub is_pinned: bool,
     pub is_off_topic: bool,
     pub is_deleted: bool,
     pub deleted_at: Option<DateTime<Utc>>,
+    pub is_shadowed: bool,
+    pub is_hidden: bool,
+    pub quoted_message_id: Option<Uuid>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub user_display_name: Option<String>,
     pub user_avatar_url: Option<String>,
+    pub user_is_bot: bool,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+/// A quoted message joined with its author's display name, for inline previews.
+#[derive(Debug, Clone, FromRow)]
+pub struct QuotedMessage {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+    pub user_display_name: Option<String>,
+}
+
+/// Room file attached to a message, joined with its file metadata.
+#[derive(Debug, Clone, FromRow)]
+pub struct MessageAttachment {
+    pub message_id: Uuid,
+    pub file_id: Uuid,
+    pub file_name: String,
+    pub file_url: String,
+    pub file_size: i64,
+    pub mime_type: String,
+}
+
+/// Query filters for listing room messages. Defaults match the unfiltered listing.
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct MessageListQuery {
+    #[serde(default)]
+    pub pinned_only: bool,
+    pub from_user: Option<Uuid>,
+    pub content_type: Option<ContentType>,
+    #[serde(default)]
+    pub order: SortOrder,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct CreateMessageRequest {
-    #[validate(length(min = 1, max = 5000))]
+    /// Up to `MESSAGE_MAX_CHARS` characters.
+    #[validate(length(min = 1))]
     pub content: String,
     pub content_type: Option<ContentType>,
+    /// Room files uploaded by the author to attach to the message, within
+    /// `MESSAGE_MAX_ATTACHMENTS` and `MESSAGE_MAX_ATTACHMENT_BYTES`.
+    #[serde(default)]
+    pub file_ids: Vec<Uuid>,
+    /// Earlier message in the same room to show as an inline quote.
+    pub quoted_message_id: Option<Uuid>,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct UpdateMessageRequest {
-    #[validate(length(min = 1, max = 5000))]
+    /// Up to `MESSAGE_MAX_CHARS` characters.
+    #[validate(length(min = 1))]
     pub content: Option<String>,
     pub is_pinned: Option<bool>,
     pub is_off_topic: Option<bool>,
 }
 
 /// Message response for API consumers.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct MessageResponse {
     pub id: Uuid,
     pub room_id: Uuid,
     pub user_id: Uuid,
     pub content: String,
     pub content_type: ContentType,
+    pub rendered_safe: Option<String>,
     pub is_pinned: bool,
     pub is_off_topic: bool,
     pub is_deleted: bool,
+    pub deleted_at: Option<DateTime<Utc>>,
     pub user_display_name: Option<String>,
     pub user_avatar_url: Option<String>,
+    pub user_is_bot: bool,
+    pub attachments: Vec<AttachmentResponse>,
+    pub quoted_message_id: Option<Uuid>,
+    /// Preview of the quoted message; `null` once the original is deleted or hidden.
+    pub quoted: Option<QuotedMessageResponse>,
+    /// Only reported to moderators, so shadow-banned authors can't tell.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_shadowed: Option<bool>,
+    /// Hidden pending review after repeated reports; only reported to moderators.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_hidden: Option<bool>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -71,13 +133,103 @@ impl From<ChatMessageWithUser> for MessageResponse {
             user_id: m.user_id,
             content: m.content,
             content_type: m.content_type,
+            rendered_safe: m.rendered_safe,
             is_pinned: m.is_pinned,
             is_off_topic: m.is_off_topic,
             is_deleted: m.is_deleted,
+            deleted_at: m.deleted_at,
             user_display_name: m.user_display_name,
             user_avatar_url: m.user_avatar_url,
+            user_is_bot: m.user_is_bot,
+            attachments: Vec::new(),
+            quoted_message_id: m.quoted_message_id,
+            quoted: None,
+            is_shadowed: None,
+            is_hidden: None,
             created_at: m.created_at,
             updated_at: m.updated_at,
         }
     }
 }
+
+impl MessageResponse {
+    /// Strip a deleted message down to what a sync client needs to drop it.
+    pub fn into_tombstone(self) -> Self {
+        Self {
+            content: String::new(),
+            rendered_safe: None,
+            attachments: Vec::new(),
+            quoted: None,
+            ..self
+        }
+    }
+
+    /// Tombstone for a message the caller may not see, such as one hidden pending review
+    /// or shadowed. It reads as a deletion, without the author's profile, so it gives away
+    /// neither the report nor the shadow ban.
+    pub fn into_withheld_tombstone(self) -> Self {
+        Self {
+            is_deleted: true,
+            is_pinned: false,
+            is_off_topic: false,
+            user_display_name: None,
+            user_avatar_url: None,
+            quoted_message_id: None,
+            ..self.into_tombstone()
+        }
+    }
+}
+
+/// Attachment metadata for API consumers. Images use the file itself as the thumbnail.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AttachmentResponse {
+    pub file_id: Uuid,
+    pub file_name: String,
+    pub file_url: String,
+    pub file_size: i64,
+    pub mime_type: String,
+    pub thumbnail_url: Option<String>,
+}
+
+impl From<MessageAttachment> for AttachmentResponse {
+    fn from(a: MessageAttachment) -> Self {
+        let thumbnail_url = a
+            .mime_type
+            .starts_with("image/")
+            .then(|| a.file_url.clone());
+        Self {
+            file_id: a.file_id,
+            file_name: a.file_name,
+            file_url: a.file_url,
+            file_size: a.file_size,
+            mime_type: a.mime_type,
+            thumbnail_url,
+        }
+    }
+}
+
+/// Longest snippet of a quoted message included in a preview.
+pub const QUOTE_SNIPPET_CHARS: usize = 200;
+
+/// Inline preview of a quoted message.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct QuotedMessageResponse {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub user_display_name: Option<String>,
+    /// Start of the quoted content, cut to `QUOTE_SNIPPET_CHARS` characters.
+    pub snippet: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<QuotedMessage> for QuotedMessageResponse {
+    fn from(q: QuotedMessage) -> Self {
+        Self {
+            id: q.id,
+            user_id: q.user_id,
+            user_display_name: q.user_display_name,
+            snippet: q.content.chars().take(QUOTE_SNIPPET_CHARS).collect(),
+            created_at: q.created_at,
+        }
+    }
+}
@@ -1,12 +1,17 @@
 pub mod alert;
+pub mod analytics;
 pub mod auth;
+pub mod draft;
 pub mod media_track;
 pub mod membership;
 pub mod message;
 pub mod moderation;
 pub mod notification;
+pub mod patch;
 pub mod poll;
 pub mod private_chat;
 pub mod room;
+pub mod room_hook;
 pub mod tenant;
 pub mod user;
+pub mod webhook;
@@ -12,6 +12,23 @@ pub enum ReportStatus {
     Dismissed,
 }
 
+/// What a report is about; stored as text in `reported_content.content_type`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportContentType {
+    Message,
+    User,
+}
+
+impl ReportContentType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ReportContentType::Message => "message",
+            ReportContentType::User => "user",
+        }
+    }
+}
+
 #[derive(Debug, Clone, FromRow, Serialize)]
 pub struct BannedUser {
     pub id: Uuid,
@@ -44,6 +61,8 @@ pub struct ReportedContent {
     pub reason: String,
     pub status: ReportStatus,
     pub reviewed_by: Option<Uuid>,
+    /// Distinct users who reported this content while the report was open.
+    pub report_count: i32,
     pub created_at: DateTime<Utc>,
 }
 
@@ -73,6 +92,55 @@ impl From<BannedUser> for BannedUserResponse {
     }
 }
 
+/// Which bans a listing includes, by whether they are still in force.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BanStatusFilter {
+    #[default]
+    All,
+    Active,
+    Expired,
+}
+
+/// Filters for the banned-users listing. `q` matches the banned user's display name.
+#[derive(Debug, Deserialize)]
+pub struct BannedUserListQuery {
+    #[serde(default)]
+    pub status: BanStatusFilter,
+    pub q: Option<String>,
+}
+
+/// A ban joined with the display names of the banned user and of the moderator.
+#[derive(Debug, FromRow)]
+pub struct BannedUserListing {
+    #[sqlx(flatten)]
+    pub ban: BannedUser,
+    pub user_display_name: Option<String>,
+    pub banned_by_display_name: Option<String>,
+}
+
+/// Banned-users listing entry.
+#[derive(Debug, Serialize)]
+pub struct BannedUserListingResponse {
+    #[serde(flatten)]
+    pub ban: BannedUserResponse,
+    pub user_display_name: Option<String>,
+    pub banned_by_display_name: Option<String>,
+    /// Whether the ban is still in force; bans without `expires_at` never lapse.
+    pub is_active: bool,
+}
+
+impl From<BannedUserListing> for BannedUserListingResponse {
+    fn from(b: BannedUserListing) -> Self {
+        Self {
+            is_active: b.ban.expires_at.is_none_or(|at| at > Utc::now()),
+            ban: BannedUserResponse::from(b.ban),
+            user_display_name: b.user_display_name,
+            banned_by_display_name: b.banned_by_display_name,
+        }
+    }
+}
+
 /// Moderation log response.
 #[derive(Debug, Serialize)]
 pub struct ModerationLogResponse {
@@ -110,6 +178,8 @@ pub struct ReportedContentResponse {
     pub reason: String,
     pub status: ReportStatus,
     pub reviewed_by: Option<Uuid>,
+    /// Distinct users who reported this content while the report was open.
+    pub report_count: i32,
     pub created_at: DateTime<Utc>,
 }
 
@@ -124,6 +194,7 @@ impl From<ReportedContent> for ReportedContentResponse {
             reason: r.reason,
             status: r.status,
             reviewed_by: r.reviewed_by,
+            report_count: r.report_count,
             created_at: r.created_at,
         }
     }
@@ -1,7 +1,97 @@
 use chrono::{DateTime, Utc};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq)]
+#[sqlx(type_name = "notification_type", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationType {
+    Mention,
+    Dm,
+    ModAction,
+    Poll,
+    Alert,
+    System,
+}
+
+/// Typed `data` payload for each notification type. Only the inner object is stored
+/// in `notifications.data`; the variant determines `notification_type`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum NotificationData {
+    Mention {
+        room_id: Uuid,
+        message_id: Uuid,
+        mentioned_by: Uuid,
+    },
+    Dm {
+        chat_id: Uuid,
+        message_id: Uuid,
+    },
+    ModAction {
+        room_id: Uuid,
+        moderator_id: Uuid,
+        action: String,
+    },
+    /// Reports on a piece of content crossed the room's auto-hide threshold.
+    ReportThreshold {
+        room_id: Uuid,
+        report_id: Uuid,
+        content_type: String,
+        content_id: Uuid,
+        report_count: i32,
+    },
+    Poll {
+        room_id: Uuid,
+        poll_id: Uuid,
+    },
+    Alert {
+        room_id: Uuid,
+        alert_id: Uuid,
+    },
+    System {
+        link: Option<String>,
+    },
+}
+
+impl NotificationData {
+    pub fn notification_type(&self) -> NotificationType {
+        match self {
+            NotificationData::Mention { .. } => NotificationType::Mention,
+            NotificationData::Dm { .. } => NotificationType::Dm,
+            NotificationData::ModAction { .. } | NotificationData::ReportThreshold { .. } => {
+                NotificationType::ModAction
+            }
+            NotificationData::Poll { .. } => NotificationType::Poll,
+            NotificationData::Alert { .. } => NotificationType::Alert,
+            NotificationData::System { .. } => NotificationType::System,
+        }
+    }
+
+    /// The room whose mute setting silences this notification. Moderator actions taken
+    /// against the recipient always get through.
+    pub fn muted_by_room(&self) -> Option<Uuid> {
+        match self {
+            NotificationData::Mention { room_id, .. }
+            | NotificationData::ReportThreshold { room_id, .. }
+            | NotificationData::Poll { room_id, .. }
+            | NotificationData::Alert { room_id, .. } => Some(*room_id),
+            NotificationData::Dm { .. }
+            | NotificationData::ModAction { .. }
+            | NotificationData::System { .. } => None,
+        }
+    }
+
+    /// The untagged payload object stored in `notifications.data`.
+    pub fn to_value(&self) -> serde_json::Value {
+        serde_json::to_value(self)
+            .ok()
+            .and_then(|mut v| v.get_mut("data").map(serde_json::Value::take))
+            .unwrap_or_default()
+    }
+}
 
 #[derive(Debug, Clone, FromRow, Serialize)]
 pub struct Notification {
@@ -9,10 +99,48 @@ pub struct Notification {
     pub user_id: Uuid,
     pub title: String,
     pub body: String,
-    pub notification_type: String,
+    pub notification_type: NotificationType,
     pub is_read: bool,
     pub data: Option<serde_json::Value>,
+    pub group_key: Option<String>,
+    pub count: i32,
     pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+/// Query filters for listing notifications.
+#[derive(Debug, Deserialize)]
+pub struct NotificationListQuery {
+    #[serde(default)]
+    pub unread_only: bool,
+    #[serde(rename = "type")]
+    pub notification_type: Option<NotificationType>,
+    /// Only notifications created before this instant, for paging back through history.
+    pub before: Option<DateTime<Utc>>,
+}
+
+/// Targets for a selective mark-read: either explicit IDs or every notification of a type.
+#[derive(Debug, Deserialize, Validate)]
+pub struct MarkReadRequest {
+    #[validate(length(min = 1, max = 500))]
+    pub ids: Option<Vec<Uuid>>,
+    #[serde(rename = "type")]
+    pub notification_type: Option<NotificationType>,
+}
+
+/// Which notifications `DELETE /` clears; by default only those already read.
+#[derive(Debug, Deserialize)]
+pub struct BulkDeleteQuery {
+    #[serde(default)]
+    pub all: bool,
+}
+
+/// Notifications to delete by ID.
+#[derive(Debug, Deserialize, Validate)]
+pub struct DeleteNotificationsRequest {
+    #[validate(length(min = 1, max = 500))]
+    pub ids: Vec<Uuid>,
 }
 
 /// Notification response for API consumers.
@@ -22,10 +150,14 @@ pub struct NotificationResponse {
     pub user_id: Uuid,
     pub title: String,
     pub body: String,
-    pub notification_type: String,
+    pub notification_type: NotificationType,
     pub is_read: bool,
     pub data: Option<serde_json::Value>,
+    /// Number of occurrences folded into this notification.
+    pub count: i32,
     pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
 impl From<Notification> for NotificationResponse {
@@ -38,7 +170,22 @@ impl From<Notification> for NotificationResponse {
             notification_type: n.notification_type,
             is_read: n.is_read,
             data: n.data,
+            count: n.count,
             created_at: n.created_at,
+            updated_at: n.updated_at,
+            deleted_at: n.deleted_at,
+        }
+    }
+}
+
+impl NotificationResponse {
+    /// Strip a deleted notification down to what a sync client needs to drop it.
+    pub fn into_tombstone(self) -> Self {
+        Self {
+            title: String::new(),
+            body: String::new(),
+            data: None,
+            ..self
         }
     }
 }
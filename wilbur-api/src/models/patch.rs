@@ -0,0 +1,77 @@
+use std::borrow::Cow;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use validator::{ValidateLength, ValidateRange, ValidateUrl};
+
+/// A nullable field in an update body. Unlike `Option<T>`, it tells an absent field
+/// (leave the column alone) apart from an explicit `null` (clear the column).
+/// Fields of this type need `#[serde(default)]` so that absent ones deserialize.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Patch<T> {
+    #[default]
+    Absent,
+    Null,
+    Value(T),
+}
+
+impl<T> Patch<T> {
+    pub fn is_absent(&self) -> bool {
+        matches!(self, Patch::Absent)
+    }
+
+    /// Whether the column should be written, either with a value or with NULL.
+    pub fn is_set(&self) -> bool {
+        !self.is_absent()
+    }
+
+    /// The new column value; `None` both when clearing and when absent.
+    pub fn value(&self) -> Option<&T> {
+        match self {
+            Patch::Value(v) => Some(v),
+            Patch::Absent | Patch::Null => None,
+        }
+    }
+
+    /// Treat an explicit `null` as absent, the way `PUT` updates always have.
+    pub fn ignore_null(self) -> Self {
+        match self {
+            Patch::Null => Patch::Absent,
+            other => other,
+        }
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Patch<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Option::<T>::deserialize(deserializer)?.map_or(Patch::Null, Patch::Value))
+    }
+}
+
+/// Serializes like `Option<T>`; an absent field becomes `null`.
+impl<T: Serialize> Serialize for Patch<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.value().serialize(serializer)
+    }
+}
+
+impl<T: ValidateLength<u64>> ValidateLength<u64> for Patch<T> {
+    fn length(&self) -> Option<u64> {
+        self.value().and_then(ValidateLength::length)
+    }
+}
+
+impl<T: ValidateUrl> ValidateUrl for Patch<T> {
+    fn as_url_string(&self) -> Option<Cow<'_, str>> {
+        self.value().and_then(ValidateUrl::as_url_string)
+    }
+}
+
+impl<T: PartialOrd> ValidateRange<T> for Patch<T> {
+    fn greater_than(&self, max: T) -> Option<bool> {
+        self.value().map(|v| *v > max)
+    }
+
+    fn less_than(&self, min: T) -> Option<bool> {
+        self.value().map(|v| *v < min)
+    }
+}
@@ -48,6 +48,16 @@ pub struct VoteRequest {
     pub option_index: i32,
 }
 
+/// Aggregate vote counts broadcast after each vote; individual votes are never broadcast.
+#[derive(Debug, Serialize)]
+pub struct PollResults {
+    pub poll_id: Uuid,
+    pub room_id: Uuid,
+    /// Votes per option, in option order.
+    pub counts: Vec<i64>,
+    pub total_votes: i64,
+}
+
 /// Poll response including vote counts.
 #[derive(Debug, Serialize)]
 pub struct PollResponse {
@@ -77,3 +87,14 @@ impl From<Poll> for PollResponse {
         }
     }
 }
+
+/// A single poll with its current tallies and the caller's own vote.
+#[derive(Debug, Serialize)]
+pub struct PollDetailResponse {
+    #[serde(flatten)]
+    pub poll: PollResponse,
+    /// Votes per option, in option order.
+    pub counts: Vec<i64>,
+    /// Index of the option the caller voted for, if they have voted.
+    pub my_vote: Option<i32>,
+}
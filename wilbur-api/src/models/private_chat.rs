@@ -1,8 +1,10 @@
 use chrono::{DateTime, Utc};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 
+use crate::models::message::ContentType;
+
 #[derive(Debug, Clone, FromRow, Serialize)]
 pub struct PrivateChat {
     pub id: Uuid,
@@ -17,8 +19,12 @@ pub struct PrivateMessage {
     pub chat_id: Uuid,
     pub sender_id: Uuid,
     pub content: String,
+    pub content_type: ContentType,
     pub is_read: bool,
+    pub is_deleted: bool,
+    pub deleted_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
+    pub updated_at: Option<DateTime<Utc>>,
 }
 
 /// Private chat response.
@@ -41,15 +47,108 @@ impl From<PrivateChat> for PrivateChatResponse {
     }
 }
 
-/// Private message response.
+/// A DM conversation as listed for one participant: the other user's profile and the
+/// latest message, as kept up to date on the chat row.
+#[derive(Debug, Clone, FromRow)]
+pub struct ChatSummary {
+    pub id: Uuid,
+    pub participant_one: Uuid,
+    pub participant_two: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub other_user_id: Uuid,
+    pub other_display_name: Option<String>,
+    pub other_avatar_url: Option<String>,
+    pub last_message_id: Option<Uuid>,
+    pub last_message_sender_id: Option<Uuid>,
+    pub last_message_preview: Option<String>,
+    pub last_message_at: Option<DateTime<Utc>>,
+    pub archived: bool,
+}
+
+/// Query filters for listing DM conversations.
+#[derive(Debug, Deserialize)]
+pub struct ChatListQuery {
+    /// Also return conversations the user has archived.
+    #[serde(default)]
+    pub include_archived: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChatParticipant {
+    pub id: Uuid,
+    pub display_name: Option<String>,
+    pub avatar_url: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LastMessagePreview {
+    pub id: Uuid,
+    pub sender_id: Uuid,
+    pub preview: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Conversation list entry.
+#[derive(Debug, Serialize)]
+pub struct ChatSummaryResponse {
+    pub id: Uuid,
+    pub participant_one: Uuid,
+    pub participant_two: Uuid,
+    pub other_user: ChatParticipant,
+    pub last_message: Option<LastMessagePreview>,
+    pub archived: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<ChatSummary> for ChatSummaryResponse {
+    fn from(c: ChatSummary) -> Self {
+        let last_message = match (
+            c.last_message_id,
+            c.last_message_sender_id,
+            c.last_message_preview,
+            c.last_message_at,
+        ) {
+            (Some(id), Some(sender_id), Some(preview), Some(created_at)) => {
+                Some(LastMessagePreview {
+                    id,
+                    sender_id,
+                    preview,
+                    created_at,
+                })
+            }
+            _ => None,
+        };
+
+        Self {
+            id: c.id,
+            participant_one: c.participant_one,
+            participant_two: c.participant_two,
+            other_user: ChatParticipant {
+                id: c.other_user_id,
+                display_name: c.other_display_name,
+                avatar_url: c.other_avatar_url,
+            },
+            last_message,
+            archived: c.archived,
+            created_at: c.created_at,
+        }
+    }
+}
+
+/// Private message response. Deleted messages are kept as placeholders with their
+/// content blanked, so the conversation still reads coherently for the other party.
 #[derive(Debug, Serialize)]
 pub struct PrivateMessageResponse {
     pub id: Uuid,
     pub chat_id: Uuid,
     pub sender_id: Uuid,
     pub content: String,
+    pub content_type: ContentType,
     pub is_read: bool,
+    pub is_deleted: bool,
+    pub deleted_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
+    pub updated_at: Option<DateTime<Utc>>,
 }
 
 impl From<PrivateMessage> for PrivateMessageResponse {
@@ -58,9 +157,17 @@ impl From<PrivateMessage> for PrivateMessageResponse {
             id: m.id,
             chat_id: m.chat_id,
             sender_id: m.sender_id,
-            content: m.content,
+            content: if m.is_deleted {
+                String::new()
+            } else {
+                m.content
+            },
+            content_type: m.content_type,
             is_read: m.is_read,
+            is_deleted: m.is_deleted,
+            deleted_at: m.deleted_at,
             created_at: m.created_at,
+            updated_at: m.updated_at,
         }
     }
 }
@@ -1,9 +1,13 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use utoipa::ToSchema;
 use uuid::Uuid;
 use validator::Validate;
 
+use crate::models::membership::{MemberRole, MemberStatus};
+use crate::models::patch::Patch;
+
 #[derive(Debug, Clone, FromRow, Serialize)]
 pub struct Room {
     pub id: Uuid,
@@ -19,11 +23,19 @@ pub struct Room {
     pub font_family: Option<String>,
     pub border_style: Option<String>,
     pub shadow_style: Option<String>,
+    pub sanitize_content: bool,
+    pub linkify_urls: bool,
+    pub message_retention_days: Option<i32>,
+    pub retention_exempt_pinned: bool,
+    pub report_auto_hide_threshold: Option<i32>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// User who last changed the room; `None` before anyone has.
+    pub updated_by: Option<Uuid>,
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct CreateRoomRequest {
     #[validate(length(min = 1, max = 100))]
     pub name: String,
@@ -39,28 +51,114 @@ pub struct CreateRoomRequest {
     pub font_family: Option<String>,
     pub border_style: Option<String>,
     pub shadow_style: Option<String>,
+    /// Store an HTML-safe rendering alongside message content.
+    pub sanitize_content: Option<bool>,
+    /// Turn bare URLs into safe links in the rendering.
+    pub linkify_urls: Option<bool>,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+/// Changes to a room. Absent fields are left as they are. Through `PATCH`, `null` clears a
+/// nullable field; through `PUT`, `null` is ignored (see [`UpdateRoomRequest::ignore_nulls`]).
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct UpdateRoomRequest {
     #[validate(length(min = 1, max = 100))]
     pub name: Option<String>,
     #[validate(length(min = 1, max = 200))]
-    pub title: Option<String>,
+    #[serde(default)]
+    #[schema(value_type = Option<String>)]
+    pub title: Patch<String>,
     #[validate(length(max = 2000))]
-    pub description: Option<String>,
+    #[serde(default)]
+    #[schema(value_type = Option<String>)]
+    pub description: Patch<String>,
     pub max_members: Option<i32>,
     pub is_active: Option<bool>,
-    pub background_image_url: Option<String>,
-    pub header_color: Option<String>,
-    pub accent_color: Option<String>,
-    pub font_family: Option<String>,
-    pub border_style: Option<String>,
-    pub shadow_style: Option<String>,
+    #[serde(default)]
+    #[schema(value_type = Option<String>)]
+    pub background_image_url: Patch<String>,
+    #[serde(default)]
+    #[schema(value_type = Option<String>)]
+    pub header_color: Patch<String>,
+    #[serde(default)]
+    #[schema(value_type = Option<String>)]
+    pub accent_color: Patch<String>,
+    #[serde(default)]
+    #[schema(value_type = Option<String>)]
+    pub font_family: Patch<String>,
+    #[serde(default)]
+    #[schema(value_type = Option<String>)]
+    pub border_style: Patch<String>,
+    #[serde(default)]
+    #[schema(value_type = Option<String>)]
+    pub shadow_style: Patch<String>,
+    /// Store an HTML-safe rendering alongside message content.
+    pub sanitize_content: Option<bool>,
+    /// Turn bare URLs into safe links in the rendering.
+    pub linkify_urls: Option<bool>,
+    /// Delete messages older than this many days; zero keeps them forever.
+    #[validate(range(min = 0, max = 3650))]
+    pub message_retention_days: Option<i32>,
+    /// Keep pinned messages when purging expired ones.
+    pub retention_exempt_pinned: Option<bool>,
+    /// Hide a message pending review once this many distinct members report it;
+    /// `null` turns auto-hiding off.
+    #[validate(range(min = 1, max = 1000))]
+    #[serde(default)]
+    #[schema(value_type = Option<i32>)]
+    pub report_auto_hide_threshold: Patch<i32>,
+}
+
+impl UpdateRoomRequest {
+    /// Fields in this update that only the room host may change.
+    /// Appearance fields and title/description are open to moderators.
+    pub fn host_only_fields(&self) -> Vec<&'static str> {
+        let mut fields = Vec::new();
+        if self.name.is_some() {
+            fields.push("name");
+        }
+        if self.max_members.is_some() {
+            fields.push("max_members");
+        }
+        if self.is_active.is_some() {
+            fields.push("is_active");
+        }
+        if self.sanitize_content.is_some() {
+            fields.push("sanitize_content");
+        }
+        if self.linkify_urls.is_some() {
+            fields.push("linkify_urls");
+        }
+        if self.message_retention_days.is_some() {
+            fields.push("message_retention_days");
+        }
+        if self.retention_exempt_pinned.is_some() {
+            fields.push("retention_exempt_pinned");
+        }
+        if self.report_auto_hide_threshold.is_set() {
+            fields.push("report_auto_hide_threshold");
+        }
+        fields
+    }
+
+    /// The same update with explicit nulls treated as absent, for `PUT`.
+    pub fn ignore_nulls(self) -> Self {
+        Self {
+            title: self.title.ignore_null(),
+            description: self.description.ignore_null(),
+            background_image_url: self.background_image_url.ignore_null(),
+            header_color: self.header_color.ignore_null(),
+            accent_color: self.accent_color.ignore_null(),
+            font_family: self.font_family.ignore_null(),
+            border_style: self.border_style.ignore_null(),
+            shadow_style: self.shadow_style.ignore_null(),
+            report_auto_hide_threshold: self.report_auto_hide_threshold.ignore_null(),
+            ..self
+        }
+    }
 }
 
 /// Public room response.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct RoomResponse {
     pub id: Uuid,
     pub tenant_id: Option<Uuid>,
@@ -75,8 +173,16 @@ pub struct RoomResponse {
     pub font_family: Option<String>,
     pub border_style: Option<String>,
     pub shadow_style: Option<String>,
+    pub sanitize_content: bool,
+    pub linkify_urls: bool,
+    pub message_retention_days: Option<i32>,
+    pub retention_exempt_pinned: bool,
+    pub report_auto_hide_threshold: Option<i32>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// User who last changed the room; `None` before anyone has.
+    pub updated_by: Option<Uuid>,
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
 impl From<Room> for RoomResponse {
@@ -95,8 +201,98 @@ impl From<Room> for RoomResponse {
             font_family: r.font_family,
             border_style: r.border_style,
             shadow_style: r.shadow_style,
+            sanitize_content: r.sanitize_content,
+            linkify_urls: r.linkify_urls,
+            message_retention_days: r.message_retention_days,
+            retention_exempt_pinned: r.retention_exempt_pinned,
+            report_auto_hide_threshold: r.report_auto_hide_threshold,
             created_at: r.created_at,
             updated_at: r.updated_at,
+            updated_by: r.updated_by,
+            deleted_at: r.deleted_at,
         }
     }
 }
+
+impl RoomResponse {
+    /// Strip a deleted room down to what a sync client needs to drop it.
+    pub fn into_tombstone(self) -> Self {
+        Self {
+            name: String::new(),
+            title: None,
+            description: None,
+            background_image_url: None,
+            ..self
+        }
+    }
+}
+
+/// Room row joined with one user's membership in it, their preferences for it, and the
+/// room's active member count.
+#[derive(Debug, Clone, FromRow)]
+pub struct MemberRoom {
+    #[sqlx(flatten)]
+    pub room: Room,
+    pub role: MemberRole,
+    pub status: MemberStatus,
+    pub member_count: i64,
+    pub sort_order: Option<i32>,
+    pub is_hidden: bool,
+    pub is_muted: bool,
+}
+
+/// A room the caller belongs to, with their role and status in it and their preferences for it.
+#[derive(Debug, Serialize)]
+pub struct MemberRoomResponse {
+    #[serde(flatten)]
+    pub room: RoomResponse,
+    pub role: MemberRole,
+    pub status: MemberStatus,
+    pub member_count: i64,
+    pub sort_order: Option<i32>,
+    pub is_hidden: bool,
+    pub is_muted: bool,
+}
+
+impl From<MemberRoom> for MemberRoomResponse {
+    fn from(r: MemberRoom) -> Self {
+        Self {
+            room: RoomResponse::from(r.room),
+            role: r.role,
+            status: r.status,
+            member_count: r.member_count,
+            sort_order: r.sort_order,
+            is_hidden: r.is_hidden,
+            is_muted: r.is_muted,
+        }
+    }
+}
+
+/// Query filters for listing the caller's rooms.
+#[derive(Debug, Deserialize)]
+pub struct MemberRoomListQuery {
+    /// Also return rooms the caller has hidden.
+    #[serde(default)]
+    pub include_hidden: bool,
+}
+
+/// One user's ordering, visibility, and mute setting for a room. Rooms without a row are
+/// unhidden, unmuted, and ordered after every room with a `sort_order`.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct RoomPreferences {
+    pub room_id: Uuid,
+    pub sort_order: Option<i32>,
+    pub is_hidden: bool,
+    pub is_muted: bool,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Absent fields keep their current value; `sort_order: null` moves the room back to the end.
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateRoomPreferencesRequest {
+    #[serde(default)]
+    #[validate(range(min = 0))]
+    pub sort_order: Patch<i32>,
+    pub is_hidden: Option<bool>,
+    pub is_muted: Option<bool>,
+}
@@ -0,0 +1,88 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+use super::{alert::CreateAlertRequest, message::CreateMessageRequest};
+
+/// What an incoming hook is allowed to post. Alert-capable hooks may also post messages.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum HookScope {
+    #[default]
+    Message,
+    Alert,
+}
+
+impl HookScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HookScope::Message => "message",
+            HookScope::Alert => "alert",
+        }
+    }
+}
+
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct RoomHook {
+    pub id: Uuid,
+    pub room_id: Uuid,
+    pub bot_user_id: Uuid,
+    pub created_by: Uuid,
+    pub name: String,
+    pub scope: String,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateRoomHookRequest {
+    /// Display name of the bot that posts through this hook.
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    #[serde(default)]
+    pub scope: HookScope,
+}
+
+/// Body posted to an incoming hook, tagged by `type`.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum IncomingHookRequest {
+    Message(CreateMessageRequest),
+    Alert(CreateAlertRequest),
+}
+
+/// Hook response for API consumers. The token is only returned on creation.
+#[derive(Debug, Serialize)]
+pub struct RoomHookResponse {
+    pub id: Uuid,
+    pub room_id: Uuid,
+    pub bot_user_id: Uuid,
+    pub created_by: Uuid,
+    pub name: String,
+    pub scope: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<RoomHook> for RoomHookResponse {
+    fn from(h: RoomHook) -> Self {
+        Self {
+            id: h.id,
+            room_id: h.room_id,
+            bot_user_id: h.bot_user_id,
+            created_by: h.created_by,
+            name: h.name,
+            scope: h.scope,
+            token: None,
+            last_used_at: h.last_used_at,
+            revoked_at: h.revoked_at,
+            created_at: h.created_at,
+        }
+    }
+}
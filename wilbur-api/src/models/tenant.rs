@@ -4,6 +4,8 @@ use sqlx::FromRow;
 use uuid::Uuid;
 use validator::Validate;
 
+use crate::models::patch::Patch;
+
 #[derive(Debug, Clone, FromRow, Serialize)]
 pub struct Tenant {
     pub id: Uuid,
@@ -24,30 +26,113 @@ pub struct Tenant {
     pub login_background_url: Option<String>,
     pub dashboard_layout: Option<String>,
     pub sidebar_position: Option<String>,
+    pub email_header_url: Option<String>,
+    pub email_footer_text: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// User who last changed the tenant; `None` before anyone has.
+    pub updated_by: Option<Uuid>,
 }
 
+/// Branding changes. Through `PATCH`, `null` clears a field; through `PUT`, it is ignored.
 #[derive(Debug, Deserialize, Validate)]
 pub struct UpdateTenantRequest {
     #[validate(length(min = 1, max = 200))]
     pub business_name: Option<String>,
-    pub logo_url: Option<String>,
-    pub primary_color: Option<String>,
-    pub secondary_color: Option<String>,
-    pub accent_color: Option<String>,
-    pub header_font: Option<String>,
-    pub body_font: Option<String>,
-    pub border_radius: Option<String>,
-    pub background_image_url: Option<String>,
-    pub favicon_url: Option<String>,
-    pub tagline: Option<String>,
-    pub website_url: Option<String>,
-    pub support_email: Option<String>,
-    pub custom_css: Option<String>,
-    pub login_background_url: Option<String>,
-    pub dashboard_layout: Option<String>,
-    pub sidebar_position: Option<String>,
+    #[serde(default)]
+    pub logo_url: Patch<String>,
+    #[serde(default)]
+    pub primary_color: Patch<String>,
+    #[serde(default)]
+    pub secondary_color: Patch<String>,
+    #[serde(default)]
+    pub accent_color: Patch<String>,
+    #[serde(default)]
+    pub header_font: Patch<String>,
+    #[serde(default)]
+    pub body_font: Patch<String>,
+    #[serde(default)]
+    pub border_radius: Patch<String>,
+    #[serde(default)]
+    pub background_image_url: Patch<String>,
+    #[serde(default)]
+    pub favicon_url: Patch<String>,
+    #[serde(default)]
+    pub tagline: Patch<String>,
+    #[serde(default)]
+    pub website_url: Patch<String>,
+    #[serde(default)]
+    pub support_email: Patch<String>,
+    #[serde(default)]
+    pub custom_css: Patch<String>,
+    #[serde(default)]
+    pub login_background_url: Patch<String>,
+    #[serde(default)]
+    pub dashboard_layout: Patch<String>,
+    #[serde(default)]
+    pub sidebar_position: Patch<String>,
+    #[validate(url)]
+    #[serde(default)]
+    pub email_header_url: Patch<String>,
+    #[validate(length(max = 1000))]
+    #[serde(default)]
+    pub email_footer_text: Patch<String>,
+}
+
+impl UpdateTenantRequest {
+    /// The same update with explicit nulls treated as absent, for `PUT`.
+    pub fn ignore_nulls(self) -> Self {
+        Self {
+            business_name: self.business_name,
+            logo_url: self.logo_url.ignore_null(),
+            primary_color: self.primary_color.ignore_null(),
+            secondary_color: self.secondary_color.ignore_null(),
+            accent_color: self.accent_color.ignore_null(),
+            header_font: self.header_font.ignore_null(),
+            body_font: self.body_font.ignore_null(),
+            border_radius: self.border_radius.ignore_null(),
+            background_image_url: self.background_image_url.ignore_null(),
+            favicon_url: self.favicon_url.ignore_null(),
+            tagline: self.tagline.ignore_null(),
+            website_url: self.website_url.ignore_null(),
+            support_email: self.support_email.ignore_null(),
+            custom_css: self.custom_css.ignore_null(),
+            login_background_url: self.login_background_url.ignore_null(),
+            dashboard_layout: self.dashboard_layout.ignore_null(),
+            sidebar_position: self.sidebar_position.ignore_null(),
+            email_header_url: self.email_header_url.ignore_null(),
+            email_footer_text: self.email_footer_text.ignore_null(),
+        }
+    }
+}
+
+/// Branding images that can be uploaded instead of set as a URL, as named in the path.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TenantImage {
+    Logo,
+    /// The tenant's background image.
+    Banner,
+    Favicon,
+}
+
+impl TenantImage {
+    /// Column holding the image's URL.
+    pub fn column(self) -> &'static str {
+        match self {
+            TenantImage::Logo => "logo_url",
+            TenantImage::Banner => "background_image_url",
+            TenantImage::Favicon => "favicon_url",
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TenantImage::Logo => "logo",
+            TenantImage::Banner => "banner",
+            TenantImage::Favicon => "favicon",
+        }
+    }
 }
 
 /// Tenant response for API consumers.
@@ -71,8 +156,12 @@ pub struct TenantResponse {
     pub login_background_url: Option<String>,
     pub dashboard_layout: Option<String>,
     pub sidebar_position: Option<String>,
+    pub email_header_url: Option<String>,
+    pub email_footer_text: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// User who last changed the tenant; `None` before anyone has.
+    pub updated_by: Option<Uuid>,
 }
 
 impl From<Tenant> for TenantResponse {
@@ -96,8 +185,11 @@ impl From<Tenant> for TenantResponse {
             login_background_url: t.login_background_url,
             dashboard_layout: t.dashboard_layout,
             sidebar_position: t.sidebar_position,
+            email_header_url: t.email_header_url,
+            email_footer_text: t.email_footer_text,
             created_at: t.created_at,
             updated_at: t.updated_at,
+            updated_by: t.updated_by,
         }
     }
 }
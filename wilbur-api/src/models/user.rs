@@ -1,10 +1,13 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use utoipa::ToSchema;
 use uuid::Uuid;
 use validator::Validate;
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq)]
+use crate::models::patch::Patch;
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq, ToSchema)]
 #[sqlx(type_name = "user_role", rename_all = "lowercase")]
 #[serde(rename_all = "lowercase")]
 pub enum UserRole {
@@ -14,6 +17,17 @@ pub enum UserRole {
     Member,
 }
 
+/// Who may send DMs to a user, in new and existing conversations alike.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq, ToSchema)]
+#[sqlx(type_name = "dm_policy", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum DmPolicy {
+    Anyone,
+    /// Only users who are active members of at least one room with them.
+    SharedRooms,
+    Nobody,
+}
+
 #[derive(Debug, Clone, FromRow, Serialize)]
 pub struct User {
     pub id: Uuid,
@@ -25,11 +39,17 @@ pub struct User {
     pub role: UserRole,
     pub tokens: Option<i32>,
     pub email_verified_at: Option<DateTime<Utc>>,
+    pub is_bot: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Last authenticated request or WebSocket activity, recorded at most once a minute.
+    pub last_seen_at: Option<DateTime<Utc>>,
+    /// Tenant whose rooms the user can reach when tenant isolation is on.
+    pub tenant_id: Option<Uuid>,
+    pub dm_policy: DmPolicy,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct CreateUserRequest {
     #[validate(email)]
     pub email: String,
@@ -37,25 +57,96 @@ pub struct CreateUserRequest {
     pub password: String,
     #[validate(length(min = 1, max = 100))]
     pub display_name: Option<String>,
+    /// Tenant whose branding the welcome email should carry. Only a hint: the account is
+    /// not placed in the tenant, which is up to an admin.
+    pub tenant_id: Option<Uuid>,
 }
 
+/// Profile changes. Through `PATCH`, `null` clears a field; through `PUT`, it is ignored.
 #[derive(Debug, Deserialize, Validate)]
 pub struct UpdateUserRequest {
     #[validate(length(min = 1, max = 100))]
-    pub display_name: Option<String>,
+    #[serde(default)]
+    pub display_name: Patch<String>,
+    #[serde(default)]
+    pub avatar_url: Patch<String>,
+    pub dm_policy: Option<DmPolicy>,
+}
+
+impl UpdateUserRequest {
+    /// The same update with explicit nulls treated as absent, for `PUT`.
+    pub fn ignore_nulls(self) -> Self {
+        Self {
+            display_name: self.display_name.ignore_null(),
+            avatar_url: self.avatar_url.ignore_null(),
+            dm_policy: self.dm_policy,
+        }
+    }
+}
+
+/// How a user appears to others in presence events and calls; never includes the email.
+#[derive(Debug, Clone)]
+pub struct PublicIdentity {
+    pub display_name: String,
     pub avatar_url: Option<String>,
 }
 
+/// Path, relative to the API origin, of the generated avatar shown for users without one.
+pub fn default_avatar_path(user_id: Uuid) -> String {
+    format!("/api/v1/users/{user_id}/avatar/default.svg")
+}
+
 /// Public user response (excludes password_hash and internal fields).
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct UserResponse {
     pub id: Uuid,
     pub email: String,
     pub display_name: Option<String>,
-    pub avatar_url: Option<String>,
+    /// The uploaded avatar, or the generated default (see `has_custom_avatar`), which is a
+    /// path relative to the API origin.
+    pub avatar_url: String,
+    pub has_custom_avatar: bool,
     pub role: UserRole,
     pub tokens: Option<i32>,
+    pub is_bot: bool,
+    pub tenant_id: Option<Uuid>,
+    pub dm_policy: DmPolicy,
     pub created_at: DateTime<Utc>,
+    pub last_seen_at: Option<DateTime<Utc>>,
+    /// Whether the user has a WebSocket open right now.
+    pub online: bool,
+}
+
+impl UserResponse {
+    /// Response with `online` set, e.g. from `AppState::is_online`.
+    pub fn with_online(u: User, online: bool) -> Self {
+        Self {
+            online,
+            ..Self::from(u)
+        }
+    }
+}
+
+/// What non-admins see of other users in search results: enough to pick someone out, and
+/// nothing private (no email, role, tokens or DM policy).
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PublicUserResponse {
+    pub id: Uuid,
+    pub display_name: Option<String>,
+    /// The uploaded avatar, or the generated default.
+    pub avatar_url: String,
+    pub online: bool,
+}
+
+impl PublicUserResponse {
+    pub fn with_online(u: User, online: bool) -> Self {
+        Self {
+            id: u.id,
+            display_name: u.display_name,
+            avatar_url: u.avatar_url.unwrap_or_else(|| default_avatar_path(u.id)),
+            online,
+        }
+    }
 }
 
 impl From<User> for UserResponse {
@@ -64,10 +155,16 @@ impl From<User> for UserResponse {
             id: u.id,
             email: u.email,
             display_name: u.display_name,
-            avatar_url: u.avatar_url,
+            has_custom_avatar: u.avatar_url.is_some(),
+            avatar_url: u.avatar_url.unwrap_or_else(|| default_avatar_path(u.id)),
             role: u.role,
             tokens: u.tokens,
+            is_bot: u.is_bot,
+            tenant_id: u.tenant_id,
+            dm_policy: u.dm_policy,
             created_at: u.created_at,
+            last_seen_at: u.last_seen_at,
+            online: false,
         }
     }
 }
@@ -0,0 +1,199 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    routing::{get, post, put},
+    Router,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    error::{AppError, AppResult},
+    extractors::{auth::AuthUser, json::Json},
+    routes::auth::invalidate_all_user_tokens,
+    state::AppState,
+};
+
+/// Channels listed in `top_channels`.
+const TOP_CHANNELS: usize = 20;
+
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/ws-stats", get(ws_stats))
+        .route("/users/{id}/revoke-tokens", post(revoke_user_tokens))
+        .route("/users/{id}/tenant", put(assign_user_tenant))
+}
+
+#[derive(Debug, Serialize)]
+struct ChannelCount {
+    channel: String,
+    subscribers: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct WsStats {
+    channels: usize,
+    subscribers: usize,
+    /// Senders whose socket has gone away but that are still subscribed; a steadily
+    /// growing number points at a cleanup leak.
+    closed_subscribers: usize,
+    /// Channels with no subscribers left, which should have been removed.
+    empty_channels: usize,
+    top_channels: Vec<ChannelCount>,
+    per_channel: BTreeMap<String, usize>,
+}
+
+#[derive(Debug, Serialize)]
+struct RevokeTokensResponse {
+    user_id: Uuid,
+    sessions_revoked: u64,
+    refresh_tokens_revoked: u64,
+    /// Access tokens aren't checked against sessions, so ones already issued (and WebSockets
+    /// opened with them) keep working until they expire; none lasts past this.
+    access_tokens_valid_until: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AssignTenantRequest {
+    /// `null` takes the user out of any tenant.
+    tenant_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+struct UserTenantResponse {
+    user_id: Uuid,
+    tenant_id: Option<Uuid>,
+}
+
+/// PUT /users/{id}/tenant -- place a user in a tenant, or take them out of one (admin only).
+/// Signup never does this, so a client can't pick the tenant whose rooms it may reach.
+async fn assign_user_tenant(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(body): Json<AssignTenantRequest>,
+) -> AppResult<Json<UserTenantResponse>> {
+    if auth_user.role != "admin" {
+        return Err(AppError::Forbidden("Admin access required".into()));
+    }
+
+    if let Some(tenant_id) = body.tenant_id {
+        let exists: bool =
+            sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM tenants WHERE id = $1)")
+                .bind(tenant_id)
+                .fetch_one(&state.pool)
+                .await?;
+        if !exists {
+            return Err(AppError::NotFound("Tenant not found".into()));
+        }
+    }
+
+    let result = sqlx::query("UPDATE users SET tenant_id = $2, updated_at = NOW() WHERE id = $1")
+        .bind(id)
+        .bind(body.tenant_id)
+        .execute(&state.pool)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("User not found".into()));
+    }
+
+    tracing::info!(
+        admin_id = %auth_user.id,
+        user_id = %id,
+        tenant_id = ?body.tenant_id,
+        "Admin assigned a user's tenant"
+    );
+
+    Ok(Json(UserTenantResponse {
+        user_id: id,
+        tenant_id: body.tenant_id,
+    }))
+}
+
+/// POST /users/{id}/revoke-tokens -- sign a user out everywhere without touching their
+/// password, e.g. after a suspected compromise (admin only). Refresh is refused at once, but
+/// `AuthUser` only verifies the JWT, so access tokens already issued, and WebSockets opened
+/// with them, keep working until they expire. The response says when the last one does.
+async fn revoke_user_tokens(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> AppResult<Json<RevokeTokensResponse>> {
+    if auth_user.role != "admin" {
+        return Err(AppError::Forbidden("Admin access required".into()));
+    }
+
+    let exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM users WHERE id = $1)")
+        .bind(id)
+        .fetch_one(&state.pool)
+        .await?;
+    if !exists {
+        return Err(AppError::NotFound("User not found".into()));
+    }
+
+    let revoked = invalidate_all_user_tokens(&state.pool, id).await?;
+    tracing::warn!(
+        admin_id = %auth_user.id,
+        user_id = %id,
+        sessions = revoked.sessions,
+        refresh_tokens = revoked.refresh_tokens,
+        "Admin revoked all of a user's tokens"
+    );
+
+    // The longest access-token lifetime of any client type bounds how long one can survive
+    let longest_access_secs = state
+        .config
+        .jwt_client_profiles
+        .iter()
+        .map(|p| p.access_token_expiry_secs)
+        .fold(state.config.jwt_access_token_expiry_secs, i64::max);
+
+    Ok(Json(RevokeTokensResponse {
+        user_id: id,
+        sessions_revoked: revoked.sessions,
+        refresh_tokens_revoked: revoked.refresh_tokens,
+        access_tokens_valid_until: Utc::now() + Duration::seconds(longest_access_secs),
+    }))
+}
+
+/// GET /ws-stats -- live WebSocket subscriptions (admin only).
+async fn ws_stats(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+) -> AppResult<Json<WsStats>> {
+    if auth_user.role != "admin" {
+        return Err(AppError::Forbidden("Admin access required".into()));
+    }
+
+    // DashMap iteration read-locks one shard at a time, so sockets keep (un)subscribing
+    // elsewhere while this runs; the totals are a near-consistent snapshot.
+    let mut per_channel = BTreeMap::new();
+    let mut closed_subscribers = 0;
+    for entry in state.ws_channels.iter() {
+        closed_subscribers += entry.value().iter().filter(|s| s.is_closed()).count();
+        per_channel.insert(entry.key().clone(), entry.value().len());
+    }
+
+    let mut top_channels: Vec<ChannelCount> = per_channel
+        .iter()
+        .map(|(channel, &subscribers)| ChannelCount {
+            channel: channel.clone(),
+            subscribers,
+        })
+        .collect();
+    top_channels.sort_by_key(|c| Reverse(c.subscribers));
+    top_channels.truncate(TOP_CHANNELS);
+
+    Ok(Json(WsStats {
+        channels: per_channel.len(),
+        subscribers: per_channel.values().sum(),
+        closed_subscribers,
+        empty_channels: per_channel.values().filter(|&&n| n == 0).count(),
+        top_channels,
+        per_channel,
+    }))
+}
@@ -1,56 +1,250 @@
 use std::sync::Arc;
 
 use axum::{
-    extract::{Json, Multipart, Path, Query, State},
+    extract::{Multipart, Path, Query, State},
     http::StatusCode,
     routing::{delete, get, post},
     Router,
 };
+use chrono::Utc;
 use serde_json::{json, Value};
+use sqlx::{PgConnection, Postgres, QueryBuilder};
 use uuid::Uuid;
+use validator::Validate;
 
 use crate::{
     error::{AppError, AppResult},
-    extractors::{auth::AuthUser, pagination::PaginationParams},
-    models::alert::{Alert, AlertResponse, CreateAlertRequest},
-    routes::storage::{sanitize_filename, validate_upload, ALLOWED_MEDIA_TYPES},
+    extractors::{
+        auth::AuthUser,
+        json::Json,
+        pagination::PaginationParams,
+        room_access::{RoomMember, RoomModerator},
+        soft_delete::DeletedFilter,
+        sync::SyncFilter,
+    },
+    i18n::Message,
+    models::alert::{
+        Alert, AlertListQuery, AlertResponse, AlertStatusFilter, CreateAlertRequest,
+        MIN_LEGAL_DISCLOSURE_CHARS,
+    },
+    routes::{
+        created,
+        storage::{
+            prepare_upload, put_upload, require_storage, sanitize_filename, validate_upload,
+            ALLOWED_MEDIA_TYPES,
+        },
+        Created,
+    },
+    services::{
+        room_permissions::{require_capability, Capability},
+        tenant_config,
+    },
     state::AppState,
-    ws::manager::WsManager,
+    ws::{channels::Channel, outbox},
 };
 
 pub fn router() -> Router<Arc<AppState>> {
     Router::new()
         .route("/", get(list_alerts))
         .route("/", post(create_alert))
+        .route("/scheduled", get(list_scheduled_alerts))
         .route("/{id}", delete(delete_alert))
+        .route("/{id}/cancel", post(cancel_scheduled_alert))
+        .route("/{id}/pin", post(toggle_alert_pin))
+        .route("/{id}/ack", post(acknowledge_alert))
         .route("/{id}/media", post(upload_alert_media))
 }
 
-/// GET / -- list alerts for a room.
+/// GET / -- list alerts for a room, pinned alerts first, with their acknowledgement counts.
+/// `status=all` also returns deleted alerts and is limited to moderators. With
+/// `updated_after`, returns changes since then instead, deletions included.
 async fn list_alerts(
     State(state): State<Arc<AppState>>,
-    _auth_user: AuthUser,
+    auth_user: AuthUser,
+    _member: RoomMember,
     Path(room_id): Path<Uuid>,
-    Query(pagination): Query<PaginationParams>,
+    pagination: PaginationParams,
+    Query(filter): Query<AlertListQuery>,
+    Query(sync): Query<SyncFilter>,
 ) -> AppResult<Json<Value>> {
-    let limit = pagination.limit();
-    let offset = pagination.offset();
+    let include_deleted = DeletedFilter {
+        include_deleted: filter.status == AlertStatusFilter::All,
+    }
+    .resolve(&state.pool, &auth_user, Some(room_id))
+    .await?;
+
+    let mut query = QueryBuilder::<Postgres>::new(
+        r#"
+        SELECT id, room_id, author_id, title, body, alert_type,
+               ticker_symbol, entry_price::float8 as entry_price,
+               stop_loss::float8 as stop_loss, take_profit::float8 as take_profit,
+               media_url, legal_disclosure, is_active, is_pinned, pinned_at, publish_at, created_at, updated_at, deleted_at,
+               (SELECT COUNT(*) FROM alert_acknowledgements ack WHERE ack.alert_id = alerts.id)
+                   AS ack_count,
+               EXISTS(SELECT 1 FROM alert_acknowledgements ack
+                      WHERE ack.alert_id = alerts.id AND ack.user_id = "#,
+    );
+    query
+        .push_bind(auth_user.id)
+        .push(") AS acked_by_me FROM alerts WHERE room_id = ")
+        .push_bind(room_id);
+
+    if let Some(updated_after) = sync.updated_after {
+        query.push(" AND updated_at > ").push_bind(updated_after);
+    }
+    if include_deleted || sync.updated_after.is_some() {
+        // Pending scheduled alerts stay hidden until published
+        query.push(" AND (is_active = true OR publish_at IS NULL)");
+    } else {
+        query.push(" AND is_active = true");
+    }
+    if let Some(alert_type) = filter.alert_type {
+        query.push(" AND alert_type = ").push_bind(alert_type);
+    }
+    if let Some(ticker) = filter.ticker_symbol.filter(|t| !t.trim().is_empty()) {
+        query
+            .push(" AND UPPER(ticker_symbol) = UPPER(")
+            .push_bind(ticker.trim().to_string())
+            .push(")");
+    }
+
+    query
+        .push(if sync.updated_after.is_some() {
+            " ORDER BY updated_at ASC, id ASC LIMIT "
+        } else {
+            " ORDER BY is_pinned DESC, pinned_at DESC, created_at DESC LIMIT "
+        })
+        .push_bind(pagination.limit())
+        .push(" OFFSET ")
+        .push_bind(pagination.offset());
+
+    let alerts = query
+        .build_query_as::<Alert>()
+        .fetch_all(&state.pool)
+        .await?;
+
+    let data: Vec<AlertResponse> = alerts
+        .into_iter()
+        .map(|a| {
+            let tombstone = !a.is_active && !include_deleted;
+            let response = AlertResponse::from(a);
+            if tombstone {
+                response.into_tombstone()
+            } else {
+                response
+            }
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "room_id": room_id,
+        "page": pagination.page,
+        "per_page": pagination.per_page(),
+        "data": data
+    })))
+}
+
+/// POST / -- create a new alert in the room.
+async fn create_alert(
+    State(state): State<Arc<AppState>>,
+    RoomMember(member): RoomMember,
+    Path(room_id): Path<Uuid>,
+    Json(body): Json<CreateAlertRequest>,
+) -> AppResult<Created<Value>> {
+    require_capability(&state.pool, &member, Capability::PostAlert).await?;
+
+    body.validate()
+        .map_err(|e| AppError::Validation(e.to_string().into()))?;
+
+    let mut tx = state.pool.begin().await?;
+
+    let alert = insert_alert(&mut tx, room_id, member.user_id, &body).await?;
+    let alert_id = alert.id;
+    let is_scheduled = !alert.is_active;
+
+    let response = AlertResponse::from(alert);
+    let response_json = serde_json::to_value(&response)
+        .map_err(|e| AppError::Internal(format!("Serialization error: {e}")))?;
+
+    // Scheduled alerts are broadcast by the scheduler when they go live
+    if !is_scheduled {
+        // Queue the broadcast in the same transaction so it is only sent once committed
+        let channel = Channel::room_alerts(room_id);
+        outbox::enqueue(&mut tx, &channel, "alert_created", &response_json).await?;
+    }
+
+    tx.commit().await?;
+    outbox::wake(&state);
+
+    Ok(created(
+        format!("/api/v1/rooms/{room_id}/alerts/{alert_id}"),
+        response_json,
+    ))
+}
+
+/// DELETE /{id} -- delete an alert (soft-delete by setting is_active = false).
+async fn delete_alert(
+    State(state): State<Arc<AppState>>,
+    _moderator: RoomModerator,
+    Path((room_id, id)): Path<(Uuid, Uuid)>,
+) -> AppResult<StatusCode> {
+    let mut tx = state.pool.begin().await?;
+
+    // Clearing publish_at keeps a deleted alert from looking like a pending scheduled one
+    let result = sqlx::query(
+        r#"
+        UPDATE alerts SET is_active = false, publish_at = NULL, is_pinned = false, pinned_at = NULL,
+                          deleted_at = COALESCE(deleted_at, NOW())
+        WHERE id = $1 AND room_id = $2
+        "#,
+    )
+    .bind(id)
+    .bind(room_id)
+    .execute(&mut *tx)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Alert not found".into()));
+    }
+
+    // Queue the deletion broadcast alongside the update
+    let channel = Channel::room_alerts(room_id);
+    outbox::enqueue(
+        &mut tx,
+        &channel,
+        "alert_deleted",
+        &json!({ "id": id, "room_id": room_id }),
+    )
+    .await?;
+
+    tx.commit().await?;
+    outbox::wake(&state);
+
+    Ok(StatusCode::NO_CONTENT)
+}
 
+/// GET /scheduled -- list alerts waiting to be published (moderator-only).
+async fn list_scheduled_alerts(
+    State(state): State<Arc<AppState>>,
+    _moderator: RoomModerator,
+    Path(room_id): Path<Uuid>,
+    pagination: PaginationParams,
+) -> AppResult<Json<Value>> {
     let alerts = sqlx::query_as::<_, Alert>(
         r#"
         SELECT id, room_id, author_id, title, body, alert_type,
                ticker_symbol, entry_price::float8 as entry_price,
                stop_loss::float8 as stop_loss, take_profit::float8 as take_profit,
-               media_url, legal_disclosure, is_active, created_at
+               media_url, legal_disclosure, is_active, is_pinned, pinned_at, publish_at, created_at, updated_at, deleted_at
         FROM alerts
-        WHERE room_id = $1 AND is_active = true
-        ORDER BY created_at DESC
+        WHERE room_id = $1 AND is_active = false AND publish_at IS NOT NULL
+        ORDER BY publish_at ASC
         LIMIT $2 OFFSET $3
         "#,
     )
     .bind(room_id)
-    .bind(limit)
-    .bind(offset)
+    .bind(pagination.limit())
+    .bind(pagination.offset())
     .fetch_all(&state.pool)
     .await?;
 
@@ -64,94 +258,162 @@ async fn list_alerts(
     })))
 }
 
-/// POST / -- create a new alert in the room.
-async fn create_alert(
+/// POST /{id}/cancel -- cancel a scheduled alert before it publishes (moderator-only).
+async fn cancel_scheduled_alert(
     State(state): State<Arc<AppState>>,
-    auth_user: AuthUser,
-    Path(room_id): Path<Uuid>,
-    Json(body): Json<CreateAlertRequest>,
-) -> AppResult<(StatusCode, Json<Value>)> {
-    let alert_id = Uuid::new_v4();
+    _moderator: RoomModerator,
+    Path((room_id, id)): Path<(Uuid, Uuid)>,
+) -> AppResult<StatusCode> {
+    let result = sqlx::query(
+        r#"
+        UPDATE alerts SET publish_at = NULL, deleted_at = NOW()
+        WHERE id = $1 AND room_id = $2 AND is_active = false AND publish_at IS NOT NULL
+        "#,
+    )
+    .bind(id)
+    .bind(room_id)
+    .execute(&state.pool)
+    .await?;
 
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound(
+            "Scheduled alert not found or already published".into(),
+        ));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /{id}/pin -- pin a published alert to the top of the room's list, or unpin it if
+/// already pinned (moderator-only).
+async fn toggle_alert_pin(
+    State(state): State<Arc<AppState>>,
+    _moderator: RoomModerator,
+    Path((room_id, id)): Path<(Uuid, Uuid)>,
+) -> AppResult<Json<Value>> {
+    let mut tx = state.pool.begin().await?;
+
+    // Right-hand sides see the pre-update row, so both columns flip together
     let alert = sqlx::query_as::<_, Alert>(
         r#"
-        INSERT INTO alerts (
-            id, room_id, author_id, title, body, alert_type,
-            ticker_symbol, entry_price, stop_loss, take_profit,
-            media_url, legal_disclosure, is_active, created_at
-        )
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, true, NOW())
+        UPDATE alerts SET
+            is_pinned = NOT is_pinned,
+            pinned_at = CASE WHEN is_pinned THEN NULL ELSE NOW() END
+        WHERE id = $1 AND room_id = $2 AND is_active = true
         RETURNING id, room_id, author_id, title, body, alert_type,
                   ticker_symbol, entry_price::float8 as entry_price,
                   stop_loss::float8 as stop_loss, take_profit::float8 as take_profit,
-                  media_url, legal_disclosure, is_active, created_at
+                  media_url, legal_disclosure, is_active, is_pinned, pinned_at, publish_at, created_at, updated_at, deleted_at,
+                  (SELECT COUNT(*) FROM alert_acknowledgements ack WHERE ack.alert_id = alerts.id)
+                      AS ack_count
         "#,
     )
-    .bind(alert_id)
+    .bind(id)
     .bind(room_id)
-    .bind(auth_user.id)
-    .bind(&body.title)
-    .bind(&body.body)
-    .bind(&body.alert_type)
-    .bind(&body.ticker_symbol)
-    .bind(body.entry_price)
-    .bind(body.stop_loss)
-    .bind(body.take_profit)
-    .bind(&body.media_url)
-    .bind(&body.legal_disclosure)
-    .fetch_one(&state.pool)
-    .await?;
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Alert not found".into()))?;
 
-    let response = AlertResponse::from(alert);
-    let response_json = serde_json::to_value(&response)
+    let event = if alert.is_pinned {
+        "alert_pinned"
+    } else {
+        "alert_unpinned"
+    };
+    let response_json = serde_json::to_value(AlertResponse::from(alert))
         .map_err(|e| AppError::Internal(format!("Serialization error: {e}")))?;
 
-    // Broadcast to WebSocket channel
-    let channel = format!("room:{}:alerts", room_id);
-    WsManager::notify_change(&state, &channel, "alert_created", response_json.clone());
+    let channel = Channel::room_alerts(room_id);
+    outbox::enqueue(&mut tx, &channel, event, &response_json).await?;
 
-    Ok((StatusCode::CREATED, Json(response_json)))
+    tx.commit().await?;
+    outbox::wake(&state);
+
+    Ok(Json(response_json))
 }
 
-/// DELETE /{id} -- delete an alert (soft-delete by setting is_active = false).
-async fn delete_alert(
+/// POST /{id}/ack -- acknowledge a published alert as a room member. Repeat
+/// acknowledgements are ignored; returns the current count either way.
+async fn acknowledge_alert(
     State(state): State<Arc<AppState>>,
-    _auth_user: AuthUser,
+    RoomMember(member): RoomMember,
     Path((room_id, id)): Path<(Uuid, Uuid)>,
-) -> AppResult<StatusCode> {
-    let result = sqlx::query("UPDATE alerts SET is_active = false WHERE id = $1 AND room_id = $2")
-        .bind(id)
-        .bind(room_id)
-        .execute(&state.pool)
-        .await?;
+) -> AppResult<Json<Value>> {
+    let mut tx = state.pool.begin().await?;
 
-    if result.rows_affected() == 0 {
+    let published = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS(SELECT 1 FROM alerts WHERE id = $1 AND room_id = $2 AND is_active = true)",
+    )
+    .bind(id)
+    .bind(room_id)
+    .fetch_one(&mut *tx)
+    .await?;
+    if !published {
         return Err(AppError::NotFound("Alert not found".into()));
     }
 
-    // Broadcast deletion to WebSocket channel
-    let channel = format!("room:{}:alerts", room_id);
-    WsManager::notify_change(
-        &state,
-        &channel,
-        "alert_deleted",
-        json!({ "id": id, "room_id": room_id }),
-    );
+    let inserted = sqlx::query(
+        r#"
+        INSERT INTO alert_acknowledgements (alert_id, user_id, created_at)
+        VALUES ($1, $2, NOW())
+        ON CONFLICT (alert_id, user_id) DO NOTHING
+        "#,
+    )
+    .bind(id)
+    .bind(member.user_id)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected()
+        > 0;
 
-    Ok(StatusCode::NO_CONTENT)
+    let ack_count = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM alert_acknowledgements WHERE alert_id = $1",
+    )
+    .bind(id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    if inserted {
+        let channel = Channel::room_alerts(room_id);
+        outbox::enqueue(
+            &mut tx,
+            &channel,
+            "alert_acked",
+            &json!({
+                "id": id,
+                "room_id": room_id,
+                "user_id": member.user_id,
+                "ack_count": ack_count
+            }),
+        )
+        .await?;
+    }
+
+    tx.commit().await?;
+    if inserted {
+        outbox::wake(&state);
+    }
+
+    Ok(Json(json!({
+        "id": id,
+        "ack_count": ack_count,
+        "acked_by_me": true
+    })))
 }
 
 /// POST /{id}/media -- upload media for an alert via multipart.
 async fn upload_alert_media(
     State(state): State<Arc<AppState>>,
-    _auth_user: AuthUser,
+    RoomMember(member): RoomMember,
     Path((room_id, id)): Path<(Uuid, Uuid)>,
     mut multipart: Multipart,
 ) -> AppResult<Json<Value>> {
+    require_capability(&state.pool, &member, Capability::PostAlert).await?;
+    require_storage(&state)?;
+
     while let Some(field) = multipart
         .next_field()
         .await
-        .map_err(|e| AppError::BadRequest(format!("Multipart error: {e}")))?
+        .map_err(|e| AppError::BadRequest(format!("Multipart error: {e}").into()))?
     {
         if field.name() == Some("media") {
             let raw_name = field.file_name().unwrap_or("media.bin").to_string();
@@ -162,47 +424,44 @@ async fn upload_alert_media(
             let data = field
                 .bytes()
                 .await
-                .map_err(|e| AppError::BadRequest(format!("Failed to read file: {e}")))?;
+                .map_err(|e| AppError::BadRequest(format!("Failed to read file: {e}").into()))?;
 
             let file_name = sanitize_filename(&raw_name);
             validate_upload(data.len(), &content_type, ALLOWED_MEDIA_TYPES)?;
+            let upload = prepare_upload(&state, file_name, content_type, data).await;
 
-            let key = format!("alerts/{}/{}/{}", room_id, id, file_name);
+            let key = format!("alerts/{}/{}/{}", room_id, id, upload.file_name);
 
-            state
-                .s3
-                .put_object()
-                .bucket(&state.config.s3_bucket)
-                .key(&key)
-                .body(data.into())
-                .content_type(&content_type)
-                .send()
-                .await
-                .map_err(|e| AppError::Internal(format!("S3 upload failed: {e}")))?;
+            put_upload(&state, &key, &upload).await?;
 
             let media_url = format!(
                 "{}/{}/{}",
                 state.config.s3_endpoint, state.config.s3_bucket, key
             );
 
-            // Update the alert's media_url in the database
+            // Update the alert's media_url and announce it together
+            let mut tx = state.pool.begin().await?;
             sqlx::query("UPDATE alerts SET media_url = $1 WHERE id = $2 AND room_id = $3")
                 .bind(&media_url)
                 .bind(id)
                 .bind(room_id)
-                .execute(&state.pool)
+                .execute(&mut *tx)
                 .await?;
-
-            // Broadcast media update
-            let channel = format!("room:{}:alerts", room_id);
-            WsManager::notify_change(
-                &state,
-                &channel,
+            outbox::enqueue(
+                &mut tx,
+                &Channel::room_alerts(room_id),
                 "alert_media_uploaded",
-                json!({ "id": id, "media_url": media_url }),
-            );
+                &json!({ "id": id, "media_url": media_url }),
+            )
+            .await?;
+            tx.commit().await?;
+            outbox::wake(&state);
 
-            return Ok(Json(json!({ "media_url": media_url })));
+            return Ok(Json(json!({
+                "media_url": media_url,
+                "content_type": upload.content_type,
+                "size": upload.size()
+            })));
         }
     }
 
@@ -210,3 +469,129 @@ async fn upload_alert_media(
         "No media field found in multipart body".into(),
     ))
 }
+
+/// Insert an alert, holding it back as a pending scheduled alert when `publish_at` is set.
+/// Announcements must have a body and no ticker or price fields. `buy`/`sell` alerts without a disclosure get the tenant's default, if any.
+/// Shared by moderator posts and incoming hooks; callers queue the broadcast.
+pub(crate) async fn insert_alert(
+    conn: &mut PgConnection,
+    room_id: Uuid,
+    author_id: Uuid,
+    body: &CreateAlertRequest,
+) -> AppResult<Alert> {
+    if let Some(publish_at) = body.publish_at {
+        if publish_at <= Utc::now() {
+            return Err(AppError::Validation(
+                "publish_at must be in the future".into(),
+            ));
+        }
+    }
+    check_announcement(body)?;
+    let is_scheduled = body.publish_at.is_some();
+    let legal_disclosure = resolve_legal_disclosure(&mut *conn, room_id, body).await?;
+
+    let alert = sqlx::query_as::<_, Alert>(
+        r#"
+        INSERT INTO alerts (
+            id, room_id, author_id, title, body, alert_type,
+            ticker_symbol, entry_price, stop_loss, take_profit,
+            media_url, legal_disclosure, is_active, publish_at, created_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, NOW())
+        RETURNING id, room_id, author_id, title, body, alert_type,
+                  ticker_symbol, entry_price::float8 as entry_price,
+                  stop_loss::float8 as stop_loss, take_profit::float8 as take_profit,
+                  media_url, legal_disclosure, is_active, is_pinned, pinned_at, publish_at, created_at, updated_at, deleted_at
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(room_id)
+    .bind(author_id)
+    .bind(&body.title)
+    .bind(&body.body)
+    .bind(&body.alert_type)
+    .bind(&body.ticker_symbol)
+    .bind(body.entry_price)
+    .bind(body.stop_loss)
+    .bind(body.take_profit)
+    .bind(&body.media_url)
+    .bind(&legal_disclosure)
+    .bind(!is_scheduled)
+    .bind(body.publish_at)
+    .fetch_one(conn)
+    .await?;
+
+    Ok(alert)
+}
+
+/// Reject trade fields on alert types that don't allow them, and announcements without a body.
+fn check_announcement(body: &CreateAlertRequest) -> AppResult<()> {
+    if body.alert_type.allows_trade_fields() {
+        return Ok(());
+    }
+
+    let has_trade_fields = body.ticker_symbol.is_some()
+        || body.entry_price.is_some()
+        || body.stop_loss.is_some()
+        || body.take_profit.is_some();
+    if has_trade_fields {
+        return Err(AppError::Validation(
+            "Announcements cannot have ticker_symbol, entry_price, stop_loss, or take_profit"
+                .into(),
+        ));
+    }
+    if body.body.as_deref().is_none_or(|b| b.trim().is_empty()) {
+        return Err(AppError::Validation("Announcements require a body".into()));
+    }
+
+    Ok(())
+}
+
+/// The disclosure to store on a new alert. `buy`/`sell` alerts must carry one of at least
+/// `MIN_LEGAL_DISCLOSURE_CHARS` characters, falling back to the tenant's default when omitted;
+/// other alert types keep whatever was sent.
+async fn resolve_legal_disclosure(
+    conn: &mut PgConnection,
+    room_id: Uuid,
+    body: &CreateAlertRequest,
+) -> AppResult<Option<String>> {
+    let given = body
+        .legal_disclosure
+        .as_deref()
+        .filter(|d| !d.trim().is_empty());
+    if !body.alert_type.requires_disclosure() {
+        return Ok(given.map(str::to_string));
+    }
+
+    let disclosure = match given {
+        Some(d) => Some(d.to_string()),
+        None => {
+            let tenant_id: Option<Uuid> =
+                sqlx::query_scalar("SELECT tenant_id FROM rooms WHERE id = $1")
+                    .bind(room_id)
+                    .fetch_one(&mut *conn)
+                    .await?;
+            match tenant_id {
+                Some(tenant_id) => {
+                    tenant_config::get(
+                        &mut *conn,
+                        tenant_id,
+                        &tenant_config::DEFAULT_LEGAL_DISCLOSURE,
+                    )
+                    .await?
+                }
+                None => None,
+            }
+        }
+    };
+
+    match disclosure {
+        Some(d) if d.trim().chars().count() >= MIN_LEGAL_DISCLOSURE_CHARS => Ok(Some(d)),
+        _ => Err(AppError::BadRequest(
+            Message::new(
+                "Buy and sell alerts require a legal_disclosure of at least {min} characters",
+            )
+            .with("min", MIN_LEGAL_DISCLOSURE_CHARS),
+        )),
+    }
+}
@@ -0,0 +1,233 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{Path, Query, State},
+    routing::get,
+    Json, Router,
+};
+use chrono::{NaiveDate, NaiveTime, Utc};
+use uuid::Uuid;
+
+use crate::{
+    error::{AppError, AppResult},
+    extractors::room_access::RoomModerator,
+    models::analytics::{
+        AlertTypeCount, AnalyticsQuery, AnalyticsTotals, DailyActivity, PollParticipation,
+        RoomAnalytics, TopPoster,
+    },
+    state::AppState,
+};
+
+/// Days covered when `from` is omitted.
+const DEFAULT_RANGE_DAYS: i64 = 30;
+
+/// Longest range a single request may cover.
+const MAX_RANGE_DAYS: i64 = 366;
+
+/// How long a computed summary is served from cache.
+const CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Number of entries in `top_posters`.
+const TOP_POSTERS_LIMIT: i64 = 10;
+
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new().route("/", get(room_analytics))
+}
+
+/// GET / -- activity summary for a room over `from..=to` (UTC days, default last 30 days).
+/// Hosts and moderators only; results are cached briefly per room and range.
+async fn room_analytics(
+    State(state): State<Arc<AppState>>,
+    _moderator: RoomModerator,
+    Path(room_id): Path<Uuid>,
+    Query(query): Query<AnalyticsQuery>,
+) -> AppResult<Json<RoomAnalytics>> {
+    let to = query.to.unwrap_or_else(|| Utc::now().date_naive());
+    let from = query
+        .from
+        .unwrap_or(to - chrono::Duration::days(DEFAULT_RANGE_DAYS - 1));
+    if from > to {
+        return Err(AppError::BadRequest("'from' must not be after 'to'".into()));
+    }
+    if (to - from).num_days() >= MAX_RANGE_DAYS {
+        return Err(AppError::BadRequest(
+            format!("Date range cannot exceed {MAX_RANGE_DAYS} days").into(),
+        ));
+    }
+
+    let key = (room_id, from, to);
+    if let Some(entry) = state.analytics_cache.get(&key) {
+        let (computed_at, analytics) = entry.value();
+        if computed_at.elapsed() < CACHE_TTL {
+            return Ok(Json(analytics.clone()));
+        }
+    }
+
+    let analytics = compute(&state, room_id, from, to).await?;
+
+    state
+        .analytics_cache
+        .retain(|_, (computed_at, _)| computed_at.elapsed() < CACHE_TTL);
+    state
+        .analytics_cache
+        .insert(key, (Instant::now(), analytics.clone()));
+
+    Ok(Json(analytics))
+}
+
+/// Run the aggregate queries for one room and range.
+async fn compute(
+    state: &AppState,
+    room_id: Uuid,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> AppResult<RoomAnalytics> {
+    let start = from.and_time(NaiveTime::MIN).and_utc();
+    let end = (to + chrono::Duration::days(1))
+        .and_time(NaiveTime::MIN)
+        .and_utc();
+    let pool = &state.pool;
+
+    let totals = sqlx::query_as::<_, AnalyticsTotals>(
+        r#"
+        SELECT
+            (SELECT COUNT(*) FROM chatmessages
+             WHERE room_id = $1 AND is_deleted = false AND created_at >= $2 AND created_at < $3) AS messages,
+            (SELECT COUNT(DISTINCT user_id) FROM chatmessages
+             WHERE room_id = $1 AND is_deleted = false AND created_at >= $2 AND created_at < $3) AS active_members,
+            (SELECT COUNT(*) FROM room_memberships
+             WHERE room_id = $1 AND status = 'active') AS members,
+            (SELECT COUNT(*) FROM room_memberships
+             WHERE room_id = $1 AND created_at >= $2 AND created_at < $3) AS new_members,
+            (SELECT COUNT(*) FROM alerts
+             WHERE room_id = $1 AND created_at >= $2 AND created_at < $3) AS alerts,
+            (SELECT COUNT(*) FROM polls
+             WHERE room_id = $1 AND created_at >= $2 AND created_at < $3) AS polls,
+            (SELECT COUNT(*) FROM poll_votes v JOIN polls p ON p.id = v.poll_id
+             WHERE p.room_id = $1 AND v.created_at >= $2 AND v.created_at < $3) AS poll_votes
+        "#,
+    )
+    .bind(room_id)
+    .bind(start)
+    .bind(end);
+
+    // Buckets are UTC days; generate_series fills in days with no activity
+    let days = sqlx::query_as::<_, DailyActivity>(
+        r#"
+        WITH days AS (
+            SELECT d::date AS day FROM generate_series($4::date, $5::date, interval '1 day') AS d
+        ),
+        messages AS (
+            SELECT (created_at AT TIME ZONE 'UTC')::date AS day,
+                   COUNT(*) AS messages, COUNT(DISTINCT user_id) AS active_members
+            FROM chatmessages
+            WHERE room_id = $1 AND is_deleted = false AND created_at >= $2 AND created_at < $3
+            GROUP BY 1
+        ),
+        joins AS (
+            SELECT (created_at AT TIME ZONE 'UTC')::date AS day, COUNT(*) AS new_members
+            FROM room_memberships
+            WHERE room_id = $1 AND created_at >= $2 AND created_at < $3
+            GROUP BY 1
+        ),
+        alert_days AS (
+            SELECT (created_at AT TIME ZONE 'UTC')::date AS day, COUNT(*) AS alerts
+            FROM alerts
+            WHERE room_id = $1 AND created_at >= $2 AND created_at < $3
+            GROUP BY 1
+        ),
+        votes AS (
+            SELECT (v.created_at AT TIME ZONE 'UTC')::date AS day, COUNT(*) AS poll_votes
+            FROM poll_votes v JOIN polls p ON p.id = v.poll_id
+            WHERE p.room_id = $1 AND v.created_at >= $2 AND v.created_at < $3
+            GROUP BY 1
+        )
+        SELECT d.day AS date,
+               COALESCE(m.messages, 0) AS messages,
+               COALESCE(m.active_members, 0) AS active_members,
+               COALESCE(j.new_members, 0) AS new_members,
+               COALESCE(a.alerts, 0) AS alerts,
+               COALESCE(v.poll_votes, 0) AS poll_votes
+        FROM days d
+        LEFT JOIN messages m ON m.day = d.day
+        LEFT JOIN joins j ON j.day = d.day
+        LEFT JOIN alert_days a ON a.day = d.day
+        LEFT JOIN votes v ON v.day = d.day
+        ORDER BY d.day
+        "#,
+    )
+    .bind(room_id)
+    .bind(start)
+    .bind(end)
+    .bind(from)
+    .bind(to);
+
+    let top_posters = sqlx::query_as::<_, TopPoster>(
+        r#"
+        SELECT m.user_id, u.display_name, COUNT(*) AS messages
+        FROM chatmessages m
+        JOIN users u ON u.id = m.user_id
+        WHERE m.room_id = $1 AND m.is_deleted = false AND m.created_at >= $2 AND m.created_at < $3
+        GROUP BY m.user_id, u.display_name
+        ORDER BY messages DESC, m.user_id
+        LIMIT $4
+        "#,
+    )
+    .bind(room_id)
+    .bind(start)
+    .bind(end)
+    .bind(TOP_POSTERS_LIMIT);
+
+    let alerts_by_type = sqlx::query_as::<_, AlertTypeCount>(
+        r#"
+        SELECT alert_type, COUNT(*) AS count
+        FROM alerts
+        WHERE room_id = $1 AND alert_type IS NOT NULL AND created_at >= $2 AND created_at < $3
+        GROUP BY alert_type
+        ORDER BY alert_type
+        "#,
+    )
+    .bind(room_id)
+    .bind(start)
+    .bind(end);
+
+    let polls = sqlx::query_as::<_, PollParticipation>(
+        r#"
+        WITH members AS (
+            SELECT COUNT(*) AS n FROM room_memberships WHERE room_id = $1 AND status = 'active'
+        )
+        SELECT p.id AS poll_id, p.question, p.created_at,
+               COUNT(v.id) AS votes,
+               COUNT(v.id)::float8 / GREATEST((SELECT n FROM members), 1) AS participation_rate
+        FROM polls p
+        LEFT JOIN poll_votes v ON v.poll_id = p.id
+        WHERE p.room_id = $1 AND p.created_at >= $2 AND p.created_at < $3
+        GROUP BY p.id
+        ORDER BY p.created_at
+        "#,
+    )
+    .bind(room_id)
+    .bind(start)
+    .bind(end);
+
+    let (totals, days, top_posters, alerts_by_type, polls) = tokio::try_join!(
+        totals.fetch_one(pool),
+        days.fetch_all(pool),
+        top_posters.fetch_all(pool),
+        alerts_by_type.fetch_all(pool),
+        polls.fetch_all(pool),
+    )?;
+
+    Ok(RoomAnalytics {
+        room_id,
+        from,
+        to,
+        totals,
+        days,
+        top_posters,
+        alerts_by_type,
+        polls,
+        generated_at: Utc::now(),
+    })
+}
@@ -5,8 +5,9 @@ use argon2::{
     Argon2,
 };
 use axum::{
-    extract::{Json, State},
+    extract::{Query, State},
     http::StatusCode,
+    response::Redirect,
     routing::{get, post},
     Router,
 };
@@ -18,31 +19,49 @@ use uuid::Uuid;
 use validator::Validate;
 
 use crate::{
-    error::{AppError, AppResult},
-    extractors::auth::{AuthUser, Claims},
+    config::{AppConfig, TokenProfile},
+    error::{AppError, AppResult, ErrorBody},
+    extractors::{
+        auth::{AuthUser, Claims},
+        json::Json,
+    },
+    i18n::Locale,
     models::{
         auth::{
-            AuthResponse, ChangePasswordRequest, ForgotPasswordRequest, LoginRequest,
-            RefreshRequest, ResendVerificationRequest, ResetPasswordRequest,
+            AuthResponse, ChangeEmailRequest, ChangePasswordRequest, ConfirmEmailChangeRequest,
+            ConsumeMagicLinkRequest, ForgotPasswordRequest, LoginRequest, MagicLinkRequest,
+            RefreshRequest, ResendVerificationRequest, ResetPasswordRequest, VerifyEmailQuery,
         },
+        tenant::Tenant,
         user::{CreateUserRequest, User, UserResponse, UserRole},
     },
-    services::email_service::EmailService,
+    routes::{created, Created},
+    services::email_templates::EmailBranding,
     state::AppState,
 };
 
+/// How long a magic sign-in link stays valid.
+const MAGIC_LINK_TTL_MINUTES: i64 = 15;
+
 pub fn router() -> Router<Arc<AppState>> {
     Router::new()
         .route("/register", post(register))
         .route("/login", post(login))
         .route("/logout", post(logout))
         .route("/refresh", post(refresh))
-        .route("/verify-email", post(verify_email))
+        .route("/verify-email", get(verify_email_link).post(verify_email))
         .route("/resend-verification", post(resend_verification))
         .route("/forgot-password", post(forgot_password))
         .route("/reset-password", post(reset_password))
+        .route("/magic-link", post(request_magic_link))
+        .route("/magic-link/consume", post(consume_magic_link))
         .route("/me", get(me))
         .route("/change-password", post(change_password))
+        .route("/change-email", post(change_email))
+        .route(
+            "/confirm-email-change",
+            get(confirm_email_change_link).post(confirm_email_change),
+        )
 }
 
 // ---------------------------------------------------------------------------
@@ -69,14 +88,31 @@ fn verify_password(password: &str, hash: &str) -> AppResult<bool> {
 }
 
 /// SHA-256 hash a token for secure storage. Never store raw tokens.
-fn hash_token(token: &str) -> String {
+pub(crate) fn hash_token(token: &str) -> String {
     let mut hasher = Sha256::new();
     hasher.update(token.as_bytes());
     format!("{:x}", hasher.finalize())
 }
 
+/// Look up the token profile for a requested client type, rejecting unknown ones.
+fn resolve_token_profile(config: &AppConfig, client_type: Option<&str>) -> AppResult<TokenProfile> {
+    config.token_profile(client_type).ok_or_else(|| {
+        AppError::BadRequest(
+            format!(
+                "Unknown client_type; expected one of: {}",
+                config.client_types().join(", ")
+            )
+            .into(),
+        )
+    })
+}
+
 /// Generate a pair of JWT tokens (access + refresh) for the given user.
-fn generate_tokens(user: &User, config: &crate::config::AppConfig) -> AppResult<(String, String)> {
+fn generate_tokens(
+    user: &User,
+    config: &AppConfig,
+    profile: &TokenProfile,
+) -> AppResult<(String, String)> {
     let now = Utc::now().timestamp();
 
     // Access token (short-lived)
@@ -85,7 +121,7 @@ fn generate_tokens(user: &User, config: &crate::config::AppConfig) -> AppResult<
         email: user.email.clone(),
         role: format!("{:?}", user.role).to_lowercase(),
         iat: now,
-        exp: now + config.jwt_access_token_expiry_secs,
+        exp: now + profile.access_token_expiry_secs,
     };
     let access_token = encode(
         &Header::default(),
@@ -100,7 +136,7 @@ fn generate_tokens(user: &User, config: &crate::config::AppConfig) -> AppResult<
         email: user.email.clone(),
         role: format!("{:?}", user.role).to_lowercase(),
         iat: now,
-        exp: now + config.jwt_refresh_token_expiry_secs,
+        exp: now + profile.refresh_token_expiry_secs,
     };
     let refresh_token = encode(
         &Header::default(),
@@ -132,16 +168,16 @@ async fn store_refresh_token(
     pool: &sqlx::PgPool,
     user_id: Uuid,
     raw_token: &str,
-    expiry_secs: i64,
+    profile: &TokenProfile,
 ) -> AppResult<()> {
     let token_hash = hash_token(raw_token);
     let now = Utc::now();
-    let expires_at = now + chrono::Duration::seconds(expiry_secs);
+    let expires_at = now + chrono::Duration::seconds(profile.refresh_token_expiry_secs);
 
     sqlx::query(
         r#"
-        INSERT INTO refresh_tokens (id, user_id, token_hash, expires_at, revoked, created_at)
-        VALUES ($1, $2, $3, $4, false, $5)
+        INSERT INTO refresh_tokens (id, user_id, token_hash, expires_at, revoked, created_at, client_type)
+        VALUES ($1, $2, $3, $4, false, $5, $6)
         "#,
     )
     .bind(Uuid::new_v4())
@@ -149,29 +185,87 @@ async fn store_refresh_token(
     .bind(&token_hash)
     .bind(expires_at)
     .bind(now)
+    .bind(&profile.client_type)
     .execute(pool)
     .await?;
 
     Ok(())
 }
 
-/// Revoke all refresh tokens for a user.
-async fn revoke_all_refresh_tokens(pool: &sqlx::PgPool, user_id: Uuid) -> AppResult<()> {
-    sqlx::query("UPDATE refresh_tokens SET revoked = true WHERE user_id = $1 AND revoked = false")
-        .bind(user_id)
-        .execute(pool)
-        .await?;
-    Ok(())
+/// Revoke all refresh tokens for a user, returning how many were still live.
+async fn revoke_all_refresh_tokens(pool: &sqlx::PgPool, user_id: Uuid) -> AppResult<u64> {
+    let result = sqlx::query(
+        "UPDATE refresh_tokens SET revoked = true WHERE user_id = $1 AND revoked = false",
+    )
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// What [`invalidate_all_user_tokens`] removed.
+#[derive(Debug)]
+pub(crate) struct RevokedTokens {
+    pub sessions: u64,
+    pub refresh_tokens: u64,
 }
 
 /// Invalidate all sessions and refresh tokens for a user.
-async fn invalidate_all_user_tokens(pool: &sqlx::PgPool, user_id: Uuid) -> AppResult<()> {
-    sqlx::query("DELETE FROM sessions WHERE user_id = $1")
+pub(crate) async fn invalidate_all_user_tokens(
+    pool: &sqlx::PgPool,
+    user_id: Uuid,
+) -> AppResult<RevokedTokens> {
+    let sessions = sqlx::query("DELETE FROM sessions WHERE user_id = $1")
         .bind(user_id)
         .execute(pool)
-        .await?;
-    revoke_all_refresh_tokens(pool, user_id).await?;
-    Ok(())
+        .await?
+        .rows_affected();
+    let refresh_tokens = revoke_all_refresh_tokens(pool, user_id).await?;
+    Ok(RevokedTokens {
+        sessions,
+        refresh_tokens,
+    })
+}
+
+/// Start the user's only session: revoke earlier ones, then issue and store a new token pair.
+async fn open_session(
+    state: &AppState,
+    user: User,
+    profile: &TokenProfile,
+) -> AppResult<AuthResponse> {
+    // Enforce single session — revoke previous sessions and refresh tokens
+    invalidate_all_user_tokens(&state.pool, user.id).await?;
+
+    // Generate tokens
+    let (access_token, refresh_token) = generate_tokens(&user, &state.config, profile)?;
+    let now = Utc::now();
+
+    // Store session with hashed token
+    let session_token_hash = hash_token(&access_token);
+    sqlx::query(
+        r#"
+        INSERT INTO sessions (id, user_id, token_hash, expires_at, created_at, client_type)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(user.id)
+    .bind(&session_token_hash)
+    .bind(now + chrono::Duration::seconds(profile.access_token_expiry_secs))
+    .bind(now)
+    .bind(&profile.client_type)
+    .execute(&state.pool)
+    .await?;
+
+    // Store refresh token (hashed)
+    store_refresh_token(&state.pool, user.id, &refresh_token, profile).await?;
+
+    Ok(build_auth_response(
+        user,
+        access_token,
+        refresh_token,
+        profile.access_token_expiry_secs,
+    ))
 }
 
 // ---------------------------------------------------------------------------
@@ -180,12 +274,23 @@ async fn invalidate_all_user_tokens(pool: &sqlx::PgPool, user_id: Uuid) -> AppRe
 
 /// POST /register -- create a new user account.
 /// Returns user info with a message to verify email. Does NOT issue tokens.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/register",
+    tag = "auth",
+    request_body = CreateUserRequest,
+    responses(
+        (status = 201, description = "Account created; verify the email address before signing in", body = Value),
+        (status = 409, description = "Email already registered", body = ErrorBody),
+        (status = 422, description = "Invalid request body", body = ErrorBody),
+    ),
+)]
 async fn register(
     State(state): State<Arc<AppState>>,
     Json(body): Json<CreateUserRequest>,
-) -> AppResult<(StatusCode, Json<Value>)> {
+) -> AppResult<Created<Value>> {
     body.validate()
-        .map_err(|e| AppError::Validation(e.to_string()))?;
+        .map_err(|e| AppError::Validation(e.to_string().into()))?;
 
     // Check for duplicate email
     let existing = sqlx::query_scalar::<_, bool>(
@@ -229,14 +334,14 @@ async fn register(
     .await?;
 
     if state.config.auth_skip_email_verification {
-        tracing::info!(user_id = %user_id, email = %body.email, "New user registered (AUTH_SKIP_EMAIL_VERIFICATION)");
-        return Ok((
-            StatusCode::CREATED,
-            Json(json!({
+        tracing::info!(user_id = %user_id, "New user registered (AUTH_SKIP_EMAIL_VERIFICATION)");
+        return Ok(created(
+            format!("/api/v1/users/{user_id}"),
+            json!({
                 "message": "Account created. You can sign in now.",
                 "user_id": user_id,
                 "email_verification_skipped": true
-            })),
+            }),
         ));
     }
 
@@ -259,37 +364,51 @@ async fn register(
     .await?;
 
     // Send verification email
-    if let Ok(email_service) = EmailService::new(&state.config) {
-        if let Err(e) = email_service
-            .send_verification_email(
-                &body.email,
-                &verification_token,
-                &state.config.frontend_base_url,
-            )
-            .await
-        {
+    if let Some(email_service) = &state.email {
+        let branding = email_branding(&state, body.tenant_id).await?;
+        if let Err(e) = email_service.send_verification_email(
+            &body.email,
+            &verification_token,
+            &state.config.api_public_url,
+            &branding,
+            Locale::current(),
+        ) {
             tracing::warn!(user_id = %user_id, error = %e, "Failed to send verification email");
         }
     }
 
-    tracing::info!(user_id = %user_id, email = %body.email, "New user registered — verification email sent");
+    tracing::info!(user_id = %user_id, "New user registered — verification email sent");
 
-    Ok((
-        StatusCode::CREATED,
-        Json(json!({
+    Ok(created(
+        format!("/api/v1/users/{user_id}"),
+        json!({
             "message": "Account created. Please check your email to verify your address before logging in.",
             "user_id": user_id
-        })),
+        }),
     ))
 }
 
 /// POST /login -- authenticate and return tokens.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/login",
+    tag = "auth",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Signed in", body = AuthResponse),
+        (status = 400, description = "Unknown client_type", body = ErrorBody),
+        (status = 401, description = "Invalid credentials", body = ErrorBody),
+        (status = 403, description = "Email address not verified", body = ErrorBody),
+        (status = 422, description = "Invalid request body", body = ErrorBody),
+    ),
+)]
 async fn login(
     State(state): State<Arc<AppState>>,
     Json(body): Json<LoginRequest>,
 ) -> AppResult<Json<AuthResponse>> {
     body.validate()
-        .map_err(|e| AppError::Validation(e.to_string()))?;
+        .map_err(|e| AppError::Validation(e.to_string().into()))?;
+    let profile = resolve_token_profile(&state.config, body.client_type.as_deref())?;
 
     // Find user by email
     let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE LOWER(email) = LOWER($1)")
@@ -298,6 +417,11 @@ async fn login(
         .await?
         .ok_or_else(|| AppError::Unauthorized("Invalid email or password".into()))?;
 
+    // Bot accounts only post through incoming hooks
+    if user.is_bot {
+        return Err(AppError::Unauthorized("Invalid email or password".into()));
+    }
+
     // Verify password
     if !verify_password(&body.password, &user.password_hash)? {
         return Err(AppError::Unauthorized("Invalid email or password".into()));
@@ -310,51 +434,23 @@ async fn login(
         ));
     }
 
-    // Enforce single session — revoke previous sessions and refresh tokens
-    invalidate_all_user_tokens(&state.pool, user.id).await?;
-
-    // Generate tokens
-    let (access_token, refresh_token) = generate_tokens(&user, &state.config)?;
-    let now = Utc::now();
-
-    // Store session with hashed token
-    let session_token_hash = hash_token(&access_token);
-    sqlx::query(
-        r#"
-        INSERT INTO sessions (id, user_id, token_hash, expires_at, created_at)
-        VALUES ($1, $2, $3, $4, $5)
-        "#,
-    )
-    .bind(Uuid::new_v4())
-    .bind(user.id)
-    .bind(&session_token_hash)
-    .bind(now + chrono::Duration::seconds(state.config.jwt_access_token_expiry_secs))
-    .bind(now)
-    .execute(&state.pool)
-    .await?;
-
-    // Store refresh token (hashed)
-    store_refresh_token(
-        &state.pool,
-        user.id,
-        &refresh_token,
-        state.config.jwt_refresh_token_expiry_secs,
-    )
-    .await?;
-
-    tracing::info!(user_id = %user.id, "User logged in");
-
-    let resp = build_auth_response(
-        user,
-        access_token,
-        refresh_token,
-        state.config.jwt_access_token_expiry_secs,
-    );
+    let resp = open_session(&state, user, &profile).await?;
+    tracing::info!(user_id = %resp.user.id, "User logged in");
 
     Ok(Json(resp))
 }
 
 /// POST /logout -- invalidate the current session and all refresh tokens.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/logout",
+    tag = "auth",
+    responses(
+        (status = 204, description = "Signed out everywhere"),
+        (status = 401, description = "Missing or invalid access token", body = ErrorBody),
+    ),
+    security(("bearer_auth" = [])),
+)]
 async fn logout(State(state): State<Arc<AppState>>, auth_user: AuthUser) -> AppResult<StatusCode> {
     invalidate_all_user_tokens(&state.pool, auth_user.id).await?;
 
@@ -363,6 +459,17 @@ async fn logout(State(state): State<Arc<AppState>>, auth_user: AuthUser) -> AppR
 }
 
 /// POST /refresh -- exchange a refresh token for new tokens (token rotation).
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/refresh",
+    tag = "auth",
+    request_body = RefreshRequest,
+    responses(
+        (status = 200, description = "New token pair", body = AuthResponse),
+        (status = 400, description = "client_type does not match the session", body = ErrorBody),
+        (status = 401, description = "Invalid or expired refresh token", body = ErrorBody),
+    ),
+)]
 async fn refresh(
     State(state): State<Arc<AppState>>,
     Json(body): Json<RefreshRequest>,
@@ -373,28 +480,44 @@ async fn refresh(
         &jsonwebtoken::DecodingKey::from_secret(state.config.jwt_secret.as_bytes()),
         &jsonwebtoken::Validation::default(),
     )
-    .map_err(|e| AppError::Unauthorized(format!("Invalid refresh token: {e}")))?;
+    .map_err(|e| AppError::Unauthorized(format!("Invalid refresh token: {e}").into()))?;
 
     let user_id = token_data.claims.sub;
     let token_hash = hash_token(&body.refresh_token);
 
     // Verify the refresh token exists, is not revoked, and has not expired
-    let token_exists = sqlx::query_scalar::<_, bool>(
-        "SELECT EXISTS(SELECT 1 FROM refresh_tokens WHERE user_id = $1 AND token_hash = $2 AND revoked = false AND expires_at > NOW())",
+    let client_type = sqlx::query_scalar::<_, String>(
+        "SELECT client_type FROM refresh_tokens WHERE user_id = $1 AND token_hash = $2 AND revoked = false AND expires_at > NOW()",
     )
     .bind(user_id)
     .bind(&token_hash)
-    .fetch_one(&state.pool)
+    .fetch_optional(&state.pool)
     .await?;
 
-    if !token_exists {
+    let Some(client_type) = client_type else {
         // Possible token reuse attack — revoke all tokens for safety
         invalidate_all_user_tokens(&state.pool, user_id).await?;
         tracing::warn!(user_id = %user_id, "Refresh token reuse detected — all tokens revoked");
         return Err(AppError::Unauthorized(
             "Session expired or invalid. Please log in again.".into(),
         ));
+    };
+
+    // The session keeps the profile it signed in with
+    if body
+        .client_type
+        .as_deref()
+        .is_some_and(|requested| requested != client_type)
+    {
+        return Err(AppError::BadRequest(
+            "client_type does not match this session".into(),
+        ));
     }
+    // A profile removed from the configuration falls back to the default
+    let profile = match state.config.token_profile(Some(&client_type)) {
+        Some(profile) => profile,
+        None => resolve_token_profile(&state.config, None)?,
+    };
 
     // Revoke the used refresh token (rotation)
     sqlx::query("UPDATE refresh_tokens SET revoked = true WHERE user_id = $1 AND token_hash = $2")
@@ -411,7 +534,7 @@ async fn refresh(
         .ok_or_else(|| AppError::NotFound("User not found".into()))?;
 
     // Generate new tokens
-    let (access_token, refresh_token) = generate_tokens(&user, &state.config)?;
+    let (access_token, refresh_token) = generate_tokens(&user, &state.config, &profile)?;
     let now = Utc::now();
 
     // Replace session
@@ -423,44 +546,64 @@ async fn refresh(
     let session_token_hash = hash_token(&access_token);
     sqlx::query(
         r#"
-        INSERT INTO sessions (id, user_id, token_hash, expires_at, created_at)
-        VALUES ($1, $2, $3, $4, $5)
+        INSERT INTO sessions (id, user_id, token_hash, expires_at, created_at, client_type)
+        VALUES ($1, $2, $3, $4, $5, $6)
         "#,
     )
     .bind(Uuid::new_v4())
     .bind(user.id)
     .bind(&session_token_hash)
-    .bind(now + chrono::Duration::seconds(state.config.jwt_access_token_expiry_secs))
+    .bind(now + chrono::Duration::seconds(profile.access_token_expiry_secs))
     .bind(now)
+    .bind(&profile.client_type)
     .execute(&state.pool)
     .await?;
 
     // Store new refresh token (hashed)
-    store_refresh_token(
-        &state.pool,
-        user.id,
-        &refresh_token,
-        state.config.jwt_refresh_token_expiry_secs,
-    )
-    .await?;
+    store_refresh_token(&state.pool, user.id, &refresh_token, &profile).await?;
 
     let resp = build_auth_response(
         user,
         access_token,
         refresh_token,
-        state.config.jwt_access_token_expiry_secs,
+        profile.access_token_expiry_secs,
     );
 
     Ok(Json(resp))
 }
 
+/// Branding for emails sent on behalf of `tenant_id`; unknown or absent tenants get the default look.
+/// Emails to existing accounts pass the account's own tenant, never one the client names.
+async fn email_branding(state: &AppState, tenant_id: Option<Uuid>) -> AppResult<EmailBranding> {
+    let Some(tenant_id) = tenant_id else {
+        return Ok(EmailBranding::default());
+    };
+
+    let tenant = sqlx::query_as::<_, Tenant>("SELECT * FROM tenants WHERE id = $1")
+        .bind(tenant_id)
+        .fetch_optional(&state.pool)
+        .await?;
+
+    Ok(tenant.as_ref().map(EmailBranding::from).unwrap_or_default())
+}
+
 /// POST /resend-verification — issue a new verification email (anti-enumeration messaging).
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/resend-verification",
+    tag = "auth",
+    request_body = ResendVerificationRequest,
+    responses(
+        (status = 200, description = "Sent if the account exists and is unverified", body = Value),
+        (status = 422, description = "Invalid request body", body = ErrorBody),
+    ),
+)]
 async fn resend_verification(
     State(state): State<Arc<AppState>>,
     Json(body): Json<ResendVerificationRequest>,
 ) -> AppResult<Json<Value>> {
     body.validate()
-        .map_err(|e| AppError::Validation(e.to_string()))?;
+        .map_err(|e| AppError::Validation(e.to_string().into()))?;
 
     let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE LOWER(email) = LOWER($1)")
         .bind(&body.email)
@@ -500,15 +643,15 @@ async fn resend_verification(
     .execute(&state.pool)
     .await?;
 
-    if let Ok(email_service) = EmailService::new(&state.config) {
-        if let Err(e) = email_service
-            .send_verification_email(
-                &user.email,
-                &verification_token,
-                &state.config.frontend_base_url,
-            )
-            .await
-        {
+    if let Some(email_service) = &state.email {
+        let branding = email_branding(&state, user.tenant_id).await?;
+        if let Err(e) = email_service.send_verification_email(
+            &user.email,
+            &verification_token,
+            &state.config.api_public_url,
+            &branding,
+            Locale::current(),
+        ) {
             tracing::warn!(user_id = %user.id, error = %e, "Failed to send verification email (resend)");
         }
     }
@@ -517,6 +660,16 @@ async fn resend_verification(
 }
 
 /// POST /verify-email -- verify a user's email with a token.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/verify-email",
+    tag = "auth",
+    request_body = Value,
+    responses(
+        (status = 200, description = "Email verified", body = Value),
+        (status = 400, description = "Invalid or expired token", body = ErrorBody),
+    ),
+)]
 async fn verify_email(
     State(state): State<Arc<AppState>>,
     Json(body): Json<Value>,
@@ -526,6 +679,39 @@ async fn verify_email(
         .and_then(|t| t.as_str())
         .ok_or_else(|| AppError::BadRequest("Missing token field".into()))?;
 
+    consume_verification_token(&state, token).await?;
+
+    Ok(Json(json!({ "message": "Email verified successfully" })))
+}
+
+/// GET /verify-email?token=... -- email-click flow; verifies like the POST variant and
+/// redirects to the configured success or failure page.
+#[utoipa::path(
+    get,
+    path = "/api/v1/auth/verify-email",
+    tag = "auth",
+    params(
+        VerifyEmailQuery,
+    ),
+    responses(
+        (status = 303, description = "Redirect to the web app with the verification result"),
+    ),
+)]
+async fn verify_email_link(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<VerifyEmailQuery>,
+) -> AppResult<Redirect> {
+    match consume_verification_token(&state, &query.token).await {
+        Ok(()) => Ok(Redirect::to(&state.config.email_verified_redirect_url)),
+        Err(AppError::BadRequest(_)) => {
+            Ok(Redirect::to(&state.config.email_verify_failed_redirect_url))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Mark the token's user as verified and delete the token.
+async fn consume_verification_token(state: &AppState, token: &str) -> AppResult<()> {
     // Find valid verification token and update user in a transaction
     let mut tx = state.pool.begin().await?;
 
@@ -554,16 +740,26 @@ async fn verify_email(
 
     tx.commit().await?;
 
-    Ok(Json(json!({ "message": "Email verified successfully" })))
+    Ok(())
 }
 
 /// POST /forgot-password -- send a password reset email.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/forgot-password",
+    tag = "auth",
+    request_body = ForgotPasswordRequest,
+    responses(
+        (status = 200, description = "Sent if the account exists", body = Value),
+        (status = 422, description = "Invalid request body", body = ErrorBody),
+    ),
+)]
 async fn forgot_password(
     State(state): State<Arc<AppState>>,
     Json(body): Json<ForgotPasswordRequest>,
 ) -> AppResult<Json<Value>> {
     body.validate()
-        .map_err(|e| AppError::Validation(e.to_string()))?;
+        .map_err(|e| AppError::Validation(e.to_string().into()))?;
 
     // Always return success to prevent user enumeration
     let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE LOWER(email) = LOWER($1)")
@@ -589,15 +785,15 @@ async fn forgot_password(
         .await?;
 
         // Send password reset email
-        if let Ok(email_service) = EmailService::new(&state.config) {
-            if let Err(e) = email_service
-                .send_password_reset_email(
-                    &user.email,
-                    &reset_token,
-                    &state.config.frontend_base_url,
-                )
-                .await
-            {
+        if let Some(email_service) = &state.email {
+            let branding = email_branding(&state, user.tenant_id).await?;
+            if let Err(e) = email_service.send_password_reset_email(
+                &user.email,
+                &reset_token,
+                &state.config.frontend_base_url,
+                &branding,
+                Locale::current(),
+            ) {
                 tracing::warn!(user_id = %user.id, error = %e, "Failed to send password reset email");
             }
         }
@@ -611,12 +807,23 @@ async fn forgot_password(
 }
 
 /// POST /reset-password -- reset password using a token.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/reset-password",
+    tag = "auth",
+    request_body = ResetPasswordRequest,
+    responses(
+        (status = 200, description = "Password reset", body = Value),
+        (status = 400, description = "Invalid or expired token", body = ErrorBody),
+        (status = 422, description = "Invalid request body", body = ErrorBody),
+    ),
+)]
 async fn reset_password(
     State(state): State<Arc<AppState>>,
     Json(body): Json<ResetPasswordRequest>,
 ) -> AppResult<Json<Value>> {
     body.validate()
-        .map_err(|e| AppError::Validation(e.to_string()))?;
+        .map_err(|e| AppError::Validation(e.to_string().into()))?;
 
     let mut tx = state.pool.begin().await?;
 
@@ -656,7 +863,129 @@ async fn reset_password(
     Ok(Json(json!({ "message": "Password reset successfully" })))
 }
 
+/// POST /magic-link -- email a single-use sign-in link to a verified account.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/magic-link",
+    tag = "auth",
+    request_body = MagicLinkRequest,
+    responses(
+        (status = 200, description = "Sent if a verified account exists", body = Value),
+        (status = 422, description = "Invalid request body", body = ErrorBody),
+    ),
+)]
+async fn request_magic_link(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<MagicLinkRequest>,
+) -> AppResult<Json<Value>> {
+    body.validate()
+        .map_err(|e| AppError::Validation(e.to_string().into()))?;
+
+    // Always return success to prevent user enumeration
+    let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE LOWER(email) = LOWER($1)")
+        .bind(&body.email)
+        .fetch_optional(&state.pool)
+        .await?
+        .filter(|u| !u.is_bot && u.email_verified_at.is_some());
+
+    if let Some(user) = user {
+        let token = Uuid::new_v4().to_string();
+        let expires_at = Utc::now() + chrono::Duration::minutes(MAGIC_LINK_TTL_MINUTES);
+
+        // One outstanding link per user; a new request replaces the previous one
+        sqlx::query(
+            r#"
+            INSERT INTO magic_link_tokens (id, user_id, token_hash, expires_at, created_at)
+            VALUES ($1, $2, $3, $4, NOW())
+            ON CONFLICT (user_id) DO UPDATE
+                SET token_hash = EXCLUDED.token_hash,
+                    expires_at = EXCLUDED.expires_at,
+                    created_at = NOW()
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(user.id)
+        .bind(hash_token(&token))
+        .bind(expires_at)
+        .execute(&state.pool)
+        .await?;
+
+        if let Some(email_service) = &state.email {
+            let branding = email_branding(&state, user.tenant_id).await?;
+            if let Err(e) = email_service.send_magic_link_email(
+                &user.email,
+                &token,
+                &state.config.frontend_base_url,
+                &branding,
+                Locale::current(),
+            ) {
+                tracing::warn!(user_id = %user.id, error = %e, "Failed to send magic link email");
+            }
+        }
+
+        tracing::info!(user_id = %user.id, "Magic link email sent");
+    }
+
+    Ok(Json(json!({
+        "message": "If a verified account with that email exists, a sign-in link has been sent"
+    })))
+}
+
+/// POST /magic-link/consume -- exchange a magic link token for a token pair.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/magic-link/consume",
+    tag = "auth",
+    request_body = ConsumeMagicLinkRequest,
+    responses(
+        (status = 200, description = "Signed in", body = AuthResponse),
+        (status = 400, description = "Unknown client_type", body = ErrorBody),
+        (status = 401, description = "Invalid, used, or expired link", body = ErrorBody),
+    ),
+)]
+async fn consume_magic_link(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<ConsumeMagicLinkRequest>,
+) -> AppResult<Json<AuthResponse>> {
+    let profile = resolve_token_profile(&state.config, body.client_type.as_deref())?;
+
+    // Deleting the row is what makes the link single-use
+    let user_id = sqlx::query_scalar::<_, Uuid>(
+        r#"
+        DELETE FROM magic_link_tokens
+        WHERE token_hash = $1 AND expires_at > NOW()
+        RETURNING user_id
+        "#,
+    )
+    .bind(hash_token(&body.token))
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or_else(|| AppError::Unauthorized("Invalid or expired sign-in link".into()))?;
+
+    let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_optional(&state.pool)
+        .await?
+        .filter(|u| !u.is_bot && u.email_verified_at.is_some())
+        .ok_or_else(|| AppError::Unauthorized("Invalid or expired sign-in link".into()))?;
+
+    let resp = open_session(&state, user, &profile).await?;
+    tracing::info!(user_id = %resp.user.id, "User logged in with a magic link");
+
+    Ok(Json(resp))
+}
+
 /// GET /me -- return the authenticated user's profile.
+#[utoipa::path(
+    get,
+    path = "/api/v1/auth/me",
+    tag = "auth",
+    responses(
+        (status = 200, description = "The authenticated user", body = UserResponse),
+        (status = 401, description = "Missing or invalid access token", body = ErrorBody),
+    ),
+    security(("bearer_auth" = [])),
+)]
 async fn me(
     State(state): State<Arc<AppState>>,
     auth_user: AuthUser,
@@ -667,17 +996,33 @@ async fn me(
         .await?
         .ok_or_else(|| AppError::NotFound("User not found".into()))?;
 
-    Ok(Json(UserResponse::from(user)))
+    Ok(Json(UserResponse::with_online(
+        user,
+        state.is_online(auth_user.id),
+    )))
 }
 
 /// POST /change-password -- change password while authenticated.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/change-password",
+    tag = "auth",
+    request_body = ChangePasswordRequest,
+    responses(
+        (status = 200, description = "Password changed; other sessions are signed out", body = Value),
+        (status = 400, description = "Current password is incorrect", body = ErrorBody),
+        (status = 401, description = "Missing or invalid access token", body = ErrorBody),
+        (status = 422, description = "Invalid request body", body = ErrorBody),
+    ),
+    security(("bearer_auth" = [])),
+)]
 async fn change_password(
     State(state): State<Arc<AppState>>,
     auth_user: AuthUser,
     Json(body): Json<ChangePasswordRequest>,
 ) -> AppResult<Json<Value>> {
     body.validate()
-        .map_err(|e| AppError::Validation(e.to_string()))?;
+        .map_err(|e| AppError::Validation(e.to_string().into()))?;
 
     // Fetch current user
     let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
@@ -705,3 +1050,197 @@ async fn change_password(
 
     Ok(Json(json!({ "message": "Password changed successfully" })))
 }
+
+/// POST /change-email -- request a new sign-in email.
+///
+/// The address is stored as pending and only replaces the current one after the link
+/// sent to the new address is followed. The current address is told about the request.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/change-email",
+    tag = "auth",
+    request_body = ChangeEmailRequest,
+    responses(
+        (status = 200, description = "Confirmation sent to the new address", body = Value),
+        (status = 400, description = "Current password is incorrect", body = ErrorBody),
+        (status = 401, description = "Missing or invalid access token", body = ErrorBody),
+        (status = 409, description = "Email is already in use", body = ErrorBody),
+        (status = 422, description = "Invalid request body", body = ErrorBody),
+    ),
+    security(("bearer_auth" = [])),
+)]
+async fn change_email(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Json(body): Json<ChangeEmailRequest>,
+) -> AppResult<Json<Value>> {
+    body.validate()
+        .map_err(|e| AppError::Validation(e.to_string().into()))?;
+
+    let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
+        .bind(auth_user.id)
+        .fetch_one(&state.pool)
+        .await?;
+
+    if !verify_password(&body.current_password, &user.password_hash)? {
+        return Err(AppError::BadRequest("Current password is incorrect".into()));
+    }
+
+    if user.email.eq_ignore_ascii_case(&body.new_email) {
+        return Err(AppError::BadRequest(
+            "New email matches your current email".into(),
+        ));
+    }
+
+    let in_use: bool =
+        sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM users WHERE LOWER(email) = LOWER($1))")
+            .bind(&body.new_email)
+            .fetch_one(&state.pool)
+            .await?;
+    if in_use {
+        return Err(AppError::Conflict("Email is already in use".into()));
+    }
+
+    let token = Uuid::new_v4().to_string();
+    let expires_at = Utc::now() + chrono::Duration::hours(24);
+
+    // One pending change per user; a new request replaces the previous one
+    sqlx::query(
+        r#"
+        INSERT INTO email_change_requests (id, user_id, new_email, token_hash, expires_at, created_at)
+        VALUES ($1, $2, $3, $4, $5, NOW())
+        ON CONFLICT (user_id) DO UPDATE
+            SET new_email = EXCLUDED.new_email,
+                token_hash = EXCLUDED.token_hash,
+                expires_at = EXCLUDED.expires_at,
+                created_at = NOW()
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(user.id)
+    .bind(&body.new_email)
+    .bind(hash_token(&token))
+    .bind(expires_at)
+    .execute(&state.pool)
+    .await?;
+
+    if let Some(email_service) = &state.email {
+        let branding = email_branding(&state, user.tenant_id).await?;
+        if let Err(e) = email_service.send_email_change_verification(
+            &body.new_email,
+            &token,
+            &state.config.api_public_url,
+            &branding,
+            Locale::current(),
+        ) {
+            tracing::warn!(user_id = %user.id, error = %e, "Failed to send email change verification");
+        }
+        if let Err(e) = email_service.send_email_change_notice(
+            &user.email,
+            &body.new_email,
+            &state.config.frontend_base_url,
+            &branding,
+            Locale::current(),
+        ) {
+            tracing::warn!(user_id = %user.id, error = %e, "Failed to send email change notice");
+        }
+    }
+
+    Ok(Json(json!({
+        "message": "Check your new email address for a confirmation link",
+        "pending_email": body.new_email
+    })))
+}
+
+/// POST /confirm-email-change -- apply a pending email change with its token.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/confirm-email-change",
+    tag = "auth",
+    request_body = ConfirmEmailChangeRequest,
+    responses(
+        (status = 200, description = "Email changed; all sessions are signed out", body = Value),
+        (status = 400, description = "Invalid or expired token", body = ErrorBody),
+        (status = 409, description = "Email is already in use", body = ErrorBody),
+    ),
+)]
+async fn confirm_email_change(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<ConfirmEmailChangeRequest>,
+) -> AppResult<Json<Value>> {
+    let email = consume_email_change_token(&state, &body.token).await?;
+
+    Ok(Json(
+        json!({ "message": "Email changed successfully", "email": email }),
+    ))
+}
+
+/// GET /confirm-email-change?token=... -- email-click flow; applies and redirects to the web app.
+#[utoipa::path(
+    get,
+    path = "/api/v1/auth/confirm-email-change",
+    tag = "auth",
+    params(
+        ConfirmEmailChangeRequest,
+    ),
+    responses(
+        (status = 303, description = "Redirect to the web app with the result"),
+    ),
+)]
+async fn confirm_email_change_link(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ConfirmEmailChangeRequest>,
+) -> AppResult<Redirect> {
+    let base_url = &state.config.frontend_base_url;
+
+    match consume_email_change_token(&state, &query.token).await {
+        Ok(_) => Ok(Redirect::to(&format!(
+            "{base_url}/login?email_changed=true"
+        ))),
+        Err(AppError::BadRequest(_) | AppError::Conflict(_)) => Ok(Redirect::to(&format!(
+            "{base_url}/settings?error=email_change_failed"
+        ))),
+        Err(e) => Err(e),
+    }
+}
+
+/// Swap in the pending email for the token's user, marking it verified, and sign the user
+/// out everywhere since their login identifier changed. Returns the new email.
+async fn consume_email_change_token(state: &AppState, token: &str) -> AppResult<String> {
+    let mut tx = state.pool.begin().await?;
+
+    let (user_id, new_email) = sqlx::query_as::<_, (Uuid, String)>(
+        r#"
+        DELETE FROM email_change_requests
+        WHERE token_hash = $1 AND expires_at > NOW()
+        RETURNING user_id, new_email
+        "#,
+    )
+    .bind(hash_token(token))
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| AppError::BadRequest("Invalid or expired email change token".into()))?;
+
+    // The address may have been claimed since the change was requested
+    let result = sqlx::query(
+        r#"
+        UPDATE users SET email = $1, email_verified_at = NOW(), updated_at = NOW()
+        WHERE id = $2
+          AND NOT EXISTS (SELECT 1 FROM users WHERE LOWER(email) = LOWER($1) AND id <> $2)
+        "#,
+    )
+    .bind(&new_email)
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::Conflict("Email is already in use".into()));
+    }
+
+    tx.commit().await?;
+
+    invalidate_all_user_tokens(&state.pool, user_id).await?;
+
+    Ok(new_email)
+}
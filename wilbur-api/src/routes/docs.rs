@@ -0,0 +1,109 @@
+use std::sync::Arc;
+
+use axum::{
+    http::{header, HeaderValue},
+    middleware::map_response,
+    response::Response,
+    Router,
+};
+use utoipa::{
+    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    Modify, OpenApi,
+};
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::{extractors::pagination::SortOrder, routes, state::AppState};
+
+/// Generated OpenAPI description of the documented endpoints.
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Wilbur API"),
+    paths(
+        routes::auth::register,
+        routes::auth::login,
+        routes::auth::logout,
+        routes::auth::refresh,
+        routes::auth::resend_verification,
+        routes::auth::verify_email,
+        routes::auth::verify_email_link,
+        routes::auth::forgot_password,
+        routes::auth::reset_password,
+        routes::auth::request_magic_link,
+        routes::auth::consume_magic_link,
+        routes::auth::me,
+        routes::auth::change_password,
+        routes::auth::change_email,
+        routes::auth::confirm_email_change,
+        routes::auth::confirm_email_change_link,
+        routes::rooms::list_rooms,
+        routes::rooms::create_room,
+        routes::rooms::list_rooms_by_tenant,
+        routes::rooms::get_room,
+        routes::rooms::update_room,
+        routes::rooms::patch_room,
+        routes::rooms::delete_room,
+        routes::rooms::upload_room_background,
+        routes::rooms::get_room_features,
+        routes::rooms::update_room_features,
+        routes::rooms::get_room_permissions,
+        routes::rooms::update_room_permissions,
+        routes::rooms::list_members,
+        routes::rooms::invite_member,
+        routes::rooms::remove_member,
+        routes::rooms::update_member_role,
+        routes::messages::list_messages,
+        routes::messages::create_message,
+        routes::messages::update_message,
+        routes::messages::delete_message,
+        routes::messages::pin_message,
+        routes::messages::unpin_message,
+        routes::messages::mark_off_topic,
+    ),
+    // Referenced only from query parameters, so not collected automatically
+    components(schemas(SortOrder)),
+    modifiers(&BearerAuth),
+    tags(
+        (name = "auth", description = "Accounts, sessions, and email verification"),
+        (name = "rooms", description = "Rooms and their memberships"),
+        (name = "messages", description = "Room chat messages"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Registers the `bearer_auth` scheme referenced by authenticated endpoints.
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .description(Some("Access token from /api/v1/auth/login"))
+                    .build(),
+            ),
+        );
+    }
+}
+
+/// Swagger UI at `/api-docs` and the raw spec at `/api-docs/openapi.json`.
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .merge(SwaggerUi::new("/api-docs").url("/api-docs/openapi.json", ApiDoc::openapi()))
+        .layer(map_response(swagger_csp))
+}
+
+/// The API-wide CSP blocks all resource loading; the docs page needs its own scripts,
+/// styles, and inline images. The security middleware keeps a CSP that is already set.
+async fn swagger_csp(mut response: Response) -> Response {
+    response.headers_mut().insert(
+        header::CONTENT_SECURITY_POLICY,
+        HeaderValue::from_static(
+            "default-src 'self'; img-src 'self' data:; style-src 'self' 'unsafe-inline'; frame-ancestors 'none'",
+        ),
+    );
+    response
+}
@@ -0,0 +1,104 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::{delete, get, put},
+    Router,
+};
+use uuid::Uuid;
+
+use crate::{
+    error::{AppError, AppResult},
+    extractors::{json::Json, room_access::RoomMember},
+    models::draft::{Draft, DraftKind, DraftListQuery, SaveDraftRequest},
+    state::AppState,
+};
+
+/// Largest draft body accepted, measured as serialized JSON.
+const MAX_DRAFT_BYTES: usize = 64 * 1024;
+
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", get(list_drafts))
+        .route("/", put(save_draft))
+        .route("/{kind}", delete(delete_draft))
+}
+
+/// GET / -- the caller's drafts in this room, optionally of one `kind`.
+async fn list_drafts(
+    State(state): State<Arc<AppState>>,
+    RoomMember(member): RoomMember,
+    Path(room_id): Path<Uuid>,
+    Query(query): Query<DraftListQuery>,
+) -> AppResult<Json<Vec<Draft>>> {
+    let drafts = sqlx::query_as::<_, Draft>(
+        r#"
+        SELECT room_id, kind, content, created_at, updated_at
+        FROM drafts
+        WHERE user_id = $1 AND room_id = $2 AND ($3::draft_kind IS NULL OR kind = $3)
+        ORDER BY kind
+        "#,
+    )
+    .bind(member.user_id)
+    .bind(room_id)
+    .bind(query.kind)
+    .fetch_all(&state.pool)
+    .await?;
+
+    Ok(Json(drafts))
+}
+
+/// PUT / -- save the caller's draft of a kind, replacing any earlier one. Never broadcast.
+async fn save_draft(
+    State(state): State<Arc<AppState>>,
+    RoomMember(member): RoomMember,
+    Path(room_id): Path<Uuid>,
+    Json(body): Json<SaveDraftRequest>,
+) -> AppResult<Json<Draft>> {
+    if body.content.to_string().len() > MAX_DRAFT_BYTES {
+        return Err(AppError::Validation(
+            format!("Draft exceeds maximum size of {}KB", MAX_DRAFT_BYTES / 1024).into(),
+        ));
+    }
+
+    let draft = sqlx::query_as::<_, Draft>(
+        r#"
+        INSERT INTO drafts (user_id, room_id, kind, content, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, NOW(), NOW())
+        ON CONFLICT (user_id, room_id, kind) DO UPDATE
+            SET content = EXCLUDED.content,
+                updated_at = NOW()
+        RETURNING room_id, kind, content, created_at, updated_at
+        "#,
+    )
+    .bind(member.user_id)
+    .bind(room_id)
+    .bind(body.kind)
+    .bind(&body.content)
+    .fetch_one(&state.pool)
+    .await?;
+
+    Ok(Json(draft))
+}
+
+/// DELETE /{kind} -- discard the caller's draft of a kind.
+async fn delete_draft(
+    State(state): State<Arc<AppState>>,
+    RoomMember(member): RoomMember,
+    Path((room_id, kind)): Path<(Uuid, DraftKind)>,
+) -> AppResult<StatusCode> {
+    let result =
+        sqlx::query("DELETE FROM drafts WHERE user_id = $1 AND room_id = $2 AND kind = $3")
+            .bind(member.user_id)
+            .bind(room_id)
+            .bind(kind)
+            .execute(&state.pool)
+            .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Draft not found".into()));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
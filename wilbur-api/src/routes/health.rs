@@ -1,9 +1,20 @@
 use std::sync::Arc;
+use std::time::Duration;
 
 use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
 use serde_json::{json, Value};
 
-use crate::state::AppState;
+use crate::{state::AppState, MIGRATOR};
+
+/// Commit the binary was built from, passed in as `GIT_SHA` at compile time.
+const GIT_SHA: &str = match option_env!("GIT_SHA") {
+    Some(sha) => sha,
+    None => "unknown",
+};
+
+/// How long `/health` waits on the database for the migration version before reporting
+/// it as unknown; liveness must not hang on a degraded database.
+const MIGRATION_LOOKUP_TIMEOUT: Duration = Duration::from_secs(2);
 
 pub fn router() -> Router<Arc<AppState>> {
     Router::new()
@@ -11,9 +22,43 @@ pub fn router() -> Router<Arc<AppState>> {
         .route("/ready", get(readiness_check))
 }
 
-/// GET /health -- returns {"status":"ok"} unconditionally.
-async fn health_check() -> Json<Value> {
-    Json(json!({ "status": "ok" }))
+/// GET /health -- always `"ok"` while the process is up, with build and schema details.
+/// `migrations.applied` is null when the database can't be reached.
+async fn health_check(State(state): State<Arc<AppState>>) -> Json<Value> {
+    let expected = MIGRATOR.iter().map(|m| m.version).max();
+    let applied = latest_applied_migration(&state).await;
+
+    Json(json!({
+        "status": "ok",
+        "version": env!("CARGO_PKG_VERSION"),
+        "git_sha": GIT_SHA,
+        "uptime_secs": state.started_at.elapsed().as_secs(),
+        "migrations": {
+            "applied": applied,
+            "expected": expected,
+            "pending": applied.map(|applied| Some(applied) < expected),
+        },
+    }))
+}
+
+/// Version of the newest successfully applied migration, or `None` if it can't be read.
+async fn latest_applied_migration(state: &AppState) -> Option<i64> {
+    let query = sqlx::query_scalar::<_, Option<i64>>(
+        "SELECT MAX(version) FROM _sqlx_migrations WHERE success",
+    )
+    .fetch_one(&state.pool);
+
+    match tokio::time::timeout(MIGRATION_LOOKUP_TIMEOUT, query).await {
+        Ok(Ok(version)) => version,
+        Ok(Err(e)) => {
+            tracing::warn!("Could not read applied migrations: {e}");
+            None
+        }
+        Err(_) => {
+            tracing::warn!("Timed out reading applied migrations");
+            None
+        }
+    }
 }
 
 /// GET /ready -- verifies the database pool is reachable.
@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use axum::{
-    extract::{Json, Path, Query, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     routing::{delete, get, post},
     Router,
@@ -11,7 +11,7 @@ use serde_json::{json, Value};
 
 use crate::{
     error::{AppError, AppResult},
-    extractors::auth::AuthUser,
+    extractors::{auth::AuthUser, json::Json},
     state::AppState,
 };
 
@@ -36,10 +36,13 @@ struct ConnectQuery {
 fn validate_provider(provider: &str) -> AppResult<()> {
     match provider {
         "spotify" | "x" | "linkedin" => Ok(()),
-        _ => Err(AppError::BadRequest(format!(
-            "Unsupported provider: {}. Supported: spotify, x, linkedin",
-            provider
-        ))),
+        _ => Err(AppError::BadRequest(
+            format!(
+                "Unsupported provider: {}. Supported: spotify, x, linkedin",
+                provider
+            )
+            .into(),
+        )),
     }
 }
 
@@ -1,17 +1,14 @@
 use std::sync::Arc;
 
-use axum::{
-    extract::{Json, State},
-    routing::post,
-    Router,
-};
+use axum::{extract::State, routing::post, Router};
 use livekit_api::access_token::{AccessToken, VideoGrants};
 use serde::{Deserialize, Serialize};
 
 use crate::{
     error::{AppError, AppResult},
-    extractors::{auth::AuthUser, room_access::require_room_member},
+    extractors::{auth::AuthUser, json::Json, room_access::require_room_member},
     models::room::Room,
+    routes::users::public_identity,
     state::AppState,
 };
 
@@ -46,17 +43,20 @@ async fn generate_token(
             .ok_or_else(|| AppError::NotFound("Room not found".into()))?;
 
     // Verify the user is a member of the room
-    require_room_member(&state.pool, auth_user.id, room.id).await?;
+    require_room_member(&state, auth_user.id, room.id).await?;
 
     // Identity MUST always be the authenticated user's ID to prevent spoofing
     let identity = auth_user.id.to_string();
+    let identity_name = public_identity(&state.pool, auth_user.id)
+        .await?
+        .display_name;
 
     let token = AccessToken::with_api_key(
         &state.config.livekit_api_key,
         &state.config.livekit_api_secret,
     )
     .with_identity(&identity)
-    .with_name(&auth_user.email)
+    .with_name(&identity_name)
     .with_grants(VideoGrants {
         room_join: true,
         room: body.room.clone(),
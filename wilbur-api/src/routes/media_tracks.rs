@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use axum::{
-    extract::{Json, Path, State},
+    extract::{Path, State},
     http::StatusCode,
     routing::{delete, get, post, put},
     Router,
@@ -12,10 +12,14 @@ use uuid::Uuid;
 
 use crate::{
     error::{AppError, AppResult},
-    extractors::auth::AuthUser,
-    models::media_track::{MediaTrack, MediaTrackResponse},
+    extractors::{
+        json::Json,
+        room_access::{RoomMember, RoomModerator},
+    },
+    models::media_track::{MediaTrack, MediaTrackResponse, TrackType},
+    routes::{created, Created},
     state::AppState,
-    ws::manager::WsManager,
+    ws::{channels::Channel, outbox},
 };
 
 pub fn router() -> Router<Arc<AppState>> {
@@ -30,7 +34,7 @@ pub fn router() -> Router<Arc<AppState>> {
 
 #[derive(Debug, Deserialize)]
 struct CreateTrackRequest {
-    track_type: String,
+    track_type: TrackType,
     track_id: Option<String>,
     metadata: Option<Value>,
 }
@@ -49,7 +53,7 @@ struct HeartbeatRequest {
 /// GET / -- list active media tracks for a room.
 async fn list_tracks(
     State(state): State<Arc<AppState>>,
-    _auth_user: AuthUser,
+    _member: RoomMember,
     Path(room_id): Path<Uuid>,
 ) -> AppResult<Json<Value>> {
     let tracks = sqlx::query_as::<_, MediaTrack>(
@@ -70,48 +74,62 @@ async fn list_tracks(
 /// POST / -- register a new media track in the room.
 async fn create_track(
     State(state): State<Arc<AppState>>,
-    auth_user: AuthUser,
+    RoomMember(member): RoomMember,
     Path(room_id): Path<Uuid>,
     Json(body): Json<CreateTrackRequest>,
-) -> AppResult<(StatusCode, Json<Value>)> {
+) -> AppResult<Created<Value>> {
     let track_uuid = Uuid::new_v4();
     let track_id_str = body.track_id.unwrap_or_else(|| track_uuid.to_string());
 
+    let mut tx = state.pool.begin().await?;
+
     let track = sqlx::query_as::<_, MediaTrack>(
         r#"
         INSERT INTO media_tracks (id, room_id, user_id, track_id, track_type, is_active, metadata, created_at, updated_at)
-        VALUES ($1, $2, $3, $4, $5::track_type, true, $6, NOW(), NOW())
+        VALUES ($1, $2, $3, $4, $5, true, $6, NOW(), NOW())
         RETURNING *
         "#,
     )
     .bind(track_uuid)
     .bind(room_id)
-    .bind(auth_user.id)
+    .bind(member.user_id)
     .bind(&track_id_str)
     .bind(&body.track_type)
     .bind(&body.metadata)
-    .fetch_one(&state.pool)
+    .fetch_one(&mut *tx)
     .await?;
 
     let response = MediaTrackResponse::from(track);
     let response_json = serde_json::to_value(&response)
         .map_err(|e| AppError::Internal(format!("Serialization error: {e}")))?;
 
-    let channel = format!("room:{}:tracks", room_id);
-    WsManager::notify_change(&state, &channel, "track_added", response_json.clone());
+    outbox::enqueue(
+        &mut tx,
+        &Channel::room_tracks(room_id),
+        "track_added",
+        &response_json,
+    )
+    .await?;
+    tx.commit().await?;
+    outbox::wake(&state);
 
-    Ok((StatusCode::CREATED, Json(response_json)))
+    Ok(created(
+        format!("/api/v1/rooms/{room_id}/tracks/{track_uuid}"),
+        response_json,
+    ))
 }
 
 /// PUT /{id} -- update a media track (metadata and/or muted state).
 async fn update_track(
     State(state): State<Arc<AppState>>,
-    _auth_user: AuthUser,
+    _member: RoomMember,
     Path((room_id, id)): Path<(Uuid, Uuid)>,
     Json(body): Json<UpdateTrackRequest>,
 ) -> AppResult<Json<Value>> {
     let new_metadata = body.metadata.as_ref();
 
+    let mut tx = state.pool.begin().await?;
+
     let track = sqlx::query_as::<_, MediaTrack>(
         r#"
         UPDATE media_tracks
@@ -126,7 +144,7 @@ async fn update_track(
     .bind(body.muted)
     .bind(id)
     .bind(room_id)
-    .fetch_optional(&state.pool)
+    .fetch_optional(&mut *tx)
     .await?
     .ok_or_else(|| AppError::NotFound("Media track not found".into()))?;
 
@@ -134,8 +152,15 @@ async fn update_track(
     let response_json = serde_json::to_value(&response)
         .map_err(|e| AppError::Internal(format!("Serialization error: {e}")))?;
 
-    let channel = format!("room:{}:tracks", room_id);
-    WsManager::notify_change(&state, &channel, "track_updated", response_json.clone());
+    outbox::enqueue(
+        &mut tx,
+        &Channel::room_tracks(room_id),
+        "track_updated",
+        &response_json,
+    )
+    .await?;
+    tx.commit().await?;
+    outbox::wake(&state);
 
     Ok(Json(response_json))
 }
@@ -143,28 +168,32 @@ async fn update_track(
 /// DELETE /{id} -- remove a media track (soft-delete).
 async fn delete_track(
     State(state): State<Arc<AppState>>,
-    auth_user: AuthUser,
+    RoomMember(member): RoomMember,
     Path((room_id, id)): Path<(Uuid, Uuid)>,
 ) -> AppResult<StatusCode> {
+    let mut tx = state.pool.begin().await?;
+
     let result = sqlx::query(
         "UPDATE media_tracks SET is_active = false, updated_at = NOW() WHERE id = $1 AND room_id = $2",
     )
     .bind(id)
     .bind(room_id)
-    .execute(&state.pool)
+    .execute(&mut *tx)
     .await?;
 
     if result.rows_affected() == 0 {
         return Err(AppError::NotFound("Media track not found".into()));
     }
 
-    let channel = format!("room:{}:tracks", room_id);
-    WsManager::notify_change(
-        &state,
-        &channel,
+    outbox::enqueue(
+        &mut tx,
+        &Channel::room_tracks(room_id),
         "track_removed",
-        json!({ "id": id, "room_id": room_id, "user_id": auth_user.id }),
-    );
+        &json!({ "id": id, "room_id": room_id, "user_id": member.user_id }),
+    )
+    .await?;
+    tx.commit().await?;
+    outbox::wake(&state);
 
     Ok(StatusCode::NO_CONTENT)
 }
@@ -172,7 +201,7 @@ async fn delete_track(
 /// POST /heartbeat -- send heartbeat for active tracks to prevent cleanup.
 async fn heartbeat(
     State(state): State<Arc<AppState>>,
-    auth_user: AuthUser,
+    RoomMember(member): RoomMember,
     Path(room_id): Path<Uuid>,
     Json(body): Json<HeartbeatRequest>,
 ) -> AppResult<Json<Value>> {
@@ -180,13 +209,13 @@ async fn heartbeat(
         "UPDATE media_tracks SET last_heartbeat = NOW(), updated_at = NOW() WHERE id = ANY($1) AND user_id = $2",
     )
     .bind(&body.track_ids)
-    .bind(auth_user.id)
+    .bind(member.user_id)
     .execute(&state.pool)
     .await?;
 
     Ok(Json(json!({
         "room_id": room_id,
-        "user_id": auth_user.id,
+        "user_id": member.user_id,
         "track_count": body.track_ids.len(),
         "updated_count": result.rows_affected(),
         "acknowledged": true
@@ -196,9 +225,11 @@ async fn heartbeat(
 /// POST /cleanup -- remove stale tracks (called by background job or admin).
 async fn cleanup(
     State(state): State<Arc<AppState>>,
-    _auth_user: AuthUser,
+    _moderator: RoomModerator,
     Path(room_id): Path<Uuid>,
 ) -> AppResult<Json<Value>> {
+    let mut tx = state.pool.begin().await?;
+
     let result = sqlx::query(
         r#"
         UPDATE media_tracks
@@ -207,19 +238,23 @@ async fn cleanup(
         "#,
     )
     .bind(room_id)
-    .execute(&state.pool)
+    .execute(&mut *tx)
     .await?;
 
     let removed_count = result.rows_affected();
 
     if removed_count > 0 {
-        let channel = format!("room:{}:tracks", room_id);
-        WsManager::notify_change(
-            &state,
-            &channel,
+        outbox::enqueue(
+            &mut tx,
+            &Channel::room_tracks(room_id),
             "tracks_cleaned_up",
-            json!({ "room_id": room_id, "removed_count": removed_count }),
-        );
+            &json!({ "room_id": room_id, "removed_count": removed_count }),
+        )
+        .await?;
+    }
+    tx.commit().await?;
+    if removed_count > 0 {
+        outbox::wake(&state);
     }
 
     Ok(Json(json!({
@@ -1,27 +1,52 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use axum::{
-    extract::{Json, Path, Query, State},
-    http::StatusCode,
+    extract::{FromRequest, Multipart, Path, Query, Request, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
     routing::{delete, get, post, put},
     Router,
 };
+use bytes::Bytes;
 use serde_json::{json, Value};
+use sqlx::{PgConnection, PgExecutor, Postgres, QueryBuilder};
 use uuid::Uuid;
 use validator::Validate;
 
 use crate::{
-    error::{AppError, AppResult},
+    error::{AppError, AppResult, ErrorBody},
     extractors::{
         auth::AuthUser,
-        pagination::PaginationParams,
-        room_access::{require_room_member, require_room_moderator},
+        json::Json,
+        pagination::{PaginationParams, RawPaginationParams},
+        room_access::{RoomMember, RoomModerator},
+        soft_delete::DeletedFilter,
+        sync::SyncFilter,
     },
-    models::message::{
-        ChatMessageWithUser, CreateMessageRequest, MessageResponse, UpdateMessageRequest,
+    models::{
+        membership::MemberRole,
+        message::{
+            AttachmentResponse, ChatMessageWithUser, ContentType, CreateMessageRequest,
+            MessageAttachment, MessageListQuery, MessageResponse, QuotedMessage,
+            QuotedMessageResponse, UpdateMessageRequest,
+        },
+    },
+    routes::{
+        created,
+        storage::{
+            prepare_upload, require_storage, sanitize_filename, store_room_file, validate_upload,
+            ALLOWED_CONTENT_TYPES,
+        },
+        Created,
+    },
+    services::{
+        content_sanitizer::render_safe,
+        message_limits,
+        room_permissions::{require_capability, Capability},
+        storage_cleanup,
     },
     state::AppState,
-    ws::manager::WsManager,
+    ws::{channels::Channel, outbox},
 };
 
 pub fn router() -> Router<Arc<AppState>> {
@@ -36,91 +61,290 @@ pub fn router() -> Router<Arc<AppState>> {
 }
 
 /// GET / -- list messages for a room (paginated). Room ID comes from the nested path.
+/// With `updated_after`, returns changes since then instead, deletions included; messages
+/// the caller can no longer see, such as ones hidden pending review, come back as deletions.
+#[utoipa::path(
+    get,
+    path = "/api/v1/rooms/{room_id}/messages",
+    tag = "messages",
+    params(
+        ("room_id" = Uuid, Path, description = "Room ID"),
+        RawPaginationParams,
+        MessageListQuery,
+        DeletedFilter,
+        SyncFilter,
+    ),
+    responses(
+        (status = 200, description = "Messages visible to the caller", body = Vec<MessageResponse>),
+        (status = 401, description = "Missing or invalid access token", body = ErrorBody),
+        (status = 403, description = "Not allowed in this room, or deleted messages requested by a non-moderator", body = ErrorBody),
+    ),
+    security(("bearer_auth" = [])),
+)]
 async fn list_messages(
     State(state): State<Arc<AppState>>,
     auth_user: AuthUser,
-    Path(room_id): Path<Uuid>,
-    Query(pagination): Query<PaginationParams>,
+    RoomMember(membership): RoomMember,
+    pagination: PaginationParams,
+    Query(filter): Query<MessageListQuery>,
+    Query(deleted): Query<DeletedFilter>,
+    Query(sync): Query<SyncFilter>,
 ) -> AppResult<Json<Vec<MessageResponse>>> {
-    // Verify the user is a member of the room
-    require_room_member(&state.pool, auth_user.id, room_id).await?;
+    let room_id = membership.room_id;
+    let is_moderator = matches!(membership.role, MemberRole::Host | MemberRole::Moderator);
+    let include_deleted = deleted
+        .resolve(&state.pool, &auth_user, Some(room_id))
+        .await?;
 
-    let messages = sqlx::query_as::<_, ChatMessageWithUser>(
+    let mut query = QueryBuilder::<Postgres>::new(
         r#"
-        SELECT m.*, u.display_name AS user_display_name, u.avatar_url AS user_avatar_url
+        SELECT m.*, u.display_name AS user_display_name, u.avatar_url AS user_avatar_url,
+               u.is_bot AS user_is_bot
         FROM chatmessages m
         JOIN users u ON u.id = m.user_id
-        WHERE m.room_id = $1 AND m.is_deleted = false
-        ORDER BY m.created_at DESC
-        LIMIT $2 OFFSET $3
-        "#,
-    )
-    .bind(room_id)
-    .bind(pagination.limit())
-    .bind(pagination.offset())
-    .fetch_all(&state.pool)
-    .await?;
+        WHERE m.room_id = "#,
+    );
+    query.push_bind(room_id);
+    match sync.updated_after {
+        // Deleted messages come back as tombstones
+        Some(updated_after) => {
+            query.push(" AND m.updated_at > ").push_bind(updated_after);
+        }
+        None if !include_deleted => {
+            query.push(" AND m.is_deleted = false");
+        }
+        None => {}
+    }
+
+    // Shadowed messages are visible only to their author and to moderators;
+    // messages hidden pending report review only to moderators. A sync still gets them,
+    // as tombstones, so clients drop content they synced before it was hidden.
+    if !is_moderator && sync.updated_after.is_none() {
+        query
+            .push(" AND m.is_hidden = false AND (m.is_shadowed = false OR m.user_id = ")
+            .push_bind(auth_user.id)
+            .push(")");
+    }
+
+    if filter.pinned_only {
+        query.push(" AND m.is_pinned = true");
+    }
+    if let Some(from_user) = filter.from_user {
+        query.push(" AND m.user_id = ").push_bind(from_user);
+    }
+    if let Some(content_type) = filter.content_type {
+        query.push(" AND m.content_type = ").push_bind(content_type);
+    }
 
-    let results: Vec<MessageResponse> = messages.into_iter().map(MessageResponse::from).collect();
+    if sync.updated_after.is_some() {
+        query.push(" ORDER BY m.updated_at ASC, m.id ASC");
+    } else {
+        query
+            .push(" ORDER BY m.created_at ")
+            .push(filter.order.as_sql());
+    }
+    query
+        .push(" LIMIT ")
+        .push_bind(pagination.limit())
+        .push(" OFFSET ")
+        .push_bind(pagination.offset());
+
+    let messages = query
+        .build_query_as::<ChatMessageWithUser>()
+        .fetch_all(&state.pool)
+        .await?;
+
+    let ids: Vec<Uuid> = messages.iter().map(|m| m.id).collect();
+    let mut attachments = load_attachments(&state.pool, &ids).await?;
+    let quoted_ids: Vec<Uuid> = messages
+        .iter()
+        .filter_map(|m| m.quoted_message_id)
+        .collect();
+    let quotes = load_quotes(&state.pool, &quoted_ids).await?;
+
+    let results: Vec<MessageResponse> = messages
+        .into_iter()
+        .map(|m| {
+            let (is_shadowed, is_hidden) = (m.is_shadowed, m.is_hidden);
+            let visible =
+                is_moderator || (!is_hidden && (!is_shadowed || m.user_id == auth_user.id));
+            let tombstone = m.is_deleted && !include_deleted;
+            let mut response = MessageResponse::from(m);
+            if !visible {
+                return response.into_withheld_tombstone();
+            }
+            if tombstone {
+                return response.into_tombstone();
+            }
+            response.attachments = attachments.remove(&response.id).unwrap_or_default();
+            response.quoted = response
+                .quoted_message_id
+                .and_then(|id| quotes.get(&id).cloned());
+            if is_moderator {
+                response.is_shadowed = Some(is_shadowed);
+                response.is_hidden = Some(is_hidden);
+            }
+            response
+        })
+        .collect();
     Ok(Json(results))
 }
 
-/// POST / -- create a new message in the room.
+/// Body of `POST /`: a JSON [`CreateMessageRequest`], or `multipart/form-data` with a
+/// `content` text field and a `file` to upload and attach in the same call.
+enum CreateMessageBody {
+    Json(CreateMessageRequest),
+    Multipart(Multipart),
+}
+
+impl<S: Send + Sync> FromRequest<S> for CreateMessageBody {
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let is_multipart = req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("multipart/form-data"));
+
+        if is_multipart {
+            Multipart::from_request(req, state)
+                .await
+                .map(Self::Multipart)
+                .map_err(|e| {
+                    AppError::BadRequest(format!("Multipart error: {e}").into()).into_response()
+                })
+        } else {
+            Json::from_request(req, state)
+                .await
+                .map(|Json(body)| Self::Json(body))
+                .map_err(IntoResponse::into_response)
+        }
+    }
+}
+
+/// A file sent inline with a multipart message, checked against the upload rules but not yet stored.
+struct InlineFile {
+    file_name: String,
+    content_type: String,
+    data: Bytes,
+}
+
+/// POST / -- create a new message in the room. A multipart body uploads its `file` to the
+/// room and attaches it; the message is only posted if the upload succeeds.
+#[utoipa::path(
+    post,
+    path = "/api/v1/rooms/{room_id}/messages",
+    tag = "messages",
+    params(
+        ("room_id" = Uuid, Path, description = "Room ID"),
+    ),
+    request_body(
+        content = CreateMessageRequest,
+        description = "JSON, or multipart/form-data with a `content` caption and one `file` to attach",
+    ),
+    responses(
+        (status = 201, description = "Message posted", body = MessageResponse),
+        (status = 400, description = "Invalid multipart body, rejected file, or storage not configured", body = ErrorBody),
+        (status = 401, description = "Missing or invalid access token", body = ErrorBody),
+        (status = 403, description = "Not allowed in this room", body = ErrorBody),
+        (status = 422, description = "Invalid request body", body = ErrorBody),
+        (status = 503, description = "File storage temporarily unavailable; retry", body = ErrorBody),
+    ),
+    security(("bearer_auth" = [])),
+)]
 async fn create_message(
     State(state): State<Arc<AppState>>,
-    auth_user: AuthUser,
+    RoomMember(membership): RoomMember,
     Path(room_id): Path<Uuid>,
-    Json(body): Json<CreateMessageRequest>,
-) -> AppResult<(StatusCode, Json<MessageResponse>)> {
-    // Verify the user is a member of the room
-    require_room_member(&state.pool, auth_user.id, room_id).await?;
+    body: CreateMessageBody,
+) -> AppResult<Created<MessageResponse>> {
+    require_capability(&state.pool, &membership, Capability::PostMessage).await?;
+
+    let (mut body, file) = match body {
+        CreateMessageBody::Json(body) => (body, None),
+        CreateMessageBody::Multipart(multipart) => {
+            require_capability(&state.pool, &membership, Capability::UploadFile).await?;
+            require_storage(&state)?;
+            let (body, file) = read_message_form(multipart).await?;
+            (body, Some(file))
+        }
+    };
 
     body.validate()
-        .map_err(|e| AppError::Validation(e.to_string()))?;
+        .map_err(|e| AppError::Validation(e.to_string().into()))?;
+    message_limits::check_content(&state.config, &body.content)?;
 
-    let content_type = body
-        .content_type
-        .unwrap_or(crate::models::message::ContentType::Text);
-    let msg_id = Uuid::new_v4();
-    let now = chrono::Utc::now();
+    let content_type = body.content_type.unwrap_or(ContentType::Text);
 
-    let message = sqlx::query_as::<_, ChatMessageWithUser>(
-        r#"
-        WITH inserted AS (
-            INSERT INTO chatmessages (id, room_id, user_id, content, content_type, is_pinned, is_off_topic, is_deleted, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, false, false, false, $6, $7)
-            RETURNING *
-        )
-        SELECT i.*, u.display_name AS user_display_name, u.avatar_url AS user_avatar_url
-        FROM inserted i
-        JOIN users u ON u.id = i.user_id
-        "#,
+    let upload = match file {
+        Some(file) => {
+            Some(prepare_upload(&state, file.file_name, file.content_type, file.data).await)
+        }
+        None => None,
+    };
+
+    let mut tx = state.pool.begin().await?;
+
+    // Stored inside the transaction so a failed upload leaves no message behind
+    if let Some(upload) = &upload {
+        let stored = store_room_file(&state, &mut *tx, room_id, membership.user_id, upload).await?;
+        body.file_ids.push(stored.id);
+    }
+
+    message_limits::check_attachments(&state.config, &mut tx, &body.file_ids).await?;
+
+    let response = insert_message(
+        &mut tx,
+        room_id,
+        membership.user_id,
+        &body.content,
+        &content_type,
+        &body.file_ids,
+        body.quoted_message_id,
     )
-    .bind(msg_id)
-    .bind(room_id)
-    .bind(auth_user.id)
-    .bind(&body.content)
-    .bind(&content_type)
-    .bind(now)
-    .bind(now)
-    .fetch_one(&state.pool)
     .await?;
 
-    let response = MessageResponse::from(message);
+    // Queue the broadcast in the same transaction so it is only sent once committed.
+    // Shadow-banned members get a normal response but nobody else is told.
+    if !membership.shadow_banned {
+        let channel = Channel::room_chat(room_id);
+        outbox::enqueue(
+            &mut tx,
+            &channel,
+            "message_created",
+            &serde_json::to_value(&response).unwrap_or_default(),
+        )
+        .await?;
+    }
 
-    // Broadcast to WebSocket channel
-    let channel = format!("room:{}:chat", room_id);
-    WsManager::notify_change(
-        &state,
-        &channel,
-        "message_created",
-        serde_json::to_value(&response).unwrap_or_default(),
-    );
+    tx.commit().await?;
+    outbox::wake(&state);
 
-    Ok((StatusCode::CREATED, Json(response)))
+    Ok(created(
+        format!("/api/v1/rooms/{room_id}/messages/{}", response.id),
+        response,
+    ))
 }
 
 /// PUT /{id} -- update a message.
+#[utoipa::path(
+    put,
+    path = "/api/v1/rooms/{room_id}/messages/{id}",
+    tag = "messages",
+    params(
+        ("room_id" = Uuid, Path, description = "Room ID"),
+        ("id" = Uuid, Path, description = "Message ID"),
+    ),
+    request_body = UpdateMessageRequest,
+    responses(
+        (status = 200, description = "Updated message", body = MessageResponse),
+        (status = 401, description = "Missing or invalid access token", body = ErrorBody),
+        (status = 404, description = "Message not found or not owned by you", body = ErrorBody),
+        (status = 422, description = "Invalid request body", body = ErrorBody),
+    ),
+    security(("bearer_auth" = [])),
+)]
 async fn update_message(
     State(state): State<Arc<AppState>>,
     auth_user: AuthUser,
@@ -128,20 +352,32 @@ async fn update_message(
     Json(body): Json<UpdateMessageRequest>,
 ) -> AppResult<Json<MessageResponse>> {
     body.validate()
-        .map_err(|e| AppError::Validation(e.to_string()))?;
+        .map_err(|e| AppError::Validation(e.to_string().into()))?;
+    if let Some(content) = &body.content {
+        message_limits::check_content(&state.config, content)?;
+    }
+
+    let mut tx = state.pool.begin().await?;
+
+    let rendered_safe = match &body.content {
+        Some(content) => render_for_room(&mut tx, room_id, content).await?,
+        None => None,
+    };
 
     let message = sqlx::query_as::<_, ChatMessageWithUser>(
         r#"
         WITH updated AS (
             UPDATE chatmessages SET
                 content    = COALESCE($1, content),
+                rendered_safe = CASE WHEN $1 IS NULL THEN rendered_safe ELSE $7 END,
                 is_pinned  = COALESCE($2, is_pinned),
                 is_off_topic = COALESCE($3, is_off_topic),
                 updated_at = NOW()
             WHERE id = $4 AND room_id = $5 AND user_id = $6 AND is_deleted = false
             RETURNING *
         )
-        SELECT u2.*, usr.display_name AS user_display_name, usr.avatar_url AS user_avatar_url
+        SELECT u2.*, usr.display_name AS user_display_name, usr.avatar_url AS user_avatar_url,
+               usr.is_bot AS user_is_bot
         FROM updated u2
         JOIN users usr ON usr.id = u2.user_id
         "#,
@@ -152,29 +388,63 @@ async fn update_message(
     .bind(id)
     .bind(room_id)
     .bind(auth_user.id)
-    .fetch_optional(&state.pool)
+    .bind(&rendered_safe)
+    .fetch_optional(&mut *tx)
     .await?
     .ok_or_else(|| AppError::NotFound("Message not found or not owned by you".into()))?;
 
-    let response = MessageResponse::from(message);
+    let is_visible = !message.is_shadowed && !message.is_hidden;
+    let mut response = MessageResponse::from(message);
+    response.attachments = load_attachments(&mut *tx, &[id])
+        .await?
+        .remove(&id)
+        .unwrap_or_default();
+    if let Some(quoted_id) = response.quoted_message_id {
+        response.quoted = load_quotes(&mut *tx, &[quoted_id])
+            .await?
+            .remove(&quoted_id);
+    }
 
-    let channel = format!("room:{}:chat", room_id);
-    WsManager::notify_change(
-        &state,
-        &channel,
-        "message_updated",
-        serde_json::to_value(&response).unwrap_or_default(),
-    );
+    if is_visible {
+        let channel = Channel::room_chat(room_id);
+        outbox::enqueue(
+            &mut tx,
+            &channel,
+            "message_updated",
+            &serde_json::to_value(&response).unwrap_or_default(),
+        )
+        .await?;
+    }
+
+    tx.commit().await?;
+    outbox::wake(&state);
 
     Ok(Json(response))
 }
 
 /// DELETE /{id} -- soft-delete a message.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/rooms/{room_id}/messages/{id}",
+    tag = "messages",
+    params(
+        ("room_id" = Uuid, Path, description = "Room ID"),
+        ("id" = Uuid, Path, description = "Message ID"),
+    ),
+    responses(
+        (status = 204, description = "Message deleted"),
+        (status = 401, description = "Missing or invalid access token", body = ErrorBody),
+        (status = 404, description = "Message not found or not owned by you", body = ErrorBody),
+    ),
+    security(("bearer_auth" = [])),
+)]
 async fn delete_message(
     State(state): State<Arc<AppState>>,
     auth_user: AuthUser,
     Path((room_id, id)): Path<(Uuid, Uuid)>,
 ) -> AppResult<StatusCode> {
+    let mut tx = state.pool.begin().await?;
+
     let result = sqlx::query(
         r#"
         UPDATE chatmessages SET is_deleted = true, deleted_at = NOW(), updated_at = NOW()
@@ -184,7 +454,7 @@ async fn delete_message(
     .bind(id)
     .bind(room_id)
     .bind(auth_user.id)
-    .execute(&state.pool)
+    .execute(&mut *tx)
     .await?;
 
     if result.rows_affected() == 0 {
@@ -193,109 +463,464 @@ async fn delete_message(
         ));
     }
 
-    let channel = format!("room:{}:chat", room_id);
-    WsManager::notify_change(
-        &state,
+    // Remove attached files only once no remaining message references them
+    sqlx::query(
+        r#"
+        DELETE FROM room_files f
+        USING message_attachments a
+        WHERE a.message_id = $1 AND f.id = a.file_id
+          AND NOT EXISTS (
+              SELECT 1 FROM message_attachments a2
+              JOIN chatmessages m ON m.id = a2.message_id
+              WHERE a2.file_id = f.id AND m.is_deleted = false
+          )
+        "#,
+    )
+    .bind(id)
+    .execute(&mut *tx)
+    .await?;
+
+    let channel = Channel::room_chat(room_id);
+    outbox::enqueue(
+        &mut tx,
         &channel,
         "message_deleted",
-        json!({ "id": id, "room_id": room_id }),
-    );
+        &json!({ "id": id, "room_id": room_id }),
+    )
+    .await?;
+
+    tx.commit().await?;
+    outbox::wake(&state);
+    storage_cleanup::wake(&state);
 
     Ok(StatusCode::NO_CONTENT)
 }
 
 /// POST /{id}/pin -- pin a message.
+#[utoipa::path(
+    post,
+    path = "/api/v1/rooms/{room_id}/messages/{id}/pin",
+    tag = "messages",
+    params(
+        ("room_id" = Uuid, Path, description = "Room ID"),
+        ("id" = Uuid, Path, description = "Message ID"),
+    ),
+    responses(
+        (status = 200, description = "Message pinned", body = Value),
+        (status = 401, description = "Missing or invalid access token", body = ErrorBody),
+        (status = 403, description = "Not allowed in this room", body = ErrorBody),
+        (status = 404, description = "Message not found", body = ErrorBody),
+    ),
+    security(("bearer_auth" = [])),
+)]
 async fn pin_message(
     State(state): State<Arc<AppState>>,
-    auth_user: AuthUser,
+    _moderator: RoomModerator,
     Path((room_id, id)): Path<(Uuid, Uuid)>,
 ) -> AppResult<Json<Value>> {
-    // Only host or moderator can pin messages
-    require_room_moderator(&state.pool, auth_user.id, room_id).await?;
+    let mut tx = state.pool.begin().await?;
 
     let result = sqlx::query(
         "UPDATE chatmessages SET is_pinned = true, updated_at = NOW() WHERE id = $1 AND room_id = $2",
     )
     .bind(id)
     .bind(room_id)
-    .execute(&state.pool)
+    .execute(&mut *tx)
     .await?;
 
     if result.rows_affected() == 0 {
         return Err(AppError::NotFound("Message not found".into()));
     }
 
-    let channel = format!("room:{}:chat", room_id);
-    WsManager::notify_change(
-        &state,
+    let channel = Channel::room_chat(room_id);
+    outbox::enqueue(
+        &mut tx,
         &channel,
         "message_pinned",
-        json!({ "id": id, "room_id": room_id }),
-    );
+        &json!({ "id": id, "room_id": room_id }),
+    )
+    .await?;
+
+    tx.commit().await?;
+    outbox::wake(&state);
 
     Ok(Json(json!({ "message": "Message pinned" })))
 }
 
 /// POST /{id}/unpin -- unpin a message.
+#[utoipa::path(
+    post,
+    path = "/api/v1/rooms/{room_id}/messages/{id}/unpin",
+    tag = "messages",
+    params(
+        ("room_id" = Uuid, Path, description = "Room ID"),
+        ("id" = Uuid, Path, description = "Message ID"),
+    ),
+    responses(
+        (status = 200, description = "Message unpinned", body = Value),
+        (status = 401, description = "Missing or invalid access token", body = ErrorBody),
+        (status = 403, description = "Not allowed in this room", body = ErrorBody),
+        (status = 404, description = "Message not found", body = ErrorBody),
+    ),
+    security(("bearer_auth" = [])),
+)]
 async fn unpin_message(
     State(state): State<Arc<AppState>>,
-    auth_user: AuthUser,
+    _moderator: RoomModerator,
     Path((room_id, id)): Path<(Uuid, Uuid)>,
 ) -> AppResult<Json<Value>> {
-    // Only host or moderator can unpin messages
-    require_room_moderator(&state.pool, auth_user.id, room_id).await?;
+    let mut tx = state.pool.begin().await?;
 
     let result = sqlx::query(
         "UPDATE chatmessages SET is_pinned = false, updated_at = NOW() WHERE id = $1 AND room_id = $2",
     )
     .bind(id)
     .bind(room_id)
-    .execute(&state.pool)
+    .execute(&mut *tx)
     .await?;
 
     if result.rows_affected() == 0 {
         return Err(AppError::NotFound("Message not found".into()));
     }
 
-    let channel = format!("room:{}:chat", room_id);
-    WsManager::notify_change(
-        &state,
+    let channel = Channel::room_chat(room_id);
+    outbox::enqueue(
+        &mut tx,
         &channel,
         "message_unpinned",
-        json!({ "id": id, "room_id": room_id }),
-    );
+        &json!({ "id": id, "room_id": room_id }),
+    )
+    .await?;
+
+    tx.commit().await?;
+    outbox::wake(&state);
 
     Ok(Json(json!({ "message": "Message unpinned" })))
 }
 
 /// POST /{id}/off-topic -- mark a message as off-topic.
+#[utoipa::path(
+    post,
+    path = "/api/v1/rooms/{room_id}/messages/{id}/off-topic",
+    tag = "messages",
+    params(
+        ("room_id" = Uuid, Path, description = "Room ID"),
+        ("id" = Uuid, Path, description = "Message ID"),
+    ),
+    responses(
+        (status = 200, description = "Message marked off-topic", body = Value),
+        (status = 401, description = "Missing or invalid access token", body = ErrorBody),
+        (status = 403, description = "Not allowed in this room", body = ErrorBody),
+        (status = 404, description = "Message not found", body = ErrorBody),
+    ),
+    security(("bearer_auth" = [])),
+)]
 async fn mark_off_topic(
     State(state): State<Arc<AppState>>,
-    auth_user: AuthUser,
+    _moderator: RoomModerator,
     Path((room_id, id)): Path<(Uuid, Uuid)>,
 ) -> AppResult<Json<Value>> {
-    // Only host or moderator can mark messages as off-topic
-    require_room_moderator(&state.pool, auth_user.id, room_id).await?;
+    let mut tx = state.pool.begin().await?;
 
     let result = sqlx::query(
         "UPDATE chatmessages SET is_off_topic = true, updated_at = NOW() WHERE id = $1 AND room_id = $2",
     )
     .bind(id)
     .bind(room_id)
-    .execute(&state.pool)
+    .execute(&mut *tx)
     .await?;
 
     if result.rows_affected() == 0 {
         return Err(AppError::NotFound("Message not found".into()));
     }
 
-    let channel = format!("room:{}:chat", room_id);
-    WsManager::notify_change(
-        &state,
+    let channel = Channel::room_chat(room_id);
+    outbox::enqueue(
+        &mut tx,
         &channel,
         "message_off_topic",
-        json!({ "id": id, "room_id": room_id }),
-    );
+        &json!({ "id": id, "room_id": room_id }),
+    )
+    .await?;
+
+    tx.commit().await?;
+    outbox::wake(&state);
 
     Ok(Json(json!({ "message": "Message marked as off-topic" })))
 }
+
+/// Read a multipart message: a required `content` caption and `file`, and an optional
+/// `quoted_message_id`. The message's
+/// content type follows the file's (`image` for images, otherwise `file`).
+async fn read_message_form(
+    mut multipart: Multipart,
+) -> AppResult<(CreateMessageRequest, InlineFile)> {
+    let mut content = None;
+    let mut quoted_message_id = None;
+    let mut file = None;
+
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| AppError::BadRequest(format!("Multipart error: {e}").into()))?
+    {
+        match field.name() {
+            Some("content") => {
+                content = Some(field.text().await.map_err(|e| {
+                    AppError::BadRequest(format!("Failed to read content: {e}").into())
+                })?);
+            }
+            Some("quoted_message_id") => {
+                let raw = field.text().await.map_err(|e| {
+                    AppError::BadRequest(format!("Failed to read quoted_message_id: {e}").into())
+                })?;
+                quoted_message_id = Some(Uuid::parse_str(raw.trim()).map_err(|_| {
+                    AppError::BadRequest("quoted_message_id must be a UUID".into())
+                })?);
+            }
+            Some("file") => {
+                let raw_name = field.file_name().unwrap_or("upload.bin").to_string();
+                let content_type = field
+                    .content_type()
+                    .unwrap_or("application/octet-stream")
+                    .to_string();
+                let data = field.bytes().await.map_err(|e| {
+                    AppError::BadRequest(format!("Failed to read file: {e}").into())
+                })?;
+
+                validate_upload(data.len(), &content_type, ALLOWED_CONTENT_TYPES)?;
+                file = Some(InlineFile {
+                    file_name: sanitize_filename(&raw_name),
+                    content_type,
+                    data,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    let file =
+        file.ok_or_else(|| AppError::BadRequest("No file field found in multipart body".into()))?;
+    let content_type = if file.content_type.starts_with("image/") {
+        ContentType::Image
+    } else {
+        ContentType::File
+    };
+    let body = CreateMessageRequest {
+        content: content.unwrap_or_default(),
+        content_type: Some(content_type),
+        file_ids: Vec::new(),
+        quoted_message_id,
+    };
+
+    Ok((body, file))
+}
+
+/// Insert a chat message with its attachments and quote, and return it joined with the
+/// author's display info. Shared by member posts and incoming hooks; callers check the message
+/// limits and queue the broadcast.
+pub(crate) async fn insert_message(
+    conn: &mut PgConnection,
+    room_id: Uuid,
+    user_id: Uuid,
+    content: &str,
+    content_type: &ContentType,
+    file_ids: &[Uuid],
+    quoted_message_id: Option<Uuid>,
+) -> AppResult<MessageResponse> {
+    let now = chrono::Utc::now();
+    let rendered_safe = render_for_room(&mut *conn, room_id, content).await?;
+    let quoted = match quoted_message_id {
+        Some(quoted_id) => Some(require_quotable(&mut *conn, room_id, user_id, quoted_id).await?),
+        None => None,
+    };
+
+    let message = sqlx::query_as::<_, ChatMessageWithUser>(
+        r#"
+        WITH inserted AS (
+            INSERT INTO chatmessages (id, room_id, user_id, content, content_type, rendered_safe, is_pinned, is_off_topic, is_deleted, is_shadowed, quoted_message_id, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, false, false, false,
+                    COALESCE((SELECT shadow_banned FROM room_memberships WHERE user_id = $3 AND room_id = $2), false),
+                    $9, $7, $8)
+            RETURNING *
+        )
+        SELECT i.*, u.display_name AS user_display_name, u.avatar_url AS user_avatar_url,
+               u.is_bot AS user_is_bot
+        FROM inserted i
+        JOIN users u ON u.id = i.user_id
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(room_id)
+    .bind(user_id)
+    .bind(content)
+    .bind(content_type)
+    .bind(&rendered_safe)
+    .bind(now)
+    .bind(now)
+    .bind(quoted_message_id)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let mut response = MessageResponse::from(message);
+    response.quoted = quoted;
+    if !file_ids.is_empty() {
+        attach_files(&mut *conn, response.id, room_id, user_id, file_ids).await?;
+        response.attachments = load_attachments(conn, &[response.id])
+            .await?
+            .remove(&response.id)
+            .unwrap_or_default();
+    }
+
+    Ok(response)
+}
+
+/// Link room files to a message. Each file must belong to the room and have been uploaded by the author.
+async fn attach_files(
+    conn: &mut PgConnection,
+    message_id: Uuid,
+    room_id: Uuid,
+    user_id: Uuid,
+    file_ids: &[Uuid],
+) -> AppResult<()> {
+    let mut unique: Vec<Uuid> = Vec::with_capacity(file_ids.len());
+    for id in file_ids {
+        if !unique.contains(id) {
+            unique.push(*id);
+        }
+    }
+
+    let (owned,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM room_files WHERE id = ANY($1) AND room_id = $2 AND uploaded_by = $3",
+    )
+    .bind(&unique)
+    .bind(room_id)
+    .bind(user_id)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    if owned != unique.len() as i64 {
+        return Err(AppError::Validation(
+            "Attachments must be files you uploaded to this room".into(),
+        ));
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO message_attachments (message_id, file_id, position, created_at)
+        SELECT $1, f.id, f.ord - 1, NOW()
+        FROM UNNEST($2::uuid[]) WITH ORDINALITY AS f(id, ord)
+        "#,
+    )
+    .bind(message_id)
+    .bind(&unique)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Check that a message can be quoted by `user_id` in `room_id`: it must be in the same room,
+/// not deleted, and visible to the quoter. Returns its preview.
+async fn require_quotable(
+    conn: &mut PgConnection,
+    room_id: Uuid,
+    user_id: Uuid,
+    quoted_id: Uuid,
+) -> AppResult<QuotedMessageResponse> {
+    let quoted = sqlx::query_as::<_, QuotedMessage>(
+        r#"
+        SELECT m.id, m.user_id, m.content, m.created_at, u.display_name AS user_display_name
+        FROM chatmessages m
+        JOIN users u ON u.id = m.user_id
+        WHERE m.id = $1 AND m.room_id = $2 AND m.is_deleted = false AND m.is_hidden = false
+          AND (m.is_shadowed = false OR m.user_id = $3)
+        "#,
+    )
+    .bind(quoted_id)
+    .bind(room_id)
+    .bind(user_id)
+    .fetch_optional(conn)
+    .await?
+    .ok_or_else(|| AppError::Validation("Quoted message must be a message in this room".into()))?;
+
+    Ok(QuotedMessageResponse::from(quoted))
+}
+
+/// Load previews of the given quoted messages, keyed by message ID. Deleted, hidden, and
+/// shadowed messages are left out so their quotes render as `null`.
+async fn load_quotes<'e>(
+    executor: impl PgExecutor<'e>,
+    message_ids: &[Uuid],
+) -> AppResult<HashMap<Uuid, QuotedMessageResponse>> {
+    if message_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let rows = sqlx::query_as::<_, QuotedMessage>(
+        r#"
+        SELECT m.id, m.user_id, m.content, m.created_at, u.display_name AS user_display_name
+        FROM chatmessages m
+        JOIN users u ON u.id = m.user_id
+        WHERE m.id = ANY($1)
+          AND m.is_deleted = false AND m.is_hidden = false AND m.is_shadowed = false
+        "#,
+    )
+    .bind(message_ids)
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|q| (q.id, QuotedMessageResponse::from(q)))
+        .collect())
+}
+
+/// Load attachments for the given messages, keyed by message ID and kept in attachment order.
+async fn load_attachments<'e>(
+    executor: impl PgExecutor<'e>,
+    message_ids: &[Uuid],
+) -> AppResult<HashMap<Uuid, Vec<AttachmentResponse>>> {
+    let mut by_message: HashMap<Uuid, Vec<AttachmentResponse>> = HashMap::new();
+    if message_ids.is_empty() {
+        return Ok(by_message);
+    }
+
+    let rows = sqlx::query_as::<_, MessageAttachment>(
+        r#"
+        SELECT a.message_id, f.id AS file_id, f.file_name, f.file_url, f.file_size, f.mime_type
+        FROM message_attachments a
+        JOIN room_files f ON f.id = a.file_id
+        WHERE a.message_id = ANY($1)
+        ORDER BY a.message_id, a.position
+        "#,
+    )
+    .bind(message_ids)
+    .fetch_all(executor)
+    .await?;
+
+    for row in rows {
+        by_message
+            .entry(row.message_id)
+            .or_default()
+            .push(AttachmentResponse::from(row));
+    }
+
+    Ok(by_message)
+}
+
+/// Render message content per the room's sanitization settings; `None` when disabled.
+async fn render_for_room(
+    conn: &mut PgConnection,
+    room_id: Uuid,
+    content: &str,
+) -> AppResult<Option<String>> {
+    let (sanitize, linkify): (bool, bool) =
+        sqlx::query_as("SELECT sanitize_content, linkify_urls FROM rooms WHERE id = $1")
+            .bind(room_id)
+            .fetch_optional(conn)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Room not found".into()))?;
+
+    Ok(sanitize.then(|| render_safe(content, linkify)))
+}
@@ -1,5 +1,9 @@
+pub mod admin;
 pub mod alerts;
+pub mod analytics;
 pub mod auth;
+pub mod docs;
+pub mod drafts;
 pub mod health;
 pub mod integrations;
 pub mod livekit;
@@ -9,9 +13,53 @@ pub mod moderation;
 pub mod notifications;
 pub mod polls;
 pub mod private_chats;
+pub mod room_hooks;
 pub mod rooms;
 pub mod storage;
+pub mod stream;
 pub mod tenants;
 pub mod themes;
 pub mod users;
+pub mod webhooks;
 pub mod ws;
+
+use axum::{
+    http::{header, HeaderName, StatusCode},
+    Json,
+};
+
+/// A 201 response whose `Location` header points at the created resource.
+pub type Created<T> = (StatusCode, [(HeaderName, String); 1], Json<T>);
+
+/// Build a [`Created`] response for the resource at `location` (an absolute path on this API).
+pub fn created<T>(location: String, body: T) -> Created<T> {
+    (
+        StatusCode::CREATED,
+        [(header::LOCATION, location)],
+        Json(body),
+    )
+}
+
+/// Escape `%`, `_`, and `\` so user input matches literally in a `LIKE`/`ILIKE` pattern
+/// with `ESCAPE '\'`.
+pub fn escape_like(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+    for c in input.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_like_escapes_wildcards_and_the_escape_character() {
+        assert_eq!(escape_like("100%_done\\"), "100\\%\\_done\\\\");
+        assert_eq!(escape_like("plain name"), "plain name");
+    }
+}
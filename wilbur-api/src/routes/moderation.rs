@@ -1,34 +1,52 @@
 use std::sync::Arc;
 
 use axum::{
-    extract::{Json, Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     routing::{get, post},
     Router,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use sqlx::{Acquire, PgConnection, PgExecutor};
 use uuid::Uuid;
 
 use crate::{
     error::{AppError, AppResult},
     extractors::{
         auth::AuthUser,
-        room_access::{require_room_member, require_room_moderator},
+        json::Json,
+        pagination::PaginationParams,
+        room_access::{require_room_member, require_room_moderator, RoomMember},
     },
-    models::moderation::{
-        BannedUser, BannedUserResponse, ModerationLog, ModerationLogResponse, ReportedContent,
-        ReportedContentResponse,
+    i18n::Message,
+    models::{
+        membership::{MemberRole, RoomMembership},
+        moderation::{
+            BanStatusFilter, BannedUser, BannedUserListQuery, BannedUserListing,
+            BannedUserListingResponse, BannedUserResponse, ModerationLog, ModerationLogResponse,
+            ReportContentType, ReportStatus, ReportedContent, ReportedContentResponse,
+        },
+        notification::NotificationData,
     },
+    routes::escape_like,
+    services::notifier::{self, NewNotification},
     state::AppState,
+    ws::{channels::Channel, outbox},
 };
 
+/// Most actions accepted by one bulk moderation request.
+const MAX_BULK_ACTIONS: usize = 100;
+
 pub fn router() -> Router<Arc<AppState>> {
     Router::new()
         .route("/ban", post(ban_user))
         .route("/unban", post(unban_user))
         .route("/kick", post(kick_user))
         .route("/mute", post(mute_user))
+        .route("/bulk", post(bulk_moderate))
+        .route("/shadow-ban", post(shadow_ban_user))
+        .route("/unshadow-ban", post(unshadow_ban_user))
         .route("/log/{room_id}", get(get_moderation_log))
         .route("/banned/{room_id}", get(get_banned_users))
         .route("/report", post(create_report))
@@ -63,6 +81,50 @@ struct MuteRequest {
     duration_secs: Option<i64>,
 }
 
+#[derive(Debug, Deserialize)]
+struct ShadowBanRequest {
+    user_id: Uuid,
+    room_id: Uuid,
+    reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResolveReportRequest {
+    /// Defaults to `reviewed`.
+    status: Option<ReportStatus>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum BulkActionKind {
+    Ban,
+    Kick,
+    Mute,
+}
+
+#[derive(Debug, Deserialize)]
+struct BulkAction {
+    action: BulkActionKind,
+    user_id: Uuid,
+    reason: Option<String>,
+    duration_secs: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BulkModerationRequest {
+    room_id: Uuid,
+    actions: Vec<BulkAction>,
+}
+
+#[derive(Debug, Serialize)]
+struct BulkActionResult {
+    user_id: Uuid,
+    action: BulkActionKind,
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 struct ReportRequest {
     room_id: Uuid,
@@ -71,21 +133,93 @@ struct ReportRequest {
     message_id: Option<Uuid>,
 }
 
-/// POST /ban -- ban a user from a room.
-/// Uses a transaction: INSERT into banned_users + UPDATE room_memberships + INSERT into moderation_log.
-async fn ban_user(
-    State(state): State<Arc<AppState>>,
-    auth_user: AuthUser,
-    Json(body): Json<BanRequest>,
-) -> AppResult<Json<Value>> {
-    // Only host or moderator can ban users
-    require_room_moderator(&state.pool, auth_user.id, body.room_id).await?;
+/// Queue a mod-action notification for the target user as part of the caller's transaction.
+async fn notify_mod_action(
+    conn: &mut PgConnection,
+    target_user_id: Uuid,
+    room_id: Uuid,
+    moderator_id: Uuid,
+    action: &str,
+    body: String,
+) -> AppResult<()> {
+    let room_name: Option<String> = sqlx::query_scalar("SELECT name FROM rooms WHERE id = $1")
+        .bind(room_id)
+        .fetch_optional(&mut *conn)
+        .await?;
 
-    let mut tx = state.pool.begin().await?;
+    notifier::notify(
+        conn,
+        NewNotification {
+            user_id: target_user_id,
+            title: format!(
+                "Moderator action in {}",
+                room_name.as_deref().unwrap_or("a room")
+            ),
+            body,
+            data: NotificationData::ModAction {
+                room_id,
+                moderator_id,
+                action: action.to_string(),
+            },
+            group_key: None,
+        },
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Enforce the moderation hierarchy for `actor` acting on `target_user_id`: nobody can
+/// target themselves, the host can't be moderated at all, and only the host can act on
+/// moderators. Returns the target's role, or `None` if they aren't a member.
+pub(crate) async fn ensure_can_moderate<'e>(
+    executor: impl PgExecutor<'e>,
+    actor: &RoomMembership,
+    target_user_id: Uuid,
+) -> AppResult<Option<MemberRole>> {
+    let target_role = sqlx::query_scalar::<_, MemberRole>(
+        "SELECT role FROM room_memberships WHERE user_id = $1 AND room_id = $2",
+    )
+    .bind(target_user_id)
+    .bind(actor.room_id)
+    .fetch_optional(executor)
+    .await?;
+
+    check_hierarchy(actor.user_id, &actor.role, target_user_id, target_role)
+}
 
-    let expires_at = body
-        .duration_secs
-        .map(|secs| chrono::Utc::now() + chrono::Duration::seconds(secs));
+/// The checks of `ensure_can_moderate`, once the target's role is known.
+fn check_hierarchy(
+    actor_id: Uuid,
+    actor_role: &MemberRole,
+    target_user_id: Uuid,
+    target_role: Option<MemberRole>,
+) -> AppResult<Option<MemberRole>> {
+    if target_user_id == actor_id {
+        return Err(AppError::Forbidden("You cannot moderate yourself".into()));
+    }
+
+    match target_role {
+        Some(MemberRole::Host) => Err(AppError::Forbidden(
+            "The room host cannot be moderated".into(),
+        )),
+        Some(MemberRole::Moderator) if *actor_role != MemberRole::Host => Err(AppError::Forbidden(
+            "Only the host can moderate a moderator".into(),
+        )),
+        role => Ok(role),
+    }
+}
+
+/// Ban `user_id` from the room, log it, and notify them. Part of the caller's transaction.
+async fn apply_ban(
+    conn: &mut PgConnection,
+    moderator_id: Uuid,
+    room_id: Uuid,
+    user_id: Uuid,
+    reason: Option<&str>,
+    duration_secs: Option<i64>,
+) -> AppResult<BannedUser> {
+    let expires_at = duration_secs.map(|secs| chrono::Utc::now() + chrono::Duration::seconds(secs));
 
     // Insert into banned_users
     let ban = sqlx::query_as::<_, BannedUser>(
@@ -101,39 +235,172 @@ async fn ban_user(
         "#,
     )
     .bind(Uuid::new_v4())
-    .bind(body.room_id)
-    .bind(body.user_id)
-    .bind(auth_user.id)
-    .bind(&body.reason)
+    .bind(room_id)
+    .bind(user_id)
+    .bind(moderator_id)
+    .bind(reason)
     .bind(expires_at)
-    .fetch_one(&mut *tx)
+    .fetch_one(&mut *conn)
     .await?;
 
     // Update room_memberships status to banned
     sqlx::query(
-        "UPDATE room_memberships SET status = 'banned'::member_status, updated_at = NOW() WHERE user_id = $1 AND room_id = $2",
+        r#"
+        UPDATE room_memberships SET status = 'banned'::member_status, updated_at = NOW(), updated_by = $3
+        WHERE user_id = $1 AND room_id = $2
+        "#,
+    )
+    .bind(user_id)
+    .bind(room_id)
+    .bind(moderator_id)
+    .execute(&mut *conn)
+    .await?;
+
+    log_action(conn, room_id, moderator_id, user_id, "ban", reason).await?;
+
+    notify_mod_action(
+        conn,
+        user_id,
+        room_id,
+        moderator_id,
+        "ban",
+        match reason {
+            Some(reason) => format!("You were banned: {reason}"),
+            None => "You were banned".to_string(),
+        },
+    )
+    .await?;
+
+    Ok(ban)
+}
+
+/// Remove `user_id` from the room, log it, and notify them. Part of the caller's transaction.
+async fn apply_kick(
+    conn: &mut PgConnection,
+    moderator_id: Uuid,
+    room_id: Uuid,
+    user_id: Uuid,
+    reason: Option<&str>,
+) -> AppResult<()> {
+    // Delete from room_memberships
+    let result = sqlx::query("DELETE FROM room_memberships WHERE user_id = $1 AND room_id = $2")
+        .bind(user_id)
+        .bind(room_id)
+        .execute(&mut *conn)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound(
+            "User is not a member of this room".into(),
+        ));
+    }
+
+    log_action(conn, room_id, moderator_id, user_id, "kick", reason).await?;
+
+    notify_mod_action(
+        conn,
+        user_id,
+        room_id,
+        moderator_id,
+        "kick",
+        match reason {
+            Some(reason) => format!("You were removed from the room: {reason}"),
+            None => "You were removed from the room".to_string(),
+        },
+    )
+    .await
+}
+
+/// Mute `user_id` in the room, log it, and notify them. Part of the caller's transaction.
+async fn apply_mute(
+    conn: &mut PgConnection,
+    moderator_id: Uuid,
+    room_id: Uuid,
+    user_id: Uuid,
+    reason: Option<&str>,
+    duration_secs: Option<i64>,
+) -> AppResult<()> {
+    let details = match (duration_secs, reason) {
+        (Some(secs), Some(reason)) => Some(format!("duration_secs: {secs}; {reason}")),
+        (Some(secs), None) => Some(format!("duration_secs: {secs}")),
+        (None, reason) => reason.map(str::to_string),
+    };
+
+    log_action(
+        conn,
+        room_id,
+        moderator_id,
+        user_id,
+        "mute",
+        details.as_deref(),
     )
-    .bind(body.user_id)
-    .bind(body.room_id)
-    .execute(&mut *tx)
     .await?;
 
-    // Insert into moderation_log
+    notify_mod_action(
+        conn,
+        user_id,
+        room_id,
+        moderator_id,
+        "mute",
+        match duration_secs {
+            Some(secs) => format!("You were muted for {secs} seconds"),
+            None => "You were muted".to_string(),
+        },
+    )
+    .await
+}
+
+/// Insert a moderation_log entry.
+async fn log_action(
+    conn: &mut PgConnection,
+    room_id: Uuid,
+    moderator_id: Uuid,
+    target_user_id: Uuid,
+    action: &str,
+    details: Option<&str>,
+) -> AppResult<()> {
     sqlx::query(
         r#"
         INSERT INTO moderation_log (id, room_id, moderator_id, target_user_id, action, details, created_at)
-        VALUES ($1, $2, $3, $4, 'ban', $5, NOW())
+        VALUES ($1, $2, $3, $4, $5, $6, NOW())
         "#,
     )
     .bind(Uuid::new_v4())
-    .bind(body.room_id)
-    .bind(auth_user.id)
-    .bind(body.user_id)
-    .bind(&body.reason)
-    .execute(&mut *tx)
+    .bind(room_id)
+    .bind(moderator_id)
+    .bind(target_user_id)
+    .bind(action)
+    .bind(details)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// POST /ban -- ban a user from a room.
+/// Uses a transaction: INSERT into banned_users + UPDATE room_memberships + INSERT into moderation_log.
+async fn ban_user(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Json(body): Json<BanRequest>,
+) -> AppResult<Json<Value>> {
+    // Only host or moderator can ban users
+    let actor = require_room_moderator(&state, auth_user.id, body.room_id).await?;
+    ensure_can_moderate(&state.pool, &actor, body.user_id).await?;
+
+    let mut tx = state.pool.begin().await?;
+    let ban = apply_ban(
+        &mut tx,
+        auth_user.id,
+        body.room_id,
+        body.user_id,
+        body.reason.as_deref(),
+        body.duration_secs,
+    )
     .await?;
 
     tx.commit().await?;
+    outbox::wake(&state);
 
     let response = BannedUserResponse::from(ban);
     let response_json = serde_json::to_value(&response)
@@ -150,7 +417,7 @@ async fn unban_user(
     Json(body): Json<UnbanRequest>,
 ) -> AppResult<Json<Value>> {
     // Only host or moderator can unban users
-    require_room_moderator(&state.pool, auth_user.id, body.room_id).await?;
+    require_room_moderator(&state, auth_user.id, body.room_id).await?;
 
     let mut tx = state.pool.begin().await?;
 
@@ -167,28 +434,39 @@ async fn unban_user(
 
     // Update room_memberships status back to active
     sqlx::query(
-        "UPDATE room_memberships SET status = 'active'::member_status, updated_at = NOW() WHERE user_id = $1 AND room_id = $2",
+        r#"
+        UPDATE room_memberships SET status = 'active'::member_status, updated_at = NOW(), updated_by = $3
+        WHERE user_id = $1 AND room_id = $2
+        "#,
     )
     .bind(body.user_id)
     .bind(body.room_id)
+    .bind(auth_user.id)
     .execute(&mut *tx)
     .await?;
 
-    // Insert into moderation_log
-    sqlx::query(
-        r#"
-        INSERT INTO moderation_log (id, room_id, moderator_id, target_user_id, action, details, created_at)
-        VALUES ($1, $2, $3, $4, 'unban', NULL, NOW())
-        "#,
+    log_action(
+        &mut tx,
+        body.room_id,
+        auth_user.id,
+        body.user_id,
+        "unban",
+        None,
+    )
+    .await?;
+
+    notify_mod_action(
+        &mut tx,
+        body.user_id,
+        body.room_id,
+        auth_user.id,
+        "unban",
+        "Your ban was lifted".to_string(),
     )
-    .bind(Uuid::new_v4())
-    .bind(body.room_id)
-    .bind(auth_user.id)
-    .bind(body.user_id)
-    .execute(&mut *tx)
     .await?;
 
     tx.commit().await?;
+    outbox::wake(&state);
 
     Ok(Json(json!({
         "moderator_id": auth_user.id,
@@ -206,39 +484,21 @@ async fn kick_user(
     Json(body): Json<KickRequest>,
 ) -> AppResult<Json<Value>> {
     // Only host or moderator can kick users
-    require_room_moderator(&state.pool, auth_user.id, body.room_id).await?;
+    let actor = require_room_moderator(&state, auth_user.id, body.room_id).await?;
+    ensure_can_moderate(&state.pool, &actor, body.user_id).await?;
 
     let mut tx = state.pool.begin().await?;
-
-    // Delete from room_memberships
-    let result = sqlx::query("DELETE FROM room_memberships WHERE user_id = $1 AND room_id = $2")
-        .bind(body.user_id)
-        .bind(body.room_id)
-        .execute(&mut *tx)
-        .await?;
-
-    if result.rows_affected() == 0 {
-        return Err(AppError::NotFound(
-            "User is not a member of this room".into(),
-        ));
-    }
-
-    // Insert into moderation_log
-    sqlx::query(
-        r#"
-        INSERT INTO moderation_log (id, room_id, moderator_id, target_user_id, action, details, created_at)
-        VALUES ($1, $2, $3, $4, 'kick', $5, NOW())
-        "#,
+    apply_kick(
+        &mut tx,
+        auth_user.id,
+        body.room_id,
+        body.user_id,
+        body.reason.as_deref(),
     )
-    .bind(Uuid::new_v4())
-    .bind(body.room_id)
-    .bind(auth_user.id)
-    .bind(body.user_id)
-    .bind(&body.reason)
-    .execute(&mut *tx)
     .await?;
 
     tx.commit().await?;
+    outbox::wake(&state);
 
     Ok(Json(json!({
         "moderator_id": auth_user.id,
@@ -257,24 +517,23 @@ async fn mute_user(
     Json(body): Json<MuteRequest>,
 ) -> AppResult<Json<Value>> {
     // Only host or moderator can mute users
-    require_room_moderator(&state.pool, auth_user.id, body.room_id).await?;
-
-    let details = body.duration_secs.map(|s| format!("duration_secs: {}", s));
+    let actor = require_room_moderator(&state, auth_user.id, body.room_id).await?;
+    ensure_can_moderate(&state.pool, &actor, body.user_id).await?;
 
-    sqlx::query(
-        r#"
-        INSERT INTO moderation_log (id, room_id, moderator_id, target_user_id, action, details, created_at)
-        VALUES ($1, $2, $3, $4, 'mute', $5, NOW())
-        "#,
+    let mut tx = state.pool.begin().await?;
+    apply_mute(
+        &mut tx,
+        auth_user.id,
+        body.room_id,
+        body.user_id,
+        None,
+        body.duration_secs,
     )
-    .bind(Uuid::new_v4())
-    .bind(body.room_id)
-    .bind(auth_user.id)
-    .bind(body.user_id)
-    .bind(&details)
-    .execute(&state.pool)
     .await?;
 
+    tx.commit().await?;
+    outbox::wake(&state);
+
     Ok(Json(json!({
         "moderator_id": auth_user.id,
         "user_id": body.user_id,
@@ -284,25 +543,261 @@ async fn mute_user(
     })))
 }
 
-/// GET /log/{room_id} -- get the moderation log for a room.
-async fn get_moderation_log(
+/// POST /shadow-ban -- hide a member's future messages from everyone but them and moderators.
+/// The target is deliberately not notified.
+async fn shadow_ban_user(
     State(state): State<Arc<AppState>>,
     auth_user: AuthUser,
+    Json(body): Json<ShadowBanRequest>,
+) -> AppResult<Json<Value>> {
+    // Only host or moderator can shadow-ban users
+    let actor = require_room_moderator(&state, auth_user.id, body.room_id).await?;
+    ensure_can_moderate(&state.pool, &actor, body.user_id).await?;
+
+    set_shadow_ban(&state, auth_user.id, &body, true).await?;
+
+    Ok(Json(json!({
+        "moderator_id": auth_user.id,
+        "user_id": body.user_id,
+        "room_id": body.room_id,
+        "shadow_banned": true
+    })))
+}
+
+/// POST /unshadow-ban -- lift a shadow ban. Messages sent while it was active stay hidden.
+async fn unshadow_ban_user(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Json(body): Json<ShadowBanRequest>,
+) -> AppResult<Json<Value>> {
+    // Only host or moderator can lift shadow bans
+    require_room_moderator(&state, auth_user.id, body.room_id).await?;
+
+    set_shadow_ban(&state, auth_user.id, &body, false).await?;
+
+    Ok(Json(json!({
+        "moderator_id": auth_user.id,
+        "user_id": body.user_id,
+        "room_id": body.room_id,
+        "shadow_banned": false
+    })))
+}
+
+/// Flip the membership's shadow-ban flag and log the change.
+async fn set_shadow_ban(
+    state: &AppState,
+    moderator_id: Uuid,
+    body: &ShadowBanRequest,
+    shadow_banned: bool,
+) -> AppResult<()> {
+    let mut tx = state.pool.begin().await?;
+
+    let result = sqlx::query(
+        r#"
+        UPDATE room_memberships SET shadow_banned = $1, updated_at = NOW(), updated_by = $4
+        WHERE user_id = $2 AND room_id = $3
+        "#,
+    )
+    .bind(shadow_banned)
+    .bind(body.user_id)
+    .bind(body.room_id)
+    .bind(moderator_id)
+    .execute(&mut *tx)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound(
+            "User is not a member of this room".into(),
+        ));
+    }
+
+    let action = if shadow_banned {
+        "shadow_ban"
+    } else {
+        "unshadow_ban"
+    };
+    log_action(
+        &mut tx,
+        body.room_id,
+        moderator_id,
+        body.user_id,
+        action,
+        body.reason.as_deref(),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// POST /bulk -- apply many ban/kick/mute actions in one room in a single transaction.
+///
+/// Each action runs in its own savepoint, so a failing target (not a member, or the caller
+/// themselves) is reported without undoing the others. One summarized `moderation_bulk`
+/// event is broadcast to the room instead of one per target.
+async fn bulk_moderate(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Json(body): Json<BulkModerationRequest>,
+) -> AppResult<Json<Value>> {
+    if body.actions.is_empty() || body.actions.len() > MAX_BULK_ACTIONS {
+        return Err(AppError::BadRequest(
+            format!("Provide between 1 and {MAX_BULK_ACTIONS} actions").into(),
+        ));
+    }
+
+    // Only host or moderator can moderate, checked once for the whole batch
+    let actor = require_room_moderator(&state, auth_user.id, body.room_id).await?;
+
+    let mut tx = state.pool.begin().await?;
+    let mut results = Vec::with_capacity(body.actions.len());
+
+    for action in &body.actions {
+        let mut savepoint = tx.begin().await?;
+        let outcome = apply_bulk_action(&mut savepoint, &actor, action).await;
+
+        let error = match outcome {
+            Ok(()) => {
+                savepoint.commit().await?;
+                None
+            }
+            Err(e) => {
+                savepoint.rollback().await?;
+                Some(match e {
+                    AppError::NotFound(msg)
+                    | AppError::BadRequest(msg)
+                    | AppError::Forbidden(msg) => msg.localized(),
+                    other => {
+                        tracing::error!(user_id = %action.user_id, "Bulk moderation action failed: {other}");
+                        Message::new("Action failed").localized()
+                    }
+                })
+            }
+        };
+
+        results.push(BulkActionResult {
+            user_id: action.user_id,
+            action: action.action,
+            success: error.is_none(),
+            error,
+        });
+    }
+
+    let succeeded: Vec<&BulkActionResult> = results.iter().filter(|r| r.success).collect();
+    if !succeeded.is_empty() {
+        let count = |kind: BulkActionKind| succeeded.iter().filter(|r| r.action == kind).count();
+        outbox::enqueue(
+            &mut tx,
+            &Channel::room_chat(body.room_id),
+            "moderation_bulk",
+            &json!({
+                "room_id": body.room_id,
+                "moderator_id": auth_user.id,
+                "banned": count(BulkActionKind::Ban),
+                "kicked": count(BulkActionKind::Kick),
+                "muted": count(BulkActionKind::Mute),
+                "user_ids": succeeded.iter().map(|r| r.user_id).collect::<Vec<_>>(),
+            }),
+        )
+        .await?;
+    }
+
+    tx.commit().await?;
+    outbox::wake(&state);
+
+    let failed = results.len() - succeeded.len();
+    Ok(Json(json!({
+        "room_id": body.room_id,
+        "succeeded": succeeded.len(),
+        "failed": failed,
+        "results": results,
+    })))
+}
+
+/// Validate one bulk target and apply its action.
+async fn apply_bulk_action(
+    conn: &mut PgConnection,
+    actor: &RoomMembership,
+    action: &BulkAction,
+) -> AppResult<()> {
+    let (moderator_id, room_id) = (actor.user_id, actor.room_id);
+
+    if ensure_can_moderate(&mut *conn, actor, action.user_id)
+        .await?
+        .is_none()
+    {
+        return Err(AppError::NotFound(
+            "User is not a member of this room".into(),
+        ));
+    }
+
+    let reason = action.reason.as_deref();
+    match action.action {
+        BulkActionKind::Ban => apply_ban(
+            conn,
+            moderator_id,
+            room_id,
+            action.user_id,
+            reason,
+            action.duration_secs,
+        )
+        .await
+        .map(|_| ()),
+        BulkActionKind::Kick => {
+            apply_kick(conn, moderator_id, room_id, action.user_id, reason).await
+        }
+        BulkActionKind::Mute => {
+            apply_mute(
+                conn,
+                moderator_id,
+                room_id,
+                action.user_id,
+                reason,
+                action.duration_secs,
+            )
+            .await
+        }
+    }
+}
+
+/// GET /log/{room_id} -- the moderation log for a room, newest first (paginated).
+async fn get_moderation_log(
+    State(state): State<Arc<AppState>>,
+    RoomMember(membership): RoomMember,
     Path(room_id): Path<Uuid>,
+    pagination: PaginationParams,
 ) -> AppResult<Json<Value>> {
-    // Verify the user is a member of the room
-    require_room_member(&state.pool, auth_user.id, room_id).await?;
+    let is_moderator = matches!(membership.role, MemberRole::Host | MemberRole::Moderator);
+
+    // Shadow bans only work if their targets can't find them in the log
+    let total: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*)
+        FROM moderation_log
+        WHERE room_id = $1
+          AND ($2 OR action NOT IN ('shadow_ban', 'unshadow_ban'))
+        "#,
+    )
+    .bind(room_id)
+    .bind(is_moderator)
+    .fetch_one(&state.pool)
+    .await?;
 
     let entries = sqlx::query_as::<_, ModerationLog>(
         r#"
         SELECT id, room_id, moderator_id, target_user_id, action, details, created_at
         FROM moderation_log
         WHERE room_id = $1
-        ORDER BY created_at DESC
-        LIMIT 100
+          AND ($2 OR action NOT IN ('shadow_ban', 'unshadow_ban'))
+        ORDER BY created_at DESC, id
+        LIMIT $3 OFFSET $4
         "#,
     )
     .bind(room_id)
+    .bind(is_moderator)
+    .bind(pagination.limit())
+    .bind(pagination.offset())
     .fetch_all(&state.pool)
     .await?;
 
@@ -313,68 +808,202 @@ async fn get_moderation_log(
 
     Ok(Json(json!({
         "room_id": room_id,
+        "page": pagination.page,
+        "per_page": pagination.per_page(),
+        "total": total,
         "entries": data
     })))
 }
 
-/// GET /banned/{room_id} -- get all banned users for a room.
+/// GET /banned/{room_id} -- banned users for a room, newest first (paginated), optionally
+/// only active or expired bans and searched by the banned user's display name.
 async fn get_banned_users(
     State(state): State<Arc<AppState>>,
-    auth_user: AuthUser,
+    _member: RoomMember,
     Path(room_id): Path<Uuid>,
+    pagination: PaginationParams,
+    Query(query): Query<BannedUserListQuery>,
 ) -> AppResult<Json<Value>> {
-    // Verify the user is a member of the room
-    require_room_member(&state.pool, auth_user.id, room_id).await?;
+    let (active_only, expired_only) = match query.status {
+        BanStatusFilter::All => (false, false),
+        BanStatusFilter::Active => (true, false),
+        BanStatusFilter::Expired => (false, true),
+    };
+    let pattern = query
+        .q
+        .as_deref()
+        .map(str::trim)
+        .filter(|q| !q.is_empty())
+        .map(|q| format!("%{}%", escape_like(q)));
 
-    let bans = sqlx::query_as::<_, BannedUser>(
+    let total: i64 = sqlx::query_scalar(
         r#"
-        SELECT id, room_id, user_id, banned_by, reason, expires_at, created_at
-        FROM banned_users
-        WHERE room_id = $1
+        SELECT COUNT(*)
+        FROM banned_users b
+        JOIN users u ON u.id = b.user_id
+        WHERE b.room_id = $1
+          AND (NOT $2 OR b.expires_at IS NULL OR b.expires_at > NOW())
+          AND (NOT $3 OR b.expires_at <= NOW())
+          AND ($4::TEXT IS NULL OR u.display_name ILIKE $4 ESCAPE '\')
         "#,
     )
     .bind(room_id)
+    .bind(active_only)
+    .bind(expired_only)
+    .bind(&pattern)
+    .fetch_one(&state.pool)
+    .await?;
+
+    let bans = sqlx::query_as::<_, BannedUserListing>(
+        r#"
+        SELECT b.id, b.room_id, b.user_id, b.banned_by, b.reason, b.expires_at, b.created_at,
+               u.display_name AS user_display_name,
+               m.display_name AS banned_by_display_name
+        FROM banned_users b
+        JOIN users u ON u.id = b.user_id
+        LEFT JOIN users m ON m.id = b.banned_by
+        WHERE b.room_id = $1
+          AND (NOT $2 OR b.expires_at IS NULL OR b.expires_at > NOW())
+          AND (NOT $3 OR b.expires_at <= NOW())
+          AND ($4::TEXT IS NULL OR u.display_name ILIKE $4 ESCAPE '\')
+        ORDER BY b.created_at DESC, b.id
+        LIMIT $5 OFFSET $6
+        "#,
+    )
+    .bind(room_id)
+    .bind(active_only)
+    .bind(expired_only)
+    .bind(&pattern)
+    .bind(pagination.limit())
+    .bind(pagination.offset())
     .fetch_all(&state.pool)
     .await?;
 
-    let data: Vec<BannedUserResponse> = bans.into_iter().map(BannedUserResponse::from).collect();
+    let data: Vec<BannedUserListingResponse> = bans
+        .into_iter()
+        .map(BannedUserListingResponse::from)
+        .collect();
 
     Ok(Json(json!({
         "room_id": room_id,
+        "page": pagination.page,
+        "per_page": pagination.per_page(),
+        "total": total,
         "banned_users": data
     })))
 }
 
 /// POST /report -- report a user or message.
+///
+/// Reports are deduplicated per piece of content: while a report is open, further reports
+/// add their reporter to it and bump `report_count` instead of creating new rows. When a
+/// message reaches the room's `report_auto_hide_threshold` of distinct reporters it is
+/// hidden pending review and the room's moderators are notified.
 async fn create_report(
     State(state): State<Arc<AppState>>,
     auth_user: AuthUser,
     Json(body): Json<ReportRequest>,
 ) -> AppResult<(StatusCode, Json<Value>)> {
-    let report_id = Uuid::new_v4();
-
-    // Determine content_type and content_id based on whether message_id is provided
+    // A message report targets the message; otherwise the report is about the user
     let (content_type, content_id) = match body.message_id {
-        Some(msg_id) => ("message".to_string(), msg_id),
-        None => ("user".to_string(), body.reported_user_id),
+        Some(msg_id) => (ReportContentType::Message, msg_id),
+        None => (ReportContentType::User, body.reported_user_id),
     };
 
-    let report = sqlx::query_as::<_, ReportedContent>(
+    let reported_user_id = match content_type {
+        ReportContentType::Message => sqlx::query_scalar::<_, Uuid>(
+            "SELECT user_id FROM chatmessages WHERE id = $1 AND room_id = $2 AND is_deleted = false",
+        )
+        .bind(content_id)
+        .bind(body.room_id)
+        .fetch_optional(&state.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Message not found in this room".into()))?,
+        ReportContentType::User => {
+            let is_member = sqlx::query_scalar::<_, bool>(
+                "SELECT EXISTS(SELECT 1 FROM room_memberships WHERE user_id = $1 AND room_id = $2)",
+            )
+            .bind(content_id)
+            .bind(body.room_id)
+            .fetch_one(&state.pool)
+            .await?;
+            if !is_member {
+                return Err(AppError::NotFound(
+                    "User is not a member of this room".into(),
+                ));
+            }
+            content_id
+        }
+    };
+    if reported_user_id == auth_user.id {
+        return Err(AppError::BadRequest("You cannot report yourself".into()));
+    }
+
+    let mut tx = state.pool.begin().await?;
+
+    // Find or open the report for this content
+    let report_id = sqlx::query_scalar::<_, Uuid>(
         r#"
-        INSERT INTO reported_content (id, room_id, reporter_id, content_type, content_id, reason, status, created_at)
-        VALUES ($1, $2, $3, $4, $5, $6, 'pending'::report_status, NOW())
-        RETURNING id, room_id, reporter_id, content_type, content_id, reason, status, reviewed_by, created_at
+        INSERT INTO reported_content (id, room_id, reporter_id, content_type, content_id, reason, status, report_count, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6, 'pending'::report_status, 0, NOW())
+        ON CONFLICT (room_id, content_type, content_id) WHERE status = 'pending'
+            DO UPDATE SET room_id = EXCLUDED.room_id
+        RETURNING id
         "#,
     )
-    .bind(report_id)
+    .bind(Uuid::new_v4())
     .bind(body.room_id)
     .bind(auth_user.id)
-    .bind(&content_type)
+    .bind(content_type.as_str())
     .bind(content_id)
     .bind(&body.reason)
-    .fetch_one(&state.pool)
+    .fetch_one(&mut *tx)
     .await?;
 
+    let added = sqlx::query(
+        r#"
+        INSERT INTO report_reporters (report_id, reporter_id, reason, created_at)
+        VALUES ($1, $2, $3, NOW())
+        ON CONFLICT DO NOTHING
+        "#,
+    )
+    .bind(report_id)
+    .bind(auth_user.id)
+    .bind(&body.reason)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected()
+        > 0;
+
+    let report = sqlx::query_as::<_, ReportedContent>(
+        r#"
+        UPDATE reported_content
+        SET report_count = (SELECT COUNT(*) FROM report_reporters WHERE report_id = $1)
+        WHERE id = $1
+        RETURNING id, room_id, reporter_id, content_type, content_id, reason, status, reviewed_by, report_count, created_at
+        "#,
+    )
+    .bind(report_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    if added && report.content_type == ReportContentType::Message.as_str() {
+        let threshold: Option<i32> =
+            sqlx::query_scalar("SELECT report_auto_hide_threshold FROM rooms WHERE id = $1")
+                .bind(report.room_id)
+                .fetch_optional(&mut *tx)
+                .await?
+                .flatten();
+
+        // Fire once, on the report that crosses the threshold
+        if threshold == Some(report.report_count) {
+            auto_hide_reported_message(&mut tx, &report).await?;
+        }
+    }
+
+    tx.commit().await?;
+    outbox::wake(&state);
+
     let response = ReportedContentResponse::from(report);
     let response_json = serde_json::to_value(&response)
         .map_err(|e| AppError::Internal(format!("Serialization error: {e}")))?;
@@ -382,44 +1011,255 @@ async fn create_report(
     Ok((StatusCode::CREATED, Json(response_json)))
 }
 
-/// POST /report/{id}/resolve -- resolve a report.
+/// Hide a reported message pending review and notify the room's moderators.
+async fn auto_hide_reported_message(
+    conn: &mut PgConnection,
+    report: &ReportedContent,
+) -> AppResult<()> {
+    let hidden = sqlx::query(
+        "UPDATE chatmessages SET is_hidden = true, updated_at = NOW() WHERE id = $1 AND room_id = $2 AND is_hidden = false",
+    )
+    .bind(report.content_id)
+    .bind(report.room_id)
+    .execute(&mut *conn)
+    .await?
+    .rows_affected()
+        > 0;
+
+    if hidden {
+        outbox::enqueue(
+            conn,
+            &Channel::room_chat(report.room_id),
+            "message_hidden",
+            &json!({ "id": report.content_id, "room_id": report.room_id }),
+        )
+        .await?;
+    }
+
+    let moderators = sqlx::query_scalar::<_, Uuid>(
+        r#"
+        SELECT user_id FROM room_memberships
+        WHERE room_id = $1 AND role IN ('host', 'moderator') AND status = 'active'
+        "#,
+    )
+    .bind(report.room_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    for moderator_id in moderators {
+        notifier::notify(
+            conn,
+            NewNotification {
+                user_id: moderator_id,
+                title: "Message hidden after repeated reports".to_string(),
+                body: format!(
+                    "{} members reported a message; it is hidden until reviewed",
+                    report.report_count
+                ),
+                data: NotificationData::ReportThreshold {
+                    room_id: report.room_id,
+                    report_id: report.id,
+                    content_type: report.content_type.clone(),
+                    content_id: report.content_id,
+                    report_count: report.report_count,
+                },
+                group_key: Some(report.id.to_string()),
+            },
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Whether a caller may resolve a report, given their active role in the report's room
+/// (`None` if they have none there): global admins always, hosts and moderators of that room.
+fn check_report_resolver(is_admin: bool, role_in_room: Option<&MemberRole>) -> AppResult<()> {
+    match role_in_room {
+        _ if is_admin => Ok(()),
+        Some(MemberRole::Host | MemberRole::Moderator) => Ok(()),
+        _ => Err(AppError::Forbidden(
+            "Only an admin or the room's hosts and moderators can resolve this report".into(),
+        )),
+    }
+}
+
+/// POST /report/{id}/resolve -- resolve a report. Global admins may resolve any report;
+/// a room's hosts and moderators only those filed in their room.
 async fn resolve_report(
     State(state): State<Arc<AppState>>,
     auth_user: AuthUser,
     Path(id): Path<Uuid>,
-    Json(body): Json<Value>,
+    Json(body): Json<ResolveReportRequest>,
 ) -> AppResult<Json<Value>> {
-    // Only admin can resolve reports
-    if auth_user.role != "admin" {
-        return Err(AppError::Forbidden(
-            "Only admins can resolve reports".into(),
+    let is_admin = auth_user.role == "admin";
+    let role_in_room = if is_admin {
+        None
+    } else {
+        let room_id =
+            sqlx::query_scalar::<_, Uuid>("SELECT room_id FROM reported_content WHERE id = $1")
+                .bind(id)
+                .fetch_optional(&state.pool)
+                .await?
+                .ok_or_else(|| AppError::NotFound("Report not found".into()))?;
+        match require_room_member(&state, auth_user.id, room_id).await {
+            Ok(membership) => Some(membership.role),
+            Err(AppError::Forbidden(_)) => None,
+            Err(e) => return Err(e),
+        }
+    };
+    check_report_resolver(is_admin, role_in_room.as_ref())?;
+
+    let status = body.status.unwrap_or(ReportStatus::Reviewed);
+    if status == ReportStatus::Pending {
+        return Err(AppError::BadRequest(
+            "A report can only be resolved as reviewed or dismissed".into(),
         ));
     }
 
-    let status_str = body
-        .get("status")
-        .and_then(|s| s.as_str())
-        .unwrap_or("reviewed");
+    let mut tx = state.pool.begin().await?;
 
     let report = sqlx::query_as::<_, ReportedContent>(
         r#"
         UPDATE reported_content
-        SET status = $1::report_status,
+        SET status = $1,
             reviewed_by = $2
         WHERE id = $3
-        RETURNING id, room_id, reporter_id, content_type, content_id, reason, status, reviewed_by, created_at
+        RETURNING id, room_id, reporter_id, content_type, content_id, reason, status, reviewed_by, report_count, created_at
         "#,
     )
-    .bind(status_str)
+    .bind(&status)
     .bind(auth_user.id)
     .bind(id)
-    .fetch_optional(&state.pool)
+    .fetch_optional(&mut *tx)
     .await?
     .ok_or_else(|| AppError::NotFound("Report not found".into()))?;
 
+    // A dismissed report releases a message that was auto-hidden pending review
+    if report.status == ReportStatus::Dismissed
+        && report.content_type == ReportContentType::Message.as_str()
+    {
+        let restored = sqlx::query(
+            "UPDATE chatmessages SET is_hidden = false, updated_at = NOW() WHERE id = $1 AND room_id = $2 AND is_hidden = true",
+        )
+        .bind(report.content_id)
+        .bind(report.room_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected()
+            > 0;
+
+        if restored {
+            outbox::enqueue(
+                &mut tx,
+                &Channel::room_chat(report.room_id),
+                "message_unhidden",
+                &json!({ "id": report.content_id, "room_id": report.room_id }),
+            )
+            .await?;
+        }
+    }
+
+    tx.commit().await?;
+    outbox::wake(&state);
+
     let response = ReportedContentResponse::from(report);
     let response_json = serde_json::to_value(&response)
         .map_err(|e| AppError::Internal(format!("Serialization error: {e}")))?;
 
     Ok(Json(response_json))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_forbidden(result: AppResult<Option<MemberRole>>) -> bool {
+        matches!(result, Err(AppError::Forbidden(_)))
+    }
+
+    #[test]
+    fn nobody_can_moderate_themselves() {
+        let id = Uuid::new_v4();
+        for role in [MemberRole::Host, MemberRole::Moderator] {
+            assert!(is_forbidden(check_hierarchy(
+                id,
+                &role,
+                id,
+                Some(role.clone())
+            )));
+        }
+    }
+
+    #[test]
+    fn moderator_cannot_moderate_another_moderator() {
+        let result = check_hierarchy(
+            Uuid::new_v4(),
+            &MemberRole::Moderator,
+            Uuid::new_v4(),
+            Some(MemberRole::Moderator),
+        );
+        assert!(is_forbidden(result));
+    }
+
+    #[test]
+    fn host_cannot_be_moderated() {
+        for actor_role in [MemberRole::Host, MemberRole::Moderator] {
+            let result = check_hierarchy(
+                Uuid::new_v4(),
+                &actor_role,
+                Uuid::new_v4(),
+                Some(MemberRole::Host),
+            );
+            assert!(is_forbidden(result));
+        }
+    }
+
+    #[test]
+    fn host_can_moderate_a_moderator() {
+        let result = check_hierarchy(
+            Uuid::new_v4(),
+            &MemberRole::Host,
+            Uuid::new_v4(),
+            Some(MemberRole::Moderator),
+        );
+        assert_eq!(result.unwrap(), Some(MemberRole::Moderator));
+    }
+
+    #[test]
+    fn moderator_of_another_room_cannot_resolve_a_report() {
+        // Their moderator role is in a different room; in the report's room they have none
+        assert!(matches!(
+            check_report_resolver(false, None),
+            Err(AppError::Forbidden(_))
+        ));
+        assert!(matches!(
+            check_report_resolver(false, Some(&MemberRole::Member)),
+            Err(AppError::Forbidden(_))
+        ));
+    }
+
+    #[test]
+    fn moderators_of_the_report_room_can_resolve_it() {
+        assert!(check_report_resolver(false, Some(&MemberRole::Moderator)).is_ok());
+        assert!(check_report_resolver(false, Some(&MemberRole::Host)).is_ok());
+    }
+
+    #[test]
+    fn admins_can_resolve_any_report() {
+        assert!(check_report_resolver(true, None).is_ok());
+    }
+
+    #[test]
+    fn moderator_can_moderate_members_and_non_members() {
+        for target_role in [Some(MemberRole::Member), None] {
+            let result = check_hierarchy(
+                Uuid::new_v4(),
+                &MemberRole::Moderator,
+                Uuid::new_v4(),
+                target_role.clone(),
+            );
+            assert_eq!(result.unwrap(), target_role);
+        }
+    }
+}
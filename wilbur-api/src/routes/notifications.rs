@@ -1,58 +1,159 @@
 use std::sync::Arc;
 
 use axum::{
-    extract::{Json, Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     routing::{delete, get, post},
     Router,
 };
 use serde_json::{json, Value};
+use sqlx::{Postgres, QueryBuilder};
 use uuid::Uuid;
+use validator::Validate;
 
 use crate::{
     error::{AppError, AppResult},
-    extractors::auth::AuthUser,
-    models::notification::{Notification, NotificationResponse},
+    extractors::{auth::AuthUser, json::Json, pagination::PaginationParams, sync::SyncFilter},
+    models::notification::{
+        BulkDeleteQuery, DeleteNotificationsRequest, MarkReadRequest, Notification,
+        NotificationListQuery, NotificationResponse,
+    },
     state::AppState,
 };
 
 pub fn router() -> Router<Arc<AppState>> {
     Router::new()
         .route("/", get(list_notifications))
+        .route("/", delete(delete_all_notifications))
+        .route("/delete", post(delete_selected_notifications))
+        .route("/read", post(mark_selected_read))
         .route("/read-all", post(read_all_notifications))
         .route("/{id}/read", post(mark_read))
         .route("/{id}", delete(delete_notification))
 }
 
-/// GET / -- list notifications for the authenticated user.
+/// GET / -- list notifications for the authenticated user, optionally filtered.
+/// With `updated_after`, returns changes since then instead, deletions included.
 async fn list_notifications(
     State(state): State<Arc<AppState>>,
     auth_user: AuthUser,
+    pagination: PaginationParams,
+    Query(filter): Query<NotificationListQuery>,
+    Query(sync): Query<SyncFilter>,
 ) -> AppResult<Json<Value>> {
-    let notifications = sqlx::query_as::<_, Notification>(
+    let mut query = QueryBuilder::<Postgres>::new(
         r#"
-        SELECT id, user_id, title, body, notification_type, is_read, data, created_at
+        SELECT id, user_id, title, body, notification_type, is_read, data, group_key, count, created_at, updated_at, deleted_at
         FROM notifications
-        WHERE user_id = $1
-        ORDER BY created_at DESC
-        LIMIT 50
-        "#,
-    )
-    .bind(auth_user.id)
-    .fetch_all(&state.pool)
-    .await?;
+        WHERE user_id = "#,
+    );
+    query.push_bind(auth_user.id);
+
+    // Syncing clients also get tombstones for notifications deleted since their last sync
+    match sync.updated_after {
+        Some(updated_after) => {
+            query.push(" AND updated_at > ").push_bind(updated_after);
+        }
+        None => {
+            query.push(" AND deleted_at IS NULL");
+        }
+    }
+    if filter.unread_only {
+        query.push(" AND is_read = false");
+    }
+    if let Some(notification_type) = filter.notification_type {
+        query
+            .push(" AND notification_type = ")
+            .push_bind(notification_type);
+    }
+    if let Some(before) = filter.before {
+        query.push(" AND created_at < ").push_bind(before);
+    }
+
+    query
+        .push(if sync.updated_after.is_some() {
+            " ORDER BY updated_at ASC, id ASC LIMIT "
+        } else {
+            " ORDER BY created_at DESC LIMIT "
+        })
+        .push_bind(pagination.limit())
+        .push(" OFFSET ")
+        .push_bind(pagination.offset());
+
+    let notifications = query
+        .build_query_as::<Notification>()
+        .fetch_all(&state.pool)
+        .await?;
 
     let data: Vec<NotificationResponse> = notifications
         .into_iter()
-        .map(NotificationResponse::from)
+        .map(|n| {
+            let deleted = n.deleted_at.is_some();
+            let response = NotificationResponse::from(n);
+            if deleted {
+                response.into_tombstone()
+            } else {
+                response
+            }
+        })
         .collect();
 
     Ok(Json(json!({
         "user_id": auth_user.id,
+        "page": pagination.page,
+        "per_page": pagination.per_page(),
         "notifications": data
     })))
 }
 
+/// POST /read -- mark a targeted set of notifications read, by `ids` or by `type`.
+async fn mark_selected_read(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Json(body): Json<MarkReadRequest>,
+) -> AppResult<Json<Value>> {
+    body.validate()
+        .map_err(|e| AppError::Validation(e.to_string().into()))?;
+
+    let result = match (&body.ids, &body.notification_type) {
+        (Some(ids), None) => {
+            sqlx::query(
+                r#"
+                UPDATE notifications SET is_read = true
+                WHERE user_id = $1 AND id = ANY($2) AND is_read = false AND deleted_at IS NULL
+                "#,
+            )
+            .bind(auth_user.id)
+            .bind(ids)
+            .execute(&state.pool)
+            .await?
+        }
+        (None, Some(notification_type)) => {
+            sqlx::query(
+                r#"
+                UPDATE notifications SET is_read = true
+                WHERE user_id = $1 AND notification_type = $2 AND is_read = false
+                  AND deleted_at IS NULL
+                "#,
+            )
+            .bind(auth_user.id)
+            .bind(notification_type)
+            .execute(&state.pool)
+            .await?
+        }
+        _ => {
+            return Err(AppError::BadRequest(
+                "Provide exactly one of 'ids' or 'type'".into(),
+            ))
+        }
+    };
+
+    Ok(Json(json!({
+        "user_id": auth_user.id,
+        "updated_count": result.rows_affected()
+    })))
+}
+
 /// POST /{id}/read -- mark a notification as read.
 async fn mark_read(
     State(state): State<Arc<AppState>>,
@@ -60,7 +161,7 @@ async fn mark_read(
     Path(id): Path<Uuid>,
 ) -> AppResult<Json<Value>> {
     let result =
-        sqlx::query("UPDATE notifications SET is_read = true WHERE id = $1 AND user_id = $2")
+        sqlx::query("UPDATE notifications SET is_read = true WHERE id = $1 AND user_id = $2 AND deleted_at IS NULL")
             .bind(id)
             .bind(auth_user.id)
             .execute(&state.pool)
@@ -77,13 +178,15 @@ async fn mark_read(
     })))
 }
 
-/// DELETE /{id} -- delete a notification.
+/// DELETE /{id} -- delete a notification (soft-delete, leaving a tombstone for sync).
 async fn delete_notification(
     State(state): State<Arc<AppState>>,
     auth_user: AuthUser,
     Path(id): Path<Uuid>,
 ) -> AppResult<StatusCode> {
-    let result = sqlx::query("DELETE FROM notifications WHERE id = $1 AND user_id = $2")
+    let result = sqlx::query(
+        "UPDATE notifications SET deleted_at = NOW() WHERE id = $1 AND user_id = $2 AND deleted_at IS NULL",
+    )
         .bind(id)
         .bind(auth_user.id)
         .execute(&state.pool)
@@ -96,13 +199,64 @@ async fn delete_notification(
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// DELETE / -- delete the caller's read notifications, or all of them with `?all=true`
+/// (soft-delete, leaving tombstones for sync).
+async fn delete_all_notifications(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Query(query): Query<BulkDeleteQuery>,
+) -> AppResult<Json<Value>> {
+    let result = sqlx::query(
+        r#"
+        UPDATE notifications SET deleted_at = NOW()
+        WHERE user_id = $1 AND deleted_at IS NULL AND ($2 OR is_read = true)
+        "#,
+    )
+    .bind(auth_user.id)
+    .bind(query.all)
+    .execute(&state.pool)
+    .await?;
+
+    Ok(Json(json!({
+        "user_id": auth_user.id,
+        "deleted_count": result.rows_affected()
+    })))
+}
+
+/// POST /delete -- delete the caller's notifications listed in `ids`; IDs that aren't the
+/// caller's, or are already deleted, are skipped.
+async fn delete_selected_notifications(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Json(body): Json<DeleteNotificationsRequest>,
+) -> AppResult<Json<Value>> {
+    body.validate()
+        .map_err(|e| AppError::Validation(e.to_string().into()))?;
+
+    let result = sqlx::query(
+        r#"
+        UPDATE notifications SET deleted_at = NOW()
+        WHERE user_id = $1 AND id = ANY($2) AND deleted_at IS NULL
+        "#,
+    )
+    .bind(auth_user.id)
+    .bind(&body.ids)
+    .execute(&state.pool)
+    .await?;
+
+    Ok(Json(json!({
+        "user_id": auth_user.id,
+        "deleted_count": result.rows_affected()
+    })))
+}
+
 /// POST /read-all -- mark all notifications as read for the current user.
 async fn read_all_notifications(
     State(state): State<Arc<AppState>>,
     auth_user: AuthUser,
 ) -> AppResult<Json<Value>> {
     let result = sqlx::query(
-        "UPDATE notifications SET is_read = true WHERE user_id = $1 AND is_read = false",
+        "UPDATE notifications SET is_read = true WHERE user_id = $1 AND is_read = false AND deleted_at IS NULL",
     )
     .bind(auth_user.id)
     .execute(&state.pool)
@@ -1,28 +1,45 @@
+use std::collections::HashSet;
 use std::sync::Arc;
 
 use axum::{
-    extract::{Json, Path, Query, State},
+    extract::{Path, State},
     http::StatusCode,
     routing::{delete, get, post},
     Router,
 };
 use serde_json::{json, Value};
+use sqlx::PgConnection;
 use uuid::Uuid;
+use validator::Validate;
 
 use crate::{
     error::{AppError, AppResult},
-    extractors::{auth::AuthUser, pagination::PaginationParams},
-    models::poll::{CreatePollRequest, Poll, PollResponse, PollVote, VoteRequest},
+    extractors::{
+        json::Json,
+        pagination::PaginationParams,
+        room_access::{RoomMember, RoomModerator},
+    },
+    models::{
+        membership::MemberRole,
+        poll::{
+            CreatePollRequest, Poll, PollDetailResponse, PollResponse, PollResults, PollVote,
+            VoteRequest,
+        },
+    },
+    routes::{created, Created},
+    services::room_permissions::{require_capability, Capability},
     state::AppState,
-    ws::manager::WsManager,
+    ws::{channels::Channel, outbox},
 };
 
 pub fn router() -> Router<Arc<AppState>> {
     Router::new()
         .route("/", get(list_polls))
         .route("/", post(create_poll))
+        .route("/{id}", get(get_poll))
         .route("/{id}", delete(delete_poll))
         .route("/{id}/vote", post(cast_vote))
+        .route("/{id}/vote", delete(retract_vote))
         .route("/{id}/votes", get(get_votes))
         .route("/{id}/close", post(close_poll))
 }
@@ -30,9 +47,9 @@ pub fn router() -> Router<Arc<AppState>> {
 /// GET / -- list polls for a room.
 async fn list_polls(
     State(state): State<Arc<AppState>>,
-    _auth_user: AuthUser,
+    _member: RoomMember,
     Path(room_id): Path<Uuid>,
-    Query(pagination): Query<PaginationParams>,
+    pagination: PaginationParams,
 ) -> AppResult<Json<Value>> {
     let limit = pagination.limit();
     let offset = pagination.offset();
@@ -62,17 +79,48 @@ async fn list_polls(
     })))
 }
 
+/// Reject options that repeat another once surrounding whitespace is ignored.
+fn check_unique_options(options: &[String]) -> AppResult<()> {
+    let mut seen = HashSet::with_capacity(options.len());
+    match options.iter().find(|o| !seen.insert(o.trim())) {
+        Some(duplicate) => Err(AppError::Validation(
+            format!("Duplicate poll option '{duplicate}'").into(),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Reject a vote for an option the poll doesn't have.
+fn check_option_index(option_index: i32, option_count: i32) -> AppResult<()> {
+    if (0..option_count).contains(&option_index) {
+        Ok(())
+    } else {
+        Err(AppError::BadRequest(
+            format!("option_index must be between 0 and {}", option_count - 1).into(),
+        ))
+    }
+}
+
 /// POST / -- create a new poll.
 async fn create_poll(
     State(state): State<Arc<AppState>>,
-    auth_user: AuthUser,
+    RoomMember(member): RoomMember,
     Path(room_id): Path<Uuid>,
     Json(body): Json<CreatePollRequest>,
-) -> AppResult<(StatusCode, Json<Value>)> {
+) -> AppResult<Created<Value>> {
+    require_capability(&state.pool, &member, Capability::CreatePoll).await?;
+
+    body.validate()
+        .map_err(|e| AppError::Validation(e.to_string().into()))?;
+
+    check_unique_options(&body.options)?;
+
     let poll_id = Uuid::new_v4();
     let options_json = serde_json::to_value(&body.options)
         .map_err(|e| AppError::Internal(format!("Failed to serialize options: {e}")))?;
 
+    let mut tx = state.pool.begin().await?;
+
     let poll = sqlx::query_as::<_, Poll>(
         r#"
         INSERT INTO polls (id, room_id, creator_id, question, options, status, closes_at, created_at)
@@ -82,35 +130,84 @@ async fn create_poll(
     )
     .bind(poll_id)
     .bind(room_id)
-    .bind(auth_user.id)
+    .bind(member.user_id)
     .bind(&body.question)
     .bind(&options_json)
     .bind(body.closes_at)
-    .fetch_one(&state.pool)
+    .fetch_one(&mut *tx)
     .await?;
 
     let response = PollResponse::from(poll);
     let response_json = serde_json::to_value(&response)
         .map_err(|e| AppError::Internal(format!("Serialization error: {e}")))?;
 
-    let channel = format!("room:{}:polls", room_id);
-    WsManager::notify_change(&state, &channel, "poll_created", response_json.clone());
+    let channel = Channel::room_polls(room_id);
+    outbox::enqueue(&mut tx, &channel, "poll_created", &response_json).await?;
+
+    tx.commit().await?;
+    outbox::wake(&state);
+
+    Ok(created(
+        format!("/api/v1/rooms/{room_id}/polls/{poll_id}"),
+        response_json,
+    ))
+}
+
+/// GET /{id} -- a single poll with its current tallies and the caller's vote.
+async fn get_poll(
+    State(state): State<Arc<AppState>>,
+    RoomMember(member): RoomMember,
+    Path((room_id, id)): Path<(Uuid, Uuid)>,
+) -> AppResult<Json<PollDetailResponse>> {
+    let mut conn = state.pool.acquire().await?;
+
+    let poll = sqlx::query_as::<_, Poll>(
+        r#"
+        SELECT id, room_id, creator_id, question, options, status, closes_at, created_at
+        FROM polls
+        WHERE id = $1 AND room_id = $2
+        "#,
+    )
+    .bind(id)
+    .bind(room_id)
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Poll not found".into()))?;
+
+    let results = poll_results(&mut conn, room_id, id).await?;
+
+    let my_vote: Option<i32> = sqlx::query_scalar(
+        "SELECT option_index FROM poll_votes WHERE poll_id = $1 AND user_id = $2",
+    )
+    .bind(id)
+    .bind(member.user_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    let mut response = PollResponse::from(poll);
+    response.total_votes = results.total_votes;
 
-    Ok((StatusCode::CREATED, Json(response_json)))
+    Ok(Json(PollDetailResponse {
+        poll: response,
+        counts: results.counts,
+        my_vote,
+    }))
 }
 
 /// DELETE /{id} -- delete a poll (only the creator can delete).
 async fn delete_poll(
     State(state): State<Arc<AppState>>,
-    auth_user: AuthUser,
+    RoomMember(member): RoomMember,
     Path((room_id, id)): Path<(Uuid, Uuid)>,
 ) -> AppResult<StatusCode> {
+    let mut tx = state.pool.begin().await?;
+
     let result =
         sqlx::query("DELETE FROM polls WHERE id = $1 AND room_id = $2 AND creator_id = $3")
             .bind(id)
             .bind(room_id)
-            .bind(auth_user.id)
-            .execute(&state.pool)
+            .bind(member.user_id)
+            .execute(&mut *tx)
             .await?;
 
     if result.rows_affected() == 0 {
@@ -119,26 +216,58 @@ async fn delete_poll(
         ));
     }
 
-    let channel = format!("room:{}:polls", room_id);
-    WsManager::notify_change(
-        &state,
+    let channel = Channel::room_polls(room_id);
+    outbox::enqueue(
+        &mut tx,
         &channel,
         "poll_deleted",
-        json!({ "id": id, "room_id": room_id }),
-    );
+        &json!({ "id": id, "room_id": room_id }),
+    )
+    .await?;
+
+    tx.commit().await?;
+    outbox::wake(&state);
 
     Ok(StatusCode::NO_CONTENT)
 }
 
-/// POST /{id}/vote -- cast a vote on a poll.
+/// POST /{id}/vote -- cast or change a vote while the poll is open. The vote itself goes
+/// only to the voter; the room is sent the updated tallies.
 async fn cast_vote(
     State(state): State<Arc<AppState>>,
-    auth_user: AuthUser,
+    RoomMember(member): RoomMember,
     Path((room_id, id)): Path<(Uuid, Uuid)>,
     Json(body): Json<VoteRequest>,
 ) -> AppResult<Json<Value>> {
     let vote_id = Uuid::new_v4();
 
+    let mut tx = state.pool.begin().await?;
+
+    // Serialize votes per poll so each broadcast tally includes every earlier vote and
+    // the last one sent is the final count
+    let (option_count, open): (i32, bool) = sqlx::query_as(
+        r#"
+        SELECT jsonb_array_length(options),
+               status = 'active'::poll_status AND (closes_at IS NULL OR closes_at > NOW())
+        FROM polls
+        WHERE id = $1 AND room_id = $2
+        FOR UPDATE
+        "#,
+    )
+    .bind(id)
+    .bind(room_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Poll not found".into()))?;
+
+    if !open {
+        return Err(AppError::Conflict(
+            "Votes cannot be cast on a closed poll".into(),
+        ));
+    }
+
+    check_option_index(body.option_index, option_count)?;
+
     let vote = sqlx::query_as::<_, PollVote>(
         r#"
         INSERT INTO poll_votes (id, poll_id, user_id, option_index, created_at)
@@ -151,26 +280,97 @@ async fn cast_vote(
     )
     .bind(vote_id)
     .bind(id)
-    .bind(auth_user.id)
+    .bind(member.user_id)
     .bind(body.option_index)
-    .fetch_one(&state.pool)
+    .fetch_one(&mut *tx)
     .await?;
 
     let response_json = serde_json::to_value(&vote)
         .map_err(|e| AppError::Internal(format!("Serialization error: {e}")))?;
 
-    let channel = format!("room:{}:polls", room_id);
-    WsManager::notify_change(&state, &channel, "poll_vote_cast", response_json.clone());
+    let results = poll_results(&mut tx, room_id, id).await?;
+    let results_json = serde_json::to_value(&results)
+        .map_err(|e| AppError::Internal(format!("Serialization error: {e}")))?;
+
+    let channel = Channel::room_polls(room_id);
+    outbox::enqueue(&mut tx, &channel, "poll_results_updated", &results_json).await?;
+
+    tx.commit().await?;
+    outbox::wake(&state);
 
     Ok(Json(response_json))
 }
 
-/// GET /{id}/votes -- get all votes for a poll.
+/// DELETE /{id}/vote -- withdraw the caller's vote while the poll is open, returning and
+/// broadcasting the updated tallies.
+async fn retract_vote(
+    State(state): State<Arc<AppState>>,
+    RoomMember(member): RoomMember,
+    Path((room_id, id)): Path<(Uuid, Uuid)>,
+) -> AppResult<Json<PollResults>> {
+    let mut tx = state.pool.begin().await?;
+
+    // Same per-poll lock as casting, so tallies stay in order
+    let (open,): (bool,) = sqlx::query_as(
+        r#"
+        SELECT status = 'active'::poll_status AND (closes_at IS NULL OR closes_at > NOW())
+        FROM polls
+        WHERE id = $1 AND room_id = $2
+        FOR UPDATE
+        "#,
+    )
+    .bind(id)
+    .bind(room_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Poll not found".into()))?;
+
+    if !open {
+        return Err(AppError::Conflict(
+            "Votes cannot be retracted from a closed poll".into(),
+        ));
+    }
+
+    let result = sqlx::query("DELETE FROM poll_votes WHERE poll_id = $1 AND user_id = $2")
+        .bind(id)
+        .bind(member.user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("You have not voted on this poll".into()));
+    }
+
+    let results = poll_results(&mut tx, room_id, id).await?;
+    let results_json = serde_json::to_value(&results)
+        .map_err(|e| AppError::Internal(format!("Serialization error: {e}")))?;
+
+    let channel = Channel::room_polls(room_id);
+    outbox::enqueue(&mut tx, &channel, "poll_results_updated", &results_json).await?;
+
+    tx.commit().await?;
+    outbox::wake(&state);
+
+    Ok(Json(results))
+}
+
+/// GET /{id}/votes -- who voted for what. Ballots are only visible to the room's hosts and
+/// moderators and to the poll's creator; everyone else sees the tallies from `GET /{id}`.
 async fn get_votes(
     State(state): State<Arc<AppState>>,
-    _auth_user: AuthUser,
-    Path((_room_id, id)): Path<(Uuid, Uuid)>,
+    RoomMember(member): RoomMember,
+    Path((room_id, id)): Path<(Uuid, Uuid)>,
 ) -> AppResult<Json<Value>> {
+    let creator_id: Uuid =
+        sqlx::query_scalar("SELECT creator_id FROM polls WHERE id = $1 AND room_id = $2")
+            .bind(id)
+            .bind(room_id)
+            .fetch_optional(&state.pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Poll not found".into()))?;
+
+    check_ballot_viewer(&member.role, member.user_id, creator_id)?;
+
     let votes = sqlx::query_as::<_, PollVote>(
         "SELECT id, poll_id, user_id, option_index, created_at FROM poll_votes WHERE poll_id = $1",
     )
@@ -184,18 +384,31 @@ async fn get_votes(
     })))
 }
 
+/// Whether a member with `role` may see individual ballots on a poll created by `creator_id`.
+fn check_ballot_viewer(role: &MemberRole, user_id: Uuid, creator_id: Uuid) -> AppResult<()> {
+    match role {
+        MemberRole::Host | MemberRole::Moderator => Ok(()),
+        _ if user_id == creator_id => Ok(()),
+        _ => Err(AppError::Forbidden(
+            "Only the poll's creator or the room's hosts and moderators can see who voted".into(),
+        )),
+    }
+}
+
 /// POST /{id}/close -- close a poll.
 async fn close_poll(
     State(state): State<Arc<AppState>>,
-    _auth_user: AuthUser,
+    _moderator: RoomModerator,
     Path((room_id, id)): Path<(Uuid, Uuid)>,
 ) -> AppResult<Json<Value>> {
+    let mut tx = state.pool.begin().await?;
+
     let result = sqlx::query(
         "UPDATE polls SET status = 'closed'::poll_status WHERE id = $1 AND room_id = $2",
     )
     .bind(id)
     .bind(room_id)
-    .execute(&state.pool)
+    .execute(&mut *tx)
     .await?;
 
     if result.rows_affected() == 0 {
@@ -208,8 +421,110 @@ async fn close_poll(
         "status": "closed"
     });
 
-    let channel = format!("room:{}:polls", room_id);
-    WsManager::notify_change(&state, &channel, "poll_closed", response.clone());
+    let channel = Channel::room_polls(room_id);
+    outbox::enqueue(&mut tx, &channel, "poll_closed", &response).await?;
+
+    tx.commit().await?;
+    outbox::wake(&state);
 
     Ok(Json(response))
 }
+
+/// Tally a poll's votes per option, zeros included. Votes for an index past the last
+/// option are not counted.
+async fn poll_results(
+    conn: &mut PgConnection,
+    room_id: Uuid,
+    poll_id: Uuid,
+) -> AppResult<PollResults> {
+    let (counts,): (Vec<i64>,) = sqlx::query_as(
+        r#"
+        SELECT COALESCE(array_agg(COALESCE(v.votes, 0) ORDER BY o.idx), '{}')
+        FROM polls p
+        CROSS JOIN LATERAL generate_series(0, jsonb_array_length(p.options) - 1) AS o(idx)
+        LEFT JOIN (
+            SELECT option_index, COUNT(*) AS votes
+            FROM poll_votes
+            WHERE poll_id = $1
+            GROUP BY option_index
+        ) v ON v.option_index = o.idx
+        WHERE p.id = $1
+        "#,
+    )
+    .bind(poll_id)
+    .fetch_one(conn)
+    .await?;
+
+    Ok(PollResults {
+        poll_id,
+        room_id,
+        total_votes: counts.iter().sum(),
+        counts,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn members_cannot_see_other_peoples_ballots() {
+        let result = check_ballot_viewer(&MemberRole::Member, Uuid::new_v4(), Uuid::new_v4());
+        assert!(matches!(result, Err(AppError::Forbidden(_))));
+    }
+
+    #[test]
+    fn creator_and_moderators_can_see_ballots() {
+        let creator = Uuid::new_v4();
+        assert!(check_ballot_viewer(&MemberRole::Member, creator, creator).is_ok());
+        for role in [MemberRole::Host, MemberRole::Moderator] {
+            assert!(check_ballot_viewer(&role, Uuid::new_v4(), creator).is_ok());
+        }
+    }
+
+    #[test]
+    fn last_option_is_a_valid_vote() {
+        assert!(check_option_index(0, 3).is_ok());
+        assert!(check_option_index(2, 3).is_ok());
+    }
+
+    #[test]
+    fn index_equal_to_option_count_is_rejected() {
+        assert!(matches!(
+            check_option_index(3, 3),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn negative_index_is_rejected() {
+        assert!(matches!(
+            check_option_index(-1, 3),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn duplicate_options_are_rejected() {
+        assert!(check_unique_options(&options(&["Yes", "No"])).is_ok());
+        assert!(matches!(
+            check_unique_options(&options(&["Yes", "No", " Yes "])),
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn poll_needs_at_least_two_options() {
+        let request = |values: &[&str]| CreatePollRequest {
+            question: "Which?".to_string(),
+            options: options(values),
+            closes_at: None,
+        };
+        assert!(request(&["Only"]).validate().is_err());
+        assert!(request(&["One", "Two"]).validate().is_ok());
+    }
+}
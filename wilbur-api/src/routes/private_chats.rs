@@ -1,32 +1,52 @@
 use std::sync::Arc;
 
 use axum::{
-    extract::{Json, Path, Query, State},
+    extract::{Path, Query, State},
     http::StatusCode,
-    routing::{get, post},
+    routing::{delete, get, post, put},
     Router,
 };
 use serde::Deserialize;
 use serde_json::{json, Value};
+use sqlx::PgExecutor;
 use uuid::Uuid;
+use validator::Validate;
 
 use crate::{
     error::{AppError, AppResult},
-    extractors::{auth::AuthUser, pagination::PaginationParams},
-    models::private_chat::{
-        PrivateChat, PrivateChatResponse, PrivateMessage, PrivateMessageResponse,
+    extractors::{auth::AuthUser, json::Json, pagination::PaginationParams},
+    models::{
+        message::ContentType,
+        notification::NotificationData,
+        private_chat::{
+            ChatListQuery, ChatSummary, ChatSummaryResponse, PrivateChat, PrivateChatResponse,
+            PrivateMessage, PrivateMessageResponse,
+        },
+        user::DmPolicy,
+    },
+    routes::{created, Created},
+    services::{
+        message_limits,
+        notifier::{self, NewNotification},
     },
     state::AppState,
-    ws::manager::WsManager,
+    ws::{channels::Channel, outbox},
 };
 
+/// Characters of the latest message stored for the conversation list.
+const PREVIEW_CHARS: i32 = 140;
+
 pub fn router() -> Router<Arc<AppState>> {
     Router::new()
         .route("/", get(list_chats))
         .route("/", post(create_chat))
         .route("/user/{user_id}", get(find_chat_by_user))
+        .route("/{id}/archive", post(archive_chat))
+        .route("/{id}/unarchive", post(unarchive_chat))
         .route("/{id}/messages", get(list_chat_messages))
         .route("/{id}/messages", post(send_chat_message))
+        .route("/{id}/messages/{message_id}", put(update_chat_message))
+        .route("/{id}/messages/{message_id}", delete(delete_chat_message))
 }
 
 #[derive(Debug, Deserialize)]
@@ -35,36 +55,59 @@ struct CreateChatRequest {
     user_id: Uuid,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 struct SendMessageRequest {
+    #[validate(length(min = 1))]
     content: String,
+    content_type: Option<ContentType>,
 }
 
-/// GET / -- list all DM conversations for the authenticated user.
+#[derive(Debug, Deserialize, Validate)]
+struct UpdateMessageRequest {
+    #[validate(length(min = 1))]
+    content: String,
+}
+
+/// GET / -- list the authenticated user's DM conversations, most recently active first.
+/// Archived conversations are left out unless `include_archived` is set.
 async fn list_chats(
     State(state): State<Arc<AppState>>,
     auth_user: AuthUser,
-    Query(pagination): Query<PaginationParams>,
+    pagination: PaginationParams,
+    Query(filter): Query<ChatListQuery>,
 ) -> AppResult<Json<Value>> {
     let limit = pagination.limit();
     let offset = pagination.offset();
 
-    let chats = sqlx::query_as::<_, PrivateChat>(
+    let chats = sqlx::query_as::<_, ChatSummary>(
         r#"
-        SELECT id, participant_one, participant_two, created_at
-        FROM private_chats
-        WHERE participant_one = $1 OR participant_two = $1
-        ORDER BY created_at DESC
+        SELECT c.id, c.participant_one, c.participant_two, c.created_at,
+               u.id AS other_user_id,
+               u.display_name AS other_display_name,
+               u.avatar_url AS other_avatar_url,
+               c.last_message_id,
+               c.last_message_sender_id,
+               c.last_message_preview,
+               c.last_message_at,
+               COALESCE(st.archived, false) AS archived
+        FROM private_chats c
+        JOIN users u ON u.id = CASE WHEN c.participant_one = $1
+                                    THEN c.participant_two ELSE c.participant_one END
+        LEFT JOIN private_chat_states st ON st.chat_id = c.id AND st.user_id = $1
+        WHERE (c.participant_one = $1 OR c.participant_two = $1)
+          AND ($4 OR COALESCE(st.archived, false) = false)
+        ORDER BY COALESCE(c.last_message_at, c.created_at) DESC
         LIMIT $2 OFFSET $3
         "#,
     )
     .bind(auth_user.id)
     .bind(limit)
     .bind(offset)
+    .bind(filter.include_archived)
     .fetch_all(&state.pool)
     .await?;
 
-    let data: Vec<PrivateChatResponse> = chats.into_iter().map(PrivateChatResponse::from).collect();
+    let data: Vec<ChatSummaryResponse> = chats.into_iter().map(ChatSummaryResponse::from).collect();
 
     Ok(Json(json!({
         "user_id": auth_user.id,
@@ -74,12 +117,13 @@ async fn list_chats(
     })))
 }
 
-/// POST / -- create a new DM conversation.
+/// POST / -- create a new DM conversation, or return the existing one, if the other user's
+/// DM policy allows the caller to reach them.
 async fn create_chat(
     State(state): State<Arc<AppState>>,
     auth_user: AuthUser,
     Json(body): Json<CreateChatRequest>,
-) -> AppResult<(StatusCode, Json<Value>)> {
+) -> AppResult<Created<Value>> {
     if auth_user.id == body.user_id {
         return Err(AppError::BadRequest(
             "Cannot create a DM with yourself".into(),
@@ -93,6 +137,8 @@ async fn create_chat(
         (body.user_id, auth_user.id)
     };
 
+    require_dm_allowed(&state.pool, auth_user.id, body.user_id).await?;
+
     let chat_id = Uuid::new_v4();
 
     let chat = sqlx::query_as::<_, PrivateChat>(
@@ -114,7 +160,63 @@ async fn create_chat(
     let response_json = serde_json::to_value(&response)
         .map_err(|e| AppError::Internal(format!("Serialization error: {e}")))?;
 
-    Ok((StatusCode::CREATED, Json(response_json)))
+    Ok(created(
+        format!("/api/v1/dm/user/{}", body.user_id),
+        response_json,
+    ))
+}
+
+/// Refuse with 403 when `recipient`'s DM policy doesn't let `sender` reach them.
+async fn require_dm_allowed(pool: &sqlx::PgPool, sender: Uuid, recipient: Uuid) -> AppResult<()> {
+    let (policy, shares_room): (DmPolicy, bool) = sqlx::query_as(
+        r#"
+        SELECT u.dm_policy,
+               EXISTS (
+                   SELECT 1
+                   FROM room_memberships a
+                   JOIN room_memberships b ON b.room_id = a.room_id
+                   JOIN rooms r ON r.id = a.room_id
+                   WHERE a.user_id = $1 AND a.status = 'active'
+                     AND b.user_id = u.id AND b.status = 'active'
+                     AND r.is_active = true
+               )
+        FROM users u
+        WHERE u.id = $2
+        "#,
+    )
+    .bind(sender)
+    .bind(recipient)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound("User not found".into()))?;
+
+    match policy {
+        DmPolicy::Anyone => Ok(()),
+        DmPolicy::SharedRooms if shares_room => Ok(()),
+        DmPolicy::SharedRooms => Err(AppError::Forbidden(
+            "This user only accepts direct messages from members of a room they are in".into(),
+        )),
+        DmPolicy::Nobody => Err(AppError::Forbidden(
+            "This user does not accept direct messages".into(),
+        )),
+    }
+}
+
+/// The other participant of `chat`, once their current DM policy has been checked. The
+/// policy applies to every message, so tightening it also quiets conversations that already
+/// exist.
+pub(crate) async fn require_can_message(
+    pool: &sqlx::PgPool,
+    sender: Uuid,
+    chat: &PrivateChat,
+) -> AppResult<Uuid> {
+    let recipient = if chat.participant_one == sender {
+        chat.participant_two
+    } else {
+        chat.participant_one
+    };
+    require_dm_allowed(pool, sender, recipient).await?;
+    Ok(recipient)
 }
 
 /// GET /user/{user_id} -- find an existing DM conversation with a specific user.
@@ -156,7 +258,12 @@ async fn find_chat_by_user(
 }
 
 /// Verify the authenticated user is a participant of the given chat.
-async fn require_chat_participant(
+///
+/// The counterpart needs no separate check: deleting a user cascades to their chats, so
+/// a chat that still exists always has both participants. There are no user-level
+/// blocks yet; once there are, sending should be refused here when either side has
+/// blocked the other.
+pub(crate) async fn require_chat_participant(
     pool: &sqlx::PgPool,
     user_id: Uuid,
     chat_id: Uuid,
@@ -177,12 +284,55 @@ async fn require_chat_participant(
     Ok(chat)
 }
 
+/// Mark every message the other participant has sent in a chat as read by `reader_id`,
+/// and tell the chat. Returns how many messages were newly marked.
+pub(crate) async fn mark_chat_read(
+    state: &Arc<AppState>,
+    chat_id: Uuid,
+    reader_id: Uuid,
+) -> AppResult<u64> {
+    let mut tx = state.pool.begin().await?;
+
+    let result = sqlx::query(
+        r#"
+        UPDATE private_messages SET is_read = true
+        WHERE chat_id = $1 AND sender_id <> $2 AND is_read = false AND is_deleted = false
+        "#,
+    )
+    .bind(chat_id)
+    .bind(reader_id)
+    .execute(&mut *tx)
+    .await?;
+
+    let read_count = result.rows_affected();
+    if read_count > 0 {
+        outbox::enqueue(
+            &mut tx,
+            &Channel::dm(chat_id),
+            "dm_read",
+            &json!({
+                "chat_id": chat_id,
+                "reader_id": reader_id,
+                "read_count": read_count,
+                "read_at": chrono::Utc::now(),
+            }),
+        )
+        .await?;
+    }
+    tx.commit().await?;
+    if read_count > 0 {
+        outbox::wake(state);
+    }
+
+    Ok(read_count)
+}
+
 /// GET /{id}/messages -- list messages in a DM conversation.
 async fn list_chat_messages(
     State(state): State<Arc<AppState>>,
     auth_user: AuthUser,
     Path(id): Path<Uuid>,
-    Query(pagination): Query<PaginationParams>,
+    pagination: PaginationParams,
 ) -> AppResult<Json<Value>> {
     // Verify the authenticated user is a participant of the chat
     require_chat_participant(&state.pool, auth_user.id, id).await?;
@@ -192,7 +342,7 @@ async fn list_chat_messages(
 
     let messages = sqlx::query_as::<_, PrivateMessage>(
         r#"
-        SELECT id, chat_id, sender_id, content, is_read, created_at
+        SELECT id, chat_id, sender_id, content, content_type, is_read, is_deleted, deleted_at, created_at, updated_at
         FROM private_messages
         WHERE chat_id = $1
         ORDER BY created_at ASC
@@ -218,44 +368,249 @@ async fn list_chat_messages(
     })))
 }
 
-/// POST /{id}/messages -- send a message in a DM conversation.
+/// POST /{id}/messages -- send a message in a DM conversation, if the other participant's DM
+/// policy still allows it.
 async fn send_chat_message(
     State(state): State<Arc<AppState>>,
     auth_user: AuthUser,
     Path(id): Path<Uuid>,
     Json(body): Json<SendMessageRequest>,
-) -> AppResult<(StatusCode, Json<Value>)> {
+) -> AppResult<Created<Value>> {
     // Verify the authenticated user is a participant of the chat
-    require_chat_participant(&state.pool, auth_user.id, id).await?;
+    let chat = require_chat_participant(&state.pool, auth_user.id, id).await?;
+    let recipient = require_can_message(&state.pool, auth_user.id, &chat).await?;
 
+    body.validate()
+        .map_err(|e| AppError::Validation(e.to_string().into()))?;
+    message_limits::check_content(&state.config, &body.content)?;
+
+    let content_type = body.content_type.unwrap_or(ContentType::Text);
     let message_id = Uuid::new_v4();
 
+    let mut tx = state.pool.begin().await?;
+
     let message = sqlx::query_as::<_, PrivateMessage>(
         r#"
-        INSERT INTO private_messages (id, chat_id, sender_id, content, created_at)
-        VALUES ($1, $2, $3, $4, NOW())
-        RETURNING id, chat_id, sender_id, content, is_read, created_at
+        INSERT INTO private_messages (id, chat_id, sender_id, content, content_type, created_at)
+        VALUES ($1, $2, $3, $4, $5, NOW())
+        RETURNING id, chat_id, sender_id, content, content_type, is_read, is_deleted, deleted_at, created_at, updated_at
         "#,
     )
     .bind(message_id)
     .bind(id)
     .bind(auth_user.id)
     .bind(&body.content)
-    .fetch_one(&state.pool)
+    .bind(&content_type)
+    .fetch_one(&mut *tx)
+    .await?;
+    refresh_last_message(&mut *tx, id).await?;
+
+    // Notify the other participant; repeated messages in the chat collapse into one entry
+    // A new message brings an archived conversation back into the recipient's list
+    set_archived(&mut *tx, id, recipient, false).await?;
+    let sender_name: Option<String> =
+        sqlx::query_scalar("SELECT display_name FROM users WHERE id = $1")
+            .bind(auth_user.id)
+            .fetch_one(&mut *tx)
+            .await?;
+    notifier::notify(
+        &mut tx,
+        NewNotification {
+            user_id: recipient,
+            title: format!(
+                "New message from {}",
+                sender_name.as_deref().unwrap_or("someone")
+            ),
+            body: body.content.chars().take(140).collect(),
+            data: NotificationData::Dm {
+                chat_id: id,
+                message_id,
+            },
+            group_key: Some(id.to_string()),
+        },
+    )
     .await?;
 
     let response = PrivateMessageResponse::from(message);
     let response_json = serde_json::to_value(&response)
         .map_err(|e| AppError::Internal(format!("Serialization error: {e}")))?;
-
-    // Notify via WebSocket
-    let channel = format!("dm:{}", id);
-    WsManager::notify_change(
-        &state,
-        &channel,
+    outbox::enqueue(
+        &mut tx,
+        &Channel::dm(id),
         "private_message_sent",
-        response_json.clone(),
-    );
+        &response_json,
+    )
+    .await?;
+
+    tx.commit().await?;
+    outbox::wake(&state);
+
+    Ok(created(
+        format!("/api/v1/dm/{id}/messages/{message_id}"),
+        response_json,
+    ))
+}
+
+/// PUT /{id}/messages/{message_id} -- edit one of your own DM messages.
+async fn update_chat_message(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path((id, message_id)): Path<(Uuid, Uuid)>,
+    Json(body): Json<UpdateMessageRequest>,
+) -> AppResult<Json<Value>> {
+    require_chat_participant(&state.pool, auth_user.id, id).await?;
+
+    body.validate()
+        .map_err(|e| AppError::Validation(e.to_string().into()))?;
+    message_limits::check_content(&state.config, &body.content)?;
+
+    let mut tx = state.pool.begin().await?;
+
+    let message = sqlx::query_as::<_, PrivateMessage>(
+        r#"
+        UPDATE private_messages SET content = $1, updated_at = NOW()
+        WHERE id = $2 AND chat_id = $3 AND sender_id = $4 AND is_deleted = false
+        RETURNING id, chat_id, sender_id, content, content_type, is_read, is_deleted, deleted_at, created_at, updated_at
+        "#,
+    )
+    .bind(&body.content)
+    .bind(message_id)
+    .bind(id)
+    .bind(auth_user.id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Message not found or not owned by you".into()))?;
+    refresh_last_message(&mut *tx, id).await?;
+
+    let response = PrivateMessageResponse::from(message);
+    let response_json = serde_json::to_value(&response)
+        .map_err(|e| AppError::Internal(format!("Serialization error: {e}")))?;
+    outbox::enqueue(
+        &mut tx,
+        &Channel::dm(id),
+        "private_message_updated",
+        &response_json,
+    )
+    .await?;
+
+    tx.commit().await?;
+    outbox::wake(&state);
+
+    Ok(Json(response_json))
+}
+
+/// DELETE /{id}/messages/{message_id} -- soft-delete one of your own DM messages.
+async fn delete_chat_message(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path((id, message_id)): Path<(Uuid, Uuid)>,
+) -> AppResult<StatusCode> {
+    require_chat_participant(&state.pool, auth_user.id, id).await?;
+
+    let mut tx = state.pool.begin().await?;
+
+    let result = sqlx::query(
+        r#"
+        UPDATE private_messages SET is_deleted = true, deleted_at = NOW(), updated_at = NOW()
+        WHERE id = $1 AND chat_id = $2 AND sender_id = $3 AND is_deleted = false
+        "#,
+    )
+    .bind(message_id)
+    .bind(id)
+    .bind(auth_user.id)
+    .execute(&mut *tx)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound(
+            "Message not found or not owned by you".into(),
+        ));
+    }
+    refresh_last_message(&mut *tx, id).await?;
+
+    outbox::enqueue(
+        &mut tx,
+        &Channel::dm(id),
+        "private_message_deleted",
+        &json!({ "id": message_id, "chat_id": id }),
+    )
+    .await?;
+
+    tx.commit().await?;
+    outbox::wake(&state);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Recompute a conversation's latest-message columns after a message is sent, edited, or
+/// deleted. Clears them when no visible messages remain.
+async fn refresh_last_message<'e>(executor: impl PgExecutor<'e>, chat_id: Uuid) -> AppResult<()> {
+    sqlx::query(
+        r#"
+        UPDATE private_chats SET
+            (last_message_id, last_message_sender_id, last_message_preview, last_message_at) = (
+                SELECT id, sender_id, LEFT(content, $2), created_at
+                FROM private_messages
+                WHERE chat_id = $1 AND is_deleted = false
+                ORDER BY created_at DESC
+                LIMIT 1
+            )
+        WHERE id = $1
+        "#,
+    )
+    .bind(chat_id)
+    .bind(PREVIEW_CHARS)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Set a participant's archive flag on a conversation.
+async fn set_archived<'e>(
+    executor: impl PgExecutor<'e>,
+    chat_id: Uuid,
+    user_id: Uuid,
+    archived: bool,
+) -> AppResult<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO private_chat_states (chat_id, user_id, archived, updated_at)
+        VALUES ($1, $2, $3, NOW())
+        ON CONFLICT (chat_id, user_id) DO UPDATE
+            SET archived = EXCLUDED.archived, updated_at = NOW()
+            WHERE private_chat_states.archived <> EXCLUDED.archived
+        "#,
+    )
+    .bind(chat_id)
+    .bind(user_id)
+    .bind(archived)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// POST /{id}/archive -- hide a conversation from your DM list until it gets a new message.
+async fn archive_chat(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> AppResult<StatusCode> {
+    require_chat_participant(&state.pool, auth_user.id, id).await?;
+    set_archived(&state.pool, id, auth_user.id, true).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /{id}/unarchive -- return an archived conversation to your DM list.
+async fn unarchive_chat(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> AppResult<StatusCode> {
+    require_chat_participant(&state.pool, auth_user.id, id).await?;
+    set_archived(&state.pool, id, auth_user.id, false).await?;
 
-    Ok((StatusCode::CREATED, Json(response_json)))
+    Ok(StatusCode::NO_CONTENT)
 }
@@ -0,0 +1,218 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{get, post},
+    Router,
+};
+use serde_json::Value;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    error::{AppError, AppResult},
+    extractors::{json::Json, room_access::RoomModerator},
+    models::{
+        alert::AlertResponse,
+        message::ContentType,
+        room_hook::{
+            CreateRoomHookRequest, HookScope, IncomingHookRequest, RoomHook, RoomHookResponse,
+        },
+    },
+    routes::{alerts::insert_alert, auth::hash_token, created, messages::insert_message, Created},
+    services::{message_limits, webhook_dispatcher::generate_secret},
+    state::AppState,
+    ws::{channels::Channel, outbox},
+};
+
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", get(list_hooks))
+        .route("/", post(create_hook))
+        // POST takes the hook token; DELETE takes the hook ID
+        .route("/{id}", post(receive_hook).delete(revoke_hook))
+}
+
+/// GET / -- list incoming hooks for a room, including revoked ones (moderator-only).
+async fn list_hooks(
+    State(state): State<Arc<AppState>>,
+    _moderator: RoomModerator,
+    Path(room_id): Path<Uuid>,
+) -> AppResult<Json<Vec<RoomHookResponse>>> {
+    let hooks = sqlx::query_as::<_, RoomHook>(
+        "SELECT * FROM room_hooks WHERE room_id = $1 ORDER BY created_at DESC",
+    )
+    .bind(room_id)
+    .fetch_all(&state.pool)
+    .await?;
+
+    let results: Vec<RoomHookResponse> = hooks.into_iter().map(RoomHookResponse::from).collect();
+    Ok(Json(results))
+}
+
+/// POST / -- create an incoming hook and its bot user. The token is returned only in this response.
+async fn create_hook(
+    State(state): State<Arc<AppState>>,
+    RoomModerator(moderator): RoomModerator,
+    Path(room_id): Path<Uuid>,
+    Json(body): Json<CreateRoomHookRequest>,
+) -> AppResult<Created<RoomHookResponse>> {
+    body.validate()
+        .map_err(|e| AppError::Validation(e.to_string().into()))?;
+
+    let hook_id = Uuid::new_v4();
+    let bot_user_id = Uuid::new_v4();
+    let token = generate_secret();
+
+    let mut tx = state.pool.begin().await?;
+
+    // Bot users have no usable password and are rejected at login; they share the room's tenant
+    sqlx::query(
+        r#"
+        INSERT INTO users (id, email, password_hash, display_name, role, is_bot, created_at, updated_at, tenant_id)
+        VALUES ($1, $2, '!', $3, 'member', true, NOW(), NOW(), (SELECT tenant_id FROM rooms WHERE id = $4))
+        "#,
+    )
+    .bind(bot_user_id)
+    .bind(format!("hook-{hook_id}@bots.invalid"))
+    .bind(&body.name)
+    .bind(room_id)
+    .execute(&mut *tx)
+    .await?;
+
+    let hook = sqlx::query_as::<_, RoomHook>(
+        r#"
+        INSERT INTO room_hooks (id, room_id, bot_user_id, created_by, name, token_hash, scope, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, NOW())
+        RETURNING *
+        "#,
+    )
+    .bind(hook_id)
+    .bind(room_id)
+    .bind(bot_user_id)
+    .bind(moderator.user_id)
+    .bind(&body.name)
+    .bind(hash_token(&token))
+    .bind(body.scope.as_str())
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    let mut response = RoomHookResponse::from(hook);
+    response.token = Some(token);
+
+    Ok(created(
+        format!("/api/v1/rooms/{room_id}/hooks/{}", response.id),
+        response,
+    ))
+}
+
+/// DELETE /{id} -- revoke an incoming hook (moderator-only). Past posts keep their bot attribution.
+async fn revoke_hook(
+    State(state): State<Arc<AppState>>,
+    _moderator: RoomModerator,
+    Path((room_id, id)): Path<(Uuid, Uuid)>,
+) -> AppResult<StatusCode> {
+    let result = sqlx::query(
+        "UPDATE room_hooks SET revoked_at = NOW() WHERE id = $1 AND room_id = $2 AND revoked_at IS NULL",
+    )
+    .bind(id)
+    .bind(room_id)
+    .execute(&state.pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Hook not found".into()));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /{token} -- post a message or alert as the hook's bot. Authenticated by the hook token.
+async fn receive_hook(
+    State(state): State<Arc<AppState>>,
+    Path((room_id, token)): Path<(Uuid, String)>,
+    Json(body): Json<IncomingHookRequest>,
+) -> AppResult<(StatusCode, Json<Value>)> {
+    let hook = sqlx::query_as::<_, RoomHook>(
+        "SELECT * FROM room_hooks WHERE room_id = $1 AND token_hash = $2 AND revoked_at IS NULL",
+    )
+    .bind(room_id)
+    .bind(hash_token(&token))
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or_else(|| AppError::Unauthorized("Invalid hook token".into()))?;
+
+    if state.hook_limiter.check_key(&hook.id).is_err() {
+        tracing::warn!(hook_id = %hook.id, "Incoming hook rate limit exceeded");
+        return Err(AppError::TooManyRequests(
+            "Too many requests. Please try again later.".into(),
+        ));
+    }
+
+    let mut tx = state.pool.begin().await?;
+
+    let response_json = match body {
+        IncomingHookRequest::Message(msg) => {
+            msg.validate()
+                .map_err(|e| AppError::Validation(e.to_string().into()))?;
+            message_limits::check_content(&state.config, &msg.content)?;
+            message_limits::check_attachments(&state.config, &mut tx, &msg.file_ids).await?;
+
+            let content_type = msg.content_type.unwrap_or(ContentType::Text);
+            let response = insert_message(
+                &mut tx,
+                room_id,
+                hook.bot_user_id,
+                &msg.content,
+                &content_type,
+                &msg.file_ids,
+                None,
+            )
+            .await?;
+
+            let response_json = serde_json::to_value(response)
+                .map_err(|e| AppError::Internal(format!("Serialization error: {e}")))?;
+
+            let channel = Channel::room_chat(room_id);
+            outbox::enqueue(&mut tx, &channel, "message_created", &response_json).await?;
+            response_json
+        }
+        IncomingHookRequest::Alert(alert) => {
+            if hook.scope != HookScope::Alert.as_str() {
+                return Err(AppError::Forbidden(
+                    "This hook is not allowed to post alerts".into(),
+                ));
+            }
+            alert
+                .validate()
+                .map_err(|e| AppError::Validation(e.to_string().into()))?;
+
+            let alert = insert_alert(&mut tx, room_id, hook.bot_user_id, &alert).await?;
+            let is_scheduled = !alert.is_active;
+
+            let mut response_json = serde_json::to_value(AlertResponse::from(alert))
+                .map_err(|e| AppError::Internal(format!("Serialization error: {e}")))?;
+            // Alerts carry no author join, so mark bot authorship explicitly
+            response_json["author_is_bot"] = Value::Bool(true);
+
+            if !is_scheduled {
+                let channel = Channel::room_alerts(room_id);
+                outbox::enqueue(&mut tx, &channel, "alert_created", &response_json).await?;
+            }
+            response_json
+        }
+    };
+
+    sqlx::query("UPDATE room_hooks SET last_used_at = NOW() WHERE id = $1")
+        .bind(hook.id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    outbox::wake(&state);
+
+    Ok((StatusCode::CREATED, Json(response_json)))
+}
@@ -1,9 +1,10 @@
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 use axum::{
-    extract::{Json, Path, Query, State},
+    extract::{Multipart, Path, Query, State},
     http::StatusCode,
-    routing::{delete, get, post, put},
+    routing::{delete, get, patch, post, put},
     Router,
 };
 use serde_json::Value;
@@ -11,61 +12,185 @@ use uuid::Uuid;
 use validator::Validate;
 
 use crate::{
-    error::{AppError, AppResult},
+    error::{AppError, AppResult, ErrorBody},
     extractors::{
         auth::AuthUser,
-        pagination::PaginationParams,
-        room_access::{require_room_host, require_room_moderator},
+        json::Json,
+        pagination::{PaginationParams, RawPaginationParams},
+        room_access::{RoomHost, RoomMember, RoomModerator},
+        soft_delete::DeletedFilter,
+        sync::SyncFilter,
     },
     models::{
         membership::{
-            MemberRole, MemberStatus, MembershipResponse, RoomMembership, UpdateMemberRoleRequest,
+            MemberRole, MemberStatus, MembershipResponse, MembershipWithLastSeen, RoomMembership,
+            UpdateMemberRoleRequest,
         },
         room::{CreateRoomRequest, Room, RoomResponse, UpdateRoomRequest},
     },
+    routes::{
+        created,
+        moderation::ensure_can_moderate,
+        storage::{queue_unreferenced_object, store_branding_image},
+        Created,
+    },
+    services::{
+        feature_flags::{Feature, FlagScope},
+        room_permissions::{room_permissions, Capability},
+        storage_cleanup, tenant_config,
+        tenant_scope::{require_room_tenant, TenantScope},
+    },
     state::AppState,
 };
 
+/// Capacity given to new rooms that don't specify one.
+const DEFAULT_MAX_MEMBERS: i32 = 100;
+
 pub fn router() -> Router<Arc<AppState>> {
     Router::new()
         .route("/", get(list_rooms))
         .route("/", post(create_room))
         .route("/by-tenant/{tenant_id}", get(list_rooms_by_tenant))
-        .route("/{id}", get(get_room))
-        .route("/{id}", put(update_room))
-        .route("/{id}", delete(delete_room))
-        .route("/{id}/members", get(list_members))
-        .route("/{id}/members", post(invite_member))
-        .route("/{id}/members/{user_id}", delete(remove_member))
-        .route("/{id}/members/{user_id}/role", put(update_member_role))
+        .route("/{room_id}", get(get_room))
+        .route("/{room_id}", put(update_room))
+        .route("/{room_id}", patch(patch_room))
+        .route("/{room_id}", delete(delete_room))
+        .route("/{room_id}/background", put(upload_room_background))
+        .route("/{room_id}/features", get(get_room_features))
+        .route("/{room_id}/features", put(update_room_features))
+        .route("/{room_id}/permissions", get(get_room_permissions))
+        .route("/{room_id}/permissions", put(update_room_permissions))
+        .route("/{room_id}/members", get(list_members))
+        .route("/{room_id}/members", post(invite_member))
+        .route("/{room_id}/members/{user_id}", delete(remove_member))
+        .route("/{room_id}/members/{user_id}/role", put(update_member_role))
 }
 
-/// GET / -- list all rooms (paginated).
+/// GET / -- list all rooms (paginated). With `updated_after`, returns changes since then
+/// instead, deletions included.
+#[utoipa::path(
+    get,
+    path = "/api/v1/rooms",
+    tag = "rooms",
+    params(
+        RawPaginationParams,
+        DeletedFilter,
+        SyncFilter,
+    ),
+    responses(
+        (status = 200, description = "Active rooms, newest first", body = Vec<RoomResponse>),
+        (status = 401, description = "Missing or invalid access token", body = ErrorBody),
+        (status = 403, description = "Deleted rooms requested by a non-admin", body = ErrorBody),
+    ),
+    security(("bearer_auth" = [])),
+)]
 async fn list_rooms(
     State(state): State<Arc<AppState>>,
-    _auth_user: AuthUser,
-    Query(pagination): Query<PaginationParams>,
+    auth_user: AuthUser,
+    pagination: PaginationParams,
+    Query(deleted): Query<DeletedFilter>,
+    Query(sync): Query<SyncFilter>,
 ) -> AppResult<Json<Vec<RoomResponse>>> {
-    let rooms = sqlx::query_as::<_, Room>(
-        "SELECT * FROM rooms WHERE is_active = true ORDER BY created_at DESC LIMIT $1 OFFSET $2",
-    )
-    .bind(pagination.limit())
-    .bind(pagination.offset())
-    .fetch_all(&state.pool)
-    .await?;
-
-    let results: Vec<RoomResponse> = rooms.into_iter().map(RoomResponse::from).collect();
+    let include_deleted = deleted.resolve(&state.pool, &auth_user, None).await?;
+    let (restricted, tenant_id) = TenantScope::for_user(&state, auth_user.id)
+        .await?
+        .sql_filter();
+
+    let rooms = match sync.updated_after {
+        // Deleted rooms come back as tombstones
+        Some(updated_after) => {
+            sqlx::query_as::<_, Room>(
+                r#"
+                SELECT * FROM rooms
+                WHERE updated_at > $3
+                  AND (NOT $4 OR tenant_id IS NULL OR tenant_id = $5
+                       OR EXISTS (SELECT 1 FROM room_memberships m
+                                  WHERE m.room_id = rooms.id AND m.user_id = $6
+                                    AND m.status = 'active'))
+                ORDER BY updated_at ASC, id ASC
+                LIMIT $1 OFFSET $2
+                "#,
+            )
+            .bind(pagination.limit())
+            .bind(pagination.offset())
+            .bind(updated_after)
+            .bind(restricted)
+            .bind(tenant_id)
+            .bind(auth_user.id)
+            .fetch_all(&state.pool)
+            .await?
+        }
+        None => {
+            sqlx::query_as::<_, Room>(
+                r#"
+                SELECT * FROM rooms
+                WHERE ($3 OR is_active = true)
+                  AND (NOT $4 OR tenant_id IS NULL OR tenant_id = $5
+                       OR EXISTS (SELECT 1 FROM room_memberships m
+                                  WHERE m.room_id = rooms.id AND m.user_id = $6
+                                    AND m.status = 'active'))
+                ORDER BY created_at DESC
+                LIMIT $1 OFFSET $2
+                "#,
+            )
+            .bind(pagination.limit())
+            .bind(pagination.offset())
+            .bind(include_deleted)
+            .bind(restricted)
+            .bind(tenant_id)
+            .bind(auth_user.id)
+            .fetch_all(&state.pool)
+            .await?
+        }
+    };
+
+    let results: Vec<RoomResponse> = rooms
+        .into_iter()
+        .map(|r| {
+            let tombstone = !r.is_active && !include_deleted;
+            let response = RoomResponse::from(r);
+            if tombstone {
+                response.into_tombstone()
+            } else {
+                response
+            }
+        })
+        .collect();
     Ok(Json(results))
 }
 
 /// POST / -- create a new room.
+#[utoipa::path(
+    post,
+    path = "/api/v1/rooms",
+    tag = "rooms",
+    request_body = CreateRoomRequest,
+    responses(
+        (status = 201, description = "Room created; the caller is its host", body = RoomResponse),
+        (status = 401, description = "Missing or invalid access token", body = ErrorBody),
+        (status = 403, description = "Tenant is not the caller's", body = ErrorBody),
+        (status = 422, description = "Invalid request body", body = ErrorBody),
+    ),
+    security(("bearer_auth" = [])),
+)]
 async fn create_room(
     State(state): State<Arc<AppState>>,
     auth_user: AuthUser,
     Json(body): Json<CreateRoomRequest>,
-) -> AppResult<(StatusCode, Json<RoomResponse>)> {
+) -> AppResult<Created<RoomResponse>> {
     body.validate()
-        .map_err(|e| AppError::Validation(e.to_string()))?;
+        .map_err(|e| AppError::Validation(e.to_string().into()))?;
+    TenantScope::for_user(&state, auth_user.id)
+        .await?
+        .require(body.tenant_id)?;
+
+    let limit = room_size_limit(&state, body.tenant_id).await?;
+    let max_members = match body.max_members {
+        Some(requested) => check_room_size(requested, limit)?,
+        None => limit.map_or(DEFAULT_MAX_MEMBERS, |l| {
+            DEFAULT_MAX_MEMBERS.min(i32::try_from(l).unwrap_or(i32::MAX))
+        }),
+    };
 
     let room_id = Uuid::new_v4();
     let now = chrono::Utc::now();
@@ -75,8 +200,9 @@ async fn create_room(
         INSERT INTO rooms (id, tenant_id, name, title, description, max_members,
                            background_image_url, header_color, accent_color,
                            font_family, border_style, shadow_style,
+                           sanitize_content, linkify_urls,
                            is_active, created_at, updated_at)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, true, $13, $14)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, true, $15, $16)
         RETURNING *
         "#,
     )
@@ -85,13 +211,15 @@ async fn create_room(
     .bind(&body.name)
     .bind(&body.title)
     .bind(&body.description)
-    .bind(body.max_members.unwrap_or(100))
+    .bind(max_members)
     .bind(&body.background_image_url)
     .bind(&body.header_color)
     .bind(&body.accent_color)
     .bind(&body.font_family)
     .bind(&body.border_style)
     .bind(&body.shadow_style)
+    .bind(body.sanitize_content.unwrap_or(true))
+    .bind(body.linkify_urls.unwrap_or(false))
     .bind(now)
     .bind(now)
     .fetch_one(&state.pool)
@@ -114,68 +242,209 @@ async fn create_room(
     .execute(&state.pool)
     .await?;
 
-    Ok((StatusCode::CREATED, Json(RoomResponse::from(room))))
+    Ok(created(
+        format!("/api/v1/rooms/{room_id}"),
+        RoomResponse::from(room),
+    ))
 }
 
-/// GET /{id} -- get a single room by ID.
+/// The tenant's `max_room_size` setting, if the room belongs to a tenant that sets one.
+async fn room_size_limit(state: &AppState, tenant_id: Option<Uuid>) -> AppResult<Option<i64>> {
+    match tenant_id {
+        Some(tenant_id) => {
+            tenant_config::get(&state.pool, tenant_id, &tenant_config::MAX_ROOM_SIZE).await
+        }
+        None => Ok(None),
+    }
+}
+
+/// Reject a room capacity above the tenant's limit.
+fn check_room_size(max_members: i32, limit: Option<i64>) -> AppResult<i32> {
+    match limit {
+        Some(limit) if i64::from(max_members) > limit => Err(AppError::Validation(
+            format!("max_members cannot exceed this tenant's room size limit of {limit}").into(),
+        )),
+        _ => Ok(max_members),
+    }
+}
+
+/// GET /{room_id} -- get a single room by ID.
+#[utoipa::path(
+    get,
+    path = "/api/v1/rooms/{room_id}",
+    tag = "rooms",
+    params(
+        ("room_id" = Uuid, Path, description = "Room ID"),
+        DeletedFilter,
+    ),
+    responses(
+        (status = 200, description = "The room", body = RoomResponse),
+        (status = 401, description = "Missing or invalid access token", body = ErrorBody),
+        (status = 403, description = "Deleted room requested by a non-moderator, or another tenant's room", body = ErrorBody),
+        (status = 404, description = "Room not found or deleted", body = ErrorBody),
+    ),
+    security(("bearer_auth" = [])),
+)]
 async fn get_room(
     State(state): State<Arc<AppState>>,
-    _auth_user: AuthUser,
+    auth_user: AuthUser,
     Path(id): Path<Uuid>,
+    Query(deleted): Query<DeletedFilter>,
 ) -> AppResult<Json<RoomResponse>> {
-    let room = sqlx::query_as::<_, Room>("SELECT * FROM rooms WHERE id = $1")
-        .bind(id)
-        .fetch_optional(&state.pool)
+    let include_deleted = deleted.resolve(&state.pool, &auth_user, Some(id)).await?;
+
+    let room =
+        sqlx::query_as::<_, Room>("SELECT * FROM rooms WHERE id = $1 AND ($2 OR is_active = true)")
+            .bind(id)
+            .bind(include_deleted)
+            .fetch_optional(&state.pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Room not found".into()))?;
+    TenantScope::for_user(&state, auth_user.id)
         .await?
-        .ok_or_else(|| AppError::NotFound("Room not found".into()))?;
+        .require_room(&state, room.id, room.tenant_id)
+        .await?;
 
     Ok(Json(RoomResponse::from(room)))
 }
 
-/// PUT /{id} -- update a room.
+/// PUT /{room_id} -- update a room. Absent and `null` fields are both left unchanged.
+#[utoipa::path(
+    put,
+    path = "/api/v1/rooms/{room_id}",
+    tag = "rooms",
+    params(
+        ("room_id" = Uuid, Path, description = "Room ID"),
+    ),
+    request_body = UpdateRoomRequest,
+    responses(
+        (status = 200, description = "Updated room", body = RoomResponse),
+        (status = 401, description = "Missing or invalid access token", body = ErrorBody),
+        (status = 403, description = "Caller may not change these fields", body = ErrorBody),
+        (status = 404, description = "Room not found", body = ErrorBody),
+        (status = 422, description = "Invalid request body", body = ErrorBody),
+    ),
+    security(("bearer_auth" = [])),
+)]
 async fn update_room(
     State(state): State<Arc<AppState>>,
-    auth_user: AuthUser,
+    RoomModerator(membership): RoomModerator,
     Path(id): Path<Uuid>,
     Json(body): Json<UpdateRoomRequest>,
 ) -> AppResult<Json<RoomResponse>> {
-    // Only host or moderator can update a room
-    require_room_moderator(&state.pool, auth_user.id, id).await?;
+    apply_room_update(&state, &membership, id, body.ignore_nulls()).await
+}
+
+/// PATCH /{room_id} -- update a room. Absent fields are left unchanged; `null` clears a
+/// nullable field.
+#[utoipa::path(
+    patch,
+    path = "/api/v1/rooms/{room_id}",
+    tag = "rooms",
+    params(
+        ("room_id" = Uuid, Path, description = "Room ID"),
+    ),
+    request_body = UpdateRoomRequest,
+    responses(
+        (status = 200, description = "Updated room", body = RoomResponse),
+        (status = 401, description = "Missing or invalid access token", body = ErrorBody),
+        (status = 403, description = "Caller may not change these fields", body = ErrorBody),
+        (status = 404, description = "Room not found", body = ErrorBody),
+        (status = 422, description = "Invalid request body", body = ErrorBody),
+    ),
+    security(("bearer_auth" = [])),
+)]
+async fn patch_room(
+    State(state): State<Arc<AppState>>,
+    RoomModerator(membership): RoomModerator,
+    Path(id): Path<Uuid>,
+    Json(body): Json<UpdateRoomRequest>,
+) -> AppResult<Json<RoomResponse>> {
+    apply_room_update(&state, &membership, id, body).await
+}
 
+/// Validate and apply a room update on behalf of a moderator or host.
+async fn apply_room_update(
+    state: &AppState,
+    membership: &RoomMembership,
+    id: Uuid,
+    body: UpdateRoomRequest,
+) -> AppResult<Json<RoomResponse>> {
     body.validate()
-        .map_err(|e| AppError::Validation(e.to_string()))?;
+        .map_err(|e| AppError::Validation(e.to_string().into()))?;
 
+    // Capacity, active state, naming, and content policy stay with the host
+    let host_only = body.host_only_fields();
+    if !host_only.is_empty() && membership.role != MemberRole::Host {
+        return Err(AppError::Forbidden(
+            format!("Only the host can change: {}", host_only.join(", ")).into(),
+        ));
+    }
+
+    if let Some(max_members) = body.max_members {
+        let tenant_id =
+            sqlx::query_scalar::<_, Option<Uuid>>("SELECT tenant_id FROM rooms WHERE id = $1")
+                .bind(id)
+                .fetch_optional(&state.pool)
+                .await?
+                .ok_or_else(|| AppError::NotFound("Room not found".into()))?;
+        check_room_size(max_members, room_size_limit(state, tenant_id).await?)?;
+    }
+
+    // Nullable columns take a "set" flag and a value, so an explicit null can clear them
     let room = sqlx::query_as::<_, Room>(
         r#"
         UPDATE rooms SET
             name                 = COALESCE($1, name),
-            title                = COALESCE($2, title),
-            description          = COALESCE($3, description),
-            max_members          = COALESCE($4, max_members),
-            is_active            = COALESCE($5, is_active),
-            background_image_url = COALESCE($6, background_image_url),
-            header_color         = COALESCE($7, header_color),
-            accent_color         = COALESCE($8, accent_color),
-            font_family          = COALESCE($9, font_family),
-            border_style         = COALESCE($10, border_style),
-            shadow_style         = COALESCE($11, shadow_style),
-            updated_at           = NOW()
-        WHERE id = $12
+            title                = CASE WHEN $2 THEN $3 ELSE title END,
+            description          = CASE WHEN $4 THEN $5 ELSE description END,
+            max_members          = COALESCE($6, max_members),
+            is_active            = COALESCE($7, is_active),
+            background_image_url = CASE WHEN $8 THEN $9 ELSE background_image_url END,
+            header_color         = CASE WHEN $10 THEN $11 ELSE header_color END,
+            accent_color         = CASE WHEN $12 THEN $13 ELSE accent_color END,
+            font_family          = CASE WHEN $14 THEN $15 ELSE font_family END,
+            border_style         = CASE WHEN $16 THEN $17 ELSE border_style END,
+            shadow_style         = CASE WHEN $18 THEN $19 ELSE shadow_style END,
+            sanitize_content     = COALESCE($20, sanitize_content),
+            linkify_urls         = COALESCE($21, linkify_urls),
+            message_retention_days  = COALESCE($22, message_retention_days),
+            retention_exempt_pinned = COALESCE($23, retention_exempt_pinned),
+            report_auto_hide_threshold =
+                CASE WHEN $24 THEN $25 ELSE report_auto_hide_threshold END,
+            updated_at           = NOW(),
+            updated_by           = $27
+        WHERE id = $26
         RETURNING *
         "#,
     )
     .bind(&body.name)
-    .bind(&body.title)
-    .bind(&body.description)
+    .bind(body.title.is_set())
+    .bind(body.title.value())
+    .bind(body.description.is_set())
+    .bind(body.description.value())
     .bind(body.max_members)
     .bind(body.is_active)
-    .bind(&body.background_image_url)
-    .bind(&body.header_color)
-    .bind(&body.accent_color)
-    .bind(&body.font_family)
-    .bind(&body.border_style)
-    .bind(&body.shadow_style)
+    .bind(body.background_image_url.is_set())
+    .bind(body.background_image_url.value())
+    .bind(body.header_color.is_set())
+    .bind(body.header_color.value())
+    .bind(body.accent_color.is_set())
+    .bind(body.accent_color.value())
+    .bind(body.font_family.is_set())
+    .bind(body.font_family.value())
+    .bind(body.border_style.is_set())
+    .bind(body.border_style.value())
+    .bind(body.shadow_style.is_set())
+    .bind(body.shadow_style.value())
+    .bind(body.sanitize_content)
+    .bind(body.linkify_urls)
+    .bind(body.message_retention_days)
+    .bind(body.retention_exempt_pinned)
+    .bind(body.report_auto_hide_threshold.is_set())
+    .bind(body.report_auto_hide_threshold.value())
     .bind(id)
+    .bind(membership.user_id)
     .fetch_optional(&state.pool)
     .await?
     .ok_or_else(|| AppError::NotFound("Room not found".into()))?;
@@ -183,20 +452,103 @@ async fn update_room(
     Ok(Json(RoomResponse::from(room)))
 }
 
-/// DELETE /{id} -- soft-delete a room by deactivating it.
-async fn delete_room(
+/// PUT /{room_id}/background -- upload a background image for the room via multipart and
+/// point `background_image_url` at it. A replaced image stored in this server's bucket is
+/// queued for deletion.
+#[utoipa::path(
+    put,
+    path = "/api/v1/rooms/{room_id}/background",
+    tag = "rooms",
+    params(
+        ("room_id" = Uuid, Path, description = "Room ID"),
+    ),
+    request_body(
+        content_type = "multipart/form-data",
+        description = "One `image` field holding a JPEG, PNG, GIF, WebP, or ICO image",
+    ),
+    responses(
+        (status = 200, description = "Updated room", body = RoomResponse),
+        (status = 400, description = "Invalid multipart body, rejected image, or storage not configured", body = ErrorBody),
+        (status = 401, description = "Missing or invalid access token", body = ErrorBody),
+        (status = 403, description = "Caller is not a moderator of the room", body = ErrorBody),
+        (status = 404, description = "Room not found", body = ErrorBody),
+    ),
+    security(("bearer_auth" = [])),
+)]
+async fn upload_room_background(
     State(state): State<Arc<AppState>>,
-    auth_user: AuthUser,
+    RoomModerator(membership): RoomModerator,
     Path(id): Path<Uuid>,
-) -> AppResult<StatusCode> {
-    // Only the host can delete a room
-    require_room_host(&state.pool, auth_user.id, id).await?;
+    multipart: Multipart,
+) -> AppResult<Json<RoomResponse>> {
+    let url = store_branding_image(
+        &state,
+        multipart,
+        "image",
+        &format!("rooms/{id}/background"),
+    )
+    .await?;
+
+    let mut tx = state.pool.begin().await?;
 
-    let result =
-        sqlx::query("UPDATE rooms SET is_active = false, updated_at = NOW() WHERE id = $1")
+    let previous: Option<String> =
+        sqlx::query_scalar("SELECT background_image_url FROM rooms WHERE id = $1 FOR UPDATE")
             .bind(id)
-            .execute(&state.pool)
-            .await?;
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Room not found".into()))?;
+
+    let room = sqlx::query_as::<_, Room>(
+        r#"
+        UPDATE rooms SET background_image_url = $1, updated_at = NOW(), updated_by = $2
+        WHERE id = $3
+        RETURNING *
+        "#,
+    )
+    .bind(&url)
+    .bind(membership.user_id)
+    .bind(id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let queued = queue_unreferenced_object(&state, &mut *tx, previous.as_deref()).await?;
+
+    tx.commit().await?;
+    if queued {
+        storage_cleanup::wake(&state);
+    }
+
+    Ok(Json(RoomResponse::from(room)))
+}
+
+/// DELETE /{room_id} -- soft-delete a room by deactivating it.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/rooms/{room_id}",
+    tag = "rooms",
+    params(
+        ("room_id" = Uuid, Path, description = "Room ID"),
+    ),
+    responses(
+        (status = 204, description = "Room deleted"),
+        (status = 401, description = "Missing or invalid access token", body = ErrorBody),
+        (status = 403, description = "Only the host can delete the room", body = ErrorBody),
+        (status = 404, description = "Room not found", body = ErrorBody),
+    ),
+    security(("bearer_auth" = [])),
+)]
+async fn delete_room(
+    State(state): State<Arc<AppState>>,
+    RoomHost(host): RoomHost,
+    Path(id): Path<Uuid>,
+) -> AppResult<StatusCode> {
+    let result = sqlx::query(
+        "UPDATE rooms SET is_active = false, deleted_at = NOW(), updated_at = NOW(), updated_by = $2 WHERE id = $1",
+    )
+    .bind(id)
+    .bind(host.user_id)
+    .execute(&state.pool)
+    .await?;
 
     if result.rows_affected() == 0 {
         return Err(AppError::NotFound("Room not found".into()));
@@ -205,34 +557,234 @@ async fn delete_room(
     Ok(StatusCode::NO_CONTENT)
 }
 
-/// GET /{id}/members -- list members of a room.
+/// GET /{room_id}/features -- whether each feature is on in a room, after tenant and server defaults.
+#[utoipa::path(
+    get,
+    path = "/api/v1/rooms/{room_id}/features",
+    tag = "rooms",
+    params(
+        ("room_id" = Uuid, Path, description = "Room ID"),
+    ),
+    responses(
+        (status = 200, description = "Feature name to enabled", body = BTreeMap<String, bool>),
+        (status = 401, description = "Missing or invalid access token", body = ErrorBody),
+        (status = 403, description = "Not a member of the room", body = ErrorBody),
+    ),
+    security(("bearer_auth" = [])),
+)]
+async fn get_room_features(
+    State(state): State<Arc<AppState>>,
+    _member: RoomMember,
+    Path(id): Path<Uuid>,
+) -> AppResult<Json<BTreeMap<Feature, bool>>> {
+    Ok(Json(state.room_features(id).await?))
+}
+
+/// PUT /{room_id}/features -- set or clear (`null`) this room's feature overrides.
+#[utoipa::path(
+    put,
+    path = "/api/v1/rooms/{room_id}/features",
+    tag = "rooms",
+    params(
+        ("room_id" = Uuid, Path, description = "Room ID"),
+    ),
+    request_body(content = BTreeMap<String, Option<bool>>, description = "Feature name to override; null falls back to the tenant"),
+    responses(
+        (status = 200, description = "Feature name to enabled, after the change", body = BTreeMap<String, bool>),
+        (status = 401, description = "Missing or invalid access token", body = ErrorBody),
+        (status = 403, description = "Only the host can change room features", body = ErrorBody),
+        (status = 404, description = "Room not found", body = ErrorBody),
+        (status = 422, description = "Unknown feature name", body = ErrorBody),
+    ),
+    security(("bearer_auth" = [])),
+)]
+async fn update_room_features(
+    State(state): State<Arc<AppState>>,
+    RoomHost(host): RoomHost,
+    Path(id): Path<Uuid>,
+    Json(body): Json<BTreeMap<String, Option<bool>>>,
+) -> AppResult<Json<BTreeMap<Feature, bool>>> {
+    if let Some(name) = body.keys().find(|name| Feature::parse(name).is_none()) {
+        return Err(AppError::Validation(
+            format!("Unknown feature '{name}'").into(),
+        ));
+    }
+
+    // Merging nulls and then stripping them removes those overrides
+    let result = sqlx::query(
+        r#"
+        UPDATE rooms SET feature_flags = jsonb_strip_nulls(feature_flags || $1),
+                         updated_at = NOW(), updated_by = $3
+        WHERE id = $2
+        "#,
+    )
+    .bind(sqlx::types::Json(&body))
+    .bind(id)
+    .bind(host.user_id)
+    .execute(&state.pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Room not found".into()));
+    }
+
+    state.invalidate_features(FlagScope::Room(id));
+    Ok(Json(state.room_features(id).await?))
+}
+
+/// GET /{room_id}/permissions -- the minimum role for each capability in a room (host-only).
+#[utoipa::path(
+    get,
+    path = "/api/v1/rooms/{room_id}/permissions",
+    tag = "rooms",
+    params(
+        ("room_id" = Uuid, Path, description = "Room ID"),
+    ),
+    responses(
+        (status = 200, description = "Capability name to minimum role", body = BTreeMap<String, MemberRole>),
+        (status = 401, description = "Missing or invalid access token", body = ErrorBody),
+        (status = 403, description = "Only the host can view room permissions", body = ErrorBody),
+    ),
+    security(("bearer_auth" = [])),
+)]
+async fn get_room_permissions(
+    State(state): State<Arc<AppState>>,
+    _host: RoomHost,
+    Path(id): Path<Uuid>,
+) -> AppResult<Json<BTreeMap<Capability, MemberRole>>> {
+    Ok(Json(room_permissions(&state.pool, id).await?))
+}
+
+/// PUT /{room_id}/permissions -- set or reset (`null`) the minimum role for capabilities.
+#[utoipa::path(
+    put,
+    path = "/api/v1/rooms/{room_id}/permissions",
+    tag = "rooms",
+    params(
+        ("room_id" = Uuid, Path, description = "Room ID"),
+    ),
+    request_body(content = BTreeMap<String, Option<String>>, description = "Capability name (post_alert, create_poll, post_message, upload_file) to minimum role (host, moderator, member); null restores the default"),
+    responses(
+        (status = 200, description = "Capability name to minimum role, after the change", body = BTreeMap<String, MemberRole>),
+        (status = 401, description = "Missing or invalid access token", body = ErrorBody),
+        (status = 403, description = "Only the host can change room permissions", body = ErrorBody),
+        (status = 404, description = "Room not found", body = ErrorBody),
+        (status = 422, description = "Unknown capability or role", body = ErrorBody),
+    ),
+    security(("bearer_auth" = [])),
+)]
+async fn update_room_permissions(
+    State(state): State<Arc<AppState>>,
+    RoomHost(host): RoomHost,
+    Path(id): Path<Uuid>,
+    Json(body): Json<BTreeMap<String, Option<MemberRole>>>,
+) -> AppResult<Json<BTreeMap<Capability, MemberRole>>> {
+    if let Some(name) = body.keys().find(|name| Capability::parse(name).is_none()) {
+        return Err(AppError::Validation(
+            format!("Unknown capability '{name}'").into(),
+        ));
+    }
+
+    // Merging nulls and then stripping them removes those settings
+    let result = sqlx::query(
+        r#"
+        UPDATE rooms SET permissions = jsonb_strip_nulls(permissions || $1),
+                         updated_at = NOW(), updated_by = $3
+        WHERE id = $2
+        "#,
+    )
+    .bind(sqlx::types::Json(&body))
+    .bind(id)
+    .bind(host.user_id)
+    .execute(&state.pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Room not found".into()));
+    }
+
+    Ok(Json(room_permissions(&state.pool, id).await?))
+}
+
+/// GET /{room_id}/members -- list members of a room.
+#[utoipa::path(
+    get,
+    path = "/api/v1/rooms/{room_id}/members",
+    tag = "rooms",
+    params(
+        ("room_id" = Uuid, Path, description = "Room ID"),
+    ),
+    responses(
+        (status = 200, description = "Room members", body = Vec<MembershipResponse>),
+        (status = 401, description = "Missing or invalid access token", body = ErrorBody),
+        (status = 403, description = "Not allowed in this room", body = ErrorBody),
+    ),
+    security(("bearer_auth" = [])),
+)]
 async fn list_members(
     State(state): State<Arc<AppState>>,
-    _auth_user: AuthUser,
+    auth_user: AuthUser,
     Path(id): Path<Uuid>,
 ) -> AppResult<Json<Vec<MembershipResponse>>> {
-    let members = sqlx::query_as::<_, RoomMembership>(
-        "SELECT * FROM room_memberships WHERE room_id = $1 ORDER BY created_at ASC",
+    require_room_tenant(&state, auth_user.id, id).await?;
+
+    let members = sqlx::query_as::<_, MembershipWithLastSeen>(
+        r#"
+        SELECT m.*, u.last_seen_at
+        FROM room_memberships m
+        JOIN users u ON u.id = m.user_id
+        WHERE m.room_id = $1
+        ORDER BY m.created_at ASC
+        "#,
     )
     .bind(id)
     .fetch_all(&state.pool)
     .await?;
 
-    let results: Vec<MembershipResponse> =
-        members.into_iter().map(MembershipResponse::from).collect();
+    // Hosts and moderators also see who last changed each membership
+    let is_moderator = members.iter().map(|m| &m.membership).any(|m| {
+        m.user_id == auth_user.id
+            && m.status == MemberStatus::Active
+            && matches!(m.role, MemberRole::Host | MemberRole::Moderator)
+    });
+    let to_response = if is_moderator {
+        MembershipResponse::with_audit
+    } else {
+        MembershipResponse::from
+    };
+    let results: Vec<MembershipResponse> = members
+        .into_iter()
+        .map(|m| {
+            let online = state.is_online(m.membership.user_id);
+            to_response(m.membership).with_presence(m.last_seen_at, online)
+        })
+        .collect();
     Ok(Json(results))
 }
 
-/// POST /{id}/members -- invite/add a member to a room.
+/// POST /{room_id}/members -- invite/add a member to a room.
+#[utoipa::path(
+    post,
+    path = "/api/v1/rooms/{room_id}/members",
+    tag = "rooms",
+    params(
+        ("room_id" = Uuid, Path, description = "Room ID"),
+    ),
+    request_body = Value,
+    responses(
+        (status = 201, description = "Member added", body = MembershipResponse),
+        (status = 400, description = "Missing or invalid user_id", body = ErrorBody),
+        (status = 401, description = "Missing or invalid access token", body = ErrorBody),
+        (status = 403, description = "Only hosts and moderators can invite", body = ErrorBody),
+    ),
+    security(("bearer_auth" = [])),
+)]
 async fn invite_member(
     State(state): State<Arc<AppState>>,
-    auth_user: AuthUser,
+    _moderator: RoomModerator,
     Path(id): Path<Uuid>,
     Json(body): Json<Value>,
-) -> AppResult<(StatusCode, Json<MembershipResponse>)> {
-    // Only host or moderator can invite members
-    require_room_moderator(&state.pool, auth_user.id, id).await?;
-
+) -> AppResult<Created<MembershipResponse>> {
     let user_id = body
         .get("user_id")
         .and_then(|v| v.as_str())
@@ -258,27 +810,36 @@ async fn invite_member(
     .fetch_one(&state.pool)
     .await?;
 
-    Ok((
-        StatusCode::CREATED,
-        Json(MembershipResponse::from(membership)),
+    Ok(created(
+        format!("/api/v1/rooms/{id}/members/{user_id}"),
+        MembershipResponse::from(membership),
     ))
 }
 
-/// DELETE /{id}/members/{user_id} -- remove a member from a room.
+/// DELETE /{room_id}/members/{user_id} -- remove a member from a room. Follows the
+/// moderation hierarchy: not yourself, never the host, and moderators only by the host.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/rooms/{room_id}/members/{user_id}",
+    tag = "rooms",
+    params(
+        ("room_id" = Uuid, Path, description = "Room ID"),
+        ("user_id" = Uuid, Path, description = "Member user ID"),
+    ),
+    responses(
+        (status = 204, description = "Member removed"),
+        (status = 401, description = "Missing or invalid access token", body = ErrorBody),
+        (status = 403, description = "Not a host or moderator, or the target outranks the caller", body = ErrorBody),
+        (status = 404, description = "Membership not found", body = ErrorBody),
+    ),
+    security(("bearer_auth" = [])),
+)]
 async fn remove_member(
     State(state): State<Arc<AppState>>,
-    auth_user: AuthUser,
+    RoomModerator(moderator): RoomModerator,
     Path((room_id, user_id)): Path<(Uuid, Uuid)>,
 ) -> AppResult<StatusCode> {
-    // Only host or moderator can remove members
-    require_room_moderator(&state.pool, auth_user.id, room_id).await?;
-
-    // Cannot remove yourself
-    if auth_user.id == user_id {
-        return Err(AppError::BadRequest(
-            "You cannot remove yourself from the room".into(),
-        ));
-    }
+    ensure_can_moderate(&state.pool, &moderator, user_id).await?;
 
     let result = sqlx::query("DELETE FROM room_memberships WHERE room_id = $1 AND user_id = $2")
         .bind(room_id)
@@ -293,19 +854,33 @@ async fn remove_member(
     Ok(StatusCode::NO_CONTENT)
 }
 
-/// PUT /{id}/members/{user_id}/role -- update a member's role.
+/// PUT /{room_id}/members/{user_id}/role -- update a member's role.
+#[utoipa::path(
+    put,
+    path = "/api/v1/rooms/{room_id}/members/{user_id}/role",
+    tag = "rooms",
+    params(
+        ("room_id" = Uuid, Path, description = "Room ID"),
+        ("user_id" = Uuid, Path, description = "Member user ID"),
+    ),
+    request_body = UpdateMemberRoleRequest,
+    responses(
+        (status = 200, description = "Updated membership", body = MembershipResponse),
+        (status = 401, description = "Missing or invalid access token", body = ErrorBody),
+        (status = 403, description = "Only the host can change roles", body = ErrorBody),
+        (status = 404, description = "Membership not found", body = ErrorBody),
+    ),
+    security(("bearer_auth" = [])),
+)]
 async fn update_member_role(
     State(state): State<Arc<AppState>>,
-    auth_user: AuthUser,
+    RoomHost(host): RoomHost,
     Path((room_id, user_id)): Path<(Uuid, Uuid)>,
     Json(body): Json<UpdateMemberRoleRequest>,
 ) -> AppResult<Json<MembershipResponse>> {
-    // Only the host can change member roles
-    require_room_host(&state.pool, auth_user.id, room_id).await?;
-
     let membership = sqlx::query_as::<_, RoomMembership>(
         r#"
-        UPDATE room_memberships SET role = $1, updated_at = NOW()
+        UPDATE room_memberships SET role = $1, updated_at = NOW(), updated_by = $4
         WHERE room_id = $2 AND user_id = $3
         RETURNING *
         "#,
@@ -313,24 +888,47 @@ async fn update_member_role(
     .bind(&body.role)
     .bind(room_id)
     .bind(user_id)
+    .bind(host.user_id)
     .fetch_optional(&state.pool)
     .await?
     .ok_or_else(|| AppError::NotFound("Membership not found".into()))?;
 
-    Ok(Json(MembershipResponse::from(membership)))
+    Ok(Json(MembershipResponse::with_audit(membership)))
 }
 
 /// GET /by-tenant/{tenant_id} -- list rooms belonging to a tenant.
+#[utoipa::path(
+    get,
+    path = "/api/v1/rooms/by-tenant/{tenant_id}",
+    tag = "rooms",
+    params(
+        ("tenant_id" = Uuid, Path, description = "Tenant ID"),
+        RawPaginationParams,
+        DeletedFilter,
+    ),
+    responses(
+        (status = 200, description = "The tenant's active rooms", body = Vec<RoomResponse>),
+        (status = 401, description = "Missing or invalid access token", body = ErrorBody),
+        (status = 403, description = "Deleted rooms requested by a non-admin, or another tenant", body = ErrorBody),
+    ),
+    security(("bearer_auth" = [])),
+)]
 async fn list_rooms_by_tenant(
     State(state): State<Arc<AppState>>,
-    _auth_user: AuthUser,
+    auth_user: AuthUser,
     Path(tenant_id): Path<Uuid>,
-    Query(pagination): Query<PaginationParams>,
+    pagination: PaginationParams,
+    Query(deleted): Query<DeletedFilter>,
 ) -> AppResult<Json<Vec<RoomResponse>>> {
+    TenantScope::for_user(&state, auth_user.id)
+        .await?
+        .require(Some(tenant_id))?;
+    let include_deleted = deleted.resolve(&state.pool, &auth_user, None).await?;
+
     let rooms = sqlx::query_as::<_, Room>(
         r#"
         SELECT * FROM rooms
-        WHERE tenant_id = $1 AND is_active = true
+        WHERE tenant_id = $1 AND ($4 OR is_active = true)
         ORDER BY created_at DESC
         LIMIT $2 OFFSET $3
         "#,
@@ -338,6 +936,7 @@ async fn list_rooms_by_tenant(
     .bind(tenant_id)
     .bind(pagination.limit())
     .bind(pagination.offset())
+    .bind(include_deleted)
     .fetch_all(&state.pool)
     .await?;
 
@@ -1,32 +1,51 @@
 use std::sync::Arc;
+use std::time::Duration;
 
+use aws_sdk_s3::error::{DisplayErrorContext, ProvideErrorMetadata, SdkError};
 use axum::{
-    extract::{Json, Multipart, Path, State},
-    http::StatusCode,
-    routing::{delete, get, post},
+    body::Body,
+    extract::{Multipart, Path, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{delete, get, post, put},
     Router,
 };
+use bytes::Bytes;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use sqlx::FromRow;
+use sqlx::{FromRow, PgExecutor};
 use uuid::Uuid;
+use validator::Validate;
 
 use crate::{
     error::{AppError, AppResult},
-    extractors::auth::AuthUser,
+    extractors::{
+        auth::AuthUser,
+        json::Json,
+        room_access::{require_room_member, require_room_moderator, RoomMember},
+    },
+    routes::{created, Created},
+    services::{
+        image_optimizer,
+        room_permissions::{require_capability, Capability},
+        storage_cleanup,
+    },
     state::AppState,
+    ws::{channels::Channel, outbox},
 };
 
 #[derive(Debug, FromRow, Serialize)]
-struct RoomFile {
-    id: Uuid,
+pub(crate) struct RoomFile {
+    pub id: Uuid,
     room_id: Uuid,
     uploaded_by: Uuid,
     file_name: String,
     file_url: String,
     file_size: i64,
     mime_type: String,
+    #[serde(skip)]
+    storage_key: String,
     created_at: DateTime<Utc>,
 }
 
@@ -37,10 +56,29 @@ struct Note {
     user_id: Uuid,
     title: String,
     content: String,
+    locked_by: Option<Uuid>,
+    lock_expires_at: Option<DateTime<Utc>>,
     created_at: DateTime<Utc>,
     updated_at: DateTime<Utc>,
 }
 
+#[derive(Debug, FromRow, Serialize)]
+struct NoteVersion {
+    id: Uuid,
+    note_id: Uuid,
+    version: i32,
+    title: String,
+    content: String,
+    edited_by: Option<Uuid>,
+    created_at: DateTime<Utc>,
+}
+
+/// How long a note editing lock lasts without being renewed.
+const NOTE_LOCK_TTL_SECS: i32 = 120;
+
+/// Longest an upload to S3 may take before the request is failed as retryable.
+const S3_PUT_TIMEOUT: Duration = Duration::from_secs(30);
+
 pub(crate) const MAX_UPLOAD_SIZE: usize = 50 * 1024 * 1024; // 50MB
 
 pub(crate) const ALLOWED_CONTENT_TYPES: &[&str] = &[
@@ -71,6 +109,17 @@ pub(crate) const ALLOWED_MEDIA_TYPES: &[&str] = &[
     "audio/mpeg",
 ];
 
+/// Image types accepted for branding images such as room backgrounds and tenant logos.
+/// SVG is left out because it can carry script.
+pub(crate) const ALLOWED_BRANDING_IMAGE_TYPES: &[&str] = &[
+    "image/jpeg",
+    "image/png",
+    "image/gif",
+    "image/webp",
+    "image/x-icon",
+    "image/vnd.microsoft.icon",
+];
+
 /// Sanitize a filename by stripping directory components and dangerous characters
 /// to prevent path traversal attacks.
 pub(crate) fn sanitize_filename(raw: &str) -> String {
@@ -88,6 +137,22 @@ pub(crate) fn sanitize_filename(raw: &str) -> String {
     }
 }
 
+/// Object key of a room file; derived from the row so it never comes from the client.
+fn room_file_key(room_id: Uuid, file_id: Uuid, file_name: &str) -> String {
+    format!("rooms/{room_id}/files/{file_id}/{file_name}")
+}
+
+/// Reject uploads when no S3 endpoint is configured.
+pub(crate) fn require_storage(state: &AppState) -> AppResult<()> {
+    if state.config.storage_enabled() {
+        Ok(())
+    } else {
+        Err(AppError::BadRequest(
+            "File storage is not configured on this server".into(),
+        ))
+    }
+}
+
 /// Validate an upload's size and content type against an allowlist.
 pub(crate) fn validate_upload(
     data_len: usize,
@@ -100,23 +165,244 @@ pub(crate) fn validate_upload(
         ));
     }
     if !allowed_types.contains(&content_type) {
-        return Err(AppError::BadRequest(format!(
-            "File type '{}' is not allowed",
-            content_type
-        )));
+        return Err(AppError::BadRequest(
+            format!("File type '{}' is not allowed", content_type).into(),
+        ));
     }
     Ok(())
 }
 
+/// Store an uploaded object. Timeouts, connection failures, and 5xx responses from S3
+/// surface as a retryable 503; anything else is a server-side misconfiguration (500).
+pub(crate) async fn put_object(
+    state: &AppState,
+    key: &str,
+    data: Bytes,
+    content_type: &str,
+) -> AppResult<()> {
+    let request = state
+        .s3
+        .put_object()
+        .bucket(&state.config.s3_bucket)
+        .key(key)
+        .body(data.into())
+        .content_type(content_type)
+        .send();
+
+    match tokio::time::timeout(S3_PUT_TIMEOUT, request).await {
+        Ok(Ok(_)) => Ok(()),
+        Ok(Err(e)) => {
+            let transient = match &e {
+                SdkError::TimeoutError(_) | SdkError::DispatchFailure(_) => true,
+                SdkError::ResponseError(e) => e.raw().status().is_server_error(),
+                SdkError::ServiceError(e) => e.raw().status().is_server_error(),
+                _ => false,
+            };
+            let message = format!("S3 upload of {key} failed: {}", DisplayErrorContext(&e));
+            if transient {
+                Err(AppError::ServiceUnavailable(message))
+            } else {
+                Err(AppError::Internal(message))
+            }
+        }
+        Err(_) => Err(AppError::ServiceUnavailable(format!(
+            "S3 upload of {key} timed out after {}s",
+            S3_PUT_TIMEOUT.as_secs()
+        ))),
+    }
+}
+
+/// A validated upload, after large images have been re-encoded.
+pub(crate) struct PreparedUpload {
+    pub file_name: String,
+    pub content_type: String,
+    pub data: Bytes,
+    /// The untouched upload and its content type, when it was re-encoded and
+    /// `IMAGE_KEEP_ORIGINALS` is set.
+    original: Option<(Bytes, String)>,
+}
+
+impl PreparedUpload {
+    pub fn size(&self) -> i64 {
+        self.data.len() as i64
+    }
+}
+
+/// Re-encode a large image upload when enabled, renaming it to the new format's
+/// extension. Anything else, or an image that fails to transcode, passes through as-is.
+pub(crate) async fn prepare_upload(
+    state: &AppState,
+    file_name: String,
+    content_type: String,
+    data: Bytes,
+) -> PreparedUpload {
+    match image_optimizer::optimize(&state.config, &data, &content_type).await {
+        Some((format, encoded)) => PreparedUpload {
+            file_name: with_extension(&file_name, format.extension()),
+            content_type: format.content_type().to_string(),
+            data: encoded,
+            original: state
+                .config
+                .image_keep_originals
+                .then_some((data, content_type)),
+        },
+        None => PreparedUpload {
+            file_name,
+            content_type,
+            data,
+            original: None,
+        },
+    }
+}
+
+/// Store a prepared upload under `key`, and its kept original under `originals/{key}`.
+/// Returns the original's key, if one was stored.
+pub(crate) async fn put_upload(
+    state: &AppState,
+    key: &str,
+    upload: &PreparedUpload,
+) -> AppResult<Option<String>> {
+    put_object(state, key, upload.data.clone(), &upload.content_type).await?;
+
+    let Some((data, content_type)) = &upload.original else {
+        return Ok(None);
+    };
+    let original_key = format!("originals/{key}");
+    put_object(state, &original_key, data.clone(), content_type).await?;
+    Ok(Some(original_key))
+}
+
+/// Read the image in multipart field `field_name`, validate it like other uploads, and
+/// store it under `{prefix}/{random id}/{file name}` so a replacement never reuses a
+/// cached URL. Returns the stored image's public URL.
+pub(crate) async fn store_branding_image(
+    state: &AppState,
+    mut multipart: Multipart,
+    field_name: &str,
+    prefix: &str,
+) -> AppResult<String> {
+    require_storage(state)?;
+
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| AppError::BadRequest(format!("Multipart error: {e}").into()))?
+    {
+        if field.name() != Some(field_name) {
+            continue;
+        }
+        let file_name = sanitize_filename(field.file_name().unwrap_or("image.png"));
+        let content_type = field
+            .content_type()
+            .unwrap_or("application/octet-stream")
+            .to_string();
+        let data = field
+            .bytes()
+            .await
+            .map_err(|e| AppError::BadRequest(format!("Failed to read file: {e}").into()))?;
+        validate_upload(data.len(), &content_type, ALLOWED_BRANDING_IMAGE_TYPES)?;
+
+        let upload = prepare_upload(state, file_name, content_type, data).await;
+        let key = format!("{prefix}/{}/{}", Uuid::new_v4(), upload.file_name);
+        put_upload(state, &key, &upload).await?;
+
+        return Ok(format!(
+            "{}/{}/{}",
+            state.config.s3_endpoint, state.config.s3_bucket, key
+        ));
+    }
+
+    Err(AppError::BadRequest(
+        format!("No {field_name} field found in multipart body").into(),
+    ))
+}
+
+/// Queue the object behind a URL that is no longer referenced for deletion, when it lives
+/// in this server's bucket. Returns whether anything was queued; the caller wakes
+/// [`storage_cleanup`] once its transaction commits.
+pub(crate) async fn queue_unreferenced_object<'e>(
+    state: &AppState,
+    executor: impl PgExecutor<'e>,
+    url: Option<&str>,
+) -> AppResult<bool> {
+    let Some(key) = url.and_then(|url| state.config.storage_key_for_url(url)) else {
+        return Ok(false);
+    };
+    sqlx::query("INSERT INTO storage_deletions (storage_key) VALUES ($1)")
+        .bind(key)
+        .execute(executor)
+        .await?;
+    Ok(true)
+}
+
+/// Upload a prepared file to a room's storage and record it. The row is written through
+/// `executor`, so a caller's transaction can tie it to other inserts; if that
+/// transaction rolls back, the stored object is left for the storage reaper.
+pub(crate) async fn store_room_file<'e>(
+    state: &AppState,
+    executor: impl PgExecutor<'e>,
+    room_id: Uuid,
+    user_id: Uuid,
+    upload: &PreparedUpload,
+) -> AppResult<RoomFile> {
+    let file_id = Uuid::new_v4();
+    let key = room_file_key(room_id, file_id, &upload.file_name);
+
+    let original_key = put_upload(state, &key, upload).await?;
+
+    let url = format!(
+        "{}/{}/{}",
+        state.config.s3_endpoint, state.config.s3_bucket, key
+    );
+
+    let file = sqlx::query_as::<_, RoomFile>(
+        r#"
+        INSERT INTO room_files (id, room_id, uploaded_by, file_name, file_url, file_size, mime_type, storage_key, original_storage_key, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, NOW())
+        RETURNING *
+        "#,
+    )
+    .bind(file_id)
+    .bind(room_id)
+    .bind(user_id)
+    .bind(&upload.file_name)
+    .bind(&url)
+    .bind(upload.size())
+    .bind(&upload.content_type)
+    .bind(&key)
+    .bind(&original_key)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(file)
+}
+
+/// Replace (or add) a file name's extension.
+fn with_extension(file_name: &str, extension: &str) -> String {
+    let stem = file_name
+        .rsplit_once('.')
+        .map_or(file_name, |(stem, _)| stem);
+    format!("{stem}.{extension}")
+}
+
 pub fn router() -> Router<Arc<AppState>> {
     Router::new()
         .route("/upload", post(upload_file))
         .route("/files/{id}", get(serve_file))
         .route("/files/{id}", delete(delete_file))
+        .route("/files/{id}/download", get(download_file))
         .route("/rooms/{room_id}/files", get(list_room_files))
         .route("/rooms/{room_id}/files", post(create_room_file))
         .route("/rooms/{room_id}/notes", get(list_room_notes))
         .route("/rooms/{room_id}/notes", post(create_room_note))
+        .route("/rooms/{room_id}/notes/{id}", put(update_room_note))
+        .route("/rooms/{room_id}/notes/{id}", delete(delete_room_note))
+        .route(
+            "/rooms/{room_id}/notes/{id}/versions",
+            get(list_note_versions),
+        )
+        .route("/rooms/{room_id}/notes/{id}/lock", post(lock_room_note))
+        .route("/rooms/{room_id}/notes/{id}/lock", delete(unlock_room_note))
 }
 
 #[derive(Debug, Deserialize)]
@@ -125,16 +411,26 @@ struct CreateNoteRequest {
     content: String,
 }
 
-/// POST /upload -- upload a file via multipart.
+#[derive(Debug, Deserialize, Validate)]
+struct UpdateNoteRequest {
+    #[validate(length(min = 1, max = 200))]
+    title: Option<String>,
+    content: Option<String>,
+}
+
+/// POST /upload -- upload a file via multipart. The object isn't recorded as a room file, so
+/// there is no `Location`; the returned `url` is the only way to reach it.
 async fn upload_file(
     State(state): State<Arc<AppState>>,
     auth_user: AuthUser,
     mut multipart: Multipart,
 ) -> AppResult<(StatusCode, Json<Value>)> {
+    require_storage(&state)?;
+
     while let Some(field) = multipart
         .next_field()
         .await
-        .map_err(|e| AppError::BadRequest(format!("Multipart error: {e}")))?
+        .map_err(|e| AppError::BadRequest(format!("Multipart error: {e}").into()))?
     {
         if field.name() == Some("file") {
             let raw_name = field.file_name().unwrap_or("upload.bin").to_string();
@@ -145,25 +441,16 @@ async fn upload_file(
             let data = field
                 .bytes()
                 .await
-                .map_err(|e| AppError::BadRequest(format!("Failed to read file: {e}")))?;
+                .map_err(|e| AppError::BadRequest(format!("Failed to read file: {e}").into()))?;
 
             let file_name = sanitize_filename(&raw_name);
             validate_upload(data.len(), &content_type, ALLOWED_CONTENT_TYPES)?;
+            let upload = prepare_upload(&state, file_name, content_type, data).await;
 
             let file_id = Uuid::new_v4();
-            let key = format!("uploads/{}/{}/{}", auth_user.id, file_id, file_name);
-            let size = data.len() as i64;
-
-            state
-                .s3
-                .put_object()
-                .bucket(&state.config.s3_bucket)
-                .key(&key)
-                .body(data.into())
-                .content_type(&content_type)
-                .send()
-                .await
-                .map_err(|e| AppError::Internal(format!("S3 upload failed: {e}")))?;
+            let key = format!("uploads/{}/{}/{}", auth_user.id, file_id, upload.file_name);
+
+            put_upload(&state, &key, &upload).await?;
 
             let url = format!(
                 "{}/{}/{}",
@@ -174,9 +461,9 @@ async fn upload_file(
                 StatusCode::CREATED,
                 Json(json!({
                     "id": file_id,
-                    "filename": file_name,
-                    "content_type": content_type,
-                    "size": size,
+                    "filename": upload.file_name,
+                    "content_type": upload.content_type,
+                    "size": upload.size(),
                     "url": url,
                     "uploaded_by": auth_user.id
                 })),
@@ -189,22 +476,138 @@ async fn upload_file(
     ))
 }
 
+/// Load a room file the caller may read: its uploader or a member of its room.
+async fn require_file_access(
+    state: &AppState,
+    user_id: Uuid,
+    file_id: Uuid,
+) -> AppResult<RoomFile> {
+    let file = sqlx::query_as::<_, RoomFile>("SELECT * FROM room_files WHERE id = $1")
+        .bind(file_id)
+        .fetch_optional(&state.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("File not found".into()))?;
+
+    if file.uploaded_by != user_id {
+        require_room_member(state, user_id, file.room_id).await?;
+    }
+
+    Ok(file)
+}
+
 /// GET /files/{id} -- look up a file record and return its details.
 async fn serve_file(
     State(state): State<Arc<AppState>>,
-    _auth_user: AuthUser,
+    auth_user: AuthUser,
     Path(id): Path<Uuid>,
 ) -> AppResult<Json<RoomFile>> {
-    let file = sqlx::query_as::<_, RoomFile>("SELECT * FROM room_files WHERE id = $1")
-        .bind(id)
-        .fetch_optional(&state.pool)
-        .await?
-        .ok_or_else(|| AppError::NotFound("File not found".into()))?;
+    let file = require_file_access(&state, auth_user.id, id).await?;
 
     Ok(Json(file))
 }
 
+/// GET /files/{id}/download -- stream the file's contents from storage.
+///
+/// A single `Range: bytes=...` request is passed through to storage and answered with
+/// 206, so media players can seek. Multi-range requests get the whole file.
+async fn download_file(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+) -> AppResult<Response> {
+    require_storage(&state)?;
+    let file = require_file_access(&state, auth_user.id, id).await?;
+
+    let range = headers
+        .get(header::RANGE)
+        .and_then(|v| v.to_str().ok())
+        .filter(|r| r.starts_with("bytes=") && !r.contains(','));
+
+    let object = match state
+        .s3
+        .get_object()
+        .bucket(&state.config.s3_bucket)
+        .key(&file.storage_key)
+        .set_range(range.map(str::to_string))
+        .send()
+        .await
+    {
+        Ok(object) => object,
+        Err(e) => {
+            return match e.code() {
+                Some("NoSuchKey") => Err(AppError::NotFound("File not found".into())),
+                Some("InvalidRange") => Ok((
+                    StatusCode::RANGE_NOT_SATISFIABLE,
+                    [(header::CONTENT_RANGE, format!("bytes */{}", file.file_size))],
+                )
+                    .into_response()),
+                _ => Err(AppError::Internal(format!("S3 download failed: {e}"))),
+            };
+        }
+    };
+
+    let mut response_headers = HeaderMap::new();
+    if let Ok(value) = HeaderValue::from_str(&file.mime_type) {
+        response_headers.insert(header::CONTENT_TYPE, value);
+    }
+    if let Ok(value) = HeaderValue::from_str(&content_disposition(&file.file_name, &file.mime_type))
+    {
+        response_headers.insert(header::CONTENT_DISPOSITION, value);
+    }
+    response_headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+    if let Some(length) = object.content_length() {
+        response_headers.insert(header::CONTENT_LENGTH, length.into());
+    }
+    let status = match object.content_range().map(HeaderValue::from_str) {
+        Some(Ok(content_range)) => {
+            response_headers.insert(header::CONTENT_RANGE, content_range);
+            StatusCode::PARTIAL_CONTENT
+        }
+        _ => StatusCode::OK,
+    };
+
+    let stream = futures::stream::unfold(object.body, |mut body| async move {
+        body.next().await.map(|chunk| (chunk, body))
+    });
+
+    Ok((status, response_headers, Body::from_stream(stream)).into_response())
+}
+
+/// Playable media is shown inline; everything else (including SVG, which can carry
+/// script) downloads as an attachment.
+fn content_disposition(file_name: &str, mime_type: &str) -> String {
+    let disposition = if ALLOWED_MEDIA_TYPES.contains(&mime_type) {
+        "inline"
+    } else {
+        "attachment"
+    };
+    let ascii_name: String = file_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_graphic() && c != '"' && c != '\\' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let encoded_name: String = file_name
+        .bytes()
+        .map(|b| {
+            if b.is_ascii_alphanumeric() || b"-._~".contains(&b) {
+                (b as char).to_string()
+            } else {
+                format!("%{b:02X}")
+            }
+        })
+        .collect();
+    format!("{disposition}; filename=\"{ascii_name}\"; filename*=UTF-8''{encoded_name}")
+}
+
 /// DELETE /files/{id} -- delete a file (only the uploader can delete).
+///
+/// The row delete queues the stored object for removal; the cleanup worker deletes it.
 async fn delete_file(
     State(state): State<Arc<AppState>>,
     auth_user: AuthUser,
@@ -222,13 +625,15 @@ async fn delete_file(
         ));
     }
 
+    storage_cleanup::wake(&state);
+
     Ok(StatusCode::NO_CONTENT)
 }
 
 /// GET /rooms/{room_id}/files -- list files for a room.
 async fn list_room_files(
     State(state): State<Arc<AppState>>,
-    _auth_user: AuthUser,
+    _member: RoomMember,
     Path(room_id): Path<Uuid>,
 ) -> AppResult<Json<Vec<RoomFile>>> {
     let files = sqlx::query_as::<_, RoomFile>(
@@ -244,14 +649,17 @@ async fn list_room_files(
 /// POST /rooms/{room_id}/files -- associate a file with a room.
 async fn create_room_file(
     State(state): State<Arc<AppState>>,
-    auth_user: AuthUser,
+    RoomMember(member): RoomMember,
     Path(room_id): Path<Uuid>,
     mut multipart: Multipart,
-) -> AppResult<(StatusCode, Json<Value>)> {
+) -> AppResult<Created<Value>> {
+    require_capability(&state.pool, &member, Capability::UploadFile).await?;
+    require_storage(&state)?;
+
     while let Some(field) = multipart
         .next_field()
         .await
-        .map_err(|e| AppError::BadRequest(format!("Multipart error: {e}")))?
+        .map_err(|e| AppError::BadRequest(format!("Multipart error: {e}").into()))?
     {
         if field.name() == Some("file") {
             let raw_name = field.file_name().unwrap_or("upload.bin").to_string();
@@ -262,52 +670,18 @@ async fn create_room_file(
             let data = field
                 .bytes()
                 .await
-                .map_err(|e| AppError::BadRequest(format!("Failed to read file: {e}")))?;
+                .map_err(|e| AppError::BadRequest(format!("Failed to read file: {e}").into()))?;
 
             let file_name = sanitize_filename(&raw_name);
             validate_upload(data.len(), &content_type, ALLOWED_CONTENT_TYPES)?;
+            let upload = prepare_upload(&state, file_name, content_type, data).await;
 
-            let size = data.len() as i64;
-            let file_id = Uuid::new_v4();
-            let key = format!("rooms/{}/files/{}/{}", room_id, file_id, file_name);
-
-            state
-                .s3
-                .put_object()
-                .bucket(&state.config.s3_bucket)
-                .key(&key)
-                .body(data.into())
-                .content_type(&content_type)
-                .send()
-                .await
-                .map_err(|e| AppError::Internal(format!("S3 upload failed: {e}")))?;
-
-            let url = format!(
-                "{}/{}/{}",
-                state.config.s3_endpoint, state.config.s3_bucket, key
-            );
+            let file =
+                store_room_file(&state, &state.pool, room_id, member.user_id, &upload).await?;
 
-            // Store file record in DB
-            let file = sqlx::query_as::<_, RoomFile>(
-                r#"
-                INSERT INTO room_files (id, room_id, uploaded_by, file_name, file_url, file_size, mime_type, created_at)
-                VALUES ($1, $2, $3, $4, $5, $6, $7, NOW())
-                RETURNING *
-                "#,
-            )
-            .bind(file_id)
-            .bind(room_id)
-            .bind(auth_user.id)
-            .bind(&file_name)
-            .bind(&url)
-            .bind(size)
-            .bind(&content_type)
-            .fetch_one(&state.pool)
-            .await?;
-
-            return Ok((
-                StatusCode::CREATED,
-                Json(serde_json::to_value(file).unwrap_or_default()),
+            return Ok(created(
+                format!("/api/v1/storage/files/{}", file.id),
+                serde_json::to_value(file).unwrap_or_default(),
             ));
         }
     }
@@ -320,7 +694,7 @@ async fn create_room_file(
 /// GET /rooms/{room_id}/notes -- list notes for a room.
 async fn list_room_notes(
     State(state): State<Arc<AppState>>,
-    _auth_user: AuthUser,
+    _member: RoomMember,
     Path(room_id): Path<Uuid>,
 ) -> AppResult<Json<Vec<Note>>> {
     let notes = sqlx::query_as::<_, Note>(
@@ -336,10 +710,10 @@ async fn list_room_notes(
 /// POST /rooms/{room_id}/notes -- create a note in a room.
 async fn create_room_note(
     State(state): State<Arc<AppState>>,
-    auth_user: AuthUser,
+    RoomMember(member): RoomMember,
     Path(room_id): Path<Uuid>,
     Json(body): Json<CreateNoteRequest>,
-) -> AppResult<(StatusCode, Json<Note>)> {
+) -> AppResult<Created<Note>> {
     let note = sqlx::query_as::<_, Note>(
         r#"
         INSERT INTO notes (id, room_id, user_id, title, content, created_at, updated_at)
@@ -349,11 +723,256 @@ async fn create_room_note(
     )
     .bind(Uuid::new_v4())
     .bind(room_id)
-    .bind(auth_user.id)
+    .bind(member.user_id)
     .bind(&body.title)
     .bind(&body.content)
     .fetch_one(&state.pool)
     .await?;
 
-    Ok((StatusCode::CREATED, Json(note)))
+    Ok(created(
+        format!("/api/v1/storage/rooms/{room_id}/notes/{}", note.id),
+        note,
+    ))
+}
+
+/// Load a note in the room and check the caller may change it (author, or room moderator).
+async fn require_note_editor(
+    state: &Arc<AppState>,
+    user_id: Uuid,
+    room_id: Uuid,
+    id: Uuid,
+) -> AppResult<Note> {
+    let note = sqlx::query_as::<_, Note>("SELECT * FROM notes WHERE id = $1 AND room_id = $2")
+        .bind(id)
+        .bind(room_id)
+        .fetch_optional(&state.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Note not found".into()))?;
+
+    if note.user_id != user_id {
+        require_room_moderator(state, user_id, room_id).await?;
+    }
+
+    Ok(note)
+}
+
+/// PUT /rooms/{room_id}/notes/{id} -- update a note, keeping the previous version (author or moderator).
+async fn update_room_note(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path((room_id, id)): Path<(Uuid, Uuid)>,
+    Json(body): Json<UpdateNoteRequest>,
+) -> AppResult<Json<Note>> {
+    body.validate()
+        .map_err(|e| AppError::Validation(e.to_string().into()))?;
+
+    require_note_editor(&state, auth_user.id, room_id, id).await?;
+
+    let mut tx = state.pool.begin().await?;
+
+    // Snapshot the current state as the next version before overwriting it
+    sqlx::query(
+        r#"
+        INSERT INTO note_versions (id, note_id, version, title, content, edited_by, created_at)
+        SELECT $1, n.id,
+               COALESCE((SELECT MAX(version) FROM note_versions WHERE note_id = n.id), 0) + 1,
+               n.title, n.content, $2, NOW()
+        FROM notes n
+        WHERE n.id = $3
+        FOR UPDATE
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(auth_user.id)
+    .bind(id)
+    .execute(&mut *tx)
+    .await?;
+
+    let note = sqlx::query_as::<_, Note>(
+        r#"
+        UPDATE notes SET
+            title      = COALESCE($1, title),
+            content    = COALESCE($2, content),
+            updated_at = NOW()
+        WHERE id = $3 AND room_id = $4
+          AND (locked_by IS NULL OR locked_by = $5 OR lock_expires_at <= NOW())
+        RETURNING *
+        "#,
+    )
+    .bind(&body.title)
+    .bind(&body.content)
+    .bind(id)
+    .bind(room_id)
+    .bind(auth_user.id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| AppError::Conflict("Note is locked by another editor".into()))?;
+
+    let channel = Channel::room_notes(room_id);
+    outbox::enqueue(
+        &mut tx,
+        &channel,
+        "note_updated",
+        &serde_json::to_value(&note).unwrap_or_default(),
+    )
+    .await?;
+
+    tx.commit().await?;
+    outbox::wake(&state);
+
+    Ok(Json(note))
+}
+
+/// DELETE /rooms/{room_id}/notes/{id} -- delete a note and its history (author or moderator).
+async fn delete_room_note(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path((room_id, id)): Path<(Uuid, Uuid)>,
+) -> AppResult<StatusCode> {
+    require_note_editor(&state, auth_user.id, room_id, id).await?;
+
+    let mut tx = state.pool.begin().await?;
+
+    let result = sqlx::query("DELETE FROM notes WHERE id = $1 AND room_id = $2")
+        .bind(id)
+        .bind(room_id)
+        .execute(&mut *tx)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Note not found".into()));
+    }
+
+    let channel = Channel::room_notes(room_id);
+    outbox::enqueue(
+        &mut tx,
+        &channel,
+        "note_deleted",
+        &json!({ "id": id, "room_id": room_id }),
+    )
+    .await?;
+
+    tx.commit().await?;
+    outbox::wake(&state);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /rooms/{room_id}/notes/{id}/versions -- list prior versions of a note, newest first.
+async fn list_note_versions(
+    State(state): State<Arc<AppState>>,
+    _member: RoomMember,
+    Path((room_id, id)): Path<(Uuid, Uuid)>,
+) -> AppResult<Json<Vec<NoteVersion>>> {
+    let versions = sqlx::query_as::<_, NoteVersion>(
+        r#"
+        SELECT v.* FROM note_versions v
+        JOIN notes n ON n.id = v.note_id
+        WHERE v.note_id = $1 AND n.room_id = $2
+        ORDER BY v.version DESC
+        LIMIT 100
+        "#,
+    )
+    .bind(id)
+    .bind(room_id)
+    .fetch_all(&state.pool)
+    .await?;
+
+    Ok(Json(versions))
+}
+
+/// POST /rooms/{room_id}/notes/{id}/lock -- acquire or renew the editing lock on a note.
+async fn lock_room_note(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path((room_id, id)): Path<(Uuid, Uuid)>,
+) -> AppResult<Json<Value>> {
+    require_note_editor(&state, auth_user.id, room_id, id).await?;
+
+    let mut tx = state.pool.begin().await?;
+
+    let lock: Option<(DateTime<Utc>,)> = sqlx::query_as(
+        r#"
+        UPDATE notes SET
+            locked_by       = $1,
+            lock_expires_at = NOW() + make_interval(secs => $2)
+        WHERE id = $3 AND room_id = $4
+          AND (locked_by IS NULL OR locked_by = $1 OR lock_expires_at <= NOW())
+        RETURNING lock_expires_at
+        "#,
+    )
+    .bind(auth_user.id)
+    .bind(NOTE_LOCK_TTL_SECS)
+    .bind(id)
+    .bind(room_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some((expires_at,)) = lock else {
+        return Err(AppError::Conflict(
+            "Note is locked by another editor".into(),
+        ));
+    };
+
+    let display_name: Option<String> =
+        sqlx::query_scalar("SELECT display_name FROM users WHERE id = $1")
+            .bind(auth_user.id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+    let payload = json!({
+        "id": id,
+        "room_id": room_id,
+        "locked_by": auth_user.id,
+        "locked_by_name": display_name,
+        "expires_at": expires_at,
+    });
+
+    let channel = Channel::room_notes(room_id);
+    outbox::enqueue(&mut tx, &channel, "note_locked", &payload).await?;
+
+    tx.commit().await?;
+    outbox::wake(&state);
+
+    Ok(Json(payload))
+}
+
+/// DELETE /rooms/{room_id}/notes/{id}/lock -- release the editing lock (holder or moderator).
+async fn unlock_room_note(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path((room_id, id)): Path<(Uuid, Uuid)>,
+) -> AppResult<StatusCode> {
+    let note = require_note_editor(&state, auth_user.id, room_id, id).await?;
+
+    let Some(holder) = note.locked_by else {
+        return Ok(StatusCode::NO_CONTENT);
+    };
+    if holder != auth_user.id {
+        require_room_moderator(&state, auth_user.id, room_id).await?;
+    }
+
+    let mut tx = state.pool.begin().await?;
+
+    sqlx::query(
+        "UPDATE notes SET locked_by = NULL, lock_expires_at = NULL WHERE id = $1 AND room_id = $2",
+    )
+    .bind(id)
+    .bind(room_id)
+    .execute(&mut *tx)
+    .await?;
+
+    let channel = Channel::room_notes(room_id);
+    outbox::enqueue(
+        &mut tx,
+        &channel,
+        "note_unlocked",
+        &json!({ "id": id, "room_id": room_id }),
+    )
+    .await?;
+
+    tx.commit().await?;
+    outbox::wake(&state);
+
+    Ok(StatusCode::NO_CONTENT)
 }
@@ -0,0 +1,93 @@
+use std::{convert::Infallible, sync::Arc};
+
+use axum::{
+    extract::{Path, State},
+    http::HeaderMap,
+    response::sse::{Event, KeepAlive, Sse},
+    routing::get,
+    Router,
+};
+use futures::{stream, Stream, StreamExt};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use uuid::Uuid;
+
+use crate::{
+    error::AppResult,
+    extractors::room_access::RoomMember,
+    state::AppState,
+    ws::{channels::Channel, manager::WsManager, outbox, protocol::ServerMessage},
+};
+
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new().route("/", get(stream_room))
+}
+
+/// GET / -- stream room events as Server-Sent Events for clients that cannot use WebSocket.
+/// Each event carries the same JSON as a WebSocket `ServerMessage`; `Last-Event-ID` replays
+/// events still held in the outbox.
+async fn stream_room(
+    State(state): State<Arc<AppState>>,
+    RoomMember(member): RoomMember,
+    Path(room_id): Path<Uuid>,
+    headers: HeaderMap,
+) -> AppResult<Sse<impl Stream<Item = Result<Event, Infallible>>>> {
+    let channels = vec![
+        Channel::room_chat(room_id),
+        Channel::room_alerts(room_id),
+        Channel::room_polls(room_id),
+        Channel::room_tracks(room_id),
+        Channel::room_notes(room_id),
+    ];
+
+    // Subscribe before replaying so nothing published in between is missed;
+    // overlap is possible and clients de-duplicate on event_id as with WebSocket.
+    let (tx, rx) = mpsc::unbounded_channel::<String>();
+    let mut initial = Vec::with_capacity(channels.len());
+    for channel in &channels {
+        let member_count = WsManager::subscribe(&state, channel, tx.clone());
+        initial.push(ServerMessage::Subscribed {
+            channel: channel.clone(),
+            member_count,
+        });
+    }
+
+    let last_event_id = headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| Uuid::parse_str(v.trim()).ok());
+    if let Some(last_event_id) = last_event_id {
+        initial.extend(outbox::replay_since(&state, &channels, last_event_id).await?);
+    }
+
+    tracing::debug!(user_id = %member.user_id, room_id = %room_id, "SSE stream opened");
+
+    let initial = initial
+        .into_iter()
+        .filter_map(|msg| serde_json::to_string(&msg).ok());
+    let live = UnboundedReceiverStream::new(rx);
+
+    // Dropping the stream closes the receiver; the manager prunes closed senders on broadcast
+    let events = stream::iter(initial)
+        .chain(live)
+        .map(|json| Ok(to_sse_event(json)));
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+/// Wrap a serialized `ServerMessage`, using its `event_id` (if any) as the SSE id.
+fn to_sse_event(json: String) -> Event {
+    let event_id = serde_json::from_str::<serde_json::Value>(&json)
+        .ok()
+        .and_then(|v| {
+            v.get("event_id")
+                .and_then(|id| id.as_str())
+                .map(String::from)
+        });
+
+    let event = Event::default().data(json);
+    match event_id {
+        Some(id) => event.id(id),
+        None => event,
+    }
+}
@@ -1,8 +1,9 @@
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 use axum::{
-    extract::{Json, Path, State},
-    routing::{get, put},
+    extract::{Multipart, Path, State},
+    routing::{get, patch, put},
     Router,
 };
 use chrono::{DateTime, Utc};
@@ -10,11 +11,17 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use sqlx::FromRow;
 use uuid::Uuid;
+use validator::Validate;
 
 use crate::{
     error::{AppError, AppResult},
-    extractors::auth::AuthUser,
-    models::tenant::{Tenant, TenantResponse, UpdateTenantRequest},
+    extractors::{auth::AuthUser, json::Json},
+    models::tenant::{Tenant, TenantImage, TenantResponse, UpdateTenantRequest},
+    routes::storage::{queue_unreferenced_object, store_branding_image},
+    services::{
+        feature_flags::{Feature, FlagScope},
+        storage_cleanup, tenant_config,
+    },
     state::AppState,
 };
 
@@ -22,8 +29,11 @@ pub fn router() -> Router<Arc<AppState>> {
     Router::new()
         .route("/{id}", get(get_tenant))
         .route("/{id}", put(update_tenant))
+        .route("/{id}", patch(patch_tenant))
+        .route("/{id}/images/{image}", put(upload_tenant_image))
         .route("/{id}/config", get(get_tenant_config))
         .route("/{id}/config", put(update_tenant_config))
+        .route("/{id}/features", get(get_tenant_features))
         .route("/{id}/branding-history", get(get_branding_history))
 }
 
@@ -69,56 +79,104 @@ async fn get_tenant(
     Ok(Json(TenantResponse::from(tenant)))
 }
 
-/// PUT /{id} -- update a tenant.
+/// PUT /{id} -- update a tenant. Absent and `null` fields are both left unchanged.
 async fn update_tenant(
     State(state): State<Arc<AppState>>,
-    _auth_user: AuthUser,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(body): Json<UpdateTenantRequest>,
+) -> AppResult<Json<TenantResponse>> {
+    apply_tenant_update(&state, &auth_user, id, body.ignore_nulls()).await
+}
+
+/// PATCH /{id} -- update a tenant. Absent fields are left unchanged; `null` clears a field.
+async fn patch_tenant(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
     Path(id): Path<Uuid>,
     Json(body): Json<UpdateTenantRequest>,
 ) -> AppResult<Json<TenantResponse>> {
+    apply_tenant_update(&state, &auth_user, id, body).await
+}
+
+/// Validate and apply a tenant branding update.
+async fn apply_tenant_update(
+    state: &AppState,
+    auth_user: &AuthUser,
+    id: Uuid,
+    body: UpdateTenantRequest,
+) -> AppResult<Json<TenantResponse>> {
+    body.validate()
+        .map_err(|e| AppError::Validation(e.to_string().into()))?;
+
+    // Nullable columns take a "set" flag and a value, so an explicit null can clear them
     let tenant = sqlx::query_as::<_, Tenant>(
         r#"
         UPDATE tenants SET
             business_name        = COALESCE($1, business_name),
-            logo_url             = COALESCE($2, logo_url),
-            primary_color        = COALESCE($3, primary_color),
-            secondary_color      = COALESCE($4, secondary_color),
-            accent_color         = COALESCE($5, accent_color),
-            header_font          = COALESCE($6, header_font),
-            body_font            = COALESCE($7, body_font),
-            border_radius        = COALESCE($8, border_radius),
-            background_image_url = COALESCE($9, background_image_url),
-            favicon_url          = COALESCE($10, favicon_url),
-            tagline              = COALESCE($11, tagline),
-            website_url          = COALESCE($12, website_url),
-            support_email        = COALESCE($13, support_email),
-            custom_css           = COALESCE($14, custom_css),
-            login_background_url = COALESCE($15, login_background_url),
-            dashboard_layout     = COALESCE($16, dashboard_layout),
-            sidebar_position     = COALESCE($17, sidebar_position),
-            updated_at           = NOW()
-        WHERE id = $18
+            logo_url             = CASE WHEN $2 THEN $3 ELSE logo_url END,
+            primary_color        = CASE WHEN $4 THEN $5 ELSE primary_color END,
+            secondary_color      = CASE WHEN $6 THEN $7 ELSE secondary_color END,
+            accent_color         = CASE WHEN $8 THEN $9 ELSE accent_color END,
+            header_font          = CASE WHEN $10 THEN $11 ELSE header_font END,
+            body_font            = CASE WHEN $12 THEN $13 ELSE body_font END,
+            border_radius        = CASE WHEN $14 THEN $15 ELSE border_radius END,
+            background_image_url = CASE WHEN $16 THEN $17 ELSE background_image_url END,
+            favicon_url          = CASE WHEN $18 THEN $19 ELSE favicon_url END,
+            tagline              = CASE WHEN $20 THEN $21 ELSE tagline END,
+            website_url          = CASE WHEN $22 THEN $23 ELSE website_url END,
+            support_email        = CASE WHEN $24 THEN $25 ELSE support_email END,
+            custom_css           = CASE WHEN $26 THEN $27 ELSE custom_css END,
+            login_background_url = CASE WHEN $28 THEN $29 ELSE login_background_url END,
+            dashboard_layout     = CASE WHEN $30 THEN $31 ELSE dashboard_layout END,
+            sidebar_position     = CASE WHEN $32 THEN $33 ELSE sidebar_position END,
+            email_header_url     = CASE WHEN $34 THEN $35 ELSE email_header_url END,
+            email_footer_text    = CASE WHEN $36 THEN $37 ELSE email_footer_text END,
+            updated_at           = NOW(),
+            updated_by           = $39
+        WHERE id = $38
         RETURNING *
         "#,
     )
     .bind(&body.business_name)
-    .bind(&body.logo_url)
-    .bind(&body.primary_color)
-    .bind(&body.secondary_color)
-    .bind(&body.accent_color)
-    .bind(&body.header_font)
-    .bind(&body.body_font)
-    .bind(&body.border_radius)
-    .bind(&body.background_image_url)
-    .bind(&body.favicon_url)
-    .bind(&body.tagline)
-    .bind(&body.website_url)
-    .bind(&body.support_email)
-    .bind(&body.custom_css)
-    .bind(&body.login_background_url)
-    .bind(&body.dashboard_layout)
-    .bind(&body.sidebar_position)
+    .bind(body.logo_url.is_set())
+    .bind(body.logo_url.value())
+    .bind(body.primary_color.is_set())
+    .bind(body.primary_color.value())
+    .bind(body.secondary_color.is_set())
+    .bind(body.secondary_color.value())
+    .bind(body.accent_color.is_set())
+    .bind(body.accent_color.value())
+    .bind(body.header_font.is_set())
+    .bind(body.header_font.value())
+    .bind(body.body_font.is_set())
+    .bind(body.body_font.value())
+    .bind(body.border_radius.is_set())
+    .bind(body.border_radius.value())
+    .bind(body.background_image_url.is_set())
+    .bind(body.background_image_url.value())
+    .bind(body.favicon_url.is_set())
+    .bind(body.favicon_url.value())
+    .bind(body.tagline.is_set())
+    .bind(body.tagline.value())
+    .bind(body.website_url.is_set())
+    .bind(body.website_url.value())
+    .bind(body.support_email.is_set())
+    .bind(body.support_email.value())
+    .bind(body.custom_css.is_set())
+    .bind(body.custom_css.value())
+    .bind(body.login_background_url.is_set())
+    .bind(body.login_background_url.value())
+    .bind(body.dashboard_layout.is_set())
+    .bind(body.dashboard_layout.value())
+    .bind(body.sidebar_position.is_set())
+    .bind(body.sidebar_position.value())
+    .bind(body.email_header_url.is_set())
+    .bind(body.email_header_url.value())
+    .bind(body.email_footer_text.is_set())
+    .bind(body.email_footer_text.value())
     .bind(id)
+    .bind(auth_user.id)
     .fetch_optional(&state.pool)
     .await?
     .ok_or_else(|| AppError::NotFound("Tenant not found".into()))?;
@@ -126,6 +184,58 @@ async fn update_tenant(
     Ok(Json(TenantResponse::from(tenant)))
 }
 
+/// Tenants have no owner of their own, so only global admins may change their images.
+fn require_branding_admin(auth_user: &AuthUser) -> AppResult<()> {
+    if auth_user.role != "admin" {
+        return Err(AppError::Forbidden("Admin access required".into()));
+    }
+    Ok(())
+}
+
+/// PUT /{id}/images/{image} -- upload a logo, banner, or favicon via multipart and point
+/// the tenant's matching URL at it (admin only). A replaced image stored in this server's
+/// bucket is queued for deletion.
+async fn upload_tenant_image(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path((id, image)): Path<(Uuid, TenantImage)>,
+    multipart: Multipart,
+) -> AppResult<Json<TenantResponse>> {
+    require_branding_admin(&auth_user)?;
+
+    let prefix = format!("tenants/{id}/{}", image.as_str());
+    let url = store_branding_image(&state, multipart, "image", &prefix).await?;
+
+    let column = image.column();
+    let mut tx = state.pool.begin().await?;
+
+    let previous: Option<String> = sqlx::query_scalar(&format!(
+        "SELECT {column} FROM tenants WHERE id = $1 FOR UPDATE"
+    ))
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Tenant not found".into()))?;
+
+    let tenant = sqlx::query_as::<_, Tenant>(&format!(
+        "UPDATE tenants SET {column} = $1, updated_at = NOW(), updated_by = $2 WHERE id = $3 RETURNING *"
+    ))
+    .bind(&url)
+    .bind(auth_user.id)
+    .bind(id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let queued = queue_unreferenced_object(&state, &mut *tx, previous.as_deref()).await?;
+
+    tx.commit().await?;
+    if queued {
+        storage_cleanup::wake(&state);
+    }
+
+    Ok(Json(TenantResponse::from(tenant)))
+}
+
 /// GET /{id}/config -- get all configuration key-value pairs for a tenant.
 async fn get_tenant_config(
     State(state): State<Arc<AppState>>,
@@ -143,12 +253,15 @@ async fn get_tenant_config(
 }
 
 /// PUT /{id}/config -- upsert a tenant configuration key-value pair.
+/// The key must be a registered one with a value of its type, or use the `custom.` prefix.
 async fn update_tenant_config(
     State(state): State<Arc<AppState>>,
     _auth_user: AuthUser,
     Path(id): Path<Uuid>,
     Json(body): Json<UpdateTenantConfigRequest>,
 ) -> AppResult<Json<TenantConfig>> {
+    tenant_config::validate(&body.key, &body.value)?;
+
     let config = sqlx::query_as::<_, TenantConfig>(
         r#"
         INSERT INTO tenant_configuration (id, tenant_id, key, value, created_at, updated_at)
@@ -164,9 +277,32 @@ async fn update_tenant_config(
     .fetch_one(&state.pool)
     .await?;
 
+    if body.key.starts_with("feature_flags.") {
+        state.invalidate_features(FlagScope::Tenant(id));
+    }
+
     Ok(Json(config))
 }
 
+/// GET /{id}/features -- whether each feature is on for a tenant, so clients can hide
+/// what it has disabled.
+async fn get_tenant_features(
+    State(state): State<Arc<AppState>>,
+    _auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> AppResult<Json<BTreeMap<Feature, bool>>> {
+    let exists =
+        sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM tenants WHERE id = $1)")
+            .bind(id)
+            .fetch_one(&state.pool)
+            .await?;
+    if !exists {
+        return Err(AppError::NotFound("Tenant not found".into()));
+    }
+
+    Ok(Json(state.tenant_features(id).await?))
+}
+
 /// GET /{id}/branding-history -- get the branding audit log for a tenant.
 async fn get_branding_history(
     State(state): State<Arc<AppState>>,
@@ -182,3 +318,28 @@ async fn get_branding_history(
 
     Ok(Json(entries))
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::{http::StatusCode, response::IntoResponse};
+
+    use super::*;
+
+    fn user(role: &str) -> AuthUser {
+        AuthUser {
+            id: Uuid::new_v4(),
+            role: role.to_string(),
+        }
+    }
+
+    #[test]
+    fn plain_member_cannot_upload_tenant_images() {
+        let err = require_branding_admin(&user("member")).unwrap_err();
+        assert_eq!(err.into_response().status(), StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn admin_can_upload_tenant_images() {
+        assert!(require_branding_admin(&user("admin")).is_ok());
+    }
+}
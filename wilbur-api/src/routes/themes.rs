@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use axum::{
-    extract::{Json, Path, State},
+    extract::{Path, State},
     http::StatusCode,
     routing::{delete, get, post, put},
     Router,
@@ -14,7 +14,8 @@ use uuid::Uuid;
 
 use crate::{
     error::{AppError, AppResult},
-    extractors::auth::AuthUser,
+    extractors::{auth::AuthUser, json::Json},
+    routes::{created, Created},
     state::AppState,
 };
 
@@ -72,7 +73,7 @@ async fn create_theme(
     State(state): State<Arc<AppState>>,
     auth_user: AuthUser,
     Json(body): Json<CreateThemeRequest>,
-) -> AppResult<(StatusCode, Json<UserTheme>)> {
+) -> AppResult<Created<UserTheme>> {
     let theme_id = Uuid::new_v4();
     let now = Utc::now();
 
@@ -93,7 +94,7 @@ async fn create_theme(
     .fetch_one(&state.pool)
     .await?;
 
-    Ok((StatusCode::CREATED, Json(theme)))
+    Ok(created(format!("/api/v1/themes/{theme_id}"), theme))
 }
 
 /// GET /{id} -- get a specific theme (must be owned by the user).
@@ -1,27 +1,54 @@
 use std::sync::Arc;
 
 use axum::{
-    extract::{Json, Multipart, Path, Query, State},
-    routing::{get, put},
+    extract::{Multipart, Path, Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    routing::{get, patch, put},
     Router,
 };
 use serde::Deserialize;
 use serde_json::{json, Value};
+use sqlx::PgPool;
 use uuid::Uuid;
+use validator::Validate;
 
 use crate::{
     error::{AppError, AppResult},
-    extractors::auth::AuthUser,
-    models::user::{UpdateUserRequest, User, UserResponse},
+    extractors::{
+        auth::AuthUser, json::Json, pagination::PaginationParams, room_access::require_room_member,
+    },
+    models::{
+        room::{
+            MemberRoom, MemberRoomListQuery, MemberRoomResponse, RoomPreferences,
+            UpdateRoomPreferencesRequest,
+        },
+        user::{PublicIdentity, PublicUserResponse, UpdateUserRequest, User, UserResponse},
+    },
+    routes::{
+        escape_like,
+        storage::{prepare_upload, put_upload, queue_unreferenced_object, require_storage},
+    },
+    services::{identicon::identicon_svg, storage_cleanup, tenant_scope::TenantScope},
     state::AppState,
 };
 
+/// Name shown to other users for someone who hasn't set a display name.
+const UNNAMED_USER: &str = "Anonymous";
+
 pub fn router() -> Router<Arc<AppState>> {
     Router::new()
         .route("/search", get(search_users))
+        .route("/me/rooms", get(list_my_rooms))
+        .route(
+            "/me/rooms/{room_id}/preferences",
+            put(update_room_preferences),
+        )
         .route("/{id}", get(get_user))
         .route("/{id}", put(update_user))
-        .route("/{id}/avatar", put(upload_avatar))
+        .route("/{id}", patch(patch_user))
+        .route("/{id}/avatar", put(upload_avatar).delete(delete_avatar))
+        .route("/{id}/avatar/default.svg", get(default_avatar))
         .route("/{id}/profile", get(get_user_profile))
 }
 
@@ -30,6 +57,84 @@ pub struct SearchQuery {
     pub q: Option<String>,
 }
 
+/// GET /me/rooms -- rooms the caller is an active member of, with their role in each, the
+/// room's member count, and the caller's preferences. Rooms are in the caller's chosen order,
+/// then most recently joined first; hidden rooms are left out unless `include_hidden=true`.
+async fn list_my_rooms(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    pagination: PaginationParams,
+    Query(query): Query<MemberRoomListQuery>,
+) -> AppResult<Json<Vec<MemberRoomResponse>>> {
+    // Active members can always reach their rooms, so tenant scope leaves this list alone
+    let rooms = sqlx::query_as::<_, MemberRoom>(
+        r#"
+        SELECT r.*, m.role, m.status,
+               (SELECT COUNT(*) FROM room_memberships c
+                WHERE c.room_id = r.id AND c.status = 'active') AS member_count,
+               p.sort_order,
+               COALESCE(p.is_hidden, false) AS is_hidden,
+               COALESCE(p.is_muted, false) AS is_muted
+        FROM room_memberships m
+        JOIN rooms r ON r.id = m.room_id
+        LEFT JOIN user_room_preferences p ON p.user_id = m.user_id AND p.room_id = m.room_id
+        WHERE m.user_id = $1
+          AND m.status = 'active'
+          AND r.is_active = true
+          AND ($4 OR NOT COALESCE(p.is_hidden, false))
+        ORDER BY p.sort_order ASC NULLS LAST, m.created_at DESC, r.id
+        LIMIT $2 OFFSET $3
+        "#,
+    )
+    .bind(auth_user.id)
+    .bind(pagination.limit())
+    .bind(pagination.offset())
+    .bind(query.include_hidden)
+    .fetch_all(&state.pool)
+    .await?;
+
+    Ok(Json(
+        rooms.into_iter().map(MemberRoomResponse::from).collect(),
+    ))
+}
+
+/// PUT /me/rooms/{room_id}/preferences -- set the caller's ordering, visibility, or mute
+/// setting for a room they belong to. Muting a room suppresses its notifications.
+async fn update_room_preferences(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path(room_id): Path<Uuid>,
+    Json(body): Json<UpdateRoomPreferencesRequest>,
+) -> AppResult<Json<RoomPreferences>> {
+    body.validate()
+        .map_err(|e| AppError::Validation(e.to_string().into()))?;
+    require_room_member(&state, auth_user.id, room_id).await?;
+
+    let preferences = sqlx::query_as::<_, RoomPreferences>(
+        r#"
+        INSERT INTO user_room_preferences (user_id, room_id, sort_order, is_hidden, is_muted)
+        VALUES ($1, $2, $4, COALESCE($5, false), COALESCE($6, false))
+        ON CONFLICT (user_id, room_id) DO UPDATE SET
+            sort_order = CASE WHEN $3 THEN EXCLUDED.sort_order
+                              ELSE user_room_preferences.sort_order END,
+            is_hidden  = COALESCE($5, user_room_preferences.is_hidden),
+            is_muted   = COALESCE($6, user_room_preferences.is_muted),
+            updated_at = NOW()
+        RETURNING room_id, sort_order, is_hidden, is_muted, updated_at
+        "#,
+    )
+    .bind(auth_user.id)
+    .bind(room_id)
+    .bind(body.sort_order.is_set())
+    .bind(body.sort_order.value())
+    .bind(body.is_hidden)
+    .bind(body.is_muted)
+    .fetch_one(&state.pool)
+    .await?;
+
+    Ok(Json(preferences))
+}
+
 /// GET /{id} -- get a user by ID.
 async fn get_user(
     State(state): State<Arc<AppState>>,
@@ -42,40 +147,66 @@ async fn get_user(
         .await?
         .ok_or_else(|| AppError::NotFound("User not found".into()))?;
 
-    Ok(Json(UserResponse::from(user)))
+    Ok(Json(UserResponse::with_online(user, state.is_online(id))))
 }
 
-/// PUT /{id} -- update own user profile.
+/// PUT /{id} -- update own user profile. Absent and `null` fields are both left unchanged.
 async fn update_user(
     State(state): State<Arc<AppState>>,
     auth_user: AuthUser,
     Path(id): Path<Uuid>,
     Json(body): Json<UpdateUserRequest>,
+) -> AppResult<Json<UserResponse>> {
+    apply_user_update(&state, &auth_user, id, body.ignore_nulls()).await
+}
+
+/// PATCH /{id} -- update own user profile. Absent fields are left unchanged; `null` clears.
+async fn patch_user(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(body): Json<UpdateUserRequest>,
+) -> AppResult<Json<UserResponse>> {
+    apply_user_update(&state, &auth_user, id, body).await
+}
+
+/// Apply a profile update for the caller's own account.
+async fn apply_user_update(
+    state: &AppState,
+    auth_user: &AuthUser,
+    id: Uuid,
+    body: UpdateUserRequest,
 ) -> AppResult<Json<UserResponse>> {
     if auth_user.id != id {
         return Err(AppError::Forbidden(
             "You can only update your own profile".into(),
         ));
     }
+    body.validate()
+        .map_err(|e| AppError::Validation(e.to_string().into()))?;
 
     let user = sqlx::query_as::<_, User>(
         r#"
         UPDATE users
-        SET display_name = COALESCE($1, display_name),
-            avatar_url   = COALESCE($2, avatar_url),
+        SET display_name = CASE WHEN $1 THEN $2 ELSE display_name END,
+            avatar_url   = CASE WHEN $3 THEN $4 ELSE avatar_url END,
+            dm_policy    = COALESCE($6, dm_policy),
             updated_at   = NOW()
-        WHERE id = $3
+        WHERE id = $5
         RETURNING *
         "#,
     )
-    .bind(&body.display_name)
-    .bind(&body.avatar_url)
+    .bind(body.display_name.is_set())
+    .bind(body.display_name.value())
+    .bind(body.avatar_url.is_set())
+    .bind(body.avatar_url.value())
     .bind(id)
+    .bind(body.dm_policy)
     .fetch_optional(&state.pool)
     .await?
     .ok_or_else(|| AppError::NotFound("User not found".into()))?;
 
-    Ok(Json(UserResponse::from(user)))
+    Ok(Json(UserResponse::with_online(user, state.is_online(id))))
 }
 
 /// PUT /{id}/avatar -- upload an avatar image via multipart.
@@ -90,11 +221,12 @@ async fn upload_avatar(
             "You can only update your own avatar".into(),
         ));
     }
+    require_storage(&state)?;
 
     while let Some(field) = multipart
         .next_field()
         .await
-        .map_err(|e| AppError::BadRequest(format!("Multipart error: {e}")))?
+        .map_err(|e| AppError::BadRequest(format!("Multipart error: {e}").into()))?
     {
         if field.name() == Some("avatar") {
             let file_name = field.file_name().unwrap_or("avatar.png").to_string();
@@ -105,20 +237,12 @@ async fn upload_avatar(
             let data = field
                 .bytes()
                 .await
-                .map_err(|e| AppError::BadRequest(format!("Failed to read file: {e}")))?;
-
-            let key = format!("avatars/{}/{}", id, file_name);
-
-            state
-                .s3
-                .put_object()
-                .bucket(&state.config.s3_bucket)
-                .key(&key)
-                .body(data.into())
-                .content_type(&content_type)
-                .send()
-                .await
-                .map_err(|e| AppError::Internal(format!("S3 upload failed: {e}")))?;
+                .map_err(|e| AppError::BadRequest(format!("Failed to read file: {e}").into()))?;
+
+            let upload = prepare_upload(&state, file_name, content_type, data).await;
+            let key = format!("avatars/{}/{}", id, upload.file_name);
+
+            put_upload(&state, &key, &upload).await?;
 
             let avatar_url = format!(
                 "{}/{}/{}",
@@ -131,7 +255,11 @@ async fn upload_avatar(
                 .execute(&state.pool)
                 .await?;
 
-            return Ok(Json(json!({ "avatar_url": avatar_url })));
+            return Ok(Json(json!({
+                "avatar_url": avatar_url,
+                "content_type": upload.content_type,
+                "size": upload.size()
+            })));
         }
     }
 
@@ -140,29 +268,126 @@ async fn upload_avatar(
     ))
 }
 
-/// GET /search?q= -- search users by display name or email.
+/// DELETE /{id}/avatar -- remove own avatar, falling back to the generated default. The
+/// stored object is queued for deletion when it lives in this server's bucket.
+async fn delete_avatar(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> AppResult<StatusCode> {
+    if auth_user.id != id {
+        return Err(AppError::Forbidden(
+            "You can only update your own avatar".into(),
+        ));
+    }
+
+    let mut tx = state.pool.begin().await?;
+
+    let previous: Option<String> = sqlx::query_scalar(
+        r#"
+        UPDATE users u SET avatar_url = NULL, updated_at = NOW()
+        FROM (SELECT avatar_url FROM users WHERE id = $1 FOR UPDATE) old
+        WHERE u.id = $1
+        RETURNING old.avatar_url
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| AppError::NotFound("User not found".into()))?;
+
+    let queued = queue_unreferenced_object(&state, &mut *tx, previous.as_deref()).await?;
+
+    tx.commit().await?;
+    if queued {
+        storage_cleanup::wake(&state);
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /{id}/avatar/default.svg -- the generated default avatar for a user. Public so it can
+/// be used directly as an image source; it depends only on the ID, so it is cached for good.
+async fn default_avatar(Path(id): Path<Uuid>) -> impl IntoResponse {
+    (
+        [
+            (header::CONTENT_TYPE, "image/svg+xml"),
+            (header::CACHE_CONTROL, "public, max-age=31536000, immutable"),
+        ],
+        identicon_svg(id),
+    )
+}
+
+/// GET /search?q= -- search users by display name (paginated), names starting with the query
+/// first. Admins also match on email and get full user records; everyone else gets only the
+/// ID, name, avatar and online flag, so email stays out of discovery. Under
+/// tenant isolation only users of the caller's tenant, or of none, are listed.
 async fn search_users(
     State(state): State<Arc<AppState>>,
-    _auth_user: AuthUser,
+    auth_user: AuthUser,
+    pagination: PaginationParams,
     Query(params): Query<SearchQuery>,
-) -> AppResult<Json<Vec<UserResponse>>> {
+) -> AppResult<Json<Value>> {
     let query = params.q.unwrap_or_default();
-    let pattern = format!("%{}%", query);
+    let query = escape_like(query.trim());
+    let is_admin = auth_user.role == "admin";
+    let (restricted, tenant_id) = TenantScope::for_user(&state, auth_user.id)
+        .await?
+        .sql_filter();
+
+    let total: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*) FROM users
+        WHERE (display_name ILIKE '%' || $1 || '%' ESCAPE '\'
+               OR ($2 AND email ILIKE '%' || $1 || '%' ESCAPE '\'))
+          AND (NOT $3 OR tenant_id IS NULL OR tenant_id = $4)
+        "#,
+    )
+    .bind(&query)
+    .bind(is_admin)
+    .bind(restricted)
+    .bind(tenant_id)
+    .fetch_one(&state.pool)
+    .await?;
 
     let users = sqlx::query_as::<_, User>(
         r#"
         SELECT * FROM users
-        WHERE display_name ILIKE $1 OR email ILIKE $1
-        ORDER BY display_name ASC
-        LIMIT 50
+        WHERE (display_name ILIKE '%' || $1 || '%' ESCAPE '\'
+               OR ($2 AND email ILIKE '%' || $1 || '%' ESCAPE '\'))
+          AND (NOT $3 OR tenant_id IS NULL OR tenant_id = $4)
+        ORDER BY display_name ILIKE $1 || '%' ESCAPE '\' DESC, display_name ASC, id
+        LIMIT $5 OFFSET $6
         "#,
     )
-    .bind(&pattern)
+    .bind(&query)
+    .bind(is_admin)
+    .bind(restricted)
+    .bind(tenant_id)
+    .bind(pagination.limit())
+    .bind(pagination.offset())
     .fetch_all(&state.pool)
     .await?;
 
-    let results: Vec<UserResponse> = users.into_iter().map(UserResponse::from).collect();
-    Ok(Json(results))
+    // Admins get the full record; everyone else only the public projection
+    let data: Vec<Value> = users
+        .into_iter()
+        .map(|u| {
+            let online = state.is_online(u.id);
+            if is_admin {
+                json!(UserResponse::with_online(u, online))
+            } else {
+                json!(PublicUserResponse::with_online(u, online))
+            }
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "page": pagination.page,
+        "per_page": pagination.per_page(),
+        "total": total,
+        "data": data
+    })))
 }
 
 /// GET /{id}/profile -- get public profile for a user.
@@ -179,9 +404,25 @@ async fn get_user_profile(
 
     // Return a public profile view (could include room memberships, etc.)
     let profile = json!({
-        "user": UserResponse::from(user),
+        "user": UserResponse::with_online(user, state.is_online(id)),
         "endpoint": "user_profile"
     });
 
     Ok(Json(profile))
 }
+
+/// The name and avatar other users see for `user_id` in presence and calls. Never the
+/// email, which stays private to the user and admins.
+pub(crate) async fn public_identity(pool: &PgPool, user_id: Uuid) -> AppResult<PublicIdentity> {
+    let (display_name, avatar_url): (Option<String>, Option<String>) =
+        sqlx::query_as("SELECT display_name, avatar_url FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("User not found".into()))?;
+
+    Ok(PublicIdentity {
+        display_name: display_name.unwrap_or_else(|| UNNAMED_USER.to_string()),
+        avatar_url,
+    })
+}
@@ -0,0 +1,201 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{delete, get, post, put},
+    Router,
+};
+use serde_json::{json, Value};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    error::{AppError, AppResult},
+    extractors::{json::Json, pagination::PaginationParams, room_access::RoomModerator},
+    models::webhook::{
+        CreateWebhookRequest, RoomWebhook, UpdateWebhookRequest, WebhookDelivery,
+        WebhookDeliveryResponse, WebhookResponse,
+    },
+    routes::{created, Created},
+    services::webhook_dispatcher::{generate_secret, validate_webhook_url},
+    state::AppState,
+};
+
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", get(list_webhooks))
+        .route("/", post(create_webhook))
+        .route("/{id}", get(get_webhook))
+        .route("/{id}", put(update_webhook))
+        .route("/{id}", delete(delete_webhook))
+        .route("/{id}/deliveries", get(list_deliveries))
+}
+
+/// GET / -- list webhooks registered for a room (moderator-only).
+async fn list_webhooks(
+    State(state): State<Arc<AppState>>,
+    _moderator: RoomModerator,
+    Path(room_id): Path<Uuid>,
+) -> AppResult<Json<Vec<WebhookResponse>>> {
+    let webhooks = sqlx::query_as::<_, RoomWebhook>(
+        "SELECT * FROM room_webhooks WHERE room_id = $1 ORDER BY created_at DESC",
+    )
+    .bind(room_id)
+    .fetch_all(&state.pool)
+    .await?;
+
+    let results: Vec<WebhookResponse> = webhooks.into_iter().map(WebhookResponse::from).collect();
+    Ok(Json(results))
+}
+
+/// POST / -- register a webhook. The signing secret is returned only in this response.
+async fn create_webhook(
+    State(state): State<Arc<AppState>>,
+    RoomModerator(moderator): RoomModerator,
+    Path(room_id): Path<Uuid>,
+    Json(body): Json<CreateWebhookRequest>,
+) -> AppResult<Created<WebhookResponse>> {
+    body.validate()
+        .map_err(|e| AppError::Validation(e.to_string().into()))?;
+    let url = validate_webhook_url(&body.url)?;
+
+    let secret = generate_secret();
+
+    let webhook = sqlx::query_as::<_, RoomWebhook>(
+        r#"
+        INSERT INTO room_webhooks (id, room_id, created_by, url, secret, event_types, is_active, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, true, NOW(), NOW())
+        RETURNING *
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(room_id)
+    .bind(moderator.user_id)
+    .bind(url.as_str())
+    .bind(&secret)
+    .bind(&body.event_types)
+    .fetch_one(&state.pool)
+    .await?;
+
+    let mut response = WebhookResponse::from(webhook);
+    response.secret = Some(secret);
+
+    Ok(created(
+        format!("/api/v1/rooms/{room_id}/webhooks/{}", response.id),
+        response,
+    ))
+}
+
+/// GET /{id} -- get a single webhook (moderator-only).
+async fn get_webhook(
+    State(state): State<Arc<AppState>>,
+    _moderator: RoomModerator,
+    Path((room_id, id)): Path<(Uuid, Uuid)>,
+) -> AppResult<Json<WebhookResponse>> {
+    let webhook = sqlx::query_as::<_, RoomWebhook>(
+        "SELECT * FROM room_webhooks WHERE id = $1 AND room_id = $2",
+    )
+    .bind(id)
+    .bind(room_id)
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Webhook not found".into()))?;
+
+    Ok(Json(WebhookResponse::from(webhook)))
+}
+
+/// PUT /{id} -- update a webhook's URL, event types, or active flag (moderator-only).
+async fn update_webhook(
+    State(state): State<Arc<AppState>>,
+    _moderator: RoomModerator,
+    Path((room_id, id)): Path<(Uuid, Uuid)>,
+    Json(body): Json<UpdateWebhookRequest>,
+) -> AppResult<Json<WebhookResponse>> {
+    body.validate()
+        .map_err(|e| AppError::Validation(e.to_string().into()))?;
+    let url = body
+        .url
+        .as_deref()
+        .map(validate_webhook_url)
+        .transpose()?
+        .map(|u| u.to_string());
+
+    let webhook = sqlx::query_as::<_, RoomWebhook>(
+        r#"
+        UPDATE room_webhooks SET
+            url         = COALESCE($1, url),
+            event_types = COALESCE($2, event_types),
+            is_active   = COALESCE($3, is_active),
+            updated_at  = NOW()
+        WHERE id = $4 AND room_id = $5
+        RETURNING *
+        "#,
+    )
+    .bind(&url)
+    .bind(&body.event_types)
+    .bind(body.is_active)
+    .bind(id)
+    .bind(room_id)
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Webhook not found".into()))?;
+
+    Ok(Json(WebhookResponse::from(webhook)))
+}
+
+/// DELETE /{id} -- remove a webhook and its delivery log (moderator-only).
+async fn delete_webhook(
+    State(state): State<Arc<AppState>>,
+    _moderator: RoomModerator,
+    Path((room_id, id)): Path<(Uuid, Uuid)>,
+) -> AppResult<StatusCode> {
+    let result = sqlx::query("DELETE FROM room_webhooks WHERE id = $1 AND room_id = $2")
+        .bind(id)
+        .bind(room_id)
+        .execute(&state.pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Webhook not found".into()));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /{id}/deliveries -- list recent delivery attempts for a webhook (moderator-only).
+async fn list_deliveries(
+    State(state): State<Arc<AppState>>,
+    _moderator: RoomModerator,
+    Path((room_id, id)): Path<(Uuid, Uuid)>,
+    pagination: PaginationParams,
+) -> AppResult<Json<Value>> {
+    let deliveries = sqlx::query_as::<_, WebhookDelivery>(
+        r#"
+        SELECT d.*
+        FROM webhook_deliveries d
+        JOIN room_webhooks w ON w.id = d.webhook_id
+        WHERE d.webhook_id = $1 AND w.room_id = $2
+        ORDER BY d.created_at DESC
+        LIMIT $3 OFFSET $4
+        "#,
+    )
+    .bind(id)
+    .bind(room_id)
+    .bind(pagination.limit())
+    .bind(pagination.offset())
+    .fetch_all(&state.pool)
+    .await?;
+
+    let data: Vec<WebhookDeliveryResponse> = deliveries
+        .into_iter()
+        .map(WebhookDeliveryResponse::from)
+        .collect();
+
+    Ok(Json(json!({
+        "webhook_id": id,
+        "page": pagination.page,
+        "per_page": pagination.per_page(),
+        "deliveries": data
+    })))
+}
@@ -1,8 +1,9 @@
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use axum::{
     extract::{
-        ws::{Message, WebSocket, WebSocketUpgrade},
+        ws::{close_code, CloseFrame, Message, WebSocket, WebSocketUpgrade},
         Query, State,
     },
     response::IntoResponse,
@@ -12,19 +13,34 @@ use axum::{
 use futures::{SinkExt, StreamExt};
 use jsonwebtoken::{decode, DecodingKey, Validation};
 use serde::Deserialize;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot};
+use tracing::{Instrument, Span};
 use uuid::Uuid;
 
 use crate::{
-    extractors::auth::Claims,
+    error::{AppError, AppResult},
+    extractors::{auth::Claims, room_access::require_room_member},
+    middleware::request_span,
+    models::user::PublicIdentity,
+    routes::{
+        private_chats::{mark_chat_read, require_can_message, require_chat_participant},
+        users::public_identity,
+    },
     state::AppState,
     ws::{
         channels::Channel,
         manager::WsManager,
-        protocol::{ClientMessage, ServerMessage},
+        protocol::{exceeds_json_depth, ClientMessage, ServerMessage, MAX_JSON_DEPTH},
+        replay::Replay,
     },
 };
 
+/// How long a server-initiated close frame gets to reach the client.
+const CLOSE_GRACE: Duration = Duration::from_secs(1);
+
+/// How long a DM typing indicator lasts without another `typing` signal.
+const DM_TYPING_TTL: Duration = Duration::from_secs(6);
+
 pub fn router() -> Router<Arc<AppState>> {
     Router::new().route("/", get(ws_upgrade))
 }
@@ -58,16 +74,35 @@ async fn ws_upgrade(
         }
     };
 
-    ws.on_upgrade(move |socket| handle_socket(socket, state, claims))
+    let user_id = claims.sub;
+    request_span::record_user(user_id);
+
+    // Presence events go to everyone in a channel, so they carry the profile name, never
+    // the email from the token
+    // Loaded once here and reused for every presence event on this connection
+    let identity = match public_identity(&state.pool, user_id).await {
+        Ok(identity) => identity,
+        Err(e) => return e.into_response(),
+    };
+
+    // The socket outlives the request, but its logs keep the upgrade request's span
+    let span = Span::current();
+    let max_bytes = state.config.ws_max_message_bytes;
+    ws.max_message_size(max_bytes)
+        .max_frame_size(max_bytes)
+        .on_upgrade(move |socket| handle_socket(socket, state, user_id, identity).instrument(span))
         .into_response()
 }
 
 /// Handle an authenticated WebSocket connection.
-async fn handle_socket(socket: WebSocket, state: Arc<AppState>, claims: Claims) {
-    let user_id = claims.sub;
-    let display_name = claims.email.clone();
-
-    tracing::info!(user_id = %user_id, "WebSocket connected");
+async fn handle_socket(
+    socket: WebSocket,
+    state: Arc<AppState>,
+    user_id: Uuid,
+    identity: PublicIdentity,
+) {
+    tracing::debug!(user_id = %user_id, "WebSocket connected");
+    state.ws_connected(user_id);
 
     let (mut ws_sender, mut ws_receiver) = socket.split();
 
@@ -79,26 +114,57 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>, claims: Claims)
 
     // Send welcome message
     let welcome = ServerMessage::System {
-        message: format!("Connected as {}", display_name),
+        message: format!("Connected as {}", identity.display_name),
     };
     if let Ok(json) = serde_json::to_string(&welcome) {
         let _ = ws_sender.send(Message::Text(json.into())).await;
     }
 
-    // Task to forward messages from the broadcast channel to the WebSocket
-    let send_task = tokio::spawn(async move {
-        while let Some(msg) = rx.recv().await {
-            if ws_sender.send(Message::Text(msg.into())).await.is_err() {
-                break;
+    // Task to forward messages from the broadcast channel to the WebSocket, and to send the
+    // close frame when the server ends the connection
+    let (close_tx, mut close_rx) = oneshot::channel::<CloseFrame>();
+    let mut send_task = tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                biased;
+                Ok(frame) = &mut close_rx => {
+                    let _ = ws_sender.send(Message::Close(Some(frame))).await;
+                    break;
+                }
+                msg = rx.recv() => {
+                    let Some(msg) = msg else { break };
+                    if ws_sender.send(Message::Text(msg.into())).await.is_err() {
+                        break;
+                    }
+                }
             }
         }
     });
 
     // Process incoming messages from the client
-    while let Some(Ok(msg)) = ws_receiver.next().await {
+    let mut close_frame = None;
+    while let Some(msg) = ws_receiver.next().await {
+        let msg = match msg {
+            Ok(msg) => msg,
+            Err(e) => {
+                close_frame = read_error_close_frame(e, state.config.ws_max_message_bytes);
+                break;
+            }
+        };
         match msg {
             Message::Text(text) => {
+                state.touch_last_seen(user_id);
                 let text_str: &str = &text;
+                if exceeds_json_depth(text_str, MAX_JSON_DEPTH) {
+                    let err_msg = ServerMessage::Error {
+                        message: format!("Message nests deeper than {MAX_JSON_DEPTH} levels"),
+                        code: "MESSAGE_TOO_DEEP".to_string(),
+                    };
+                    if let Ok(json) = serde_json::to_string(&err_msg) {
+                        let _ = tx.send(json);
+                    }
+                    continue;
+                }
                 match serde_json::from_str::<ClientMessage>(text_str) {
                     Ok(client_msg) => {
                         handle_client_message(
@@ -106,7 +172,7 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>, claims: Claims)
                             &tx,
                             &mut subscribed_channels,
                             user_id,
-                            &display_name,
+                            &identity,
                             client_msg,
                         )
                         .await;
@@ -128,17 +194,43 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>, claims: Claims)
     }
 
     // Client disconnected -- clean up subscriptions
-    tracing::info!(user_id = %user_id, "WebSocket disconnected");
-    drop(tx); // Close the sender so the send_task ends
-    send_task.abort();
+    tracing::debug!(user_id = %user_id, "WebSocket disconnected");
 
     // Unsubscribe from all channels
     for channel in &subscribed_channels {
-        WsManager::unsubscribe(&state, channel);
+        WsManager::unsubscribe(&state, channel, &tx);
+        broadcast_leave(&state, channel, user_id, &identity);
     }
 
+    if let Some(frame) = close_frame {
+        // Give the close frame a moment to go out before tearing the socket down
+        let _ = close_tx.send(frame);
+        let _ = tokio::time::timeout(CLOSE_GRACE, &mut send_task).await;
+    }
+    drop(tx); // Close the sender so the send_task ends
+    send_task.abort();
+
     // Clean up any closed senders
     WsManager::disconnect(&state);
+    state.ws_disconnected(user_id);
+}
+
+/// Close frame for a socket whose read failed; a message over the size limit gets
+/// `1009 Message Too Big`, other errors just drop the connection.
+fn read_error_close_frame(e: axum::Error, max_bytes: usize) -> Option<CloseFrame> {
+    match e.into_inner().downcast_ref::<tungstenite::Error>() {
+        Some(tungstenite::Error::Capacity(_)) => {
+            tracing::debug!(max_bytes, "Closing WebSocket after an oversized message");
+            Some(CloseFrame {
+                code: close_code::SIZE,
+                reason: format!("Message exceeds {max_bytes} bytes").into(),
+            })
+        }
+        other => {
+            tracing::debug!("WebSocket read failed: {other:?}");
+            None
+        }
+    }
 }
 
 /// Process a single client message.
@@ -147,13 +239,13 @@ async fn handle_client_message(
     tx: &mpsc::UnboundedSender<String>,
     subscribed_channels: &mut Vec<String>,
     user_id: Uuid,
-    display_name: &str,
+    identity: &PublicIdentity,
     msg: ClientMessage,
 ) {
     match msg {
         ClientMessage::Subscribe { channel } => {
             // Validate channel format
-            if Channel::parse(&channel).is_none() {
+            let Some(parsed) = Channel::parse(&channel) else {
                 let err = ServerMessage::Error {
                     message: format!("Invalid channel: {channel}"),
                     code: "INVALID_CHANNEL".to_string(),
@@ -162,35 +254,55 @@ async fn handle_client_message(
                     let _ = tx.send(json);
                 }
                 return;
+            };
+
+            if !may_subscribe(state, user_id, &parsed).await {
+                let err = ServerMessage::Error {
+                    message: format!("Not allowed to subscribe to {channel}"),
+                    code: "FORBIDDEN".to_string(),
+                };
+                if let Ok(json) = serde_json::to_string(&err) {
+                    let _ = tx.send(json);
+                }
+                return;
             }
 
-            let member_count = WsManager::subscribe(state, &channel, tx.clone());
-            subscribed_channels.push(channel.clone());
+            let already_subscribed = subscribed_channels.contains(&channel);
+            if !already_subscribed && subscribed_channels.len() >= state.config.ws_max_subscriptions
+            {
+                let err = ServerMessage::Error {
+                    message: format!(
+                        "Subscription limit of {} channels reached",
+                        state.config.ws_max_subscriptions
+                    ),
+                    code: "SUBSCRIPTION_LIMIT".to_string(),
+                };
+                if let Ok(json) = serde_json::to_string(&err) {
+                    let _ = tx.send(json);
+                }
+                return;
+            }
 
-            let ack = ServerMessage::Subscribed {
-                channel: channel.clone(),
-                member_count,
-            };
-            if let Ok(json) = serde_json::to_string(&ack) {
-                let _ = tx.send(json);
+            let member_count = WsManager::subscribe(state, &channel, tx.clone());
+            if already_subscribed {
+                // Re-ack without touching presence; this socket is already counted
+                send_subscribed(tx, channel, member_count);
+                return;
             }
+            subscribed_channels.push(channel.clone());
+            send_subscribed(tx, channel.clone(), member_count);
 
-            // Broadcast presence join
-            let presence = ServerMessage::Presence {
-                channel,
-                event: "join".to_string(),
-                user_id,
-                display_name: display_name.to_string(),
-            };
-            // This will be broadcast to all subscribers of the channel
-            if let Ok(json) = serde_json::to_string(&presence) {
-                let _ = tx.send(json);
+            // Announce only the user's first socket here; further tabs join silently
+            if WsManager::presence_join(state, &channel, user_id) {
+                let presence = presence_message(&channel, "join", user_id, identity);
+                WsManager::broadcast(state, &channel, &presence);
             }
         }
 
         ClientMessage::Unsubscribe { channel } => {
+            let was_subscribed = subscribed_channels.contains(&channel);
             subscribed_channels.retain(|c| c != &channel);
-            WsManager::unsubscribe(state, &channel);
+            WsManager::unsubscribe(state, &channel, tx);
 
             let ack = ServerMessage::Unsubscribed {
                 channel: channel.clone(),
@@ -199,15 +311,8 @@ async fn handle_client_message(
                 let _ = tx.send(json);
             }
 
-            // Broadcast presence leave
-            let presence = ServerMessage::Presence {
-                channel,
-                event: "leave".to_string(),
-                user_id,
-                display_name: display_name.to_string(),
-            };
-            if let Ok(json) = serde_json::to_string(&presence) {
-                let _ = tx.send(json);
+            if was_subscribed {
+                broadcast_leave(state, &channel, user_id, identity);
             }
         }
 
@@ -219,15 +324,58 @@ async fn handle_client_message(
         }
 
         ClientMessage::Presence { channel, status } => {
-            let presence = ServerMessage::Presence {
-                channel: channel.clone(),
-                event: status,
-                user_id,
-                display_name: display_name.to_string(),
-            };
+            // Subscribing first also applies the channel's authorization
+            if !subscribed_channels.contains(&channel) {
+                let err = ServerMessage::Error {
+                    message: "Not subscribed to channel".to_string(),
+                    code: "NOT_SUBSCRIBED".to_string(),
+                };
+                if let Ok(json) = serde_json::to_string(&err) {
+                    let _ = tx.send(json);
+                }
+                return;
+            }
+
+            if let Some(Channel::DirectMessage(chat_id)) = Channel::parse(&channel) {
+                handle_dm_presence(state, tx, chat_id, &channel, &status, user_id, identity).await;
+                return;
+            }
+
+            let presence = presence_message(&channel, &status, user_id, identity);
             WsManager::broadcast(state, &channel, &presence);
         }
 
+        ClientMessage::Resume {
+            channel,
+            last_event_id,
+        } => {
+            // Subscribing first also applies the channel's authorization
+            if !subscribed_channels.contains(&channel) {
+                let err = ServerMessage::Error {
+                    message: "Not subscribed to channel".to_string(),
+                    code: "NOT_SUBSCRIBED".to_string(),
+                };
+                if let Ok(json) = serde_json::to_string(&err) {
+                    let _ = tx.send(json);
+                }
+                return;
+            }
+
+            let reply = match state.ws_replay.after(&channel, last_event_id) {
+                Replay::Events(events) => {
+                    let replayed = events.len();
+                    for json in events {
+                        let _ = tx.send(json);
+                    }
+                    ServerMessage::Resumed { channel, replayed }
+                }
+                Replay::Gap => ServerMessage::Gap { channel },
+            };
+            if let Ok(json) = serde_json::to_string(&reply) {
+                let _ = tx.send(json);
+            }
+        }
+
         ClientMessage::Send { channel, payload } => {
             if !subscribed_channels.contains(&channel) {
                 let err = ServerMessage::Error {
@@ -240,6 +388,20 @@ async fn handle_client_message(
                 return;
             }
 
+            // The recipient's DM policy can change after the subscription was allowed
+            if let Some(Channel::DirectMessage(chat_id)) = Channel::parse(&channel) {
+                if !may_message(state, user_id, chat_id).await {
+                    let err = ServerMessage::Error {
+                        message: "This user does not accept direct messages from you".to_string(),
+                        code: "FORBIDDEN".to_string(),
+                    };
+                    if let Ok(json) = serde_json::to_string(&err) {
+                        let _ = tx.send(json);
+                    }
+                    return;
+                }
+            }
+
             let event = ServerMessage::Event {
                 channel: channel.clone(),
                 event: "message".to_string(),
@@ -251,3 +413,143 @@ async fn handle_client_message(
         }
     }
 }
+
+/// Whether a participant of a DM chat may send into it under the other side's DM policy.
+/// A failed lookup denies.
+async fn may_message(state: &Arc<AppState>, user_id: Uuid, chat_id: Uuid) -> bool {
+    match require_chat_participant(&state.pool, user_id, chat_id).await {
+        Ok(chat) => require_can_message(&state.pool, user_id, &chat)
+            .await
+            .is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// Whether a user may subscribe to a channel. Room channels need an active membership in
+/// the room, which also enforces tenant scope and bans; a user's notifications are theirs
+/// alone; a DM channel is open only to the chat's two participants. A failed lookup denies.
+async fn may_subscribe(state: &Arc<AppState>, user_id: Uuid, channel: &Channel) -> bool {
+    match channel {
+        Channel::RoomChat(room_id)
+        | Channel::RoomAlerts(room_id)
+        | Channel::RoomTracks(room_id)
+        | Channel::RoomPresence(room_id)
+        | Channel::RoomPolls(room_id)
+        | Channel::RoomNotes(room_id) => authorized(
+            require_room_member(state, user_id, *room_id).await,
+            "room",
+            *room_id,
+        ),
+        Channel::UserNotifications(id) => *id == user_id,
+        Channel::DirectMessage(chat_id) => authorized(
+            require_chat_participant(&state.pool, user_id, *chat_id).await,
+            "DM",
+            *chat_id,
+        ),
+    }
+}
+
+/// Whether an access check passed; errors other than a refusal are logged before denying.
+fn authorized<T>(result: AppResult<T>, kind: &str, id: Uuid) -> bool {
+    match result {
+        Ok(_) => true,
+        Err(AppError::Forbidden(_) | AppError::NotFound(_)) => false,
+        Err(e) => {
+            tracing::warn!(%id, "Failed to authorize {kind} subscription: {e}");
+            false
+        }
+    }
+}
+
+/// Presence on a DM channel, from one of its participants: `typing` goes to the chat's
+/// other sockets and lapses after `DM_TYPING_TTL` with a `stopped_typing`; `read` marks
+/// the other participant's messages read and broadcasts `dm_read`.
+async fn handle_dm_presence(
+    state: &Arc<AppState>,
+    tx: &mpsc::UnboundedSender<String>,
+    chat_id: Uuid,
+    channel: &str,
+    status: &str,
+    user_id: Uuid,
+    identity: &PublicIdentity,
+) {
+    match status {
+        "typing" => {
+            let signalled_at = Instant::now();
+            state.dm_typing.insert((chat_id, user_id), signalled_at);
+            let presence = presence_message(channel, "typing", user_id, identity);
+            WsManager::broadcast_except(state, channel, &presence, tx);
+
+            let state = state.clone();
+            let channel = channel.to_string();
+            let identity = identity.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(DM_TYPING_TTL).await;
+                // A later signal restarted the indicator; its own timer will end it
+                let expired = state
+                    .dm_typing
+                    .remove_if(&(chat_id, user_id), |_, at| *at == signalled_at)
+                    .is_some();
+                if expired {
+                    let presence = presence_message(&channel, "stopped_typing", user_id, &identity);
+                    WsManager::broadcast(&state, &channel, &presence);
+                }
+            });
+        }
+        "read" => {
+            if let Err(e) = mark_chat_read(state, chat_id, user_id).await {
+                tracing::warn!(chat_id = %chat_id, "Failed to mark DM read: {e}");
+                let err = ServerMessage::Error {
+                    message: "Failed to mark messages read".to_string(),
+                    code: "READ_FAILED".to_string(),
+                };
+                if let Ok(json) = serde_json::to_string(&err) {
+                    let _ = tx.send(json);
+                }
+            }
+        }
+        _ => {
+            let err = ServerMessage::Error {
+                message: format!("Unsupported DM presence status: {status}"),
+                code: "INVALID_PRESENCE".to_string(),
+            };
+            if let Ok(json) = serde_json::to_string(&err) {
+                let _ = tx.send(json);
+            }
+        }
+    }
+}
+
+fn send_subscribed(tx: &mpsc::UnboundedSender<String>, channel: String, member_count: usize) {
+    let ack = ServerMessage::Subscribed {
+        channel,
+        member_count,
+    };
+    if let Ok(json) = serde_json::to_string(&ack) {
+        let _ = tx.send(json);
+    }
+}
+
+/// Drop one of the user's sockets from a channel's presence, announcing `leave` only
+/// once their last socket on that channel is gone.
+fn broadcast_leave(state: &Arc<AppState>, channel: &str, user_id: Uuid, identity: &PublicIdentity) {
+    if WsManager::presence_leave(state, channel, user_id) {
+        let presence = presence_message(channel, "leave", user_id, identity);
+        WsManager::broadcast(state, channel, &presence);
+    }
+}
+
+fn presence_message(
+    channel: &str,
+    event: &str,
+    user_id: Uuid,
+    identity: &PublicIdentity,
+) -> ServerMessage {
+    ServerMessage::Presence {
+        channel: channel.to_string(),
+        event: event.to_string(),
+        user_id,
+        display_name: identity.display_name.clone(),
+        avatar_url: identity.avatar_url.clone(),
+    }
+}
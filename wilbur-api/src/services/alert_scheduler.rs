@@ -0,0 +1,58 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::error::{AppError, AppResult};
+use crate::models::alert::{Alert, AlertResponse};
+use crate::state::AppState;
+use crate::ws::{channels::Channel, outbox};
+
+/// How often the scheduler checks for alerts whose `publish_at` has passed.
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Spawn the background task that publishes scheduled alerts once they are due.
+pub fn spawn(state: Arc<AppState>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            interval.tick().await;
+            match publish_due_alerts(&state).await {
+                Ok(0) => {}
+                Ok(count) => tracing::info!(count, "Published scheduled alerts"),
+                Err(e) => tracing::error!("Scheduled alert publishing failed: {e}"),
+            }
+        }
+    });
+}
+
+/// Activate every due alert and queue its `alert_created` broadcast in one transaction.
+async fn publish_due_alerts(state: &Arc<AppState>) -> AppResult<usize> {
+    let mut tx = state.pool.begin().await?;
+
+    let alerts = sqlx::query_as::<_, Alert>(
+        r#"
+        UPDATE alerts SET is_active = true
+        WHERE is_active = false AND publish_at IS NOT NULL AND publish_at <= NOW()
+        RETURNING id, room_id, author_id, title, body, alert_type,
+                  ticker_symbol, entry_price::float8 as entry_price,
+                  stop_loss::float8 as stop_loss, take_profit::float8 as take_profit,
+                  media_url, legal_disclosure, is_active, is_pinned, pinned_at, publish_at, created_at, updated_at, deleted_at
+        "#,
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let count = alerts.len();
+    for alert in alerts {
+        let channel = Channel::room_alerts(alert.room_id);
+        let payload = serde_json::to_value(AlertResponse::from(alert))
+            .map_err(|e| AppError::Internal(format!("Serialization error: {e}")))?;
+        outbox::enqueue(&mut tx, &channel, "alert_created", &payload).await?;
+    }
+
+    tx.commit().await?;
+    if count > 0 {
+        outbox::wake(state);
+    }
+
+    Ok(count)
+}
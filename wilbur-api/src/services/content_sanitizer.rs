@@ -0,0 +1,219 @@
+/// URL schemes that can execute script or smuggle content when rendered as a link.
+const UNSAFE_SCHEMES: &[&str] = &["javascript:", "data:", "vbscript:"];
+
+/// Produce an HTML-safe rendering of user-supplied message text.
+///
+/// Markdown link targets with unsafe schemes are replaced with `#`, all HTML is
+/// escaped, and, when `linkify` is set, bare `http(s)://` URLs become anchors with
+/// `rel="nofollow noopener noreferrer"`. The raw text is stored separately.
+pub fn render_safe(content: &str, linkify: bool) -> String {
+    let neutralized = neutralize_link_targets(content);
+    let escaped = escape_html(&neutralized);
+    if linkify {
+        linkify_urls(&escaped)
+    } else {
+        escaped
+    }
+}
+
+pub(crate) fn escape_html(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#x27;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Whether a link target uses a scheme that must not be rendered as a link.
+/// Browsers ignore whitespace and control characters inside schemes, so those are stripped
+/// first, along with the angle brackets markdown allows around a target.
+fn is_unsafe_target(target: &str) -> bool {
+    let normalized: String = target
+        .trim_start()
+        .trim_start_matches('<')
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .take(16)
+        .collect::<String>()
+        .to_ascii_lowercase();
+    UNSAFE_SCHEMES.iter().any(|s| normalized.starts_with(s))
+}
+
+/// Replace unsafe targets in markdown links, both inline (`[text](target)`) and reference
+/// definitions (`[label]: target`), with `#`.
+fn neutralize_link_targets(input: &str) -> String {
+    neutralize_inline_targets(&neutralize_reference_definitions(input))
+}
+
+/// Replace unsafe targets in reference definitions. The target may follow the label on the
+/// same line or, if that is left blank, start the next one.
+fn neutralize_reference_definitions(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut target_on_this_line = false;
+
+    for line in input.split_inclusive('\n') {
+        let body = line.trim_end_matches(['\r', '\n']);
+        let ending = &line[body.len()..];
+
+        if target_on_this_line {
+            target_on_this_line = false;
+            if is_unsafe_target(body) {
+                out.push('#');
+                out.push_str(ending);
+                continue;
+            }
+        }
+
+        match reference_definition_target(body) {
+            Some(target) if target.trim().is_empty() => target_on_this_line = true,
+            Some(target) if is_unsafe_target(target) => {
+                out.push_str(&body[..body.len() - target.len()]);
+                out.push_str(" #");
+                out.push_str(ending);
+                continue;
+            }
+            _ => {}
+        }
+        out.push_str(line);
+    }
+
+    out
+}
+
+/// The text after `[label]:` if `line` is a reference definition (indented at most three
+/// spaces).
+fn reference_definition_target(line: &str) -> Option<&str> {
+    let unindented = line.trim_start_matches(' ');
+    if line.len() - unindented.len() > 3 {
+        return None;
+    }
+    let label = unindented.strip_prefix('[')?;
+    let close = label.find(']')?;
+    label[close + 1..].strip_prefix(':')
+}
+
+/// Replace unsafe targets in inline links (`[text](target)`).
+fn neutralize_inline_targets(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(pos) = rest.find("](") {
+        let (before, after) = rest.split_at(pos + 2);
+        out.push_str(before);
+
+        let end = after.find(')').unwrap_or(after.len());
+        let target = &after[..end];
+        if is_unsafe_target(target) {
+            out.push('#');
+        } else {
+            out.push_str(target);
+        }
+        rest = &after[end..];
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// Wrap bare `http(s)://` URLs in already-escaped text with safe anchors.
+fn linkify_urls(escaped: &str) -> String {
+    let mut out = String::with_capacity(escaped.len());
+    let mut rest = escaped;
+
+    while let Some(start) = find_url_start(rest) {
+        out.push_str(&rest[..start]);
+        let candidate = &rest[start..];
+        let mut end = candidate
+            .find(|c: char| c.is_whitespace() || c == '(' || c == ')')
+            .unwrap_or(candidate.len());
+        // Leave trailing sentence punctuation outside the link
+        while end > 0 && candidate[..end].ends_with(['.', ',', ';', ':', '!', '?']) {
+            end -= 1;
+        }
+
+        let url = &candidate[..end];
+        if url.ends_with("://") {
+            out.push_str(url);
+        } else {
+            out.push_str("<a href=\"");
+            out.push_str(url);
+            out.push_str("\" rel=\"nofollow noopener noreferrer\" target=\"_blank\">");
+            out.push_str(url);
+            out.push_str("</a>");
+        }
+        rest = &candidate[end..];
+    }
+
+    out.push_str(rest);
+    out
+}
+
+fn find_url_start(input: &str) -> Option<usize> {
+    let http = input.find("http://");
+    let https = input.find("https://");
+    match (http, https) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inline_link_with_unsafe_scheme_is_neutralized() {
+        assert_eq!(
+            neutralize_link_targets("[x](javascript:void 0) and [y](data:text/html,hi)"),
+            "[x](#) and [y](#)"
+        );
+        assert_eq!(
+            neutralize_link_targets("[x](https://example.com)"),
+            "[x](https://example.com)"
+        );
+    }
+
+    #[test]
+    fn reference_definition_with_unsafe_scheme_is_neutralized() {
+        assert_eq!(
+            neutralize_link_targets("see [x]\n\n[x]: javascript:alert(1)\n"),
+            "see [x]\n\n[x]: #\n"
+        );
+        assert_eq!(
+            neutralize_link_targets("  [x]:  <data:text/html,hi> \"t\""),
+            "  [x]: #"
+        );
+        assert_eq!(
+            neutralize_link_targets("[x]: https://example.com"),
+            "[x]: https://example.com"
+        );
+    }
+
+    #[test]
+    fn reference_target_on_the_next_line_is_neutralized() {
+        assert_eq!(
+            neutralize_link_targets("[x]:\r\n   vbscript:msgbox\r\nafter"),
+            "[x]:\r\n#\r\nafter"
+        );
+    }
+
+    #[test]
+    fn whitespace_obfuscated_schemes_are_neutralized() {
+        assert_eq!(
+            neutralize_link_targets("[x]( java\tscript:void 0)"),
+            "[x](#)"
+        );
+        assert_eq!(
+            neutralize_link_targets("[x]: J a v a S c r i p t:alert(1)"),
+            "[x]: #"
+        );
+        assert_eq!(neutralize_link_targets("[x](<java\nscript:x>)"), "[x](#)");
+    }
+}
@@ -1,16 +1,51 @@
-use lettre::message::header::ContentType;
+use std::num::NonZeroU32;
+use std::sync::Arc;
+use std::time::Duration;
+
+use governor::{DefaultKeyedRateLimiter, Quota, RateLimiter};
+use lettre::message::{Mailbox, MultiPart};
 use lettre::transport::smtp::authentication::Credentials;
 use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use tokio::sync::mpsc;
+
+use crate::{
+    config::AppConfig,
+    i18n::Locale,
+    services::email_templates::{self, EmailBranding, RenderedEmail},
+};
+
+/// Maximum number of emails waiting to be sent before new sends are rejected.
+const QUEUE_CAPACITY: usize = 1000;
+
+/// Delivery attempts per email before giving up on transient failures.
+const MAX_ATTEMPTS: u32 = 5;
+
+/// Delay before the first retry; doubles after each failed attempt.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(2);
+
+/// Emails any single recipient address can receive per hour.
+const EMAILS_PER_ADDRESS_PER_HOUR: u32 = 3;
 
-use crate::config::AppConfig;
+/// How often idle per-address rate limit entries are dropped.
+const LIMITER_CLEANUP_INTERVAL: Duration = Duration::from_secs(10 * 60);
 
+/// Queues transactional email for background delivery.
+///
+/// Handlers enqueue fully built messages and return immediately; a worker task sends
+/// them over SMTP, retrying transient failures (connection errors, 4xx such as
+/// greylisting) with exponential backoff and dropping permanent ones (5xx).
+///
+/// Sends are also rate limited per recipient address, so flows an anonymous caller can
+/// trigger (password reset, resend verification) can't be used to flood someone's inbox.
 pub struct EmailService {
-    mailer: AsyncSmtpTransport<Tokio1Executor>,
+    queue: mpsc::Sender<Message>,
     from: String,
+    recipient_limiter: Arc<DefaultKeyedRateLimiter<String>>,
 }
 
 impl EmailService {
-    pub fn new(config: &AppConfig) -> Result<Self, String> {
+    /// Build the SMTP transport and spawn the delivery worker.
+    pub fn start(config: &AppConfig) -> Result<Self, String> {
         if config.smtp_host.is_empty() {
             return Err("SMTP not configured".to_string());
         }
@@ -23,71 +58,181 @@ impl EmailService {
             .credentials(creds)
             .build();
 
+        let (queue, mut rx) = mpsc::channel::<Message>(QUEUE_CAPACITY);
+
+        tokio::spawn(async move {
+            while let Some(email) = rx.recv().await {
+                // Retries back off independently so one slow recipient doesn't stall the queue
+                tokio::spawn(deliver(mailer.clone(), email));
+            }
+        });
+
+        let quota = Quota::per_hour(NonZeroU32::new(EMAILS_PER_ADDRESS_PER_HOUR).unwrap());
+        let recipient_limiter = Arc::new(RateLimiter::keyed(quota));
+
+        let limiter = recipient_limiter.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(LIMITER_CLEANUP_INTERVAL);
+            loop {
+                interval.tick().await;
+                limiter.retain_recent();
+            }
+        });
+
         Ok(Self {
-            mailer,
+            queue,
             from: config.smtp_from.clone(),
+            recipient_limiter,
         })
     }
 
-    pub async fn send_verification_email(
+    /// Send the address verification email, branded for the tenant the user signed up through.
+    pub fn send_verification_email(
         &self,
         to: &str,
         token: &str,
         base_url: &str,
+        branding: &EmailBranding,
+        locale: Locale,
     ) -> Result<(), String> {
-        let verify_url = format!("{base_url}/verify-email?token={token}");
-        let body = format!(
-            "Welcome to Wilbur!\n\nPlease verify your email by clicking the link below:\n\n{verify_url}\n\nThis link expires in 24 hours."
-        );
-
-        let email = Message::builder()
-            .from(
-                self.from
-                    .parse()
-                    .map_err(|e| format!("Invalid from: {e}"))?,
-            )
-            .to(to.parse().map_err(|e| format!("Invalid to: {e}"))?)
-            .subject("Verify your Wilbur account")
-            .header(ContentType::TEXT_PLAIN)
-            .body(body)
-            .map_err(|e| format!("Email build error: {e}"))?;
+        let verify_url = format!("{base_url}/api/v1/auth/verify-email?token={token}");
+        self.enqueue(
+            to,
+            branding,
+            email_templates::verification(branding, &verify_url, locale),
+        )
+    }
 
-        self.mailer
-            .send(email)
-            .await
-            .map_err(|e| format!("Email send error: {e}"))?;
+    /// Send a password reset link, branded for the tenant the request came from.
+    pub fn send_password_reset_email(
+        &self,
+        to: &str,
+        token: &str,
+        base_url: &str,
+        branding: &EmailBranding,
+        locale: Locale,
+    ) -> Result<(), String> {
+        let reset_url = format!("{base_url}/reset-password?token={token}");
+        self.enqueue(
+            to,
+            branding,
+            email_templates::password_reset(branding, &reset_url, locale),
+        )
+    }
 
-        Ok(())
+    /// Send a single-use sign-in link, branded for the tenant the request came from.
+    pub fn send_magic_link_email(
+        &self,
+        to: &str,
+        token: &str,
+        base_url: &str,
+        branding: &EmailBranding,
+        locale: Locale,
+    ) -> Result<(), String> {
+        let login_url = format!("{base_url}/magic-link?token={token}");
+        self.enqueue(
+            to,
+            branding,
+            email_templates::magic_link(branding, &login_url, locale),
+        )
     }
 
-    pub async fn send_password_reset_email(
+    /// Send the confirmation link for an email change to the new address.
+    pub fn send_email_change_verification(
         &self,
         to: &str,
         token: &str,
         base_url: &str,
+        branding: &EmailBranding,
+        locale: Locale,
     ) -> Result<(), String> {
-        let reset_url = format!("{base_url}/reset-password?token={token}");
-        let body = format!(
-            "You requested a password reset for your Wilbur account.\n\nClick the link below to reset your password:\n\n{reset_url}\n\nThis link expires in 1 hour. If you didn't request this, ignore this email."
-        );
+        let confirm_url = format!("{base_url}/api/v1/auth/confirm-email-change?token={token}");
+        self.enqueue(
+            to,
+            branding,
+            email_templates::email_change(branding, &confirm_url, locale),
+        )
+    }
+
+    /// Tell the current address that a change to `new_email` was requested.
+    pub fn send_email_change_notice(
+        &self,
+        to: &str,
+        new_email: &str,
+        base_url: &str,
+        branding: &EmailBranding,
+        locale: Locale,
+    ) -> Result<(), String> {
+        let secure_url = format!("{base_url}/forgot-password");
+        self.enqueue(
+            to,
+            branding,
+            email_templates::email_change_notice(branding, new_email, &secure_url, locale),
+        )
+    }
+
+    /// Build a multipart plaintext + HTML email and hand it to the delivery worker.
+    ///
+    /// The sender address stays the configured one; only its display name carries the
+    /// tenant's business name. Sends over the recipient's hourly limit are skipped and
+    /// reported as success so callers can't probe the limit.
+    fn enqueue(
+        &self,
+        to: &str,
+        branding: &EmailBranding,
+        rendered: RenderedEmail,
+    ) -> Result<(), String> {
+        if self
+            .recipient_limiter
+            .check_key(&to.trim().to_lowercase())
+            .is_err()
+        {
+            tracing::warn!(subject = %rendered.subject, "Recipient email rate limit reached, skipping send");
+            return Ok(());
+        }
+
+        let from: Mailbox = self
+            .from
+            .parse()
+            .map_err(|e| format!("Invalid from: {e}"))?;
+        let from = Mailbox::new(Some(branding.business_name.clone()), from.email);
 
         let email = Message::builder()
-            .from(
-                self.from
-                    .parse()
-                    .map_err(|e| format!("Invalid from: {e}"))?,
-            )
+            .from(from)
             .to(to.parse().map_err(|e| format!("Invalid to: {e}"))?)
-            .subject("Reset your Wilbur password")
-            .header(ContentType::TEXT_PLAIN)
-            .body(body)
+            .subject(rendered.subject)
+            .multipart(MultiPart::alternative_plain_html(
+                rendered.text,
+                rendered.html,
+            ))
             .map_err(|e| format!("Email build error: {e}"))?;
 
-        self.mailer
-            .send(email)
-            .await
-            .map_err(|e| format!("Email send error: {e}"))?;
+        self.queue
+            .try_send(email)
+            .map_err(|e| format!("Email queue unavailable: {e}"))
+    }
+}
 
-        Ok(())
+/// Send one email, retrying transient SMTP failures with exponential backoff.
+async fn deliver(mailer: AsyncSmtpTransport<Tokio1Executor>, email: Message) {
+    let mut backoff = INITIAL_BACKOFF;
+
+    for attempt in 1..=MAX_ATTEMPTS {
+        match mailer.send(email.clone()).await {
+            Ok(_) => return,
+            Err(e) if e.is_permanent() || e.is_client() => {
+                tracing::warn!(error = %e, "Email rejected permanently, not retrying");
+                return;
+            }
+            Err(e) if attempt == MAX_ATTEMPTS => {
+                tracing::error!(error = %e, attempts = attempt, "Email delivery failed");
+                return;
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, attempt, "Transient email failure, retrying");
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+        }
     }
 }
@@ -0,0 +1,298 @@
+use crate::{
+    i18n::{Locale, Message},
+    models::tenant::Tenant,
+    services::content_sanitizer::escape_html,
+};
+
+const DEFAULT_BUSINESS_NAME: &str = "Wilbur";
+const DEFAULT_PRIMARY_COLOR: &str = "#2563eb";
+const DEFAULT_ACCENT_COLOR: &str = "#111827";
+
+/// Look and feel applied to transactional email.
+///
+/// Built from a tenant's white-label settings; values that can't be safely placed in
+/// an inline style or `src` attribute fall back to the Wilbur defaults.
+#[derive(Debug, Clone)]
+pub struct EmailBranding {
+    pub business_name: String,
+    pub header_url: Option<String>,
+    pub footer_text: Option<String>,
+    pub primary_color: String,
+    pub accent_color: String,
+    pub support_email: Option<String>,
+}
+
+impl Default for EmailBranding {
+    fn default() -> Self {
+        Self {
+            business_name: DEFAULT_BUSINESS_NAME.to_string(),
+            header_url: None,
+            footer_text: None,
+            primary_color: DEFAULT_PRIMARY_COLOR.to_string(),
+            accent_color: DEFAULT_ACCENT_COLOR.to_string(),
+            support_email: None,
+        }
+    }
+}
+
+impl From<&Tenant> for EmailBranding {
+    fn from(t: &Tenant) -> Self {
+        Self {
+            business_name: t.business_name.clone(),
+            header_url: t
+                .email_header_url
+                .as_deref()
+                .or(t.logo_url.as_deref())
+                .filter(|url| is_http_url(url))
+                .map(str::to_string),
+            footer_text: t.email_footer_text.clone(),
+            primary_color: hex_color_or(t.primary_color.as_deref(), DEFAULT_PRIMARY_COLOR),
+            accent_color: hex_color_or(t.accent_color.as_deref(), DEFAULT_ACCENT_COLOR),
+            support_email: t.support_email.clone(),
+        }
+    }
+}
+
+/// A transactional email rendered as both a plaintext and an HTML body, in one locale.
+pub struct RenderedEmail {
+    pub subject: String,
+    pub text: String,
+    pub html: String,
+}
+
+/// Email asking the user to confirm their address.
+pub fn verification(branding: &EmailBranding, verify_url: &str, locale: Locale) -> RenderedEmail {
+    let name = &branding.business_name;
+    ActionEmail {
+        subject: Message::new("Verify your {name} account").with("name", name),
+        heading: Message::new("Welcome to {name}!").with("name", name),
+        intro: Message::new("Please verify your email address by clicking the button below."),
+        action_label: Message::new("Verify email"),
+        action_url: verify_url,
+        outro: Message::new("This link expires in 24 hours."),
+    }
+    .render(branding, locale)
+}
+
+/// Email carrying a password reset link.
+pub fn password_reset(branding: &EmailBranding, reset_url: &str, locale: Locale) -> RenderedEmail {
+    let name = &branding.business_name;
+    ActionEmail {
+        subject: Message::new("Reset your {name} password").with("name", name),
+        heading: Message::new("Reset your password"),
+        intro: Message::new("You requested a password reset for your {name} account.")
+            .with("name", name),
+        action_label: Message::new("Reset password"),
+        action_url: reset_url,
+        outro: Message::new(
+            "This link expires in 1 hour. If you didn't request this, ignore this email.",
+        ),
+    }
+    .render(branding, locale)
+}
+
+/// Email carrying a single-use sign-in link.
+pub fn magic_link(branding: &EmailBranding, login_url: &str, locale: Locale) -> RenderedEmail {
+    let name = &branding.business_name;
+    ActionEmail {
+        subject: Message::new("Sign in to {name}").with("name", name),
+        heading: Message::new("Sign in to {name}").with("name", name),
+        intro: Message::new("Use the button below to sign in without your password."),
+        action_label: Message::new("Sign in"),
+        action_url: login_url,
+        outro: Message::new("This link expires in 15 minutes and works once. If you didn't request it, ignore this email."),
+    }
+    .render(branding, locale)
+}
+
+/// Email sent to a requested new address to confirm the change.
+pub fn email_change(branding: &EmailBranding, confirm_url: &str, locale: Locale) -> RenderedEmail {
+    let name = &branding.business_name;
+    ActionEmail {
+        subject: Message::new("Confirm your new {name} email address").with("name", name),
+        heading: Message::new("Confirm your new email address"),
+        intro: Message::new(
+            "Someone asked to use this address for a {name} account. Confirm to finish the change.",
+        )
+        .with("name", name),
+        action_label: Message::new("Confirm email change"),
+        action_url: confirm_url,
+        outro: Message::new(
+            "This link expires in 24 hours. If you didn't request this, ignore this email.",
+        ),
+    }
+    .render(branding, locale)
+}
+
+/// Heads-up sent to the current address when a change to `new_email` is requested.
+pub fn email_change_notice(
+    branding: &EmailBranding,
+    new_email: &str,
+    secure_url: &str,
+    locale: Locale,
+) -> RenderedEmail {
+    let name = &branding.business_name;
+    ActionEmail {
+        subject: Message::new("Your {name} email address is being changed").with("name", name),
+        heading: Message::new("Email change requested"),
+        intro: Message::new(
+            "A request was made to change your {name} sign-in email to {new_email}. The change takes effect once the new address is confirmed.",
+        )
+        .with("name", name)
+        .with("new_email", new_email),
+        action_label: Message::new("Reset your password"),
+        action_url: secure_url,
+        outro: Message::new(
+            "If this wasn't you, reset your password right away to secure your account.",
+        ),
+    }
+    .render(branding, locale)
+}
+
+/// Content of an email built around a single call-to-action link.
+struct ActionEmail<'a> {
+    subject: Message,
+    heading: Message,
+    intro: Message,
+    action_label: Message,
+    action_url: &'a str,
+    outro: Message,
+}
+
+/// An [`ActionEmail`]'s text in one locale.
+struct ActionText<'a> {
+    heading: String,
+    intro: String,
+    action_label: String,
+    action_url: &'a str,
+    outro: String,
+    fallback_hint: String,
+    footer: Vec<String>,
+}
+
+impl ActionEmail<'_> {
+    fn render(&self, branding: &EmailBranding, locale: Locale) -> RenderedEmail {
+        let text = ActionText {
+            heading: self.heading.render(locale),
+            intro: self.intro.render(locale),
+            action_label: self.action_label.render(locale),
+            action_url: self.action_url,
+            outro: self.outro.render(locale),
+            fallback_hint: Message::new(
+                "If the button doesn't work, copy this link into your browser:",
+            )
+            .render(locale),
+            footer: footer_lines(branding, locale),
+        };
+        RenderedEmail {
+            subject: self.subject.render(locale),
+            text: text.render_text(),
+            html: text.render_html(branding, locale),
+        }
+    }
+}
+
+impl ActionText<'_> {
+    fn render_text(&self) -> String {
+        let mut text = format!(
+            "{}\n\n{}\n\n{}: {}\n\n{}",
+            self.heading, self.intro, self.action_label, self.action_url, self.outro
+        );
+        for line in &self.footer {
+            text.push_str("\n\n");
+            text.push_str(line);
+        }
+        text
+    }
+
+    fn render_html(&self, branding: &EmailBranding, locale: Locale) -> String {
+        let header = match &branding.header_url {
+            Some(url) => format!(
+                r#"<img src="{}" alt="{}" style="max-width:200px;max-height:60px;">"#,
+                escape_html(url),
+                escape_html(&branding.business_name)
+            ),
+            None => format!(
+                r#"<span style="font-size:20px;font-weight:bold;color:{};">{}</span>"#,
+                branding.accent_color,
+                escape_html(&branding.business_name)
+            ),
+        };
+
+        let footer = self
+            .footer
+            .iter()
+            .map(|line| format!("<p style=\"margin:4px 0;\">{}</p>", escape_html(line)))
+            .collect::<String>();
+
+        format!(
+            r#"<!DOCTYPE html>
+<html lang="{lang}">
+<body style="margin:0;padding:0;background:#f3f4f6;font-family:Helvetica,Arial,sans-serif;">
+<table role="presentation" width="100%" cellpadding="0" cellspacing="0" style="background:#f3f4f6;padding:24px 0;">
+<tr><td align="center">
+<table role="presentation" width="560" cellpadding="0" cellspacing="0" style="background:#ffffff;border-radius:8px;padding:32px;">
+<tr><td style="padding-bottom:24px;">{header}</td></tr>
+<tr><td style="color:{accent};">
+<h1 style="font-size:22px;margin:0 0 16px;">{heading}</h1>
+<p style="font-size:15px;line-height:1.5;margin:0 0 24px;">{intro}</p>
+<p style="margin:0 0 24px;"><a href="{url}" style="display:inline-block;background:{primary};color:#ffffff;text-decoration:none;padding:12px 24px;border-radius:6px;font-weight:bold;">{label}</a></p>
+<p style="font-size:13px;line-height:1.5;color:#6b7280;margin:0 0 8px;">{fallback_hint}<br><a href="{url}" style="color:{primary};word-break:break-all;">{url}</a></p>
+<p style="font-size:13px;line-height:1.5;color:#6b7280;margin:0;">{outro}</p>
+</td></tr>
+</table>
+<table role="presentation" width="560" cellpadding="0" cellspacing="0" style="font-size:12px;color:#9ca3af;padding:16px 32px;">
+<tr><td align="center">{footer}</td></tr>
+</table>
+</td></tr>
+</table>
+</body>
+</html>"#,
+            lang = locale.as_str(),
+            accent = branding.accent_color,
+            primary = branding.primary_color,
+            heading = escape_html(&self.heading),
+            intro = escape_html(&self.intro),
+            url = escape_html(self.action_url),
+            label = escape_html(&self.action_label),
+            outro = escape_html(&self.outro),
+            fallback_hint = escape_html(&self.fallback_hint),
+        )
+    }
+}
+
+/// Footer lines shared by the plaintext and HTML bodies. A tenant's own footer text is
+/// used as written.
+fn footer_lines(branding: &EmailBranding, locale: Locale) -> Vec<String> {
+    let mut lines = vec![branding.footer_text.clone().unwrap_or_else(|| {
+        Message::new("Sent by {name}")
+            .with("name", &branding.business_name)
+            .render(locale)
+    })];
+    if let Some(support) = &branding.support_email {
+        lines.push(
+            Message::new("Questions? Contact {support}")
+                .with("support", support)
+                .render(locale),
+        );
+    }
+    lines
+}
+
+fn is_http_url(url: &str) -> bool {
+    url.starts_with("https://") || url.starts_with("http://")
+}
+
+/// Accept only `#rgb`/`#rrggbb`-style colors so tenant values can't break out of a style attribute.
+fn hex_color_or(color: Option<&str>, default: &str) -> String {
+    match color {
+        Some(c)
+            if c.starts_with('#')
+                && matches!(c.len(), 4 | 7 | 9)
+                && c[1..].chars().all(|ch| ch.is_ascii_hexdigit()) =>
+        {
+            c.to_string()
+        }
+        _ => default.to_string(),
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+use crate::state::AppState;
+
+/// How long loaded flag overrides are reused before being read again.
+const CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// A feature that can be switched off per server, tenant, or room.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Feature {
+    Alerts,
+    Polls,
+    DirectMessages,
+    RoomAnalytics,
+}
+
+impl Feature {
+    pub const ALL: [Feature; 4] = [
+        Feature::Alerts,
+        Feature::Polls,
+        Feature::DirectMessages,
+        Feature::RoomAnalytics,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Feature::Alerts => "alerts",
+            Feature::Polls => "polls",
+            Feature::DirectMessages => "direct_messages",
+            Feature::RoomAnalytics => "room_analytics",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Feature> {
+        Feature::ALL.into_iter().find(|f| f.as_str() == name)
+    }
+}
+
+/// Scope whose overrides are cached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FlagScope {
+    Tenant(Uuid),
+    Room(Uuid),
+}
+
+/// Overrides set at one scope. For a room, also the tenant it falls back to.
+#[derive(Debug, Clone)]
+pub struct CachedFlags {
+    tenant_id: Option<Uuid>,
+    overrides: HashMap<Feature, bool>,
+    loaded_at: Instant,
+}
+
+impl AppState {
+    /// Whether `feature` is on for a tenant: its `feature_flags.<name>` setting, else the
+    /// server default. `None` (users and DMs, which aren't tenant-scoped) uses the default.
+    pub async fn feature_enabled(
+        &self,
+        tenant_id: Option<Uuid>,
+        feature: Feature,
+    ) -> AppResult<bool> {
+        if let Some(tenant_id) = tenant_id {
+            let flags = self.cached_flags(FlagScope::Tenant(tenant_id)).await?;
+            if let Some(&enabled) = flags.overrides.get(&feature) {
+                return Ok(enabled);
+            }
+        }
+        Ok(self.feature_default(feature))
+    }
+
+    /// Whether `feature` is on in a room: the room's override, else its tenant's setting,
+    /// else the server default.
+    pub async fn room_feature_enabled(&self, room_id: Uuid, feature: Feature) -> AppResult<bool> {
+        let flags = self.cached_flags(FlagScope::Room(room_id)).await?;
+        match flags.overrides.get(&feature) {
+            Some(&enabled) => Ok(enabled),
+            None => self.feature_enabled(flags.tenant_id, feature).await,
+        }
+    }
+
+    /// Every feature's state for a tenant, keyed by name.
+    pub async fn tenant_features(&self, tenant_id: Uuid) -> AppResult<BTreeMap<Feature, bool>> {
+        let mut features = BTreeMap::new();
+        for feature in Feature::ALL {
+            features.insert(
+                feature,
+                self.feature_enabled(Some(tenant_id), feature).await?,
+            );
+        }
+        Ok(features)
+    }
+
+    /// Every feature's state in a room, keyed by name.
+    pub async fn room_features(&self, room_id: Uuid) -> AppResult<BTreeMap<Feature, bool>> {
+        let mut features = BTreeMap::new();
+        for feature in Feature::ALL {
+            features.insert(feature, self.room_feature_enabled(room_id, feature).await?);
+        }
+        Ok(features)
+    }
+
+    /// Drop cached overrides after they change so the next evaluation reads them again.
+    pub fn invalidate_features(&self, scope: FlagScope) {
+        self.feature_cache.remove(&scope);
+    }
+
+    fn feature_default(&self, feature: Feature) -> bool {
+        !self
+            .config
+            .disabled_features
+            .iter()
+            .any(|name| name == feature.as_str())
+    }
+
+    async fn cached_flags(&self, scope: FlagScope) -> AppResult<CachedFlags> {
+        if let Some(entry) = self.feature_cache.get(&scope) {
+            if entry.loaded_at.elapsed() < CACHE_TTL {
+                return Ok(entry.clone());
+            }
+        }
+
+        let flags = match scope {
+            FlagScope::Tenant(tenant_id) => {
+                let rows = sqlx::query_as::<_, (String, Value)>(
+                    "SELECT key, value FROM tenant_configuration WHERE tenant_id = $1 AND key LIKE 'feature_flags.%'",
+                )
+                .bind(tenant_id)
+                .fetch_all(&self.pool)
+                .await?;
+
+                CachedFlags {
+                    tenant_id: Some(tenant_id),
+                    overrides: rows
+                        .iter()
+                        .filter_map(|(key, value)| {
+                            let feature = Feature::parse(key.strip_prefix("feature_flags.")?)?;
+                            Some((feature, value.as_bool()?))
+                        })
+                        .collect(),
+                    loaded_at: Instant::now(),
+                }
+            }
+            FlagScope::Room(room_id) => {
+                let (tenant_id, room_flags) = sqlx::query_as::<_, (Option<Uuid>, Value)>(
+                    "SELECT tenant_id, feature_flags FROM rooms WHERE id = $1",
+                )
+                .bind(room_id)
+                .fetch_optional(&self.pool)
+                .await?
+                .ok_or_else(|| AppError::NotFound("Room not found".into()))?;
+
+                CachedFlags {
+                    tenant_id,
+                    overrides: room_flags
+                        .as_object()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|(name, value)| Some((Feature::parse(name)?, value.as_bool()?)))
+                        .collect(),
+                    loaded_at: Instant::now(),
+                }
+            }
+        };
+
+        self.feature_cache.insert(scope, flags.clone());
+        Ok(flags)
+    }
+}
@@ -0,0 +1,40 @@
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+/// Cells per side of the identicon grid.
+const GRID: usize = 5;
+
+/// Rendered size of one cell, in SVG units.
+const CELL: usize = 16;
+
+/// A deterministic, GitHub-style identicon for `seed` as an SVG document: a horizontally
+/// mirrored 5x5 pattern in a single color, both derived from a hash of the seed.
+pub fn identicon_svg(seed: Uuid) -> String {
+    let hash = Sha256::digest(seed.as_bytes());
+    let hue = u16::from_be_bytes([hash[0], hash[1]]) % 360;
+    let size = GRID * CELL;
+
+    let mut svg = format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{size}" height="{size}" viewBox="0 0 {size} {size}"><rect width="{size}" height="{size}" fill="#f0f0f0"/><g fill="hsl({hue},55%,50%)">"##
+    );
+    // Only the left half (plus the middle column) is decided by the hash; the rest mirrors it
+    for row in 0..GRID {
+        for col in 0..GRID.div_ceil(2) {
+            if hash[2 + row * GRID + col] % 2 == 0 {
+                continue;
+            }
+            for x in [col, GRID - 1 - col] {
+                svg.push_str(&format!(
+                    r#"<rect x="{}" y="{}" width="{CELL}" height="{CELL}"/>"#,
+                    x * CELL,
+                    row * CELL
+                ));
+                if x == GRID - 1 - x {
+                    break;
+                }
+            }
+        }
+    }
+    svg.push_str("</g></svg>");
+    svg
+}
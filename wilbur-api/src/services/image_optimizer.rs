@@ -0,0 +1,143 @@
+use std::io::Cursor;
+
+use bytes::Bytes;
+use image::{
+    codecs::{avif::AvifEncoder, webp::WebPEncoder},
+    imageops::FilterType,
+    DynamicImage, ImageDecoder, ImageReader, ImageResult,
+};
+
+use crate::config::AppConfig;
+
+/// Uploaded image types worth re-encoding. GIFs may be animated and SVGs are vector
+/// graphics, so both are always stored as uploaded.
+const TRANSCODABLE_TYPES: &[&str] = &["image/jpeg", "image/png", "image/webp"];
+
+/// rav1e speed preset (1 slowest .. 10 fastest); uploads are encoded inline, so favor speed.
+const AVIF_SPEED: u8 = 8;
+
+/// Format large images are re-encoded to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    /// Lossless WebP; the quality setting does not apply.
+    WebP,
+    /// Lossy AVIF at the configured quality.
+    Avif,
+}
+
+impl ImageFormat {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "webp" => Some(Self::WebP),
+            "avif" => Some(Self::Avif),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::WebP => "image/webp",
+            Self::Avif => "image/avif",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::WebP => "webp",
+            Self::Avif => "avif",
+        }
+    }
+}
+
+/// Re-encode an uploaded image when it is larger than `IMAGE_OPTIMIZE_MIN_BYTES` or
+/// either side exceeds `IMAGE_MAX_DIMENSION`, scaling it down to fit the latter.
+///
+/// Best-effort: `None` means store the upload as-is, whether because optimization is
+/// off, the type isn't transcodable, decoding or encoding failed, or the result wasn't
+/// smaller.
+pub async fn optimize(
+    config: &AppConfig,
+    data: &Bytes,
+    content_type: &str,
+) -> Option<(ImageFormat, Bytes)> {
+    let format = config.image_optimize_format?;
+    if !TRANSCODABLE_TYPES.contains(&content_type) {
+        return None;
+    }
+
+    let input = data.clone();
+    let min_bytes = config.image_optimize_min_bytes;
+    let max_dimension = config.image_max_dimension;
+    let quality = config.image_quality;
+    let result = tokio::task::spawn_blocking(move || {
+        transcode(&input, format, min_bytes, max_dimension, quality)
+    })
+    .await;
+
+    match result {
+        Ok(Ok(Some(encoded))) if encoded.len() < data.len() => {
+            tracing::debug!(
+                content_type,
+                from = data.len(),
+                to = encoded.len(),
+                "Transcoded uploaded image"
+            );
+            Some((format, Bytes::from(encoded)))
+        }
+        Ok(Ok(_)) => None,
+        Ok(Err(e)) => {
+            tracing::warn!(
+                content_type,
+                "Image transcoding failed, storing original: {e}"
+            );
+            None
+        }
+        Err(e) => {
+            tracing::error!("Image transcoding task failed: {e}");
+            None
+        }
+    }
+}
+
+/// Decode, downscale, and encode on a blocking thread. `Ok(None)` when the image is
+/// already within both limits.
+fn transcode(
+    data: &[u8],
+    format: ImageFormat,
+    min_bytes: usize,
+    max_dimension: u32,
+    quality: u8,
+) -> ImageResult<Option<Vec<u8>>> {
+    let mut decoder = ImageReader::new(Cursor::new(data))
+        .with_guessed_format()?
+        .into_decoder()?;
+    let (width, height) = decoder.dimensions();
+    let oversized = width.max(height) > max_dimension;
+    if !oversized && data.len() <= min_bytes {
+        return Ok(None);
+    }
+
+    // Re-encoding drops EXIF, so bake the camera orientation into the pixels first
+    let orientation = decoder.orientation()?;
+    let mut image = DynamicImage::from_decoder(decoder)?;
+    image.apply_orientation(orientation);
+    if oversized {
+        image = image.resize(max_dimension, max_dimension, FilterType::Lanczos3);
+    }
+
+    // Both encoders take 8-bit RGB(A) only
+    let image = if image.color().has_alpha() {
+        DynamicImage::ImageRgba8(image.to_rgba8())
+    } else {
+        DynamicImage::ImageRgb8(image.to_rgb8())
+    };
+
+    let mut out = Vec::new();
+    match format {
+        ImageFormat::WebP => image.write_with_encoder(WebPEncoder::new_lossless(&mut out))?,
+        ImageFormat::Avif => image.write_with_encoder(AvifEncoder::new_with_speed_quality(
+            &mut out, AVIF_SPEED, quality,
+        ))?,
+    }
+    Ok(Some(out))
+}
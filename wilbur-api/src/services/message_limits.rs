@@ -0,0 +1,56 @@
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::{
+    config::AppConfig,
+    error::{AppError, AppResult},
+    i18n::Message,
+};
+
+/// Enforce the configured length limit on a message body, for room messages and DMs.
+pub fn check_content(config: &AppConfig, content: &str) -> AppResult<()> {
+    let max = config.message_max_chars;
+    if content.chars().count() > max {
+        return Err(AppError::BadRequest(
+            Message::new("`content` exceeds the limit of {max} characters per message")
+                .with("max", max),
+        ));
+    }
+    Ok(())
+}
+
+/// Enforce the configured attachment count and combined size limits on the room files
+/// about to be attached to a message. Files that don't exist add nothing to the size;
+/// attaching them fails later.
+pub async fn check_attachments(
+    config: &AppConfig,
+    conn: &mut PgConnection,
+    file_ids: &[Uuid],
+) -> AppResult<()> {
+    let max = config.message_max_attachments;
+    if file_ids.len() > max {
+        return Err(AppError::BadRequest(
+            Message::new("`file_ids` exceeds the limit of {max} attachments per message")
+                .with("max", max),
+        ));
+    }
+    if file_ids.is_empty() {
+        return Ok(());
+    }
+
+    let total: i64 = sqlx::query_scalar(
+        "SELECT COALESCE(SUM(file_size), 0)::BIGINT FROM room_files WHERE id = ANY($1)",
+    )
+    .bind(file_ids)
+    .fetch_one(conn)
+    .await?;
+
+    let max_bytes = config.message_max_attachment_bytes;
+    if total > max_bytes {
+        return Err(AppError::BadRequest(
+            Message::new("`file_ids` exceeds the limit of {max} attachment bytes per message")
+                .with("max", max_bytes),
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use uuid::Uuid;
+
+use crate::error::AppResult;
+use crate::services::storage_cleanup;
+use crate::state::AppState;
+
+/// How often expired messages are purged.
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Messages deleted per transaction, keeping row locks short.
+const BATCH_SIZE: i64 = 500;
+
+/// Spawn the background task that hard-deletes messages past their room's retention window.
+pub fn spawn(state: Arc<AppState>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PURGE_INTERVAL);
+        loop {
+            interval.tick().await;
+            match purge_expired_messages(&state).await {
+                Ok(counts) => {
+                    for (room_id, count) in counts {
+                        tracing::info!(%room_id, count, "Purged messages past retention");
+                    }
+                }
+                Err(e) => tracing::error!("Message retention purge failed: {e}"),
+            }
+        }
+    });
+}
+
+/// Delete expired messages batch by batch until none remain, returning counts per room.
+///
+/// Rooms with no retention (null or zero days) are skipped, and pinned messages are kept
+/// when the room exempts them. Rows locked by concurrent edits are left for the next run.
+async fn purge_expired_messages(state: &Arc<AppState>) -> AppResult<HashMap<Uuid, u64>> {
+    let mut counts: HashMap<Uuid, u64> = HashMap::new();
+
+    loop {
+        let mut tx = state.pool.begin().await?;
+
+        let expired = sqlx::query_as::<_, (Uuid, Uuid)>(
+            r#"
+            SELECT m.id, m.room_id
+            FROM chatmessages m
+            JOIN rooms r ON r.id = m.room_id
+            WHERE r.message_retention_days > 0
+              AND m.created_at < NOW() - make_interval(days => r.message_retention_days)
+              AND NOT (r.retention_exempt_pinned AND COALESCE(m.is_pinned, false))
+            LIMIT $1
+            FOR UPDATE OF m SKIP LOCKED
+            "#,
+        )
+        .bind(BATCH_SIZE)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        if expired.is_empty() {
+            break;
+        }
+
+        let ids: Vec<Uuid> = expired.iter().map(|(id, _)| *id).collect();
+
+        let file_ids = sqlx::query_scalar::<_, Uuid>(
+            "SELECT file_id FROM message_attachments WHERE message_id = ANY($1)",
+        )
+        .bind(&ids)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        sqlx::query("DELETE FROM chatmessages WHERE id = ANY($1)")
+            .bind(&ids)
+            .execute(&mut *tx)
+            .await?;
+
+        // Attachments go with their message unless a surviving message still references them
+        sqlx::query(
+            r#"
+            DELETE FROM room_files f
+            WHERE f.id = ANY($1)
+              AND NOT EXISTS (
+                  SELECT 1 FROM message_attachments a
+                  JOIN chatmessages m ON m.id = a.message_id
+                  WHERE a.file_id = f.id AND m.is_deleted = false
+              )
+            "#,
+        )
+        .bind(&file_ids)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        storage_cleanup::wake(state);
+
+        for (_, room_id) in &expired {
+            *counts.entry(*room_id).or_default() += 1;
+        }
+
+        if (expired.len() as i64) < BATCH_SIZE {
+            break;
+        }
+    }
+
+    Ok(counts)
+}
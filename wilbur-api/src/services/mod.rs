@@ -1 +1,17 @@
+pub mod alert_scheduler;
+pub mod content_sanitizer;
 pub mod email_service;
+pub mod email_templates;
+pub mod feature_flags;
+pub mod identicon;
+pub mod image_optimizer;
+pub mod message_limits;
+pub mod message_retention;
+pub mod notifier;
+pub mod presence;
+pub mod room_permissions;
+pub mod storage_cleanup;
+pub mod storage_reaper;
+pub mod tenant_config;
+pub mod tenant_scope;
+pub mod webhook_dispatcher;
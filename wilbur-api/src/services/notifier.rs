@@ -0,0 +1,119 @@
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::{
+    error::{AppError, AppResult},
+    models::notification::{Notification, NotificationData, NotificationResponse},
+    ws::{channels::Channel, outbox},
+};
+
+/// Repeats of a grouped notification within this window fold into the existing unread row.
+const GROUP_WINDOW_MINUTES: i32 = 10;
+
+/// A notification to deliver to one user.
+pub struct NewNotification {
+    pub user_id: Uuid,
+    pub title: String,
+    pub body: String,
+    /// Typed payload; also determines the notification type.
+    pub data: NotificationData,
+    /// Target identifier used to collapse repeats (e.g. a chat or message ID).
+    pub group_key: Option<String>,
+}
+
+/// Store a notification and queue its push on the user's notification channel.
+///
+/// When `group_key` is set and an unread notification with the same type and key was
+/// created within the grouping window, that row's count is incremented and its content
+/// and timestamp refreshed instead of inserting a new row. Nothing is stored and `None` is
+/// returned when the user has muted the room the notification is about. Callers must wake
+/// the outbox dispatcher after committing.
+pub async fn notify(
+    conn: &mut PgConnection,
+    new: NewNotification,
+) -> AppResult<Option<Notification>> {
+    if let Some(room_id) = new.data.muted_by_room() {
+        let muted: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM user_room_preferences
+                WHERE user_id = $1 AND room_id = $2 AND is_muted = true
+            )
+            "#,
+        )
+        .bind(new.user_id)
+        .bind(room_id)
+        .fetch_one(&mut *conn)
+        .await?;
+        if muted {
+            return Ok(None);
+        }
+    }
+
+    let notification_type = new.data.notification_type();
+    let data = new.data.to_value();
+
+    let grouped = match &new.group_key {
+        Some(group_key) => {
+            sqlx::query_as::<_, Notification>(
+                r#"
+                UPDATE notifications SET
+                    count      = count + 1,
+                    title      = $1,
+                    body       = $2,
+                    data       = $3,
+                    created_at = NOW()
+                WHERE id = (
+                    SELECT id FROM notifications
+                    WHERE user_id = $4 AND notification_type = $5 AND group_key = $6
+                      AND is_read = false AND deleted_at IS NULL
+                      AND created_at > NOW() - make_interval(mins => $7)
+                    ORDER BY created_at DESC
+                    LIMIT 1
+                    FOR UPDATE
+                )
+                RETURNING id, user_id, title, body, notification_type, is_read, data, group_key, count, created_at, updated_at, deleted_at
+                "#,
+            )
+            .bind(&new.title)
+            .bind(&new.body)
+            .bind(&data)
+            .bind(new.user_id)
+            .bind(notification_type)
+            .bind(group_key)
+            .bind(GROUP_WINDOW_MINUTES)
+            .fetch_optional(&mut *conn)
+            .await?
+        }
+        None => None,
+    };
+
+    let notification = match grouped {
+        Some(notification) => notification,
+        None => {
+            sqlx::query_as::<_, Notification>(
+                r#"
+                INSERT INTO notifications (id, user_id, title, body, notification_type, is_read, data, group_key, count, created_at)
+                VALUES ($1, $2, $3, $4, $5, false, $6, $7, 1, NOW())
+                RETURNING id, user_id, title, body, notification_type, is_read, data, group_key, count, created_at, updated_at, deleted_at
+                "#,
+            )
+            .bind(Uuid::new_v4())
+            .bind(new.user_id)
+            .bind(&new.title)
+            .bind(&new.body)
+            .bind(notification_type)
+            .bind(&data)
+            .bind(&new.group_key)
+            .fetch_one(&mut *conn)
+            .await?
+        }
+    };
+
+    let payload = serde_json::to_value(NotificationResponse::from(notification.clone()))
+        .map_err(|e| AppError::Internal(format!("Serialization error: {e}")))?;
+    let channel = Channel::user_notifications(notification.user_id);
+    outbox::enqueue(conn, &channel, "notification", &payload).await?;
+
+    Ok(Some(notification))
+}
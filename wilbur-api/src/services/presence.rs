@@ -0,0 +1,63 @@
+use std::time::{Duration, Instant};
+
+use dashmap::mapref::entry::Entry;
+use uuid::Uuid;
+
+use crate::state::AppState;
+
+/// Least time between two `last_seen_at` writes for the same user.
+const WRITE_INTERVAL: Duration = Duration::from_secs(60);
+
+impl AppState {
+    /// Note that a user was just active. Writes `users.last_seen_at` in the background,
+    /// at most once per `WRITE_INTERVAL` per user, so busy clients don't cause a write
+    /// per request or message.
+    pub fn touch_last_seen(&self, user_id: Uuid) {
+        let now = Instant::now();
+        match self.last_seen_writes.entry(user_id) {
+            Entry::Occupied(e) if now.duration_since(*e.get()) < WRITE_INTERVAL => return,
+            Entry::Occupied(mut e) => {
+                e.insert(now);
+            }
+            Entry::Vacant(e) => {
+                e.insert(now);
+            }
+        }
+
+        let pool = self.pool.clone();
+        tokio::spawn(async move {
+            if let Err(e) = sqlx::query("UPDATE users SET last_seen_at = NOW() WHERE id = $1")
+                .bind(user_id)
+                .execute(&pool)
+                .await
+            {
+                tracing::warn!(user_id = %user_id, "Failed to record last seen: {e}");
+            }
+        });
+    }
+
+    /// Forget throttle entries old enough that the next activity would be written anyway.
+    pub fn prune_last_seen(&self) {
+        self.last_seen_writes
+            .retain(|_, written_at| written_at.elapsed() < WRITE_INTERVAL);
+    }
+
+    /// Record one of a user's WebSocket connections opening.
+    pub fn ws_connected(&self, user_id: Uuid) {
+        *self.ws_connections.entry(user_id).or_insert(0) += 1;
+        self.touch_last_seen(user_id);
+    }
+
+    /// Record one of a user's WebSocket connections closing.
+    pub fn ws_disconnected(&self, user_id: Uuid) {
+        self.ws_connections.remove_if_mut(&user_id, |_, count| {
+            *count = count.saturating_sub(1);
+            *count == 0
+        });
+    }
+
+    /// Whether the user has at least one WebSocket connection open on this server.
+    pub fn is_online(&self, user_id: Uuid) -> bool {
+        self.ws_connections.contains_key(&user_id)
+    }
+}
@@ -0,0 +1,102 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+use crate::models::membership::{MemberRole, RoomMembership};
+
+/// Something a room member may be allowed to do, gated by a per-room minimum role.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    PostAlert,
+    CreatePoll,
+    PostMessage,
+    UploadFile,
+}
+
+impl Capability {
+    pub const ALL: [Capability; 4] = [
+        Capability::PostAlert,
+        Capability::CreatePoll,
+        Capability::PostMessage,
+        Capability::UploadFile,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Capability::PostAlert => "post_alert",
+            Capability::CreatePoll => "create_poll",
+            Capability::PostMessage => "post_message",
+            Capability::UploadFile => "upload_file",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Capability> {
+        Capability::ALL.into_iter().find(|c| c.as_str() == name)
+    }
+
+    /// Minimum role when the room hasn't set one.
+    pub fn default_role(self) -> MemberRole {
+        match self {
+            Capability::PostAlert => MemberRole::Moderator,
+            Capability::CreatePoll | Capability::PostMessage | Capability::UploadFile => {
+                MemberRole::Member
+            }
+        }
+    }
+}
+
+/// Every capability's minimum role in a room, with the room's settings over the defaults.
+pub async fn room_permissions(
+    pool: &PgPool,
+    room_id: Uuid,
+) -> AppResult<BTreeMap<Capability, MemberRole>> {
+    let (overrides,): (Value,) = sqlx::query_as("SELECT permissions FROM rooms WHERE id = $1")
+        .bind(room_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Room not found".into()))?;
+
+    let mut permissions: BTreeMap<Capability, MemberRole> = Capability::ALL
+        .into_iter()
+        .map(|c| (c, c.default_role()))
+        .collect();
+    for (name, role) in overrides.as_object().into_iter().flatten() {
+        let (Some(capability), Ok(role)) = (
+            Capability::parse(name),
+            serde_json::from_value::<MemberRole>(role.clone()),
+        ) else {
+            continue;
+        };
+        permissions.insert(capability, role);
+    }
+
+    Ok(permissions)
+}
+
+/// Verify the member's role meets the room's minimum for `capability`.
+/// Returns `AppError::Forbidden` if it doesn't.
+pub async fn require_capability(
+    pool: &PgPool,
+    membership: &RoomMembership,
+    capability: Capability,
+) -> AppResult<()> {
+    let permissions = room_permissions(pool, membership.room_id).await?;
+    let min = &permissions[&capability];
+
+    if membership.role.at_least(min) {
+        Ok(())
+    } else {
+        Err(AppError::Forbidden(
+            format!(
+                "Your role in this room does not allow {}",
+                capability.as_str()
+            )
+            .into(),
+        ))
+    }
+}
@@ -0,0 +1,139 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::error::AppResult;
+use crate::state::AppState;
+
+/// Base delay for exponential backoff between attempts.
+const BASE_BACKOFF_SECS: i64 = 30;
+
+/// Upper bound on the delay between attempts; failed deletions keep retrying at this rate.
+const MAX_BACKOFF_SECS: i64 = 3600;
+
+/// Attempts after which each further failure is logged as an error rather than a warning.
+const ALERT_AFTER_ATTEMPTS: i32 = 8;
+
+/// How long a claimed deletion is leased to this worker before another may retry it.
+const LEASE_SECS: i32 = 120;
+
+/// How often the worker polls for due deletions when not woken explicitly.
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Maximum number of objects deleted per batch.
+const BATCH_SIZE: i64 = 100;
+
+#[derive(Debug, FromRow)]
+struct DueDeletion {
+    id: Uuid,
+    storage_key: String,
+    attempts: i32,
+}
+
+/// Wake the cleanup worker after committing a transaction that deleted stored files.
+///
+/// Deleting a `room_files` row queues its object in `storage_deletions` (via trigger),
+/// so the object is removed even if this wake-up or the first attempt is lost.
+pub fn wake(state: &AppState) {
+    state.storage_notify.notify_one();
+}
+
+/// Spawn the background worker that removes queued objects from storage with retries.
+pub fn spawn(state: Arc<AppState>) {
+    if !state.config.storage_enabled() {
+        return;
+    }
+
+    tokio::spawn(async move {
+        loop {
+            if let Err(e) = delete_due(&state).await {
+                tracing::error!("Storage cleanup batch failed: {e}");
+            }
+
+            tokio::select! {
+                _ = state.storage_notify.notified() => {},
+                _ = tokio::time::sleep(POLL_INTERVAL) => {},
+            }
+        }
+    });
+}
+
+/// Claim a batch of due deletions and attempt each one.
+async fn delete_due(state: &Arc<AppState>) -> AppResult<()> {
+    let due = sqlx::query_as::<_, DueDeletion>(
+        r#"
+        WITH claimed AS (
+            SELECT id FROM storage_deletions
+            WHERE next_attempt_at <= NOW()
+            ORDER BY next_attempt_at ASC
+            LIMIT $1
+            FOR UPDATE SKIP LOCKED
+        )
+        UPDATE storage_deletions d
+        SET next_attempt_at = NOW() + make_interval(secs => $2)
+        FROM claimed
+        WHERE d.id = claimed.id
+        RETURNING d.id, d.storage_key, d.attempts
+        "#,
+    )
+    .bind(BATCH_SIZE)
+    .bind(LEASE_SECS)
+    .fetch_all(&state.pool)
+    .await?;
+
+    let attempts = due.into_iter().map(|d| attempt_deletion(state, d));
+    futures::future::join_all(attempts).await;
+
+    Ok(())
+}
+
+/// Delete one object and drop it from the queue, or schedule a retry on failure.
+///
+/// S3 reports success for keys that no longer exist, so retries are safe.
+async fn attempt_deletion(state: &Arc<AppState>, deletion: DueDeletion) {
+    let outcome = state
+        .s3
+        .delete_object()
+        .bucket(&state.config.s3_bucket)
+        .key(&deletion.storage_key)
+        .send()
+        .await;
+
+    let result = match outcome {
+        Ok(_) => {
+            sqlx::query("DELETE FROM storage_deletions WHERE id = $1")
+                .bind(deletion.id)
+                .execute(&state.pool)
+                .await
+        }
+        Err(e) => {
+            let attempts = deletion.attempts + 1;
+            let error = e.to_string();
+            if attempts >= ALERT_AFTER_ATTEMPTS {
+                tracing::error!(key = %deletion.storage_key, attempts, "Storage object deletion keeps failing: {error}");
+            } else {
+                tracing::warn!(key = %deletion.storage_key, attempts, "Storage object deletion failed, retrying: {error}");
+            }
+            let backoff = (BASE_BACKOFF_SECS << (attempts - 1).min(16)).min(MAX_BACKOFF_SECS);
+            sqlx::query(
+                r#"
+                UPDATE storage_deletions
+                SET attempts = $2, last_error = $3, next_attempt_at = NOW() + make_interval(secs => $4)
+                WHERE id = $1
+                "#,
+            )
+            .bind(deletion.id)
+            .bind(attempts)
+            .bind(&error)
+            .bind(backoff as f64)
+            .execute(&state.pool)
+            .await
+        }
+    };
+
+    if let Err(e) = result {
+        tracing::error!(deletion_id = %deletion.id, "Failed to record storage deletion result: {e}");
+    }
+}
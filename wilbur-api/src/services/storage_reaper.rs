@@ -0,0 +1,176 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use crate::error::{AppError, AppResult};
+use crate::state::AppState;
+
+/// How often storage is reconciled against the database.
+const RECONCILE_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Outcome of one reconciliation pass.
+#[derive(Debug, Default)]
+struct ReapReport {
+    scanned: usize,
+    orphaned: usize,
+    deleted: usize,
+    missing: usize,
+}
+
+/// Spawn the background task that finds stored objects no database row references
+/// (crashed or unrecorded uploads, failed deletes, manual edits) and rows whose object
+/// is gone.
+///
+/// Only the configured bucket and prefixes are scanned. Orphans are logged, and deleted
+/// only when `STORAGE_REAPER_DELETE` is enabled.
+pub fn spawn(state: Arc<AppState>) {
+    if !state.config.storage_enabled() || state.config.storage_reaper_prefixes.is_empty() {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(RECONCILE_INTERVAL);
+        loop {
+            interval.tick().await;
+            match reconcile(&state).await {
+                Ok(report) => tracing::info!(
+                    scanned = report.scanned,
+                    orphaned = report.orphaned,
+                    deleted = report.deleted,
+                    missing = report.missing,
+                    dry_run = !state.config.storage_reaper_delete,
+                    "Storage reconciliation finished"
+                ),
+                Err(e) => tracing::error!("Storage reconciliation failed: {e}"),
+            }
+        }
+    });
+}
+
+/// List every object under the configured prefixes, then compare against the keys the
+/// database references.
+///
+/// Objects are listed before references are loaded, so a row committed mid-scan still
+/// protects its object; the grace period covers uploads whose row isn't written yet.
+async fn reconcile(state: &AppState) -> AppResult<ReapReport> {
+    let config = &state.config;
+    let cutoff = Utc::now() - chrono::Duration::hours(config.storage_reaper_grace_hours as i64);
+
+    let mut objects: Vec<(String, Option<DateTime<Utc>>)> = Vec::new();
+    for prefix in &config.storage_reaper_prefixes {
+        let mut pages = state
+            .s3
+            .list_objects_v2()
+            .bucket(&config.s3_bucket)
+            .prefix(prefix)
+            .into_paginator()
+            .send();
+        while let Some(page) = pages.next().await {
+            let page =
+                page.map_err(|e| AppError::Internal(format!("S3 list failed for {prefix}: {e}")))?;
+            for object in page.contents() {
+                if let Some(key) = object.key() {
+                    let modified = object
+                        .last_modified()
+                        .and_then(|t| DateTime::from_timestamp(t.secs(), 0));
+                    objects.push((key.to_string(), modified));
+                }
+            }
+        }
+    }
+
+    let referenced = referenced_keys(state).await?;
+    let referenced_set: HashSet<&str> = referenced.iter().map(|(key, _)| key.as_str()).collect();
+    let mut report = ReapReport {
+        scanned: objects.len(),
+        ..Default::default()
+    };
+
+    for (key, modified) in &objects {
+        if referenced_set.contains(key.as_str()) || modified.is_none_or(|m| m > cutoff) {
+            continue;
+        }
+        report.orphaned += 1;
+
+        if !config.storage_reaper_delete {
+            tracing::info!(%key, "Orphaned storage object (dry run, not deleted)");
+            continue;
+        }
+        match state
+            .s3
+            .delete_object()
+            .bucket(&config.s3_bucket)
+            .key(key)
+            .send()
+            .await
+        {
+            Ok(_) => {
+                report.deleted += 1;
+                tracing::info!(%key, "Deleted orphaned storage object");
+            }
+            Err(e) => tracing::warn!(%key, "Failed to delete orphaned storage object: {e}"),
+        }
+    }
+
+    let listed: HashSet<&str> = objects.iter().map(|(key, _)| key.as_str()).collect();
+    for (key, since) in &referenced {
+        let scanned = config
+            .storage_reaper_prefixes
+            .iter()
+            .any(|p| key.starts_with(p.as_str()));
+        if scanned && *since < cutoff && !listed.contains(key.as_str()) {
+            report.missing += 1;
+            tracing::warn!(%key, "Database references a storage object that does not exist");
+        }
+    }
+
+    Ok(report)
+}
+
+/// Object keys referenced by database rows, with when the reference was last written.
+///
+/// Room files store their key (and their original's, if kept); alert media, avatars, room
+/// backgrounds and tenant images store a public URL, which maps back to a key only when it
+/// points at the configured endpoint and bucket.
+async fn referenced_keys(state: &AppState) -> AppResult<Vec<(String, DateTime<Utc>)>> {
+    let rows = sqlx::query_as::<_, (String, DateTime<Utc>)>(
+        r#"
+        SELECT storage_key, COALESCE(created_at, NOW()) FROM room_files
+        UNION ALL
+        SELECT original_storage_key, COALESCE(created_at, NOW()) FROM room_files
+        WHERE original_storage_key IS NOT NULL
+        UNION ALL
+        SELECT media_url, COALESCE(created_at, NOW()) FROM alerts WHERE media_url IS NOT NULL
+        UNION ALL
+        SELECT avatar_url, COALESCE(updated_at, NOW()) FROM users WHERE avatar_url IS NOT NULL
+        UNION ALL
+        SELECT background_image_url, COALESCE(updated_at, NOW()) FROM rooms
+        WHERE background_image_url IS NOT NULL
+        UNION ALL
+        SELECT logo_url, COALESCE(updated_at, NOW()) FROM tenants WHERE logo_url IS NOT NULL
+        UNION ALL
+        SELECT background_image_url, COALESCE(updated_at, NOW()) FROM tenants
+        WHERE background_image_url IS NOT NULL
+        UNION ALL
+        SELECT favicon_url, COALESCE(updated_at, NOW()) FROM tenants WHERE favicon_url IS NOT NULL
+        "#,
+    )
+    .fetch_all(&state.pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|(reference, since)| {
+            if reference.contains("://") {
+                state
+                    .config
+                    .storage_key_for_url(&reference)
+                    .map(|key| (key.to_string(), since))
+            } else {
+                Some((reference, since))
+            }
+        })
+        .collect())
+}
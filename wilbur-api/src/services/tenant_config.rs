@@ -0,0 +1,156 @@
+use std::marker::PhantomData;
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use sqlx::PgExecutor;
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+use crate::models::alert::{MAX_LEGAL_DISCLOSURE_CHARS, MIN_LEGAL_DISCLOSURE_CHARS};
+use crate::services::feature_flags::Feature;
+
+/// Keys under this prefix are stored without type checks, for settings only clients read.
+const CUSTOM_PREFIX: &str = "custom.";
+
+/// Longest accepted configuration key.
+const MAX_KEY_LEN: usize = 100;
+
+/// Value type a configuration key must hold.
+#[derive(Debug, Clone, Copy)]
+enum ValueKind {
+    Bool,
+    PositiveInt,
+    /// A string whose trimmed length, in characters, is within the bounds.
+    Text {
+        min: usize,
+        max: usize,
+    },
+}
+
+/// A known configuration key. Prefix entries cover a family of keys (`feature_flags.<name>`)
+/// whose suffix must be accepted by `names`.
+struct KeySpec {
+    key: &'static str,
+    kind: ValueKind,
+    names: Option<fn(&str) -> bool>,
+}
+
+/// Every tenant configuration key the server understands.
+static KNOWN_KEYS: &[KeySpec] = &[
+    KeySpec {
+        key: "max_room_size",
+        kind: ValueKind::PositiveInt,
+        names: None,
+    },
+    KeySpec {
+        key: "feature_flags.",
+        kind: ValueKind::Bool,
+        names: Some(|name| Feature::parse(name).is_some()),
+    },
+    KeySpec {
+        key: "default_legal_disclosure",
+        kind: ValueKind::Text {
+            min: MIN_LEGAL_DISCLOSURE_CHARS,
+            max: MAX_LEGAL_DISCLOSURE_CHARS as usize,
+        },
+        names: None,
+    },
+];
+
+/// A registered key together with the Rust type its value is read as.
+pub struct Setting<T> {
+    key: &'static str,
+    _value: PhantomData<fn() -> T>,
+}
+
+impl<T> Setting<T> {
+    const fn new(key: &'static str) -> Self {
+        Self {
+            key,
+            _value: PhantomData,
+        }
+    }
+}
+
+/// Upper bound on `max_members` for rooms in the tenant.
+pub const MAX_ROOM_SIZE: Setting<i64> = Setting::new("max_room_size");
+
+/// Disclosure used for the tenant's `buy`/`sell` alerts when the author omits one.
+pub const DEFAULT_LEGAL_DISCLOSURE: Setting<String> = Setting::new("default_legal_disclosure");
+
+/// Check a key/value pair against the registry before it is written.
+///
+/// Unknown keys are rejected unless they use the `custom.` prefix.
+pub fn validate(key: &str, value: &Value) -> AppResult<()> {
+    if key.is_empty() || key.len() > MAX_KEY_LEN {
+        return Err(AppError::Validation(
+            format!("Configuration key must be 1-{MAX_KEY_LEN} characters").into(),
+        ));
+    }
+
+    if let Some(name) = key.strip_prefix(CUSTOM_PREFIX) {
+        return if name.is_empty() {
+            Err(AppError::Validation(
+                format!("'{CUSTOM_PREFIX}' keys need a name after the prefix").into(),
+            ))
+        } else {
+            Ok(())
+        };
+    }
+
+    let spec = KNOWN_KEYS
+        .iter()
+        .find(|spec| match spec.names {
+            Some(accepts) => key.strip_prefix(spec.key).is_some_and(accepts),
+            None => key == spec.key,
+        })
+        .ok_or_else(|| {
+            AppError::Validation(format!(
+                "Unknown configuration key '{key}' (use the '{CUSTOM_PREFIX}' prefix for custom settings)"
+            ).into())
+        })?;
+
+    let valid = match spec.kind {
+        ValueKind::Bool => value.is_boolean(),
+        ValueKind::PositiveInt => value.as_i64().is_some_and(|n| n > 0),
+        ValueKind::Text { min, max } => value
+            .as_str()
+            .is_some_and(|s| (min..=max).contains(&s.trim().chars().count())),
+    };
+    if !valid {
+        let expected = match spec.kind {
+            ValueKind::Bool => "a boolean".to_string(),
+            ValueKind::PositiveInt => "a positive integer".to_string(),
+            ValueKind::Text { min, max } => format!("a string of {min}-{max} characters"),
+        };
+        return Err(AppError::Validation(
+            format!("Configuration key '{key}' must be {expected}").into(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Read a typed setting for a tenant. Returns `None` when unset, or when the stored
+/// value predates validation and doesn't match the expected type (logged).
+pub async fn get<'e, T: DeserializeOwned>(
+    executor: impl PgExecutor<'e>,
+    tenant_id: Uuid,
+    setting: &Setting<T>,
+) -> AppResult<Option<T>> {
+    let value = sqlx::query_scalar::<_, Value>(
+        "SELECT value FROM tenant_configuration WHERE tenant_id = $1 AND key = $2",
+    )
+    .bind(tenant_id)
+    .bind(setting.key)
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(value.and_then(|v| match serde_json::from_value(v) {
+        Ok(typed) => Some(typed),
+        Err(e) => {
+            tracing::warn!(%tenant_id, key = setting.key, "Ignoring invalid tenant configuration value: {e}");
+            None
+        }
+    }))
+}
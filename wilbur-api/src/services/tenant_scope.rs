@@ -0,0 +1,123 @@
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+use crate::models::user::UserRole;
+use crate::state::AppState;
+
+/// Which rooms a user may reach when tenant isolation is on: those of their own tenant,
+/// those with no tenant, and any room they are already an active member of, so turning
+/// isolation on never locks members out of rooms they belong to. Admins, and every user
+/// when isolation is off, are unrestricted.
+#[derive(Debug, Clone, Copy)]
+pub struct TenantScope {
+    user_id: Uuid,
+    restricted: bool,
+    tenant_id: Option<Uuid>,
+}
+
+impl TenantScope {
+    /// Load the scope of `user_id` from their tenant and role.
+    pub async fn for_user(state: &AppState, user_id: Uuid) -> AppResult<Self> {
+        if !state.config.tenant_isolation {
+            return Ok(Self::unrestricted(user_id));
+        }
+
+        let (tenant_id, role): (Option<Uuid>, UserRole) =
+            sqlx::query_as("SELECT tenant_id, role FROM users WHERE id = $1")
+                .bind(user_id)
+                .fetch_optional(&state.pool)
+                .await?
+                .ok_or_else(|| AppError::Unauthorized("User no longer exists".into()))?;
+
+        if role == UserRole::Admin {
+            return Ok(Self::unrestricted(user_id));
+        }
+        Ok(Self {
+            user_id,
+            restricted: true,
+            tenant_id,
+        })
+    }
+
+    fn unrestricted(user_id: Uuid) -> Self {
+        Self {
+            user_id,
+            restricted: false,
+            tenant_id: None,
+        }
+    }
+
+    /// Whether a room belonging to `room_tenant` is within this scope.
+    pub fn allows(&self, room_tenant: Option<Uuid>) -> bool {
+        !self.restricted || room_tenant.is_none() || room_tenant == self.tenant_id
+    }
+
+    /// `AppError::Forbidden` unless a room belonging to `room_tenant` is within this scope.
+    pub fn require(&self, room_tenant: Option<Uuid>) -> AppResult<()> {
+        if self.allows(room_tenant) {
+            Ok(())
+        } else {
+            Err(AppError::Forbidden(
+                "This room belongs to another tenant".into(),
+            ))
+        }
+    }
+
+    /// `AppError::Forbidden` unless the room is within this scope or the user is an active
+    /// member of it.
+    pub async fn require_room(
+        &self,
+        state: &AppState,
+        room_id: Uuid,
+        room_tenant: Option<Uuid>,
+    ) -> AppResult<()> {
+        if self.allows(room_tenant) {
+            return Ok(());
+        }
+
+        let is_member: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM room_memberships
+                WHERE room_id = $1 AND user_id = $2 AND status = 'active'
+            )
+            "#,
+        )
+        .bind(room_id)
+        .bind(self.user_id)
+        .fetch_one(&state.pool)
+        .await?;
+
+        if is_member {
+            Ok(())
+        } else {
+            self.require(room_tenant)
+        }
+    }
+
+    /// Bind values for a room filter of the form
+    /// `(NOT $n OR tenant_id IS NULL OR tenant_id = $n+1 OR <active member of the room>)`.
+    pub fn sql_filter(&self) -> (bool, Option<Uuid>) {
+        (self.restricted, self.tenant_id)
+    }
+}
+
+/// Verify the room is within the user's tenant scope. Rooms that don't exist pass, so the
+/// caller's own lookup can report them as not found.
+pub async fn require_room_tenant(state: &AppState, user_id: Uuid, room_id: Uuid) -> AppResult<()> {
+    let scope = TenantScope::for_user(state, user_id).await?;
+    if !scope.restricted {
+        return Ok(());
+    }
+
+    let room_tenant =
+        sqlx::query_scalar::<_, Option<Uuid>>("SELECT tenant_id FROM rooms WHERE id = $1")
+            .bind(room_id)
+            .fetch_optional(&state.pool)
+            .await?;
+
+    match room_tenant {
+        Some(room_tenant) => scope.require_room(state, room_id, room_tenant).await,
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,360 @@
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use serde_json::json;
+use sha2::Sha256;
+use sqlx::{FromRow, PgConnection};
+use url::Url;
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+use crate::state::AppState;
+use crate::ws::channels::Channel;
+
+/// Deliveries are marked dead after this many failed attempts.
+const MAX_ATTEMPTS: i32 = 8;
+
+/// Base delay for exponential backoff between attempts.
+const BASE_BACKOFF_SECS: i64 = 30;
+
+/// Upper bound on the delay between attempts.
+const MAX_BACKOFF_SECS: i64 = 3600;
+
+/// How long a claimed delivery is leased to this worker before another may retry it.
+const LEASE_SECS: i32 = 120;
+
+/// How often the worker polls for due deliveries when not woken explicitly.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Maximum number of deliveries attempted per batch.
+const BATCH_SIZE: i64 = 50;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, FromRow)]
+struct DueDelivery {
+    id: Uuid,
+    event: String,
+    payload: serde_json::Value,
+    attempts: i32,
+    url: String,
+    secret: String,
+}
+
+/// Validate a webhook URL against SSRF rules without resolving DNS.
+/// Requires https, a hostname, and rejects loopback/private/link-local literals.
+pub fn validate_webhook_url(raw: &str) -> AppResult<Url> {
+    let url =
+        Url::parse(raw).map_err(|_| AppError::Validation("Webhook URL is not valid".into()))?;
+
+    if url.scheme() != "https" {
+        return Err(AppError::Validation("Webhook URL must use https".into()));
+    }
+    if !url.username().is_empty() || url.password().is_some() {
+        return Err(AppError::Validation(
+            "Webhook URL must not contain credentials".into(),
+        ));
+    }
+
+    match url.host() {
+        Some(url::Host::Domain(domain)) => {
+            let domain = domain.to_ascii_lowercase();
+            if domain == "localhost"
+                || domain.ends_with(".localhost")
+                || domain.ends_with(".local")
+                || domain.ends_with(".internal")
+            {
+                return Err(AppError::Validation(
+                    "Webhook URL must point to a public host".into(),
+                ));
+            }
+        }
+        Some(url::Host::Ipv4(ip)) => ensure_public_ip(IpAddr::V4(ip))?,
+        Some(url::Host::Ipv6(ip)) => ensure_public_ip(IpAddr::V6(ip))?,
+        None => return Err(AppError::Validation("Webhook URL must have a host".into())),
+    }
+
+    Ok(url)
+}
+
+fn ensure_public_ip(ip: IpAddr) -> AppResult<()> {
+    let blocked = match ip {
+        IpAddr::V4(v4) => {
+            v4.is_private()
+                || v4.is_loopback()
+                || v4.is_link_local()
+                || v4.is_broadcast()
+                || v4.is_unspecified()
+                || v4.is_documentation()
+                // Carrier-grade NAT (100.64.0.0/10)
+                || (v4.octets()[0] == 100 && (v4.octets()[1] & 0xC0) == 64)
+        }
+        IpAddr::V6(v6) => {
+            if let Some(v4) = v6.to_ipv4_mapped() {
+                return ensure_public_ip(IpAddr::V4(v4));
+            }
+            v6.is_loopback()
+                || v6.is_unspecified()
+                // Unique local (fc00::/7) and link-local (fe80::/10)
+                || (v6.segments()[0] & 0xfe00) == 0xfc00
+                || (v6.segments()[0] & 0xffc0) == 0xfe80
+        }
+    };
+
+    if blocked {
+        return Err(AppError::Validation(
+            "Webhook URL must point to a public address".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// DNS resolver for the delivery client that fails when any address of a host is
+/// non-public. The client connects to exactly the addresses checked here, so a host can't
+/// pass a check and then be repointed at an internal service before the connection.
+struct PublicOnlyResolver;
+
+impl Resolve for PublicOnlyResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> =
+                tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+            if addrs.is_empty() {
+                return Err(format!("{host} resolved to no addresses").into());
+            }
+            for addr in &addrs {
+                ensure_public_ip(addr.ip())
+                    .map_err(|_| format!("{host} resolves to non-public address {}", addr.ip()))?;
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Generate a random signing secret for a new webhook.
+pub fn generate_secret() -> String {
+    let bytes: [u8; 32] = rand::random();
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// HMAC-SHA256 over `"{timestamp}.{body}"`, hex-encoded.
+fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    format!("{:x}", mac.finalize().into_bytes())
+}
+
+/// Queue deliveries for every active webhook in the room subscribed to `event`, as part of
+/// the transaction that records the event in the outbox so neither exists without the
+/// other. Keyed on `event_id`, so queueing the same event again adds nothing. Non-room
+/// channels are ignored. Returns how many deliveries were queued.
+pub async fn enqueue_deliveries(
+    conn: &mut PgConnection,
+    channel: &str,
+    event: &str,
+    payload: &serde_json::Value,
+    event_id: Uuid,
+) -> AppResult<u64> {
+    let room_id = match Channel::parse(channel) {
+        Some(
+            Channel::RoomChat(id)
+            | Channel::RoomAlerts(id)
+            | Channel::RoomTracks(id)
+            | Channel::RoomPresence(id)
+            | Channel::RoomPolls(id)
+            | Channel::RoomNotes(id),
+        ) => id,
+        _ => return Ok(0),
+    };
+
+    let body = json!({
+        "event_id": event_id,
+        "event": event,
+        "room_id": room_id,
+        "channel": channel,
+        "payload": payload,
+        "timestamp": Utc::now().to_rfc3339(),
+    });
+
+    let result = sqlx::query(
+        r#"
+        INSERT INTO webhook_deliveries (id, webhook_id, event_id, event, payload, status, attempts, next_attempt_at, created_at)
+        SELECT gen_random_uuid(), w.id, $3, $2, $4, 'pending', 0, NOW(), NOW()
+        FROM room_webhooks w
+        WHERE w.room_id = $1 AND w.is_active = true
+          AND (cardinality(w.event_types) = 0 OR $2 = ANY(w.event_types))
+        ON CONFLICT (webhook_id, event_id) DO NOTHING
+        "#,
+    )
+    .bind(room_id)
+    .bind(event)
+    .bind(event_id)
+    .bind(&body)
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Spawn the background worker that delivers queued webhook events with retries.
+pub fn spawn(state: Arc<AppState>) {
+    tokio::spawn(async move {
+        let client = match reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::none())
+            .dns_resolver(PublicOnlyResolver)
+            .build()
+        {
+            Ok(c) => c,
+            Err(e) => {
+                tracing::error!("Failed to build webhook HTTP client: {e}");
+                return;
+            }
+        };
+
+        loop {
+            if let Err(e) = deliver_due(&state, &client).await {
+                tracing::error!("Webhook delivery batch failed: {e}");
+            }
+
+            tokio::select! {
+                _ = state.webhook_notify.notified() => {},
+                _ = tokio::time::sleep(POLL_INTERVAL) => {},
+            }
+        }
+    });
+}
+
+/// Claim a batch of due deliveries and attempt each one. Deliveries for webhooks that
+/// have been deactivated since they were queued are dead-lettered instead.
+async fn deliver_due(state: &Arc<AppState>, client: &reqwest::Client) -> AppResult<()> {
+    sqlx::query(
+        r#"
+        UPDATE webhook_deliveries d
+        SET status = 'dead', last_error = 'Webhook was deactivated'
+        FROM room_webhooks w
+        WHERE w.id = d.webhook_id AND d.status = 'pending' AND w.is_active = false
+        "#,
+    )
+    .execute(&state.pool)
+    .await?;
+
+    let due = sqlx::query_as::<_, DueDelivery>(
+        r#"
+        WITH claimed AS (
+            SELECT d.id FROM webhook_deliveries d
+            WHERE d.status = 'pending' AND d.next_attempt_at <= NOW()
+            ORDER BY d.next_attempt_at ASC
+            LIMIT $1
+            FOR UPDATE SKIP LOCKED
+        )
+        UPDATE webhook_deliveries d
+        SET next_attempt_at = NOW() + make_interval(secs => $2)
+        FROM claimed, room_webhooks w
+        WHERE d.id = claimed.id AND w.id = d.webhook_id AND w.is_active = true
+        RETURNING d.id, d.event, d.payload, d.attempts, w.url, w.secret
+        "#,
+    )
+    .bind(BATCH_SIZE)
+    .bind(LEASE_SECS)
+    .fetch_all(&state.pool)
+    .await?;
+
+    let attempts = due.into_iter().map(|d| attempt_delivery(state, client, d));
+    futures::future::join_all(attempts).await;
+
+    Ok(())
+}
+
+/// POST one delivery and record the outcome, scheduling a retry or dead-lettering on failure.
+async fn attempt_delivery(state: &Arc<AppState>, client: &reqwest::Client, delivery: DueDelivery) {
+    let attempts = delivery.attempts + 1;
+    let outcome = post_signed(client, &delivery).await;
+
+    let result = match outcome {
+        Ok(status) => {
+            sqlx::query(
+                r#"
+                UPDATE webhook_deliveries
+                SET status = 'delivered', attempts = $2, response_status = $3,
+                    last_error = NULL, delivered_at = NOW()
+                WHERE id = $1
+                "#,
+            )
+            .bind(delivery.id)
+            .bind(attempts)
+            .bind(status)
+            .execute(&state.pool)
+            .await
+        }
+        Err((status, error)) => {
+            let dead = attempts >= MAX_ATTEMPTS;
+            let backoff = (BASE_BACKOFF_SECS << (attempts - 1).min(16)).min(MAX_BACKOFF_SECS);
+            if dead {
+                tracing::warn!(delivery_id = %delivery.id, event = %delivery.event, "Webhook delivery dead-lettered: {error}");
+            }
+            sqlx::query(
+                r#"
+                UPDATE webhook_deliveries
+                SET status = $2, attempts = $3, response_status = $4, last_error = $5,
+                    next_attempt_at = NOW() + make_interval(secs => $6)
+                WHERE id = $1
+                "#,
+            )
+            .bind(delivery.id)
+            .bind(if dead { "dead" } else { "pending" })
+            .bind(attempts)
+            .bind(status)
+            .bind(&error)
+            .bind(backoff as f64)
+            .execute(&state.pool)
+            .await
+        }
+    };
+
+    if let Err(e) = result {
+        tracing::error!(delivery_id = %delivery.id, "Failed to record webhook delivery result: {e}");
+    }
+}
+
+/// Send the signed request. Returns the HTTP status on 2xx, or `(status, error)` otherwise.
+async fn post_signed(
+    client: &reqwest::Client,
+    delivery: &DueDelivery,
+) -> Result<i32, (Option<i32>, String)> {
+    let url = validate_webhook_url(&delivery.url).map_err(|e| (None, e.to_string()))?;
+
+    let body = serde_json::to_vec(&delivery.payload).map_err(|e| (None, e.to_string()))?;
+    let timestamp = Utc::now().timestamp();
+    let signature = sign(&delivery.secret, timestamp, &body);
+
+    let response = client
+        .post(url)
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .header("X-Wilbur-Event", &delivery.event)
+        .header("X-Wilbur-Delivery", delivery.id.to_string())
+        .header("X-Wilbur-Timestamp", timestamp.to_string())
+        .header("X-Wilbur-Signature", format!("sha256={signature}"))
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| (None, format!("Request failed: {e}")))?;
+
+    let status = response.status();
+    if status.is_success() {
+        Ok(status.as_u16() as i32)
+    } else {
+        Err((
+            Some(status.as_u16() as i32),
+            format!("Endpoint responded with {status}"),
+        ))
+    }
+}
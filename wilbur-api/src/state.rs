@@ -1,8 +1,21 @@
+use std::time::Instant;
+
+use chrono::NaiveDate;
 use dashmap::DashMap;
 use sqlx::PgPool;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, Notify};
+use uuid::Uuid;
 
-use crate::config::AppConfig;
+use crate::{
+    config::AppConfig,
+    middleware::rate_limit::{create_hook_rate_limiter, ApiRateLimiter, HookRateLimiter},
+    models::analytics::RoomAnalytics,
+    services::{
+        email_service::EmailService,
+        feature_flags::{CachedFlags, FlagScope},
+    },
+    ws::replay::ReplayStore,
+};
 
 pub type WsSender = mpsc::UnboundedSender<String>;
 
@@ -11,17 +24,67 @@ pub struct AppState {
     pub pool: PgPool,
     pub config: AppConfig,
     pub s3: aws_sdk_s3::Client,
+    /// Outbound email queue; `None` when SMTP is not configured.
+    pub email: Option<EmailService>,
     /// WebSocket channel subscriptions: channel_name → list of senders
     pub ws_channels: DashMap<String, Vec<WsSender>>,
+    /// Recent events per channel, replayed to clients that resume after reconnecting.
+    pub ws_replay: ReplayStore,
+    /// Open sockets per (channel, user) subscription, so presence reflects the user
+    /// rather than individual tabs.
+    pub ws_presence: DashMap<(String, Uuid), usize>,
+    /// Open sockets per user across all channels; a user with any is online.
+    pub ws_connections: DashMap<Uuid, usize>,
+    /// Latest typing signal per (DM chat, user), so the indicator expires on its own.
+    pub dm_typing: DashMap<(Uuid, Uuid), Instant>,
+    /// When each user's `last_seen_at` was last written, to throttle the writes.
+    pub last_seen_writes: DashMap<Uuid, Instant>,
+    /// Wakes the outbox dispatcher after a transaction with outbox events commits.
+    pub outbox_notify: Notify,
+    /// Wakes the webhook delivery worker when new deliveries are queued.
+    pub webhook_notify: Notify,
+    /// Wakes the storage cleanup worker when stored files are deleted.
+    pub storage_notify: Notify,
+    /// Per-hook rate limits for incoming room hooks.
+    pub hook_limiter: HookRateLimiter,
+    /// Per-user (or, signed out, per-IP) rate limits for the general API.
+    pub api_limiter: ApiRateLimiter,
+    /// Recently computed room analytics by (room, from, to), with when each was computed.
+    pub analytics_cache: DashMap<(Uuid, NaiveDate, NaiveDate), (Instant, RoomAnalytics)>,
+    /// Recently loaded feature flag overrides per tenant and room.
+    pub feature_cache: DashMap<FlagScope, CachedFlags>,
+    /// When the server started, for uptime in `/health`.
+    pub started_at: Instant,
 }
 
 impl AppState {
-    pub fn new(pool: PgPool, config: AppConfig, s3: aws_sdk_s3::Client) -> Self {
+    pub fn new(
+        pool: PgPool,
+        config: AppConfig,
+        s3: aws_sdk_s3::Client,
+        email: Option<EmailService>,
+    ) -> Self {
+        let api_limiter = ApiRateLimiter::new(&config);
+        let ws_replay = ReplayStore::new(config.ws_replay_buffer_size);
         Self {
             pool,
             config,
             s3,
+            email,
             ws_channels: DashMap::new(),
+            ws_replay,
+            ws_presence: DashMap::new(),
+            ws_connections: DashMap::new(),
+            dm_typing: DashMap::new(),
+            last_seen_writes: DashMap::new(),
+            outbox_notify: Notify::new(),
+            webhook_notify: Notify::new(),
+            storage_notify: Notify::new(),
+            hook_limiter: create_hook_rate_limiter(),
+            api_limiter,
+            analytics_cache: DashMap::new(),
+            feature_cache: DashMap::new(),
+            started_at: Instant::now(),
         }
     }
 }
@@ -1,13 +1,16 @@
+use uuid::Uuid;
+
 /// Parsed channel types for validation.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Channel {
-    RoomChat,
-    RoomAlerts,
-    RoomTracks,
-    RoomPresence,
-    RoomPolls,
-    UserNotifications,
-    DirectMessage,
+    RoomChat(Uuid),
+    RoomAlerts(Uuid),
+    RoomTracks(Uuid),
+    RoomPresence(Uuid),
+    RoomPolls(Uuid),
+    RoomNotes(Uuid),
+    UserNotifications(Uuid),
+    DirectMessage(Uuid),
 }
 
 impl Channel {
@@ -15,14 +18,59 @@ impl Channel {
     pub fn parse(channel: &str) -> Option<Self> {
         let parts: Vec<&str> = channel.split(':').collect();
         match parts.as_slice() {
-            ["room", _id, "chat"] => Some(Channel::RoomChat),
-            ["room", _id, "alerts"] => Some(Channel::RoomAlerts),
-            ["room", _id, "tracks"] => Some(Channel::RoomTracks),
-            ["room", _id, "presence"] => Some(Channel::RoomPresence),
-            ["room", _id, "polls"] => Some(Channel::RoomPolls),
-            ["user", _id, "notifications"] => Some(Channel::UserNotifications),
-            ["dm", _id] => Some(Channel::DirectMessage),
+            ["room", id, "chat"] => Uuid::parse_str(id).ok().map(Channel::RoomChat),
+            ["room", id, "alerts"] => Uuid::parse_str(id).ok().map(Channel::RoomAlerts),
+            ["room", id, "tracks"] => Uuid::parse_str(id).ok().map(Channel::RoomTracks),
+            ["room", id, "presence"] => Uuid::parse_str(id).ok().map(Channel::RoomPresence),
+            ["room", id, "polls"] => Uuid::parse_str(id).ok().map(Channel::RoomPolls),
+            ["room", id, "notes"] => Uuid::parse_str(id).ok().map(Channel::RoomNotes),
+            ["user", id, "notifications"] => {
+                Uuid::parse_str(id).ok().map(Channel::UserNotifications)
+            }
+            ["dm", id] => Uuid::parse_str(id).ok().map(Channel::DirectMessage),
             _ => None,
         }
     }
+
+    /// Canonical channel name, the inverse of `parse`.
+    pub fn name(&self) -> String {
+        match self {
+            Channel::RoomChat(id) => format!("room:{id}:chat"),
+            Channel::RoomAlerts(id) => format!("room:{id}:alerts"),
+            Channel::RoomTracks(id) => format!("room:{id}:tracks"),
+            Channel::RoomPresence(id) => format!("room:{id}:presence"),
+            Channel::RoomPolls(id) => format!("room:{id}:polls"),
+            Channel::RoomNotes(id) => format!("room:{id}:notes"),
+            Channel::UserNotifications(id) => format!("user:{id}:notifications"),
+            Channel::DirectMessage(id) => format!("dm:{id}"),
+        }
+    }
+
+    pub fn room_chat(room_id: Uuid) -> String {
+        Channel::RoomChat(room_id).name()
+    }
+
+    pub fn room_alerts(room_id: Uuid) -> String {
+        Channel::RoomAlerts(room_id).name()
+    }
+
+    pub fn room_tracks(room_id: Uuid) -> String {
+        Channel::RoomTracks(room_id).name()
+    }
+
+    pub fn room_polls(room_id: Uuid) -> String {
+        Channel::RoomPolls(room_id).name()
+    }
+
+    pub fn room_notes(room_id: Uuid) -> String {
+        Channel::RoomNotes(room_id).name()
+    }
+
+    pub fn user_notifications(user_id: Uuid) -> String {
+        Channel::UserNotifications(user_id).name()
+    }
+
+    pub fn dm(chat_id: Uuid) -> String {
+        Channel::DirectMessage(chat_id).name()
+    }
 }
@@ -1,38 +1,77 @@
 use std::sync::Arc;
 
+use dashmap::DashMap;
 use uuid::Uuid;
 
-use crate::state::AppState;
+use crate::state::{AppState, WsSender};
 use crate::ws::protocol::ServerMessage;
 
+/// Channel name → senders subscribed to it, as held in `AppState::ws_channels`.
+type Channels = DashMap<String, Vec<WsSender>>;
+
 /// Manages WebSocket channel subscriptions and broadcasting.
 pub struct WsManager;
 
 impl WsManager {
-    /// Subscribe a sender to a channel.
-    pub fn subscribe(
-        state: &Arc<AppState>,
-        channel: &str,
-        sender: crate::state::WsSender,
-    ) -> usize {
-        let mut entry = state.ws_channels.entry(channel.to_string()).or_default();
-        entry.push(sender);
-        entry.len()
+    /// Subscribe a sender to a channel. Subscribing a sender that is already on the
+    /// channel is a no-op, so repeated Subscribe frames don't duplicate deliveries.
+    pub fn subscribe(state: &Arc<AppState>, channel: &str, sender: WsSender) -> usize {
+        add_sender(&state.ws_channels, channel, sender)
+    }
+
+    /// Unsubscribe a connection's sender from a channel, pruning closed senders too.
+    pub fn unsubscribe(state: &Arc<AppState>, channel: &str, sender: &WsSender) {
+        remove_sender(&state.ws_channels, channel, sender);
+    }
+
+    /// Record one of a user's sockets joining a channel. Returns true when it is the
+    /// user's first socket on the channel, i.e. when a presence `join` should go out.
+    pub fn presence_join(state: &Arc<AppState>, channel: &str, user_id: Uuid) -> bool {
+        let mut count = state
+            .ws_presence
+            .entry((channel.to_string(), user_id))
+            .or_insert(0);
+        *count += 1;
+        *count == 1
+    }
+
+    /// Record one of a user's sockets leaving a channel. Returns true when it was the
+    /// user's last socket on the channel, i.e. when a presence `leave` should go out.
+    pub fn presence_leave(state: &Arc<AppState>, channel: &str, user_id: Uuid) -> bool {
+        state
+            .ws_presence
+            .remove_if_mut(&(channel.to_string(), user_id), |_, count| {
+                *count = count.saturating_sub(1);
+                *count == 0
+            })
+            .is_some()
     }
 
-    /// Unsubscribe a sender from a channel by removing closed senders.
-    pub fn unsubscribe(state: &Arc<AppState>, channel: &str) {
-        if let Some(mut entry) = state.ws_channels.get_mut(channel) {
-            entry.retain(|s| !s.is_closed());
-            if entry.is_empty() {
-                drop(entry);
-                state.ws_channels.remove(channel);
+    /// Broadcast a server message to all subscribers of a channel. Events are also kept
+    /// for replay, whether or not anyone is subscribed right now.
+    pub fn broadcast(state: &Arc<AppState>, channel: &str, msg: &ServerMessage) {
+        let json = match serde_json::to_string(msg) {
+            Ok(j) => j,
+            Err(e) => {
+                tracing::error!("Failed to serialize WS message: {e}");
+                return;
             }
+        };
+        if let ServerMessage::Event { event_id, .. } = msg {
+            state.ws_replay.record(channel, *event_id, &json);
         }
+
+        deliver(&state.ws_channels, channel, &json);
     }
 
-    /// Broadcast a server message to all subscribers of a channel.
-    pub fn broadcast(state: &Arc<AppState>, channel: &str, msg: &ServerMessage) {
+    /// Broadcast a server message to every subscriber of a channel except one socket,
+    /// such as the one the message came from.
+    pub fn broadcast_except(
+        state: &Arc<AppState>,
+        channel: &str,
+        msg: &ServerMessage,
+        except: &WsSender,
+    ) {
         if let Some(mut senders) = state.ws_channels.get_mut(channel) {
             let json = match serde_json::to_string(msg) {
                 Ok(j) => j,
@@ -42,28 +81,27 @@ impl WsManager {
                 }
             };
 
-            senders.retain(|sender| sender.send(json.clone()).is_ok());
-
-            if senders.is_empty() {
-                drop(senders);
-                state.ws_channels.remove(channel);
-            }
+            senders
+                .retain(|sender| sender.same_channel(except) || sender.send(json.clone()).is_ok());
         }
     }
 
-    /// Notify a channel about a data change (used by REST handlers after mutations).
-    pub fn notify_change(
+    /// Broadcast a committed data-change event from the outbox to subscribers. Its webhook
+    /// deliveries were queued with the event itself.
+    pub fn publish(
         state: &Arc<AppState>,
         channel: &str,
         event: &str,
         payload: serde_json::Value,
+        event_id: Uuid,
+        timestamp: String,
     ) {
         let msg = ServerMessage::Event {
             channel: channel.to_string(),
             event: event.to_string(),
             payload,
-            timestamp: chrono::Utc::now().to_rfc3339(),
-            event_id: Uuid::new_v4(),
+            timestamp,
+            event_id,
         };
         Self::broadcast(state, channel, &msg);
     }
@@ -83,3 +121,101 @@ impl WsManager {
         }
     }
 }
+
+/// Add `sender` to a channel unless it is already there. Returns the subscriber count.
+fn add_sender(channels: &Channels, channel: &str, sender: WsSender) -> usize {
+    let mut entry = channels.entry(channel.to_string()).or_default();
+    if !entry.iter().any(|s| s.same_channel(&sender)) {
+        entry.push(sender);
+    }
+    entry.len()
+}
+
+/// Remove exactly `sender` from a channel, along with any closed senders, dropping the
+/// channel once nobody is left on it.
+fn remove_sender(channels: &Channels, channel: &str, sender: &WsSender) {
+    if let Some(mut entry) = channels.get_mut(channel) {
+        entry.retain(|s| !s.same_channel(sender) && !s.is_closed());
+        if entry.is_empty() {
+            drop(entry);
+            channels.remove(channel);
+        }
+    }
+}
+
+/// Send `json` to every subscriber of a channel, forgetting those that have gone away.
+fn deliver(channels: &Channels, channel: &str, json: &str) {
+    if let Some(mut senders) = channels.get_mut(channel) {
+        senders.retain(|sender| sender.send(json.to_string()).is_ok());
+
+        if senders.is_empty() {
+            drop(senders);
+            channels.remove(channel);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::mpsc;
+
+    use super::*;
+
+    const CHANNEL: &str = "room:00000000-0000-0000-0000-000000000000:chat";
+
+    #[test]
+    fn unsubscribed_connection_gets_no_further_events() {
+        let channels = Channels::new();
+        let (leaving, mut leaving_rx) = mpsc::unbounded_channel();
+        let (staying, mut staying_rx) = mpsc::unbounded_channel();
+        add_sender(&channels, CHANNEL, leaving.clone());
+        add_sender(&channels, CHANNEL, staying.clone());
+
+        deliver(&channels, CHANNEL, "before");
+        assert_eq!(leaving_rx.try_recv().unwrap(), "before");
+        assert_eq!(staying_rx.try_recv().unwrap(), "before");
+
+        remove_sender(&channels, CHANNEL, &leaving);
+        deliver(&channels, CHANNEL, "after");
+
+        assert!(leaving_rx.try_recv().is_err());
+        assert_eq!(staying_rx.try_recv().unwrap(), "after");
+        assert_eq!(channels.get(CHANNEL).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn last_unsubscribe_drops_the_channel() {
+        let channels = Channels::new();
+        let (sender, _rx) = mpsc::unbounded_channel();
+        add_sender(&channels, CHANNEL, sender.clone());
+
+        remove_sender(&channels, CHANNEL, &sender);
+
+        assert!(!channels.contains_key(CHANNEL));
+    }
+
+    #[test]
+    fn repeated_subscribe_delivers_once() {
+        let channels = Channels::new();
+        let (sender, mut rx) = mpsc::unbounded_channel();
+        assert_eq!(add_sender(&channels, CHANNEL, sender.clone()), 1);
+        assert_eq!(add_sender(&channels, CHANNEL, sender.clone()), 1);
+
+        deliver(&channels, CHANNEL, "event");
+
+        assert_eq!(rx.try_recv().unwrap(), "event");
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn closed_senders_are_pruned_on_delivery() {
+        let channels = Channels::new();
+        let (sender, rx) = mpsc::unbounded_channel();
+        add_sender(&channels, CHANNEL, sender);
+        drop(rx);
+
+        deliver(&channels, CHANNEL, "event");
+
+        assert!(!channels.contains_key(CHANNEL));
+    }
+}
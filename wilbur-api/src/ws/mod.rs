@@ -1,3 +1,5 @@
 pub mod channels;
 pub mod manager;
+pub mod outbox;
 pub mod protocol;
+pub mod replay;
@@ -0,0 +1,188 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, PgConnection};
+use uuid::Uuid;
+
+use crate::error::AppResult;
+use crate::services::webhook_dispatcher;
+use crate::state::AppState;
+use crate::ws::manager::WsManager;
+use crate::ws::protocol::ServerMessage;
+
+/// How often the dispatcher polls for undelivered events when not woken explicitly.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Maximum number of outbox rows dispatched per batch.
+const BATCH_SIZE: i64 = 200;
+
+/// Delivered rows are kept this long before being purged.
+const RETENTION_HOURS: i64 = 24;
+
+/// Maximum number of events returned when replaying for a reconnecting client.
+const REPLAY_LIMIT: i64 = 1000;
+
+#[derive(Debug, FromRow)]
+struct OutboxEvent {
+    id: Uuid,
+    channel: String,
+    event: String,
+    payload: serde_json::Value,
+    created_at: DateTime<Utc>,
+}
+
+/// Record an event in the outbox as part of the caller's transaction, along with its
+/// room webhook deliveries. The event is broadcast only after the transaction commits.
+pub async fn enqueue(
+    conn: &mut PgConnection,
+    channel: &str,
+    event: &str,
+    payload: &serde_json::Value,
+) -> AppResult<()> {
+    let event_id = Uuid::new_v4();
+
+    sqlx::query(
+        r#"
+        INSERT INTO event_outbox (id, channel, event, payload, created_at)
+        VALUES ($1, $2, $3, $4, NOW())
+        "#,
+    )
+    .bind(event_id)
+    .bind(channel)
+    .bind(event)
+    .bind(payload)
+    .execute(&mut *conn)
+    .await?;
+
+    webhook_dispatcher::enqueue_deliveries(conn, channel, event, payload, event_id).await?;
+
+    Ok(())
+}
+
+/// Wake the dispatcher, and the webhook worker, after a transaction containing outbox
+/// rows has committed.
+pub fn wake(state: &Arc<AppState>) {
+    state.outbox_notify.notify_one();
+    state.webhook_notify.notify_one();
+}
+
+/// Spawn the background task that delivers committed outbox events to WebSocket subscribers.
+pub fn spawn_dispatcher(state: Arc<AppState>) {
+    tokio::spawn(async move {
+        let mut last_purge = Utc::now();
+
+        loop {
+            match dispatch_pending(&state).await {
+                Ok(0) => {}
+                Ok(count) => tracing::debug!(count, "Dispatched outbox events"),
+                Err(e) => tracing::error!("Outbox dispatch failed: {e}"),
+            }
+
+            if Utc::now() - last_purge > chrono::Duration::hours(1) {
+                if let Err(e) = purge_delivered(&state).await {
+                    tracing::error!("Outbox purge failed: {e}");
+                }
+                last_purge = Utc::now();
+            }
+
+            tokio::select! {
+                _ = state.outbox_notify.notified() => {},
+                _ = tokio::time::sleep(POLL_INTERVAL) => {},
+            }
+        }
+    });
+}
+
+/// Broadcast one batch of undelivered events and mark them sent.
+/// Delivery is at-least-once: clients should de-duplicate on `event_id`.
+async fn dispatch_pending(state: &Arc<AppState>) -> AppResult<usize> {
+    let mut tx = state.pool.begin().await?;
+
+    let events = sqlx::query_as::<_, OutboxEvent>(
+        r#"
+        SELECT id, channel, event, payload, created_at
+        FROM event_outbox
+        WHERE sent_at IS NULL
+        ORDER BY created_at ASC
+        LIMIT $1
+        FOR UPDATE SKIP LOCKED
+        "#,
+    )
+    .bind(BATCH_SIZE)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    if events.is_empty() {
+        tx.rollback().await?;
+        return Ok(0);
+    }
+
+    let ids: Vec<Uuid> = events.iter().map(|e| e.id).collect();
+
+    for event in events {
+        WsManager::publish(
+            state,
+            &event.channel,
+            &event.event,
+            event.payload,
+            event.id,
+            event.created_at.to_rfc3339(),
+        );
+    }
+
+    sqlx::query("UPDATE event_outbox SET sent_at = NOW() WHERE id = ANY($1)")
+        .bind(&ids)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(ids.len())
+}
+
+/// Load delivered events on the given channels that followed `last_event_id`, oldest first.
+/// Returns nothing if the event is unknown, e.g. already purged or never written to the outbox.
+pub async fn replay_since(
+    state: &Arc<AppState>,
+    channels: &[String],
+    last_event_id: Uuid,
+) -> AppResult<Vec<ServerMessage>> {
+    let events = sqlx::query_as::<_, OutboxEvent>(
+        r#"
+        SELECT e.id, e.channel, e.event, e.payload, e.created_at
+        FROM event_outbox e
+        JOIN event_outbox last ON last.id = $1
+        WHERE e.channel = ANY($2)
+          AND e.sent_at IS NOT NULL
+          AND (e.created_at, e.id) > (last.created_at, last.id)
+        ORDER BY e.created_at ASC, e.id ASC
+        LIMIT $3
+        "#,
+    )
+    .bind(last_event_id)
+    .bind(channels)
+    .bind(REPLAY_LIMIT)
+    .fetch_all(&state.pool)
+    .await?;
+
+    Ok(events
+        .into_iter()
+        .map(|e| ServerMessage::Event {
+            channel: e.channel,
+            event: e.event,
+            payload: e.payload,
+            timestamp: e.created_at.to_rfc3339(),
+            event_id: e.id,
+        })
+        .collect())
+}
+
+/// Delete delivered events older than the retention window.
+async fn purge_delivered(state: &Arc<AppState>) -> AppResult<()> {
+    sqlx::query("DELETE FROM event_outbox WHERE sent_at < NOW() - make_interval(hours => $1)")
+        .bind(RETENTION_HOURS as i32)
+        .execute(&state.pool)
+        .await?;
+    Ok(())
+}
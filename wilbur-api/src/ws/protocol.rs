@@ -20,6 +20,12 @@ pub enum ClientMessage {
         channel: String,
         payload: serde_json::Value,
     },
+    /// Ask for a subscribed channel's events after the last one this client saw. Events
+    /// already delivered live since subscribing may come again; `event_id` tells them apart.
+    Resume {
+        channel: String,
+        last_event_id: Uuid,
+    },
 }
 
 /// Messages sent from server to client.
@@ -40,11 +46,22 @@ pub enum ServerMessage {
     Unsubscribed {
         channel: String,
     },
+    /// Sent after the events replayed for a `Resume`.
+    Resumed {
+        channel: String,
+        replayed: usize,
+    },
+    /// The events after a `Resume`'s `last_event_id` are no longer buffered; the client
+    /// must refetch the channel's state.
+    Gap {
+        channel: String,
+    },
     Presence {
         channel: String,
         event: String,
         user_id: Uuid,
         display_name: String,
+        avatar_url: Option<String>,
     },
     Pong,
     Error {
@@ -55,3 +72,38 @@ pub enum ServerMessage {
         message: String,
     },
 }
+
+/// Deepest array/object nesting accepted in a client message.
+pub const MAX_JSON_DEPTH: usize = 32;
+
+/// Whether `text` nests arrays or objects deeper than `max`. Scans the raw bytes, skipping
+/// string contents, so deeply nested input is turned away before any parsing.
+pub fn exceeds_json_depth(text: &str, max: usize) -> bool {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for byte in text.bytes() {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match byte {
+            b'"' => in_string = true,
+            b'{' | b'[' => {
+                depth += 1;
+                if depth > max {
+                    return true;
+                }
+            }
+            b'}' | b']' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    false
+}
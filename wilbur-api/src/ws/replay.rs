@@ -0,0 +1,95 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use dashmap::DashMap;
+use uuid::Uuid;
+
+/// Buffers with no new event for this long are dropped, so channels nobody posts to any
+/// more don't hold memory.
+const IDLE_BUFFER_TTL: Duration = Duration::from_secs(15 * 60);
+
+/// A broadcast event, kept serialized exactly as subscribers received it.
+struct BufferedEvent {
+    event_id: Uuid,
+    json: String,
+}
+
+struct ChannelBuffer {
+    events: VecDeque<BufferedEvent>,
+    last_recorded: Instant,
+}
+
+/// What a client resuming after `last_event_id` should be sent.
+pub enum Replay {
+    /// The events after it, oldest first; empty when the client is up to date.
+    Events(Vec<String>),
+    /// It is no longer (or was never) buffered, so events may have been missed and the
+    /// client must refetch.
+    Gap,
+}
+
+/// The most recent events of each channel, in a bounded ring buffer per channel, so a
+/// reconnecting client can pick up where it left off. Kept in memory on this server only.
+pub struct ReplayStore {
+    capacity: usize,
+    channels: DashMap<String, ChannelBuffer>,
+}
+
+impl ReplayStore {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            channels: DashMap::new(),
+        }
+    }
+
+    /// Remember an event sent on a channel, evicting the oldest once the buffer is full.
+    pub fn record(&self, channel: &str, event_id: Uuid, json: &str) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut buffer =
+            self.channels
+                .entry(channel.to_string())
+                .or_insert_with(|| ChannelBuffer {
+                    events: VecDeque::with_capacity(self.capacity),
+                    last_recorded: Instant::now(),
+                });
+        if buffer.events.len() == self.capacity {
+            buffer.events.pop_front();
+        }
+        buffer.events.push_back(BufferedEvent {
+            event_id,
+            json: json.to_string(),
+        });
+        buffer.last_recorded = Instant::now();
+    }
+
+    /// The buffered events on a channel after `last_event_id`.
+    pub fn after(&self, channel: &str, last_event_id: Uuid) -> Replay {
+        let Some(buffer) = self.channels.get(channel) else {
+            return Replay::Gap;
+        };
+        match buffer
+            .events
+            .iter()
+            .position(|e| e.event_id == last_event_id)
+        {
+            Some(index) => Replay::Events(
+                buffer
+                    .events
+                    .iter()
+                    .skip(index + 1)
+                    .map(|e| e.json.clone())
+                    .collect(),
+            ),
+            None => Replay::Gap,
+        }
+    }
+
+    /// Drop buffers that have been idle for `IDLE_BUFFER_TTL`.
+    pub fn prune_idle(&self) {
+        self.channels
+            .retain(|_, buffer| buffer.last_recorded.elapsed() < IDLE_BUFFER_TTL);
+    }
+}